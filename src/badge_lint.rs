@@ -0,0 +1,384 @@
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, Tag};
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem, Manifest};
+
+/// A badge that references another repository or crate than the manifest.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error("{provider} badge `{url}` references {mismatch}")]
+pub struct BadgeWarning {
+    /// The badge image url.
+    pub url: String,
+    /// The badge provider name.
+    pub provider: &'static str,
+    /// The mismatched badge reference.
+    pub mismatch: BadgeMismatch,
+    /// The badge image start node.
+    pub image: Arc<CMarkItem>,
+}
+
+/// A badge reference that differs from the manifest.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum BadgeMismatch {
+    /// The badge references another GitHub repository.
+    #[error("repository `{found}` instead of `{expected}`")]
+    Repository {
+        /// The `owner/repo` slug found in the badge url.
+        found: String,
+        /// The `owner/repo` slug of the manifest repository url.
+        expected: String,
+    },
+    /// The badge references another crate.
+    #[error("crate `{found}` instead of `{expected}`")]
+    CrateName {
+        /// The crate name found in the badge url.
+        found: String,
+        /// The manifest package name.
+        expected: String,
+    },
+}
+
+/// A repository or crate referenced by a badge url.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum BadgeRef {
+    Repository(String),
+    CrateName(String),
+}
+
+/// A badge provider host and the parser of its url path segments.
+struct BadgeProvider {
+    name: &'static str,
+    host: &'static str,
+    parse: fn(&[&str]) -> Option<BadgeRef>,
+}
+
+/// Badge providers whose urls reference a repository or a crate.
+///
+/// To support a new provider add its host and path segments parser here.
+const BADGE_PROVIDERS: &[BadgeProvider] = &[
+    BadgeProvider {
+        name: "GitHub",
+        host: "github.com",
+        parse: |segments| match segments {
+            [owner, repo, _, ..] => Some(repository(owner, repo)),
+            _ => None,
+        },
+    },
+    BadgeProvider {
+        name: "Shields.io",
+        host: "img.shields.io",
+        parse: |segments| match segments {
+            ["crates", _, name, ..] | ["docsrs", name, ..] => Some(crate_name(name)),
+            ["github", "actions", "workflow", "status", owner, repo, ..]
+            | ["github", "workflow", "status", owner, repo, ..]
+            | ["github", _, owner, repo, ..] => Some(repository(owner, repo)),
+            _ => None,
+        },
+    },
+    BadgeProvider {
+        name: "Docs.rs",
+        host: "docs.rs",
+        parse: |segments| match segments {
+            [name, "badge.svg"] => Some(crate_name(name)),
+            _ => None,
+        },
+    },
+    BadgeProvider {
+        name: "Travis CI",
+        host: "travis-ci.org",
+        parse: travis_ci,
+    },
+    BadgeProvider {
+        name: "Travis CI",
+        host: "travis-ci.com",
+        parse: travis_ci,
+    },
+    BadgeProvider {
+        name: "Travis CI",
+        host: "api.travis-ci.org",
+        parse: travis_ci,
+    },
+    BadgeProvider {
+        name: "Travis CI",
+        host: "api.travis-ci.com",
+        parse: travis_ci,
+    },
+    BadgeProvider {
+        name: "CircleCI",
+        host: "circleci.com",
+        parse: |segments| match segments {
+            ["gh", owner, repo, ..] => Some(repository(owner, repo)),
+            _ => None,
+        },
+    },
+    BadgeProvider {
+        name: "Codecov",
+        host: "codecov.io",
+        parse: |segments| match segments {
+            ["gh", owner, repo, ..] => Some(repository(owner, repo)),
+            _ => None,
+        },
+    },
+    BadgeProvider {
+        name: "Coveralls",
+        host: "coveralls.io",
+        parse: |segments| match segments {
+            ["repos", "github", owner, repo, ..] => Some(repository(owner, repo)),
+            _ => None,
+        },
+    },
+];
+
+fn travis_ci(segments: &[&str]) -> Option<BadgeRef> {
+    match segments {
+        [owner, repo, ..] => Some(repository(owner, repo)),
+        _ => None,
+    }
+}
+
+fn repository(owner: &str, repo: &str) -> BadgeRef {
+    BadgeRef::Repository(std::format!("{}/{}", owner, strip_extension(repo)))
+}
+
+fn crate_name(name: &str) -> BadgeRef {
+    BadgeRef::CrateName(String::from(strip_extension(name)))
+}
+
+fn strip_extension(segment: &str) -> &str {
+    segment
+        .strip_suffix(".svg")
+        .or_else(|| segment.strip_suffix(".git"))
+        .unwrap_or(segment)
+}
+
+/// Splits the url into the host and the path segments without the query and the fragment.
+fn url_host_and_segments(url: &str) -> Option<(&str, Vec<&str>)> {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    let mut segments = url.split('/').filter(|segment| !segment.is_empty());
+    let host = segments.next()?;
+    Some((host, segments.collect()))
+}
+
+/// Returns the provider name and the repository or crate referenced by the badge url.
+fn parse_badge_url(url: &str) -> Option<(&'static str, BadgeRef)> {
+    let (host, segments) = url_host_and_segments(url)?;
+    BADGE_PROVIDERS
+        .iter()
+        .filter(|provider| provider.host == host)
+        .find_map(|provider| Some((provider.name, (provider.parse)(&segments)?)))
+}
+
+/// Returns the `owner/repo` slug of the GitHub repository url.
+fn github_repository(url: &str) -> Option<String> {
+    match url_host_and_segments(url)? {
+        ("github.com", segments) => match segments.as_slice() {
+            [owner, repo] => Some(std::format!("{}/{}", owner, strip_extension(repo))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl CMarkData {
+    /// Returns badges that reference other repositories or crates than the specified manifest.
+    ///
+    /// It checks images with urls matching `badge_url_patterns`,
+    /// so it should be used before badges removal.
+    /// Repository slugs found in GitHub-based badges are compared
+    /// with the manifest repository url if it is a GitHub repository url.
+    /// Crate names are compared with the manifest package name,
+    /// where dashes and underscores are considered the same.
+    pub fn lint_badges(&self, manifest: &Manifest) -> Vec<BadgeWarning> {
+        let patterns = crate::badge_url_patterns();
+        let expected_repository = manifest
+            .package
+            .repository
+            .as_deref()
+            .and_then(github_repository);
+        let expected_name = &manifest.package.name;
+
+        let mut warnings = Vec::new();
+        for node in self.iter() {
+            let url = match node.event() {
+                Some(Event::Start(Tag::Image { dest_url, .. })) => dest_url,
+                _ => continue,
+            };
+            if !patterns.iter().any(|pattern| pattern.matches(url)) {
+                continue;
+            }
+            let mismatch = match parse_badge_url(url) {
+                Some((provider, BadgeRef::Repository(found))) => match &expected_repository {
+                    Some(expected) if !found.eq_ignore_ascii_case(expected) => Some((
+                        provider,
+                        BadgeMismatch::Repository {
+                            found,
+                            expected: expected.clone(),
+                        },
+                    )),
+                    _ => None,
+                },
+                Some((provider, BadgeRef::CrateName(found)))
+                    if found.replace('_', "-") != expected_name.replace('_', "-") =>
+                {
+                    Some((
+                        provider,
+                        BadgeMismatch::CrateName {
+                            found,
+                            expected: expected_name.clone(),
+                        },
+                    ))
+                }
+                _ => None,
+            };
+            if let Some((provider, mismatch)) = mismatch {
+                warnings.push(BadgeWarning {
+                    url: String::from(url.as_ref()),
+                    provider,
+                    mismatch,
+                    image: Arc::clone(node),
+                });
+            }
+        }
+        warnings
+    }
+}
+
+/// Asserts that the readme badges reference the repository and the crate of the manifest.
+///
+/// See `CMarkData::lint_badges` for the checked badges.
+#[cfg(feature = "codemap-diagnostic")]
+pub fn assert_badges_current<P, M>(readme: &crate::CMarkReadme<P, M>, manifest: &Manifest) {
+    use crate::{CodemapFiles, CodemapSpans, MatchFailed};
+    use codemap_diagnostic::{Diagnostic, Level};
+    use std::string::ToString;
+
+    let warnings = readme.lint_badges(manifest);
+    if !warnings.is_empty() {
+        let mut codemap_files = CodemapFiles::new();
+        let diags = warnings
+            .iter()
+            .map(|warning| Diagnostic {
+                level: Level::Error,
+                message: warning.to_string(),
+                code: None,
+                spans: CodemapSpans::span_labels_from(&mut codemap_files, warning.image.spans()),
+            })
+            .collect();
+        MatchFailed::from_diags(diags, Arc::new(codemap_files)).emit_to_stderr_colored();
+        panic!("{} outdated badge(s) found", warnings.len());
+    }
+}
+
+#[test]
+fn test_parse_badge_url() {
+    let parse = |url: &str| parse_badge_url(url).map(|(_, badge_ref)| badge_ref);
+    let repo = |slug: &str| Some(BadgeRef::Repository(String::from(slug)));
+    let name = |name: &str| Some(BadgeRef::CrateName(String::from(name)));
+
+    assert_eq!(
+        parse("https://github.com/zheland/readme-sync/workflows/CI/badge.svg"),
+        repo("zheland/readme-sync")
+    );
+    assert_eq!(
+        parse("https://github.com/owner/repo/actions/workflows/ci.yml/badge.svg?branch=main"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://img.shields.io/crates/v/readme-sync.svg"),
+        name("readme-sync")
+    );
+    assert_eq!(
+        parse("https://img.shields.io/crates/l/readme-sync"),
+        name("readme-sync")
+    );
+    assert_eq!(
+        parse("https://img.shields.io/docsrs/readme-sync?style=flat"),
+        name("readme-sync")
+    );
+    assert_eq!(
+        parse("https://img.shields.io/github/actions/workflow/status/owner/repo/ci.yml"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://img.shields.io/github/license/owner/repo"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://docs.rs/readme-sync/badge.svg"),
+        name("readme-sync")
+    );
+    assert_eq!(
+        parse("https://travis-ci.org/owner/repo.svg?branch=master"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://codecov.io/gh/owner/repo/branch/master/graph/badge.svg"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://coveralls.io/repos/github/owner/repo/badge.svg?branch=master"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://circleci.com/gh/owner/repo.svg?style=svg"),
+        repo("owner/repo")
+    );
+    assert_eq!(
+        parse("https://img.shields.io/badge/license-MIT-blue.svg"),
+        None
+    );
+    assert_eq!(parse("https://example.com/owner/repo/badge.svg"), None);
+
+    assert_eq!(
+        github_repository("https://github.com/zheland/readme-sync.git"),
+        Some(String::from("zheland/readme-sync"))
+    );
+    assert_eq!(github_repository("https://gitlab.com/owner/repo"), None);
+}
+
+#[test]
+fn test_lint_badges() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let data = readme_data(concat!(
+            "[![Crates.io](https://img.shields.io/crates/v/old_name.svg)](https://crates.io/crates/old-name)\n",
+            "[![Docs.rs](https://docs.rs/new-name/badge.svg)](https://docs.rs/new-name)\n",
+            "![CI](https://github.com/old-owner/new-name/workflows/CI/badge.svg)\n",
+            "![CI](https://github.com/New-Owner/New-Name/workflows/CI/badge.svg)\n",
+        )
+        );
+    let mut manifest =
+        Manifest::from_name_and_version(String::from("new_name"), String::from("1.0.0"));
+
+    let warnings: Vec<_> = data
+        .lint_badges(&manifest)
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "Shields.io badge `https://img.shields.io/crates/v/old_name.svg` \
+          references crate `old_name` instead of `new_name`"
+        ]
+    );
+
+    manifest.package.repository = Some(String::from("https://github.com/new-owner/new-name"));
+    let warnings = data.lint_badges(&manifest);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(
+        warnings[1].mismatch,
+        BadgeMismatch::Repository {
+            found: String::from("old-owner/new-name"),
+            expected: String::from("new-owner/new-name"),
+        }
+    );
+}
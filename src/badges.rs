@@ -1,42 +1,249 @@
 use std::vec::Vec;
 
-use glob::Pattern;
+use thiserror::Error;
 
 /// Returns a Vec of populatar badge url patterns.
-pub fn badge_url_patterns() -> Vec<Pattern> {
-    std::vec![
-        // GitHub badges
-        Pattern::new("http://github.com/*/badge.svg").unwrap(),
-        Pattern::new("https://github.com/*/badge.svg").unwrap(),
-        Pattern::new("http://github.com/*/badge.svg?*").unwrap(),
-        Pattern::new("https://github.com/*/badge.svg?*").unwrap(),
-        // CMarkDocs badges
-        Pattern::new("http://docs.rs/repos/*/badge.svg").unwrap(),
-        Pattern::new("https://docs.rs/repos/*/badge.svg").unwrap(),
-        // Badge providers
-        Pattern::new("http://img.shields.io/*").unwrap(),
-        Pattern::new("https://img.shields.io/*").unwrap(),
-        Pattern::new("http://meritbadge.herokuapp.com/*").unwrap(),
-        Pattern::new("https://meritbadge.herokuapp.com/*").unwrap(),
-        Pattern::new("http://badges.gitter.im/*").unwrap(),
-        Pattern::new("https://badges.gitter.im/*").unwrap(),
-        // Continuous integration
-        Pattern::new("http://travis-ci.org/*").unwrap(),
-        Pattern::new("https://travis-ci.org/*").unwrap(),
-        Pattern::new("http://travis-ci.com/*").unwrap(),
-        Pattern::new("https://travis-ci.com/*").unwrap(),
-        Pattern::new("http://api.travis-ci.org/*").unwrap(),
-        Pattern::new("https://api.travis-ci.org/*").unwrap(),
-        Pattern::new("http://api.travis-ci.com/*").unwrap(),
-        Pattern::new("https://api.travis-ci.com/*").unwrap(),
-        Pattern::new("http://ci.appveyor.com/api/projects/status/*").unwrap(),
-        Pattern::new("https://ci.appveyor.com/api/projects/status/*").unwrap(),
-        Pattern::new("http://circleci.com/gh/*").unwrap(),
-        Pattern::new("https://circleci.com/gh/*").unwrap(),
-        // Code coverage
-        Pattern::new("http://codecov.io/gh/*").unwrap(),
-        Pattern::new("https://codecov.io/gh/*").unwrap(),
-        Pattern::new("http://coveralls.io/repos/*").unwrap(),
-        Pattern::new("https://coveralls.io/repos/*").unwrap(),
-    ]
+pub fn badge_url_patterns() -> Vec<WildcardPattern> {
+    BADGE_URL_PATTERNS
+        .iter()
+        .map(|pattern| WildcardPattern::new(pattern).unwrap())
+        .collect()
+}
+
+/// Popular badge url patterns.
+pub(crate) const BADGE_URL_PATTERNS: &[&str] = &[
+    // GitHub badges
+    "http://github.com/*/badge.svg",
+    "https://github.com/*/badge.svg",
+    "http://github.com/*/badge.svg?*",
+    "https://github.com/*/badge.svg?*",
+    // CMarkDocs badges
+    "http://docs.rs/repos/*/badge.svg",
+    "https://docs.rs/repos/*/badge.svg",
+    "http://docs.rs/*/badge.svg",
+    "https://docs.rs/*/badge.svg",
+    // Badge providers
+    "http://img.shields.io/*",
+    "https://img.shields.io/*",
+    "http://meritbadge.herokuapp.com/*",
+    "https://meritbadge.herokuapp.com/*",
+    "http://badges.gitter.im/*",
+    "https://badges.gitter.im/*",
+    // Continuous integration
+    "http://travis-ci.org/*",
+    "https://travis-ci.org/*",
+    "http://travis-ci.com/*",
+    "https://travis-ci.com/*",
+    "http://api.travis-ci.org/*",
+    "https://api.travis-ci.org/*",
+    "http://api.travis-ci.com/*",
+    "https://api.travis-ci.com/*",
+    "http://ci.appveyor.com/api/projects/status/*",
+    "https://ci.appveyor.com/api/projects/status/*",
+    "http://circleci.com/gh/*",
+    "https://circleci.com/gh/*",
+    // Code coverage
+    "http://codecov.io/gh/*",
+    "https://codecov.io/gh/*",
+    "http://coveralls.io/repos/*",
+    "https://coveralls.io/repos/*",
+];
+
+/// A wildcard pattern used to match urls.
+///
+/// It supports `*` that matches any sequence of characters, `?` that matches any character,
+/// and `[...]` and `[!...]` that match any character in or not in the specified set or ranges.
+///
+/// With the `glob` feature enabled, a `glob::Pattern` can be converted into a `WildcardPattern`.
+#[derive(Clone, Debug)]
+pub struct WildcardPattern(PatternKind);
+
+#[derive(Clone, Debug)]
+enum PatternKind {
+    Wildcard(Vec<Token>),
+    #[cfg(feature = "glob")]
+    Glob(glob::Pattern),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnySequence,
+    Class {
+        is_negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// An error which can occur when parsing a wildcard pattern.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+#[error("Unclosed character class starting at character {pos}")]
+pub struct WildcardPatternError {
+    /// The position of the unclosed `[` character.
+    pub pos: usize,
+}
+
+impl WildcardPattern {
+    /// Parses the specified wildcard pattern.
+    pub fn new(pattern: &str) -> Result<Self, WildcardPatternError> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut pos = 0;
+        while pos < chars.len() {
+            match chars[pos] {
+                '*' => {
+                    if tokens.last() != Some(&Token::AnySequence) {
+                        tokens.push(Token::AnySequence);
+                    }
+                }
+                '?' => tokens.push(Token::AnyChar),
+                '[' => {
+                    let start = pos;
+                    pos += 1;
+                    let is_negated = chars.get(pos) == Some(&'!');
+                    if is_negated {
+                        pos += 1;
+                    }
+                    let mut ranges = Vec::new();
+                    // The first character of a class is always literal, so `[]]` matches `]`.
+                    let mut is_first = true;
+                    loop {
+                        match chars.get(pos) {
+                            None => return Err(WildcardPatternError { pos: start }),
+                            Some(']') if !is_first => break,
+                            Some(&from) => {
+                                if chars.get(pos + 1) == Some(&'-')
+                                    && chars.get(pos + 2).is_some_and(|&to| to != ']')
+                                {
+                                    ranges.push((from, chars[pos + 2]));
+                                    pos += 3;
+                                } else {
+                                    ranges.push((from, from));
+                                    pos += 1;
+                                }
+                            }
+                        }
+                        is_first = false;
+                    }
+                    tokens.push(Token::Class { is_negated, ranges });
+                }
+                ch => tokens.push(Token::Char(ch)),
+            }
+            pos += 1;
+        }
+        Ok(Self(PatternKind::Wildcard(tokens)))
+    }
+
+    /// Parses the specified pattern with only `*` and `?` wildcards,
+    /// so `[` and `]` match themselves, like in `#[macro_use]*`.
+    pub(crate) fn with_literal_brackets(pattern: &str) -> Self {
+        let mut tokens = Vec::new();
+        for ch in pattern.chars() {
+            match ch {
+                '*' => {
+                    if tokens.last() != Some(&Token::AnySequence) {
+                        tokens.push(Token::AnySequence);
+                    }
+                }
+                '?' => tokens.push(Token::AnyChar),
+                ch => tokens.push(Token::Char(ch)),
+            }
+        }
+        Self(PatternKind::Wildcard(tokens))
+    }
+
+    /// Returns `true` if the specified text matches the pattern.
+    pub fn matches(&self, text: &str) -> bool {
+        match &self.0 {
+            PatternKind::Wildcard(tokens) => {
+                let chars: Vec<char> = text.chars().collect();
+                matches_tokens(tokens, &chars)
+            }
+            #[cfg(feature = "glob")]
+            PatternKind::Glob(pattern) => pattern.matches(text),
+        }
+    }
+}
+
+#[cfg(feature = "glob")]
+impl From<glob::Pattern> for WildcardPattern {
+    fn from(pattern: glob::Pattern) -> Self {
+        Self(PatternKind::Glob(pattern))
+    }
+}
+
+/// Matches tokens against characters with backtracking to the last `*` token.
+fn matches_tokens(tokens: &[Token], chars: &[char]) -> bool {
+    let (mut token_idx, mut char_idx) = (0, 0);
+    let mut backtrack = None;
+    while char_idx < chars.len() {
+        let ch = chars[char_idx];
+        match tokens.get(token_idx) {
+            Some(Token::AnySequence) => {
+                backtrack = Some((token_idx, char_idx));
+                token_idx += 1;
+                continue;
+            }
+            Some(token) if token_matches(token, ch) => {
+                token_idx += 1;
+                char_idx += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match backtrack {
+            Some((star_token_idx, star_char_idx)) => {
+                backtrack = Some((star_token_idx, star_char_idx + 1));
+                token_idx = star_token_idx + 1;
+                char_idx = star_char_idx + 1;
+            }
+            None => return false,
+        }
+    }
+    tokens[token_idx..]
+        .iter()
+        .all(|token| *token == Token::AnySequence)
+}
+
+fn token_matches(token: &Token, ch: char) -> bool {
+    match token {
+        Token::Char(expected) => *expected == ch,
+        Token::AnyChar => true,
+        Token::AnySequence => false,
+        Token::Class { is_negated, ranges } => {
+            ranges.iter().any(|&(from, to)| from <= ch && ch <= to) != *is_negated
+        }
+    }
+}
+
+#[test]
+fn test_wildcard_pattern() {
+    let matches = |pattern: &str, text: &str| WildcardPattern::new(pattern).unwrap().matches(text);
+
+    assert!(matches(
+        "https://img.shields.io/*",
+        "https://img.shields.io/crates/v/foo.svg"
+    ));
+    assert!(matches(
+        "https://github.com/*/badge.svg",
+        "https://github.com/user/foo/workflows/CI/badge.svg"
+    ));
+    assert!(!matches(
+        "https://github.com/*/badge.svg",
+        "https://github.com/user/foo/workflows/CI/badge.svg?branch=master"
+    ));
+    assert!(matches("a*b*c", "aXXbYYbZc"));
+    assert!(matches("a**", "a"));
+    assert!(!matches("a*b", "aXXc"));
+    assert!(matches("v?.svg", "v1.svg"));
+    assert!(!matches("v?.svg", "v.svg"));
+    assert!(matches("[a-c]x[!0-9]", "bxy"));
+    assert!(!matches("[a-c]x[!0-9]", "bx1"));
+    assert!(matches("[]]", "]"));
+    assert!(matches("[a-]", "-"));
+    assert_eq!(
+        WildcardPattern::new("ab[cd").unwrap_err(),
+        WildcardPatternError { pos: 2 }
+    );
 }
@@ -0,0 +1,346 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkDocs, CMarkItem, CMarkReadme, SyncMismatch};
+
+/// A list of known readme and docs mismatches used to adopt sync checks incrementally.
+///
+/// Every mismatch is identified by a fingerprint of its context:
+/// the breadcrumb of the headings of the section containing it,
+/// and the event kinds and whitespace-normalized texts of the mismatched nodes.
+/// The fingerprint does not depend on the mismatch position,
+/// so edits in other sections do not change it.
+///
+/// The baseline is usually stored as a TOML file committed next to the readme.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Baseline {
+    /// Known mismatches.
+    #[serde(default, rename = "mismatch")]
+    pub mismatches: Vec<BaselineEntry>,
+}
+
+/// A known mismatch stored in a `Baseline`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct BaselineEntry {
+    /// The mismatch fingerprint.
+    pub fingerprint: String,
+    /// The headings breadcrumb of the section containing the mismatch, for reviewers.
+    pub section: String,
+}
+
+/// A mismatch found by `check_sync_with_baseline` and its baseline entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineMismatch {
+    /// The baseline entry identifying the mismatch.
+    pub entry: BaselineEntry,
+    /// The mismatch data.
+    pub mismatch: SyncMismatch,
+}
+
+/// The result of the `check_sync_with_baseline` function.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BaselineOutcome {
+    /// Mismatches present in the baseline, they should be reported as warnings.
+    pub known: Vec<BaselineMismatch>,
+    /// Mismatches not present in the baseline, they should fail the check.
+    pub new: Vec<BaselineMismatch>,
+    /// Baseline entries that no longer match any mismatch and can be removed from the baseline.
+    pub fixed: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Creates a baseline with all current mismatches of the given readme and docs.
+    pub fn from_current<P1, P2, M1, M2>(
+        readme: &CMarkReadme<P1, M1>,
+        docs: &CMarkDocs<P2, M2>,
+    ) -> Self {
+        Self {
+            mismatches: find_mismatches(readme.data(), docs.data())
+                .into_iter()
+                .map(|mismatch| mismatch.entry)
+                .collect(),
+        }
+    }
+
+    /// Parses a baseline from the TOML text.
+    pub fn from_toml_str(text: &str) -> Result<Self, BaselineError> {
+        toml::from_str(text).map_err(BaselineError::ParseError)
+    }
+
+    /// Reads a baseline from the TOML file at the specified path.
+    pub fn from_path(path: &Path) -> Result<Self, BaselineError> {
+        let text = std::fs::read_to_string(path).map_err(|err| BaselineError::IoError {
+            path: path.to_path_buf(),
+            err,
+        })?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Returns the baseline as the TOML text.
+    pub fn to_toml_string(&self) -> Result<String, BaselineError> {
+        toml::to_string(self).map_err(BaselineError::SerializeError)
+    }
+
+    /// Writes the baseline as the TOML file at the specified path.
+    pub fn write(&self, path: &Path) -> Result<(), BaselineError> {
+        std::fs::write(path, self.to_toml_string()?).map_err(|err| BaselineError::IoError {
+            path: path.to_path_buf(),
+            err,
+        })
+    }
+}
+
+impl BaselineOutcome {
+    /// Returns `true` if there are no new mismatches.
+    pub fn is_ok(&self) -> bool {
+        self.new.is_empty()
+    }
+
+    /// Returns the baseline with the known mismatches only, without the fixed ones.
+    pub fn updated_baseline(&self) -> Baseline {
+        Baseline {
+            mismatches: self
+                .known
+                .iter()
+                .map(|mismatch| mismatch.entry.clone())
+                .collect(),
+        }
+    }
+
+    /// Writes the baseline without the fixed mismatches to the specified path.
+    ///
+    /// New mismatches are not added, use `Baseline::from_current` for that.
+    pub fn write_updated(&self, path: &Path) -> Result<(), BaselineError> {
+        self.updated_baseline().write(path)
+    }
+}
+
+/// Compares the given readme and docs section by section
+/// and splits the found mismatches into known and new ones using the specified baseline.
+///
+/// Sections are compared in order, so reordered, missing or duplicated sections
+/// are reported as mismatches like `check_sync` does,
+/// and only the first mismatch of every section is reported.
+/// Without a baseline all mismatches are new.
+pub fn check_sync_with_baseline<P1, P2, M1, M2>(
+    readme: &CMarkReadme<P1, M1>,
+    docs: &CMarkDocs<P2, M2>,
+    baseline: Option<&Baseline>,
+) -> BaselineOutcome {
+    let mut fixed: Vec<_> = baseline
+        .map(|baseline| baseline.mismatches.clone())
+        .unwrap_or_default();
+    let mut outcome = BaselineOutcome::default();
+    for mismatch in find_mismatches(readme.data(), docs.data()) {
+        match fixed.iter().position(|entry| *entry == mismatch.entry) {
+            Some(idx) => {
+                let _ = fixed.remove(idx);
+                outcome.known.push(mismatch);
+            }
+            None => outcome.new.push(mismatch),
+        }
+    }
+    outcome.fixed = fixed;
+    outcome
+}
+
+/// An error which can occur when reading or writing a baseline.
+#[derive(Debug, Error)]
+pub enum BaselineError {
+    /// File reading or writing failed.
+    #[error("Failed to access baseline at `{path}`: {err}")]
+    IoError {
+        /// File path.
+        path: PathBuf,
+        /// Rust `io::Error`.
+        err: io::Error,
+    },
+    /// Baseline parsing failed.
+    #[error("Failed to parse baseline: {0}")]
+    ParseError(toml::de::Error),
+    /// Baseline serialization failed.
+    #[error("Failed to serialize baseline: {0}")]
+    SerializeError(toml::ser::Error),
+}
+
+/// Returns the first mismatch of every readme and docs section pair.
+///
+/// Sections are paired by their positions and keyed by the readme section breadcrumb.
+fn find_mismatches(readme: &CMarkData, docs: &CMarkData) -> Vec<BaselineMismatch> {
+    use crate::compare::find_mismatch;
+
+    let mut readme_sections = sections(readme).into_iter();
+    let mut docs_sections = sections(docs).into_iter();
+    let mut mismatches = Vec::new();
+    loop {
+        let (section, readme_items, docs_items) =
+            match (readme_sections.next(), docs_sections.next()) {
+                (Some((section, readme_items)), Some((_, docs_items))) => {
+                    (section, readme_items, docs_items)
+                }
+                (Some((section, readme_items)), None) => (section, readme_items, Vec::new()),
+                (None, Some((section, docs_items))) => (section, Vec::new(), docs_items),
+                (None, None) => break,
+            };
+        let readme_data = CMarkData::from_items(readme_items);
+        let docs_data = CMarkData::from_items(docs_items);
+        if let Some(mismatch) = find_mismatch(&readme_data, &docs_data) {
+            let fingerprint = fingerprint(&section, &mismatch);
+            mismatches.push(BaselineMismatch {
+                entry: BaselineEntry {
+                    fingerprint,
+                    section,
+                },
+                mismatch,
+            });
+        }
+    }
+    mismatches
+}
+
+/// Splits the data into sections keyed by their headings breadcrumbs, like `Usage > Examples`.
+pub(crate) fn sections(data: &CMarkData) -> Vec<(String, Vec<Arc<CMarkItem>>)> {
+    use crate::cmark_data::heading_text;
+    use pulldown_cmark::{Event, Tag};
+
+    let nodes: Vec<_> = data.iter().cloned().collect();
+    let mut sections = std::vec![(String::new(), Vec::new())];
+    let mut breadcrumb: Vec<(pulldown_cmark::HeadingLevel, String)> = Vec::new();
+    for (idx, node) in nodes.iter().enumerate() {
+        if let Some(Event::Start(Tag::Heading { level, .. })) = node.event() {
+            let text = heading_text(&nodes[idx..]).unwrap_or_default();
+            breadcrumb.retain(|(parent_level, _)| parent_level < level);
+            breadcrumb.push((*level, text));
+            let section: Vec<_> = breadcrumb.iter().map(|(_, text)| text.as_str()).collect();
+            sections.push((section.join(" > "), Vec::new()));
+        }
+        if let Some((_, items)) = sections.last_mut() {
+            items.push(Arc::clone(node));
+        }
+    }
+    sections.retain(|(section, items)| !section.is_empty() || !items.is_empty());
+    sections
+}
+
+/// Returns the hexadecimal FNV-1a hash of the mismatch context.
+///
+/// A fixed hash function is used instead of `DefaultHasher`,
+/// as its results may differ between Rust releases.
+fn fingerprint(section: &str, mismatch: &SyncMismatch) -> String {
+    let mut context = String::from(section);
+    for node in [&mismatch.readme_node, &mismatch.docs_node] {
+        context.push('\n');
+        if let Some(event) = node.as_ref().and_then(|node| node.event()) {
+            event_context(&mut context, event);
+        }
+    }
+    std::format!("{:016x}", crate::fingerprint::text_fingerprint(&context))
+}
+
+/// Appends the event kind and the whitespace-normalized event text to the context.
+fn event_context(context: &mut String, event: &pulldown_cmark::Event<'_>) {
+    use crate::compare::{get_event_name, get_event_start_tag, get_event_text, get_start_tag_name};
+
+    context.push_str(get_event_name(event));
+    if let Some(tag) = get_event_start_tag(event) {
+        context.push(' ');
+        context.push_str(get_start_tag_name(tag));
+    }
+    if let Some(text) = get_event_text(event) {
+        for word in text.split_whitespace() {
+            context.push(' ');
+            context.push_str(word);
+        }
+    }
+}
+
+#[test]
+fn test_check_sync_with_baseline() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let readme = |text: &str| {
+        CMarkReadme::from_data_and_package_path_and_manifest(readme_data(text), (), ())
+    };
+    let docs = |text: &str| {
+        CMarkDocs::from_data_chunks_package_pach_and_manifest(readme_data(text), (), ())
+    };
+
+    let old_readme = readme("# Crate\n\nIntro.\n\n## Usage\n\nOld usage.\n\n## License\n\nMIT.\n");
+    let old_docs = docs("# Crate\n\nIntro.\n\n## Usage\n\nNew usage.\n\n## License\n\nApache.\n");
+    let baseline = Baseline::from_current(&old_readme, &old_docs);
+    assert_eq!(baseline.mismatches.len(), 2);
+    assert_eq!(baseline.mismatches[0].section, "Crate > Usage");
+    assert_eq!(baseline.mismatches[1].section, "Crate > License");
+
+    let text = baseline.to_toml_string().unwrap();
+    assert!(text.starts_with("[[mismatch]]\nfingerprint = "));
+    assert_eq!(Baseline::from_toml_str(&text).unwrap(), baseline);
+
+    // Unrelated edits do not change known fingerprints.
+    let readme_edited =
+        readme("# Crate\n\nLonger intro.\n\n## Usage\n\nOld usage.\n\n## License\n\nApache.\n");
+    let docs_edited =
+        docs("# Crate\n\nLonger intro.\n\n## Usage\n\nNew usage.\n\n## License\n\nApache.\n");
+    let outcome = check_sync_with_baseline(&readme_edited, &docs_edited, Some(&baseline));
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.known.len(), 1);
+    assert_eq!(outcome.fixed, [baseline.mismatches[1].clone()]);
+    assert_eq!(
+        outcome.updated_baseline().mismatches,
+        [baseline.mismatches[0].clone()]
+    );
+
+    let docs_changed =
+        docs("# Crate\n\nIntro.\n\n## Usage\n\nOther usage.\n\n## License\n\nApache.\n");
+    let outcome = check_sync_with_baseline(&old_readme, &docs_changed, Some(&baseline));
+    assert!(!outcome.is_ok());
+    assert_eq!(outcome.new.len(), 1);
+    assert_eq!(outcome.new[0].entry.section, "Crate > Usage");
+    assert_eq!(outcome.known.len(), 1);
+    assert_eq!(outcome.fixed.len(), 1);
+
+    let outcome = check_sync_with_baseline(&old_readme, &old_docs, None);
+    assert_eq!(outcome.new.len(), 2);
+    assert!(outcome.new[0].mismatch.to_string().contains("Old usage."));
+}
+
+#[test]
+fn test_check_sync_with_baseline_reordered_sections() {
+    use crate::check_sync;
+    use crate::test_utils::readme_data;
+
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        readme_data("# Crate\n\n## Usage\n\nUsage.\n\n## License\n\nMIT.\n"),
+        (),
+        (),
+    );
+    let docs = |text: &str| {
+        CMarkDocs::from_data_chunks_package_pach_and_manifest(readme_data(text), (), ())
+    };
+
+    let reordered = docs("# Crate\n\n## License\n\nMIT.\n\n## Usage\n\nUsage.\n");
+    assert!(check_sync(&readme, &reordered).is_err());
+    let outcome = check_sync_with_baseline(&readme, &reordered, None);
+    assert_eq!(outcome.new.len(), 2);
+    assert_eq!(outcome.new[0].entry.section, "Crate > Usage");
+    assert_eq!(outcome.new[1].entry.section, "Crate > License");
+
+    let duplicated =
+        docs("# Crate\n\n## Usage\n\nUsage.\n\n## License\n\nMIT.\n\n## Usage\n\nUsage.\n");
+    assert!(check_sync(&readme, &duplicated).is_err());
+    let outcome = check_sync_with_baseline(&readme, &duplicated, None);
+    assert_eq!(outcome.new.len(), 1);
+    assert_eq!(outcome.new[0].entry.section, "Crate > Usage");
+
+    let same = docs("# Crate\n\n## Usage\n\nUsage.\n\n## License\n\nMIT.\n");
+    assert!(check_sync_with_baseline(&readme, &same, None)
+        .new
+        .is_empty());
+}
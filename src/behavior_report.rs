@@ -0,0 +1,125 @@
+use core::fmt::{self, Display};
+use std::string::String;
+use std::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ParseProfile;
+
+/// The behavior-affecting defaults of the current readme-sync version.
+///
+/// It allows to detect default changes on the crate upgrade,
+/// for example by saving the report as a snapshot and comparing it with the current one.
+/// The `Display` implementation writes one sorted `key = value` line for every value.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BehaviorReport {
+    /// The readme-sync version.
+    pub version: String,
+    /// The badge url patterns returned by `badge_url_patterns`.
+    pub badge_url_patterns: Vec<String>,
+    /// The code block tags returned by `codeblock_rust_test_tags`.
+    pub rust_test_tags: Vec<String>,
+    /// The parser options of the `ParseProfile::github` profile used for readme files.
+    pub readme_parse_options: Vec<String>,
+    /// The parser options of the `ParseProfile::rustdoc` profile used for docs.
+    pub docs_parse_options: Vec<String>,
+    /// The repository branch used for blob urls if the manifest does not specify one.
+    pub default_blob_branch: String,
+    /// The maximum number of lines of a multi-paragraph code block that is not suspicious.
+    pub max_codeblock_lines: usize,
+}
+
+/// Returns the behavior-affecting defaults of the current readme-sync version.
+pub fn behavior_report() -> BehaviorReport {
+    let strings = |items: &[&str]| items.iter().map(|&item| String::from(item)).collect();
+    let parse_options = |profile: ParseProfile| {
+        profile
+            .options()
+            .iter_names()
+            .map(|(name, _)| String::from(name))
+            .collect()
+    };
+    BehaviorReport {
+        version: String::from(env!("CARGO_PKG_VERSION")),
+        badge_url_patterns: strings(crate::badges::BADGE_URL_PATTERNS),
+        rust_test_tags: strings(crate::codeblock_rust_test_tags()),
+        readme_parse_options: parse_options(ParseProfile::github()),
+        docs_parse_options: parse_options(ParseProfile::rustdoc()),
+        default_blob_branch: String::from(crate::DEFAULT_BLOB_BRANCH),
+        max_codeblock_lines: crate::DEFAULT_MAX_CODEBLOCK_LINES,
+    }
+}
+
+impl Display for BehaviorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use std::format;
+        use std::string::ToString;
+
+        let values = |key: &str, values: &[String]| {
+            values
+                .iter()
+                .map(|value| format!("{} = {}", key, value))
+                .collect::<Vec<_>>()
+        };
+        let mut lines = [
+            values("badge_url_pattern", &self.badge_url_patterns),
+            values(
+                "default_blob_branch",
+                core::slice::from_ref(&self.default_blob_branch),
+            ),
+            values("docs_parse_option", &self.docs_parse_options),
+            values(
+                "max_codeblock_lines",
+                &[self.max_codeblock_lines.to_string()],
+            ),
+            values("readme_parse_option", &self.readme_parse_options),
+            values("rust_test_tag", &self.rust_test_tags),
+            values("version", core::slice::from_ref(&self.version)),
+        ]
+        .concat();
+        lines.sort();
+        for line in lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_behavior_report() {
+    use std::string::ToString;
+
+    let report = behavior_report();
+    assert_eq!(report.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(report.default_blob_branch, "master");
+    assert!(report
+        .badge_url_patterns
+        .contains(&String::from("https://img.shields.io/*")));
+    assert!(report.rust_test_tags.contains(&String::from("no_run")));
+    assert!(report
+        .readme_parse_options
+        .contains(&String::from("ENABLE_GFM")));
+    assert!(!report
+        .docs_parse_options
+        .contains(&String::from("ENABLE_GFM")));
+
+    let text = report.to_string();
+    let lines: Vec<_> = text.lines().collect();
+    let mut sorted = lines.clone();
+    sorted.sort_unstable();
+    assert_eq!(lines, sorted);
+    assert!(lines.contains(&"default_blob_branch = master"));
+    assert!(lines.contains(&"max_codeblock_lines = 40"));
+    assert!(lines.contains(&"readme_parse_option = ENABLE_TABLES"));
+    assert_eq!(
+        lines.len(),
+        report.badge_url_patterns.len()
+            + report.rust_test_tags.len()
+            + report.readme_parse_options.len()
+            + report.docs_parse_options.len()
+            + 3
+    );
+
+    let snapshot = toml::to_string(&report).unwrap();
+    assert_eq!(toml::from_str::<BehaviorReport>(&snapshot).unwrap(), report);
+}
@@ -0,0 +1,72 @@
+use crate::CheckSyncError;
+
+/// The name of the environment variable that makes readme and docs mismatches fail the build.
+pub const ENFORCE_ENV_VAR: &str = "README_SYNC_ENFORCE";
+
+/// A build script decision made after the readme and docs synchronization check.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SyncEnforcement {
+    /// Readme and docs are the same.
+    Pass,
+    /// Readme and docs are not the same, but the build should continue.
+    Warn,
+    /// Readme and docs are not the same and the build should fail.
+    Fail,
+}
+
+impl SyncEnforcement {
+    /// Returns `true` if the build should fail.
+    pub fn should_fail(self) -> bool {
+        self == Self::Fail
+    }
+}
+
+/// Prints the synchronization check diagnostics as `cargo:warning=` build script output,
+/// one line per diagnostic.
+///
+/// Cargo does not support multi-line warnings,
+/// so each diagnostic is rendered by `MatchFailed::to_single_lines`.
+pub fn emit_cargo_warnings(result: &Result<(), CheckSyncError>) {
+    use std::println;
+
+    match result {
+        Ok(()) => {}
+        Err(CheckSyncError::MatchFailed(err)) => {
+            for line in err.to_single_lines() {
+                println!("cargo:warning={}", line);
+            }
+        }
+    }
+}
+
+/// Prints the synchronization check diagnostics as cargo warnings
+/// and decides whether the build should fail.
+///
+/// The build should fail on mismatch only if the `README_SYNC_ENFORCE` environment variable
+/// is set to `1` or `true`.
+///
+/// ```rust,no_run
+/// // build.rs
+/// use readme_sync::{check_sync, enforce_from_env, CMarkDocs, CMarkReadme, Package};
+///
+/// let package = Package::from_path(env!("CARGO_MANIFEST_DIR").into()).unwrap();
+/// let readme = CMarkReadme::from_package(&package).unwrap();
+/// let docs = CMarkDocs::from_package_with_default_config(&package).unwrap();
+/// if enforce_from_env(&check_sync(&readme, &docs)).should_fail() {
+///     std::process::exit(1);
+/// }
+/// ```
+pub fn enforce_from_env(result: &Result<(), CheckSyncError>) -> SyncEnforcement {
+    use std::println;
+
+    println!("cargo:rerun-if-env-changed={}", ENFORCE_ENV_VAR);
+    emit_cargo_warnings(result);
+    let is_enforced = std::env::var(ENFORCE_ENV_VAR)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    match (result, is_enforced) {
+        (Ok(()), _) => SyncEnforcement::Pass,
+        (Err(_), false) => SyncEnforcement::Warn,
+        (Err(_), true) => SyncEnforcement::Fail,
+    }
+}
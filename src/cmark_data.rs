@@ -1,20 +1,56 @@
+use core::iter::FusedIterator;
 use core::slice::Iter;
 use std::borrow::Cow;
+use std::path::Path;
 use std::string::String;
 use std::sync::Arc;
 use std::vec::Vec;
 
-use pulldown_cmark::Event;
+use pulldown_cmark::{CowStr, Event, LinkType};
 use thiserror::Error;
 
-use crate::{CMarkItem, File, FileDocs, TextSource};
+use crate::{
+    CMarkItem, DocsUrlVersion, File, FileDocs, FileFromPathError, Manifest, ParseProfile,
+    TextSource, DEFAULT_BLOB_BRANCH,
+};
 
 /// A `CMarkItem`s container storing a list of events with multiple transformation functions.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct CMarkData(Vec<Arc<CMarkItem>>);
 
 /// A `CMarkItem`s container iterator.
-pub type CMarkDataIter<'a> = Iter<'a, Arc<CMarkItem>>;
+///
+/// The items are yielded in the document order,
+/// the same order as the indices used by `CMarkData::get`.
+/// The iterator is double-ended, exact-sized and fused.
+#[derive(Clone, Debug)]
+pub struct CMarkDataIter<'a>(Iter<'a, Arc<CMarkItem>>);
+
+impl<'a> Iterator for CMarkDataIter<'a> {
+    type Item = &'a Arc<CMarkItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for CMarkDataIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl ExactSizeIterator for CMarkDataIter<'_> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl FusedIterator for CMarkDataIter<'_> {}
 
 impl CMarkData {
     /// Creates `CMarkData` from `CMarkItem`s.
@@ -27,14 +63,42 @@ impl CMarkData {
         Self::from_text_source(TextSource::File(file))
     }
 
+    /// Creates `CMarkData` from the Markdown file
+    /// with the specified path relative to the package root.
+    ///
+    /// The relative path is kept in the file, so diagnostics refer to it.
+    pub fn from_markdown_file(path: &Path, package_root: &Path) -> Result<Self, FileFromPathError> {
+        let file = File::from_path(path.to_path_buf(), Some(package_root))?;
+        Ok(Self::from_file(Arc::new(file)))
+    }
+
     /// Creates `CMarkData` from the specified `FileDocs`.
     pub fn from_file_docs(file_docs: Arc<FileDocs>) -> Self {
         Self::from_text_source(TextSource::FileDocs(file_docs))
     }
 
     /// Creates `CMarkData` from the specified `TextSource`.
+    ///
+    /// Tables extension is enabled, as both GitHub and rustdoc support it.
     pub fn from_text_source(text_source: TextSource) -> Self {
-        use crate::IntoStatic;
+        Self::from_text_source_with_options(text_source, pulldown_cmark::Options::ENABLE_TABLES)
+    }
+
+    /// Creates `CMarkData` from the specified `TextSource`
+    /// using the specified Markdown parser profile.
+    pub fn from_text_source_with_parse_profile(
+        text_source: TextSource,
+        profile: ParseProfile,
+    ) -> Self {
+        Self::from_text_source_with_options(text_source, profile.options())
+    }
+
+    /// Creates `CMarkData` from the specified `TextSource`
+    /// using the specified Markdown parser extensions.
+    pub fn from_text_source_with_options(
+        text_source: TextSource,
+        options: pulldown_cmark::Options,
+    ) -> Self {
         use pulldown_cmark::Parser;
 
         let text = match &text_source {
@@ -43,7 +107,7 @@ impl CMarkData {
         };
 
         Self(
-            Parser::new(text)
+            Parser::new_ext(text, options)
                 .into_offset_iter()
                 .map(|(event, range)| {
                     CMarkItem::from(event.into_static(), range, text_source.clone())
@@ -58,17 +122,63 @@ impl CMarkData {
         self.0
     }
 
-    /// Iterate over `CMarkItem`s.
+    /// Iterate over `CMarkItem`s in the document order.
     pub fn iter(&self) -> CMarkDataIter<'_> {
-        self.0.iter()
+        CMarkDataIter(self.0.iter())
     }
 
-    /// Iterate over pulldown-cmark events.
+    /// Iterate over pulldown-cmark events in the document order.
     pub fn iter_events(&self) -> impl Iterator<Item = &Event<'_>> {
         self.0.iter().filter_map(|item| item.event())
     }
 
-    fn map<F>(self, func: F) -> Self
+    /// Iterate over pulldown-cmark events with the indices of their items.
+    ///
+    /// The removed items have no events and are skipped,
+    /// so the indices could be used with `get` to get the item and its spans.
+    pub fn iter_events_indexed(&self) -> impl DoubleEndedIterator<Item = (usize, &Event<'_>)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, item)| item.event().map(|event| (idx, event)))
+    }
+
+    /// Returns the item with the specified index in the document order.
+    pub fn get(&self, index: usize) -> Option<&Arc<CMarkItem>> {
+        self.0.get(index)
+    }
+
+    /// Returns the number of items, including the removed ones.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the index of the last heading start item with the level in the specified range.
+    pub fn rposition_heading<R>(&self, level_range: R) -> Option<usize>
+    where
+        R: core::ops::RangeBounds<u32>,
+    {
+        use pulldown_cmark::Tag;
+
+        self.0.iter().rposition(|item| match item.event() {
+            Some(Event::Start(Tag::Heading { level, .. })) => {
+                level_range.contains(&heading_level(*level))
+            }
+            _ => false,
+        })
+    }
+
+    /// Renders the events as Markdown text.
+    pub fn to_markdown(&self) -> String {
+        crate::render_markdown(self.iter_events())
+    }
+
+    pub(crate) fn map<F>(self, func: F) -> Self
     where
         F: FnMut(Arc<CMarkItem>) -> Arc<CMarkItem>,
     {
@@ -130,6 +240,99 @@ fn merge_text_nodes(nodes: Vec<Arc<CMarkItem>>, text: String) -> Option<Arc<CMar
     }
 }
 
+impl CMarkData {
+    /// Replace whitespace runs in text events with single spaces
+    /// and trim whitespace at the start and at the end of text events.
+    ///
+    /// Code blocks, code spans and HTML are left as is.
+    /// Whitespace-only text events are removed.
+    /// Hard line breaks are separate events and are preserved.
+    pub fn collapse_whitespace(self) -> Self {
+        use crate::{CMarkItemAsModified, CMarkItemAsRemoved};
+        use pulldown_cmark::{CowStr, Tag, TagEnd};
+
+        let mut is_codeblock = false;
+
+        self.map(|node| {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(_))) => is_codeblock = true,
+                Some(Event::End(TagEnd::CodeBlock)) => is_codeblock = false,
+                Some(Event::Text(text)) if !is_codeblock => {
+                    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if collapsed.is_empty() {
+                        return node.into_removed(Cow::from("collapse_whitespace()"));
+                    }
+                    if collapsed != text.as_ref() {
+                        let event = Event::Text(CowStr::Boxed(collapsed.into_boxed_str()));
+                        return node.into_modified(event, Cow::from("collapse_whitespace()"));
+                    }
+                }
+                _ => {}
+            }
+            node
+        })
+    }
+}
+
+impl CMarkData {
+    /// Use the same nesting order for strong emphasis and emphasis wrapping the same content.
+    ///
+    /// Markdown `***text***` is parsed as emphasis containing strong emphasis,
+    /// while `**_text_**` is parsed as strong emphasis containing emphasis,
+    /// although both are rendered the same.
+    /// This transformation places strong emphasis outside in both cases.
+    pub fn canonicalize_emphasis_nesting(self) -> Self {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let mut items = self.0;
+        let mut matching_end = std::vec![None; items.len()];
+        let mut stack = Vec::new();
+        for (idx, node) in items.iter().enumerate() {
+            match node.event() {
+                Some(Event::Start(_)) => stack.push(idx),
+                Some(Event::End(_)) => {
+                    if let Some(start) = stack.pop() {
+                        matching_end[start] = Some(idx);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let note = || Cow::from("canonicalize_emphasis_nesting()");
+        for idx in 0..items.len().saturating_sub(1) {
+            let is_emphasis_with_strong = matches!(
+                (items[idx].event(), items[idx + 1].event()),
+                (
+                    Some(Event::Start(Tag::Emphasis)),
+                    Some(Event::Start(Tag::Strong))
+                )
+            );
+            if !is_emphasis_with_strong {
+                continue;
+            }
+            if let (Some(strong_end), Some(emphasis_end)) =
+                (matching_end[idx + 1], matching_end[idx])
+            {
+                if emphasis_end == strong_end + 1 {
+                    let swaps = [
+                        (idx, Event::Start(Tag::Strong)),
+                        (idx + 1, Event::Start(Tag::Emphasis)),
+                        (strong_end, Event::End(TagEnd::Emphasis)),
+                        (emphasis_end, Event::End(TagEnd::Strong)),
+                    ];
+                    for (idx, event) in swaps {
+                        items[idx] = Arc::clone(&items[idx]).into_modified(event, note());
+                    }
+                }
+            }
+        }
+
+        Self(items)
+    }
+}
+
 impl CMarkData {
     /// Increment levels of all headings.
     ///
@@ -138,64 +341,133 @@ impl CMarkData {
     /// Rustdoc automatically adds the header of a crate name and the first level headers are used for text sections.
     ///
     /// So it is necessary to increase the level of all headings in the documentation in order to synchronize the headings.
+    ///
+    /// The sixth level headings are left as is with a warning note,
+    /// use `increment_heading_levels_checked` to reject them instead.
     pub fn increment_heading_levels(self) -> Self {
+        self.apply(&crate::IncrementHeadingLevels)
+    }
+
+    /// Shift levels of all headings so the minimum used heading level equals the specified one.
+    ///
+    /// The relative heading depths are preserved,
+    /// the headings shifted below the sixth level are clamped to it with a separate note.
+    /// It allows to compare documents regardless of their original heading depth.
+    pub fn normalize_heading_levels(self, top_level: pulldown_cmark::HeadingLevel) -> Self {
         use crate::CMarkItemAsModified;
-        use pulldown_cmark::{Tag, TagEnd};
+        use pulldown_cmark::{HeadingLevel, Tag, TagEnd};
+
+        let min_level = self
+            .iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Heading { level, .. }) => Some(heading_level(*level)),
+                _ => None,
+            })
+            .min();
+        let shift = match min_level {
+            Some(min_level) => i64::from(heading_level(top_level)) - i64::from(min_level),
+            None => return self,
+        };
+        if shift == 0 {
+            return self;
+        }
 
+        let note = std::format!("normalize_heading_levels(top_level = {})", top_level);
+        let clamped_note = std::format!("{} clamped to {}", note, HeadingLevel::H6);
+        let shifted = |level: HeadingLevel| {
+            let shifted = i64::from(heading_level(level)) + shift;
+            match usize::try_from(shifted).ok().map(HeadingLevel::try_from) {
+                Some(Ok(level)) => (level, false),
+                _ => (HeadingLevel::H6, true),
+            }
+        };
         self.map(|node| {
-            let event = match node.event() {
+            let (event, is_clamped) = match node.event() {
                 Some(Event::Start(Tag::Heading {
                     level,
                     id,
                     classes,
                     attrs,
-                })) => Some(Event::Start(Tag::Heading {
-                    level: increase_heading_level(*level),
-                    id: id.clone(),
-                    classes: classes.clone(),
-                    attrs: attrs.clone(),
-                })),
+                })) => {
+                    let (level, is_clamped) = shifted(*level);
+                    let event = Event::Start(Tag::Heading {
+                        level,
+                        id: id.clone(),
+                        classes: classes.clone(),
+                        attrs: attrs.clone(),
+                    });
+                    (event, is_clamped)
+                }
                 Some(Event::End(TagEnd::Heading(level))) => {
-                    Some(Event::End(TagEnd::Heading(increase_heading_level(*level))))
+                    let (level, is_clamped) = shifted(*level);
+                    (Event::End(TagEnd::Heading(level)), is_clamped)
                 }
-                _ => None,
+                _ => return node,
             };
-            if let Some(event) = event {
-                node.into_modified(event, Cow::from("increment_heading_levels()"))
-            } else {
-                node
-            }
+            let note = if is_clamped { &clamped_note } else { &note };
+            node.into_modified(event, Cow::from(note.clone()))
         })
     }
 
+    /// Increment levels of all headings like `increment_heading_levels`,
+    /// but returns an error if there is a sixth level heading.
+    ///
+    /// The sixth level headings can not be incremented,
+    /// so they would become indistinguishable from the incremented fifth level ones.
+    pub fn increment_heading_levels_checked(self) -> Result<Self, HeadingLevelOverflowError> {
+        use pulldown_cmark::{HeadingLevel, Tag};
+
+        for (idx, node) in self.0.iter().enumerate() {
+            if let Some(Event::Start(Tag::Heading {
+                level: HeadingLevel::H6,
+                ..
+            })) = node.event()
+            {
+                return Err(HeadingLevelOverflowError {
+                    heading: heading_text(&self.0[idx..]).unwrap_or_default(),
+                    node: Arc::clone(node),
+                });
+            }
+        }
+        Ok(self.increment_heading_levels())
+    }
+
     /// Add a first level heading with the specified text.
     ///
     /// This function could be useful after heading level incremented.
     pub fn add_title(self, text: &str) -> Self {
-        use pulldown_cmark::{CowStr, HeadingLevel, Tag, TagEnd};
-        use std::string::ToString;
+        use pulldown_cmark::HeadingLevel;
+
+        let heading = crate::events::heading(HeadingLevel::H1, text, "add_title()");
+        self.splice(0, heading)
+    }
 
-        let heading = std::vec![
-            CMarkItem::new(
-                Event::Start(Tag::Heading {
-                    level: HeadingLevel::H1,
-                    id: None,
-                    classes: std::vec![],
-                    attrs: std::vec![]
-                }),
-                Cow::from("add_title()")
-            ),
-            CMarkItem::new(
-                Event::Text(CowStr::Boxed(text.to_string().into_boxed_str())),
-                Cow::from("add_title()")
-            ),
-            CMarkItem::new(
-                Event::End(TagEnd::Heading(HeadingLevel::H1)),
-                Cow::from("add_title()")
-            ),
-        ];
-
-        Self(heading.into_iter().chain(self.0).collect())
+    /// Insert the specified items at the specified position.
+    ///
+    /// Items can be created by the functions of the `events` module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of items.
+    pub fn splice(mut self, index: usize, items: Vec<Arc<CMarkItem>>) -> Self {
+        let _ = self.0.splice(index..index, items);
+        self
+    }
+
+    /// Append a section with the specified heading level, heading text and body.
+    pub fn push_section(
+        mut self,
+        heading_level: pulldown_cmark::HeadingLevel,
+        heading: &str,
+        body: CMarkData,
+    ) -> Self {
+        self.0.extend(crate::events::heading(
+            heading_level,
+            heading,
+            "push_section()",
+        ));
+        self.0.extend(body.0);
+        self
     }
 
     /// Removes first paragraph that contains only images and image-links,
@@ -225,7 +497,7 @@ impl CMarkData {
             if !paragraph.is_empty() {
                 if is_image {
                     let event = node.event();
-                    is_image = if let Some(Event::End(TagEnd::Image { .. })) = event {
+                    is_image = if let Some(Event::End(TagEnd::Image)) = event {
                         false
                     } else {
                         true
@@ -254,7 +526,7 @@ impl CMarkData {
                             is_image = true;
                         }
                         Some(Event::Start(Tag::Link { .. }))
-                        | Some(Event::End(TagEnd::Link { .. }))
+                        | Some(Event::End(TagEnd::Link))
                         | Some(Event::SoftBreak)
                         | None => {}
                         Some(_) => {
@@ -286,6 +558,187 @@ impl CMarkData {
         })
     }
 
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// starting with the capitalized alert kind followed by a colon, like `> Note: text`.
+    pub fn normalize_github_alerts(self) -> Self {
+        self.normalize_github_alerts_with(|kind| std::format!("{}: ", github_alert_name(kind)))
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// without any alert marker.
+    pub fn strip_github_alerts(self) -> Self {
+        self.normalize_github_alerts_with(|_| String::new())
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// starting with the text returned by the specified function.
+    ///
+    /// The text is prepended to the first text of the blockquote,
+    /// an empty text removes the alert marker only.
+    pub fn normalize_github_alerts_with<F>(self, mut func: F) -> Self
+    where
+        F: FnMut(pulldown_cmark::BlockQuoteKind) -> String,
+    {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let note = || Cow::from("normalize_github_alerts()");
+        let mut result = Vec::new();
+        let mut prefix: Option<String> = None;
+        let mut is_in_paragraph = false;
+
+        for node in self.0.into_iter() {
+            match node.event() {
+                Some(Event::Start(Tag::BlockQuote(Some(kind)))) => {
+                    prefix = Some(func(*kind)).filter(|prefix| !prefix.is_empty());
+                    is_in_paragraph = false;
+                    let event = Event::Start(Tag::BlockQuote(None));
+                    result.push(node.into_modified(event, note()));
+                }
+                Some(Event::End(TagEnd::BlockQuote(Some(_)))) => {
+                    if let Some(prefix) = prefix.take() {
+                        result.extend(crate::events::paragraph(prefix.trim_end(), note()));
+                    }
+                    let event = Event::End(TagEnd::BlockQuote(None));
+                    result.push(node.into_modified(event, note()));
+                }
+                Some(Event::Text(text)) if prefix.is_some() => {
+                    let text = prefix.take().unwrap_or_default() + text;
+                    let event = Event::Text(CowStr::Boxed(text.into_boxed_str()));
+                    result.push(node.into_modified(event, note()));
+                }
+                Some(Event::Start(Tag::Paragraph)) => {
+                    is_in_paragraph = true;
+                    result.push(node);
+                }
+                None => result.push(node),
+                Some(_) => {
+                    if let Some(prefix) = prefix.take() {
+                        if is_in_paragraph {
+                            let event = Event::Text(CowStr::Boxed(prefix.into_boxed_str()));
+                            result.push(CMarkItem::new(event, note()));
+                        } else {
+                            // The alert body does not start with a paragraph,
+                            // so the prefix is placed in its own paragraph.
+                            result.extend(crate::events::paragraph(prefix.trim_end(), note()));
+                        }
+                    }
+                    result.push(node);
+                }
+            }
+        }
+
+        Self(result)
+    }
+
+    /// Remove metadata blocks, like YAML front matter,
+    /// parsed with the metadata blocks parser extensions enabled.
+    pub fn remove_metadata_blocks(self) -> Self {
+        self.remove_metadata_blocks_with(Cow::from("remove_metadata_blocks()"))
+    }
+
+    /// Remove Jekyll-style front matter, like `---\ntitle: Crate\n---`, at the start of the file.
+    ///
+    /// If the metadata blocks parser extension is not enabled, the front matter is parsed
+    /// as a thematic break followed by a setext heading with `key: value` text,
+    /// such leading thematic break and heading are removed too.
+    pub fn remove_front_matter(self) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::{HeadingLevel, Tag, TagEnd};
+
+        let note = || Cow::from("remove_front_matter()");
+        let data = self.remove_metadata_blocks_with(note());
+        let events: Vec<_> = data
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, node)| Some((idx, node.event()?)))
+            .collect();
+        let len = match events.as_slice() {
+            [(_, Event::Rule), (_, Event::Start(Tag::Heading { level, .. })), rest @ ..]
+                if *level == HeadingLevel::H2 =>
+            {
+                let end = rest
+                    .iter()
+                    .position(|(_, event)| !matches!(event, Event::Text(_) | Event::SoftBreak));
+                match end.map(|end| (&rest[..end], &rest[end])) {
+                    Some((
+                        [(_, Event::Text(first_line)), ..],
+                        (idx, Event::End(TagEnd::Heading(HeadingLevel::H2))),
+                    )) if first_line.contains(':') => Some(idx + 1),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        match len {
+            Some(len) => {
+                let mut nodes = data.0;
+                let rest = nodes.split_off(len);
+                let mut result = std::vec![nodes.into_removed(note())];
+                result.extend(rest);
+                Self(result)
+            }
+            None => data,
+        }
+    }
+
+    fn remove_metadata_blocks_with(self, note: Cow<'static, str>) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use core::mem::take;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let mut result = Vec::new();
+        let mut block = Vec::new();
+
+        for node in self.0.into_iter() {
+            match node.event() {
+                Some(Event::Start(Tag::MetadataBlock(_))) if block.is_empty() => block.push(node),
+                Some(Event::End(TagEnd::MetadataBlock(_))) if !block.is_empty() => {
+                    block.push(node);
+                    result.push(take(&mut block).into_removed(note.clone()));
+                }
+                _ if !block.is_empty() => block.push(node),
+                _ => result.push(node),
+            }
+        }
+        result.append(&mut block);
+
+        Self(result)
+    }
+
+    /// Remove all thematic breaks, like `---` or `***`.
+    ///
+    /// Readmes often separate badges from the description with a thematic break
+    /// that has no equivalent in the docs.
+    pub fn remove_thematic_breaks(self) -> Self {
+        self.remove_thematic_breaks_with(usize::MAX, Cow::from("remove_thematic_breaks()"))
+    }
+
+    /// Remove the specified number of the first thematic breaks.
+    pub fn remove_first_thematic_breaks(self, count: usize) -> Self {
+        self.remove_thematic_breaks_with(
+            count,
+            Cow::from(std::format!(
+                "remove_first_thematic_breaks(count = {})",
+                count
+            )),
+        )
+    }
+
+    fn remove_thematic_breaks_with(self, mut count: usize, note: Cow<'static, str>) -> Self {
+        use crate::CMarkItemAsRemoved;
+
+        self.map(|node| match node.event() {
+            Some(Event::Rule) if count > 0 => {
+                count -= 1;
+                node.into_removed(note.clone())
+            }
+            _ => node,
+        })
+    }
+
     /// Remove section with the specified heading text and level and its subsections.
     pub fn remove_section(self, heading: &str, level: u32) -> Self {
         use core::mem::take;
@@ -322,406 +775,3824 @@ impl CMarkData {
         Self(result)
     }
 
-    /// Remove sections with heading `Documentation` and level 2.
-    pub fn remove_documentation_section(self) -> Self {
-        self.remove_section("Documentation", 2)
+    /// Remove sections with the specified heading texts and levels and their subsections
+    /// in a single pass.
+    ///
+    /// Like `remove_section`, only the first section matching every heading is removed,
+    /// the order of the specified headings does not matter.
+    pub fn remove_sections(self, sections: &[(&str, u32)]) -> Self {
+        let mut is_removed = std::vec![false; sections.len()];
+        self.remove_sections_with(|text, level| {
+            let section_idx = sections.iter().enumerate().position(
+                |(section_idx, &(heading, section_level))| {
+                    !is_removed[section_idx] && heading == text && section_level == level
+                },
+            )?;
+            is_removed[section_idx] = true;
+            let (heading, level) = sections[section_idx];
+            Some(remove_section_note(heading, level))
+        })
     }
-}
 
-fn into_removed_section_if_matched(
-    section: Vec<Arc<CMarkItem>>,
-    heading: &str,
-    level: u32,
-) -> (Vec<Arc<CMarkItem>>, bool) {
-    use crate::CMarkItemAsRemoved;
-    use std::vec;
+    /// Remove all sections and their subsections with headings matching the specified predicate.
+    ///
+    /// The predicate is called with the heading plain text and level,
+    /// and is not called for the subsections of the removed sections.
+    pub fn remove_section_by_predicate<F>(self, mut predicate: F) -> Self
+    where
+        F: FnMut(&str, u32) -> bool,
+    {
+        let note = Cow::from("remove_section_by_predicate()");
+        self.remove_sections_with(|text, level| predicate(text, level).then(|| note.clone()))
+    }
 
-    if is_matched_section(&section, heading, level) {
-        (
-            vec![section.into_removed(Cow::from(std::format!(
-                "remove_section(name = \"{}\", level = {})",
-                heading,
-                level
-            )))],
-            true,
-        )
-    } else {
-        (section, false)
+    /// Remove all sections and their subsections with headings
+    /// for which the specified function returns the removal note.
+    ///
+    /// The function is called with the heading plain text and level,
+    /// and is not called for the subsections of the removed sections.
+    fn remove_sections_with<F>(self, mut func: F) -> Self
+    where
+        F: FnMut(&str, u32) -> Option<Cow<'static, str>>,
+    {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::Tag;
+
+        let mut removed: Option<(Vec<Arc<CMarkItem>>, u32, Cow<'static, str>)> = None;
+        let mut result = Vec::new();
+
+        for (idx, node) in self.0.iter().enumerate() {
+            if let Some(Event::Start(Tag::Heading { level, .. })) = node.event() {
+                let level = heading_level(*level);
+                if matches!(&removed, Some((_, removed_level, _)) if level <= *removed_level) {
+                    if let Some((nodes, _, note)) = removed.take() {
+                        result.push(nodes.into_removed(note));
+                    }
+                }
+                if removed.is_none() {
+                    let text = heading_text(&self.0[idx..]).unwrap_or_default();
+                    if let Some(note) = func(&text, level) {
+                        removed = Some((Vec::new(), level, note));
+                    }
+                }
+            }
+            match &mut removed {
+                Some((nodes, _, _)) => nodes.push(Arc::clone(node)),
+                None => result.push(Arc::clone(node)),
+            }
+        }
+        if let Some((nodes, _, note)) = removed {
+            result.push(nodes.into_removed(note));
+        }
+
+        Self(result)
     }
-}
 
-fn is_matched_section(section: &[Arc<CMarkItem>], heading: &str, level: u32) -> bool {
-    use pulldown_cmark::Tag;
+    /// Remove everything from the first section with the specified heading text and level
+    /// to the end of the document.
+    ///
+    /// The data is left unchanged if the section is not found.
+    pub fn remove_sections_from(self, heading: &str, level: u32) -> Self {
+        use crate::CMarkItemAsRemoved;
 
-    let first_event = section.first().and_then(|node| node.event());
-    let second_event = section.get(1).and_then(|node| node.event());
-    if let (
-        Some(Event::Start(Tag::Heading {
-            level: node_level, ..
-        })),
-        Some(Event::Text(node_text)),
-    ) = (first_event, second_event)
-    {
-        heading_level(*node_level) == level && node_text.as_ref() == heading
-    } else {
-        false
+        let mut nodes = self.0;
+        match (0..nodes.len()).find(|&idx| is_matched_section(&nodes[idx..], heading, level)) {
+            Some(start) => {
+                let removed = nodes.split_off(start);
+                nodes.push(removed.into_removed(Cow::from(std::format!(
+                    "remove_sections_from(name = \"{}\", level = {})",
+                    heading,
+                    level
+                ))));
+                Self(nodes)
+            }
+            None => Self(nodes),
+        }
     }
-}
 
-impl CMarkData {
-    /// Returns self if absolute blob links to the specified repository not found,
-    /// otherwise returns an error.
-    pub fn disallow_absolute_blob_links(
-        self,
-        repository_url: &str,
-    ) -> Result<Self, DisallowUrlsWithPrefixError> {
-        self.disallow_urls_with_prefix(&blob_path_prefix(repository_url))
+    /// Returns `true` if the section with the specified heading text and level exists.
+    pub fn has_section(&self, heading: &str, level: u32) -> bool {
+        (0..self.0.len()).any(|idx| is_matched_section(&self.0[idx..], heading, level))
     }
 
-    /// Returns self if absolute docs links to the specified repository not found,
-    /// otherwise returns an error.
-    pub fn disallow_absolute_docs_links(
-        self,
-        package_name: &str,
-        documentation_url: &str,
-    ) -> Result<Self, DisallowUrlsWithPrefixError> {
-        self.disallow_urls_with_prefix(&docs_path_prefix(package_name, documentation_url))
+    /// Keep only the first section with the specified heading text and level
+    /// and its subsections.
+    ///
+    /// If the section is not found, all events are removed,
+    /// `has_section` could be used to check it beforehand.
+    pub fn extract_section(self, heading: &str, level: u32) -> Self {
+        self.extract_section_with(heading, level, false, "extract_section")
     }
 
-    /// Returns self if links with the specified prefix not found, otherwise returns an error.
-    pub fn disallow_urls_with_prefix(
+    /// Keep only the contents of the first section with the specified heading text and level
+    /// and its subsections without the section heading.
+    ///
+    /// See `extract_section` for details.
+    pub fn extract_section_body(self, heading: &str, level: u32) -> Self {
+        self.extract_section_with(heading, level, true, "extract_section_body")
+    }
+
+    fn extract_section_with(
         self,
-        prefix: &str,
-    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        heading: &str,
+        level: u32,
+        is_heading_removed: bool,
+        name: &str,
+    ) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let note = Cow::from(std::format!(
+            "{}(name = \"{}\", level = {})",
+            name,
+            heading,
+            level
+        ));
+        let mut nodes = self.0;
+        let start = (0..nodes.len()).find(|&idx| is_matched_section(&nodes[idx..], heading, level));
+        let start = match start {
+            Some(start) => start,
+            None if nodes.is_empty() => return Self(nodes),
+            None => return Self(std::vec![nodes.into_removed(note)]),
+        };
+        let end = (start + 1..nodes.len())
+            .find(|&idx| {
+                matches!(
+                    nodes[idx].event(),
+                    Some(Event::Start(Tag::Heading { level: node_level, .. }))
+                        if heading_level(*node_level) <= level
+                )
+            })
+            .unwrap_or(nodes.len());
+        let body_start = if is_heading_removed {
+            (start..end)
+                .find(|&idx| matches!(nodes[idx].event(), Some(Event::End(TagEnd::Heading(_)))))
+                .map_or(end, |idx| idx + 1)
+        } else {
+            start
+        };
+
+        let after = nodes.split_off(end);
+        let section = nodes.split_off(body_start);
+        let mut result = Vec::new();
+        if !nodes.is_empty() {
+            result.push(nodes.into_removed(note.clone()));
+        }
+        result.extend(section);
+        if !after.is_empty() {
+            result.push(after.into_removed(note));
+        }
+        Self(result)
+    }
+
+    /// Keep only the sections with the specified heading texts and level and their subsections.
+    ///
+    /// The preamble before the first heading of the specified level is kept,
+    /// as are the sections of the higher levels, like the title before level 2 sections.
+    /// All occurrences of the specified sections are kept,
+    /// the other sections are marked as removed.
+    pub fn keep_sections(self, headings: &[&str], level: u32) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use core::mem::take;
         use pulldown_cmark::Tag;
-        use std::string::ToString;
 
-        for node in &self.0 {
-            if let Some(Event::Start(Tag::Link { dest_url, .. })) = node.event() {
-                if dest_url.starts_with(prefix) {
-                    return Err(DisallowUrlsWithPrefixError::PrefixFound {
-                        url: dest_url.as_ref().to_string(),
-                        prefix: prefix.to_string(),
-                    });
+        let note = std::format!("keep_sections(names = {:?}, level = {})", headings, level);
+        let is_removed = |section: &[Arc<CMarkItem>]| {
+            matches!(
+                section.first().and_then(|node| node.event()),
+                Some(Event::Start(Tag::Heading { level: node_level, .. }))
+                    if heading_level(*node_level) == level
+            ) && !headings
+                .iter()
+                .any(|heading| is_matched_section(section, heading, level))
+        };
+
+        let mut section = Vec::new();
+        let mut result = Vec::new();
+        for node in self.0 {
+            if let Some(Event::Start(Tag::Heading {
+                level: node_level, ..
+            })) = node.event()
+            {
+                if heading_level(*node_level) <= level {
+                    if is_removed(&section) {
+                        result.push(take(&mut section).into_removed(Cow::from(note.clone())));
+                    } else {
+                        result.append(&mut section);
+                    }
                 }
             }
+            section.push(node);
+        }
+        if is_removed(&section) {
+            result.push(section.into_removed(Cow::from(note)));
+        } else {
+            result.append(&mut section);
         }
 
-        Ok(self)
+        Self(result)
     }
 
-    /// Convert all relative links into absolute ones using
-    /// the repository url as the root address.
-    pub fn use_absolute_blob_urls(self, repository_url: &str) -> Self {
-        self.with_absolute_urls(&blob_path_prefix(repository_url))
+    /// Remove sections with heading `Documentation` and level 2.
+    pub fn remove_documentation_section(self) -> Self {
+        self.remove_section("Documentation", 2)
     }
 
-    /// Convert all relative links into absolute ones using
-    /// the package documentation url as the root address.
-    pub fn use_absolute_docs_urls(self, package_name: &str, documentation_url: &str) -> Self {
-        self.with_absolute_urls(&docs_path_prefix(package_name, documentation_url))
-    }
+    /// Keep only the first section and its subsections.
+    ///
+    /// The section starts with the first heading and ends before the next heading
+    /// of the same or higher level. Other events are marked as removed.
+    pub fn first_section(self) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::Tag;
 
-    /// Convert all relative links into absolute ones using specified url prefix.
-    pub fn with_absolute_urls(self, prefix: &str) -> Self {
-        use std::format;
+        let note = || Cow::from("first_section()");
+        let mut before = Vec::new();
+        let mut section = Vec::new();
+        let mut after = Vec::new();
+        let mut section_level = None;
 
-        self.map_links(
-            |url| {
-                if !is_absolute_url(url) && !is_fragment(url) {
-                    Cow::from([prefix, url].concat())
-                } else {
-                    Cow::from(url)
+        for node in self.0.into_iter() {
+            if let Some(Event::Start(Tag::Heading { level, .. })) = node.event() {
+                match section_level {
+                    None => section_level = Some(*level),
+                    Some(section_level) if *level <= section_level => {
+                        after.push(node);
+                        continue;
+                    }
+                    _ => {}
                 }
-            },
-            Cow::from(format!("with_absolute_urls(prefix = \"{}\")", prefix)),
-        )
+            }
+            if !after.is_empty() {
+                after.push(node);
+            } else if section_level.is_some() {
+                section.push(node);
+            } else {
+                before.push(node);
+            }
+        }
+
+        let mut result = Vec::new();
+        if !before.is_empty() {
+            result.push(before.into_removed(note()));
+        }
+        result.append(&mut section);
+        if !after.is_empty() {
+            result.push(after.into_removed(note()));
+        }
+        Self(result)
     }
 
-    /// Converts all links with function `func` applied to each link address.
-    pub fn map_links<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
-    where
-        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
-    {
-        use crate::CMarkItemAsModified;
-        use pulldown_cmark::{CowStr, Tag};
+    /// Keep only the first `count` top-level blocks, like paragraphs, code blocks and lists.
+    ///
+    /// A heading ends the leading blocks immediately. Other events are marked as removed.
+    pub fn take_leading_blocks(self, count: usize) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::Tag;
 
-        fn map_link<'a, F>(tag: &Tag<'a>, mut func: F) -> Option<Tag<'a>>
-        where
-            for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
-        {
-            if let Tag::Link {
-                link_type,
-                dest_url,
-                title,
-                id,
-            } = tag
-            {
-                let new_url = func(dest_url.as_ref());
-                if dest_url.as_ref() != new_url.as_ref() {
-                    let title = title.clone();
-                    return Some(Tag::Link {
-                        link_type: *link_type,
-                        dest_url: CowStr::from(new_url.into_owned()),
-                        title: title.clone(),
-                        id: id.clone(),
-                    });
+        let mut leading = Vec::new();
+        let mut after = Vec::new();
+        let mut depth = 0_usize;
+        let mut blocks = 0;
+
+        for node in self.0.into_iter() {
+            if !after.is_empty() {
+                after.push(node);
+                continue;
+            }
+            let is_after = match node.event() {
+                Some(Event::Start(tag)) if depth == 0 => {
+                    blocks == count || matches!(tag, Tag::Heading { .. })
                 }
+                Some(Event::Start(_)) | Some(Event::End(_)) | None => false,
+                Some(_) => depth == 0 && blocks == count,
+            };
+            if is_after {
+                after.push(node);
+                continue;
             }
-            None
+            match node.event() {
+                Some(Event::Start(_)) => depth += 1,
+                Some(Event::End(_)) => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        blocks += 1;
+                    }
+                }
+                Some(_) if depth == 0 => blocks += 1,
+                _ => {}
+            }
+            leading.push(node);
+        }
+
+        if !after.is_empty() {
+            leading.push(after.into_removed(Cow::from(std::format!(
+                "take_leading_blocks(count = {})",
+                count
+            ))));
+        }
+        Self(leading)
+    }
+
+    /// Remove the first level heading if it is the first block.
+    ///
+    /// This function could be useful to compare the texts following the title.
+    pub fn remove_title(self) -> Self {
+        self.apply(&crate::RemoveTitle)
+    }
+
+    /// Append events of the other data to the end of this data.
+    ///
+    /// Adjacent text events at the seam are concatenated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either data fails the `validate_structure` check.
+    pub fn append(mut self, mut other: Self) -> Self {
+        if let Err(err) = self.validate_structure() {
+            panic!("data appended to has unbalanced events: {}", err);
+        }
+        if let Err(err) = other.validate_structure() {
+            panic!("appended data has unbalanced events: {}", err);
         }
+        self.0.append(&mut other.0);
+        self.concat_texts()
+    }
+}
 
+impl CMarkData {
+    /// Replace the text of headings with the text returned by function `func`.
+    ///
+    /// The function is called with the heading plain text and level,
+    /// inline code, emphasis and other inline markup are flattened to their text.
+    /// If it returns a new text, all the heading inner events are replaced by a single text event
+    /// and the heading start and end events are kept unchanged.
+    pub fn map_headings<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        F: FnMut(&str, u32) -> Option<String>,
+    {
         let note = note.into();
-        self.map(|node| {
-            let event = match node.event() {
-                Some(Event::Start(tag)) => map_link(tag, &mut func).map(Event::Start),
-                _ => None,
+        self.map_heading_nodes(|heading| {
+            let level = match heading.first().and_then(|node| node.event()) {
+                Some(Event::Start(pulldown_cmark::Tag::Heading { level, .. })) => {
+                    heading_level(*level)
+                }
+                _ => return heading,
             };
-            match event {
-                Some(event) => node.into_modified(event, note.clone()),
-                None => node,
+            let text = heading_text(&heading).unwrap_or_default();
+            match func(&text, level) {
+                Some(new_text) if new_text != text => {
+                    replace_heading_text(heading, new_text, note.clone())
+                }
+                _ => heading,
             }
         })
     }
-}
 
-fn is_absolute_url(url: &str) -> bool {
-    is_url_with_scheme(url)
-}
+    /// Replace the text of headings with the specified text and level.
+    ///
+    /// The heading text is compared with inline code, emphasis and other inline markup flattened.
+    pub fn rename_section(self, from: &str, to: &str, level: u32) -> Self {
+        let note = std::format!(
+            "rename_section(from = \"{}\", to = \"{}\", level = {})",
+            from,
+            to,
+            level
+        );
+        self.map_headings(
+            |text, heading_level| {
+                if heading_level == level && text == from {
+                    Some(String::from(to))
+                } else {
+                    None
+                }
+            },
+            note,
+        )
+    }
 
-fn is_fragment(url: &str) -> bool {
-    url.starts_with('#')
-}
+    /// Replace the text of headings that matches the first value of an alias pair
+    /// with the second value of that pair.
+    pub(crate) fn rename_headings(self, aliases: &[(String, String)]) -> Self {
+        self.map_heading_nodes(|heading| {
+            let text = heading_text(&heading).unwrap_or_default();
+            let alias = aliases
+                .iter()
+                .find(|(from, to)| *from == text && *to != text);
+            match alias {
+                Some((from, to)) => {
+                    let note = Cow::from(std::format!(
+                        "rename_headings(from = \"{}\", to = \"{}\")",
+                        from,
+                        to
+                    ));
+                    replace_heading_text(heading, to.clone(), note)
+                }
+                None => heading,
+            }
+        })
+    }
 
-#[allow(clippy::match_like_matches_macro)] // requires minimum rustc version 1.42.0
-fn is_url_with_scheme(url: &str) -> bool {
-    if let Some(scheme) = url.split("//").next() {
-        if scheme.is_empty() {
-            return true;
-        } else if scheme.ends_with(':') && scheme.len() >= 2 {
-            let scheme = &scheme[..scheme.len() - 1];
-            if let b'a'..=b'z' | b'A'..=b'Z' = scheme.as_bytes()[0] {
-                return scheme.as_bytes()[1..].iter().all(|ch| {
-                    if let b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'+' | b'.' | b'-' = ch {
-                        true
-                    } else {
-                        false
-                    }
-                });
+    /// Replaces every heading nodes, from its start to its end, with the nodes returned by `func`.
+    fn map_heading_nodes<F>(self, mut func: F) -> Self
+    where
+        F: FnMut(Vec<Arc<CMarkItem>>) -> Vec<Arc<CMarkItem>>,
+    {
+        use core::mem::take;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let mut result = Vec::new();
+        let mut heading = Vec::new();
+
+        for node in self.0.into_iter() {
+            match (heading.is_empty(), node.event()) {
+                (true, Some(Event::Start(Tag::Heading { .. }))) => heading.push(node),
+                (true, _) => result.push(node),
+                (false, Some(Event::End(TagEnd::Heading(_)))) => {
+                    heading.push(node);
+                    result.append(&mut func(take(&mut heading)));
+                }
+                (false, _) => heading.push(node),
             }
         }
-    }
-    false
-}
 
-fn without_trailing_slash(value: &str) -> &str {
-    match value.as_bytes().last() {
-        Some(b'/') => &value[..value.len() - 1],
-        _ => value,
+        result.append(&mut heading);
+        Self(result)
     }
 }
 
-fn blob_path_prefix(repository_url: &str) -> String {
-    use std::string::ToString;
-    without_trailing_slash(repository_url).to_string() + "/blob/master/"
-}
-
-fn docs_path_prefix(package_name: &str, documentation_url: &str) -> String {
-    use std::string::ToString;
+/// Replaces the inner nodes of the heading with a single text node.
+fn replace_heading_text(
+    mut heading: Vec<Arc<CMarkItem>>,
+    text: String,
+    note: Cow<'static, str>,
+) -> Vec<Arc<CMarkItem>> {
+    use crate::CMarkItemAsModified;
+    use pulldown_cmark::CowStr;
 
-    let url = without_trailing_slash(documentation_url);
-    let name = package_name.to_string().replace('-', "_");
-    [url, "/*/", &name, "/"].concat()
+    let last = heading.pop().unwrap();
+    let inner = heading.split_off(1);
+    let event = Event::Text(CowStr::Boxed(text.into_boxed_str()));
+    let node = if inner.is_empty() {
+        CMarkItem::new(event, note)
+    } else {
+        inner.into_modified(event, note)
+    };
+    heading.push(node);
+    heading.push(last);
+    heading
 }
 
-impl CMarkData {
-    /// Remove the specified fenced code block tag.
-    pub fn remove_codeblock_tag(self, tag: &str) -> Self {
-        self.remove_codeblock_tags(&[tag])
-    }
-
-    /// Remove the specified fenced code block tags.
-    pub fn remove_codeblock_tags(self, tags: &[&str]) -> Self {
-        use crate::CMarkItemAsModified;
+fn into_removed_section_if_matched(
+    section: Vec<Arc<CMarkItem>>,
+    heading: &str,
+    level: u32,
+) -> (Vec<Arc<CMarkItem>>, bool) {
+    use crate::CMarkItemAsRemoved;
+    use std::vec;
 
-        self.map(|node| {
-            let event = match node.event() {
-                Some(Event::Start(tag)) => remove_codeblock_tag_tags(tag, tags).map(Event::Start),
-                _ => None,
-            };
-            match event {
-                Some(event) => node.into_modified(
-                    event,
-                    Cow::from(std::format!("remove_codeblock_tags(tags = {:?})", tags)),
-                ),
-                None => node,
-            }
-        })
+    if is_matched_section(&section, heading, level) {
+        (
+            vec![section.into_removed(remove_section_note(heading, level))],
+            true,
+        )
+    } else {
+        (section, false)
     }
 }
 
-fn remove_codeblock_tag_tags<'a>(
-    event_tag: &pulldown_cmark::Tag<'a>,
-    tags: &[&str],
-) -> Option<pulldown_cmark::Tag<'a>> {
-    use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+fn remove_section_note(heading: &str, level: u32) -> Cow<'static, str> {
+    Cow::from(std::format!(
+        "remove_section(name = \"{}\", level = {})",
+        heading,
+        level
+    ))
+}
 
-    if let Tag::CodeBlock(CodeBlockKind::Fenced(ref node_tags)) = event_tag {
-        let has_tags = node_tags
-            .split(',')
-            .any(|node_tag| tags.iter().any(|tag| &node_tag == tag));
-        if has_tags {
-            let node_tags: Vec<_> = node_tags
-                .split(',')
-                .filter(|node_tag| !tags.iter().any(|tag| node_tag == tag))
-                .collect();
-            let node_tags = CowStr::Boxed(node_tags.join(",").into_boxed_str());
-            return Some(Tag::CodeBlock(CodeBlockKind::Fenced(node_tags)));
+/// Returns `true` if the section starts with the heading of the specified level and text.
+///
+/// The heading text is compared with inline code, emphasis and other inline markup flattened.
+fn is_matched_section(section: &[Arc<CMarkItem>], heading: &str, level: u32) -> bool {
+    use pulldown_cmark::Tag;
+
+    match section.first().and_then(|node| node.event()) {
+        Some(Event::Start(Tag::Heading {
+            level: node_level, ..
+        })) if heading_level(*node_level) == level => {
+            heading_text(section).as_deref() == Some(heading)
         }
+        _ => false,
     }
-    None
 }
 
 impl CMarkData {
-    /// Remove fenced code block tags that are used by `cargo test`.
+    /// Returns self if absolute blob links to the specified repository not found,
+    /// otherwise returns an error.
     ///
-    /// See <https://doc.rust-lang.org/rustdoc/documentation-tests.html> for more details.
-    pub fn remove_codeblock_rust_test_tags(self) -> Self {
-        use crate::codeblock_rust_test_tags;
-
-        self.remove_codeblock_tags(codeblock_rust_test_tags())
+    /// The `DEFAULT_BLOB_BRANCH` repository branch is used.
+    pub fn disallow_absolute_blob_links(
+        self,
+        repository_url: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.disallow_absolute_blob_links_with_branch(repository_url, DEFAULT_BLOB_BRANCH)
     }
 
-    /// Use the specified codeblock tag, if they are not specified
-    pub fn use_default_codeblock_tag(self, tag: &str) -> Self {
-        use crate::CMarkItemAsModified;
+    /// Returns self if absolute blob links to the specified repository branch not found,
+    /// otherwise returns an error.
+    pub fn disallow_absolute_blob_links_with_branch(
+        self,
+        repository_url: &str,
+        branch: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.disallow_urls_with_prefix(&blob_path_prefix(repository_url, branch))
+    }
+
+    /// Returns self if absolute docs links to the specified repository not found,
+    /// otherwise returns an error.
+    pub fn disallow_absolute_docs_links(
+        self,
+        package_name: &str,
+        documentation_url: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.disallow_urls_with_prefix(&docs_path_prefix(package_name, documentation_url))
+    }
+
+    /// Returns self if absolute links to the manifest package documentation not found,
+    /// otherwise returns an error.
+    ///
+    /// Both the version wildcard and the manifest version documentation urls are disallowed.
+    pub(crate) fn disallow_absolute_package_docs_links(
+        self,
+        manifest: &Manifest,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.disallow_urls_with_prefix(
+            &manifest.docs_url_prefix_with_version(DocsUrlVersion::Wildcard),
+        )?
+        .disallow_urls_with_prefix(&manifest.docs_url_prefix_with_version(DocsUrlVersion::Manifest))
+    }
+
+    /// Returns self if links with the specified prefix not found, otherwise returns an error.
+    ///
+    /// The url scheme and host are compared case-insensitively,
+    /// and the prefix ending with `/` also matches the url without it,
+    /// like `https://docs.rs/foo/*/foo#features` for the `https://docs.rs/foo/*/foo/` prefix.
+    /// Fragment-only links, like `#usage`, never match an absolute prefix.
+    pub fn disallow_urls_with_prefix(
+        self,
+        prefix: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        use pulldown_cmark::Tag;
+        use std::string::ToString;
+
+        for node in &self.0 {
+            if let Some(Event::Start(Tag::Link { dest_url, .. })) = node.event() {
+                if strip_url_prefix(dest_url, prefix).is_some() {
+                    return Err(DisallowUrlsWithPrefixError::PrefixFound {
+                        url: dest_url.as_ref().to_string(),
+                        prefix: prefix.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Returns self if no link or image urls match the specified predicate,
+    /// otherwise returns an error with the first matching url and the description.
+    ///
+    /// The description should explain which urls are disallowed,
+    /// like `links to docs.rs for the old major version`.
+    pub fn disallow_urls_matching<F>(
+        self,
+        mut predicate: F,
+        description: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        use pulldown_cmark::Tag;
+        use std::string::ToString;
+
+        for node in &self.0 {
+            if let Some(Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. })) =
+                node.event()
+            {
+                if predicate(dest_url) {
+                    return Err(DisallowUrlsWithPrefixError::MatchFound {
+                        url: dest_url.as_ref().to_string(),
+                        description: description.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the repository url as the root address.
+    ///
+    /// The `DEFAULT_BLOB_BRANCH` repository branch is used.
+    /// Returns an error if an url points above the repository root.
+    pub fn use_absolute_blob_urls(
+        self,
+        repository_url: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.use_absolute_blob_urls_with_branch(repository_url, DEFAULT_BLOB_BRANCH)
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the repository url and the specified branch as the root address.
+    ///
+    /// Returns an error if an url points above the repository root.
+    pub fn use_absolute_blob_urls_with_branch(
+        self,
+        repository_url: &str,
+        branch: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.use_absolute_blob_urls_with_branch_and_subpath(repository_url, branch, "")
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the repository url and the specified repository subpath as the root address.
+    ///
+    /// The subpath is the directory of the readme file relative to the repository root
+    /// with `/` separators, for example `crates/foo`.
+    /// The `DEFAULT_BLOB_BRANCH` repository branch is used.
+    /// Returns an error if an url points above the repository root.
+    pub fn use_absolute_blob_urls_with_subpath(
+        self,
+        repository_url: &str,
+        subpath: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.use_absolute_blob_urls_with_branch_and_subpath(
+            repository_url,
+            DEFAULT_BLOB_BRANCH,
+            subpath,
+        )
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the repository url, the specified branch and repository subpath as the root address.
+    ///
+    /// See `use_absolute_blob_urls_with_subpath` for details.
+    pub fn use_absolute_blob_urls_with_branch_and_subpath(
+        self,
+        repository_url: &str,
+        branch: &str,
+        subpath: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        self.try_with_absolute_urls(&blob_path_prefix(repository_url, branch), subpath)
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the package documentation url as the root address.
+    pub fn use_absolute_docs_urls(self, package_name: &str, documentation_url: &str) -> Self {
+        self.with_absolute_urls(&docs_path_prefix(package_name, documentation_url))
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the package documentation url and the specified version as the root address.
+    ///
+    /// Unlike `use_absolute_docs_urls`, docs.rs urls contain the concrete version,
+    /// like the links generated by rustdoc on docs.rs.
+    pub fn use_absolute_docs_urls_with_version(
+        self,
+        package_name: &str,
+        documentation_url: &str,
+        version: &str,
+    ) -> Self {
+        self.with_absolute_urls(&docs_path_prefix_with_version(
+            &package_name.replace('-', "_"),
+            documentation_url,
+            version,
+        ))
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the specified package documentation items url prefix as the root address.
+    ///
+    /// Unlike `use_absolute_docs_urls`, the prefix is used as-is,
+    /// so any documentation url layout could be used.
+    pub fn use_absolute_docs_urls_with_prefix(self, prefix: &str) -> Self {
+        self.with_absolute_urls(prefix)
+    }
+
+    /// Convert all relative links and image links into absolute ones using specified url prefix.
+    ///
+    /// The leading `./` and `../` segments of the relative urls are resolved against the prefix,
+    /// which is considered to be the root address.
+    /// The urls pointing above the prefix are prefixed as is,
+    /// use `try_with_absolute_urls` to reject them instead.
+    pub fn with_absolute_urls(self, prefix: &str) -> Self {
+        self.map_absolute_urls(prefix, "", |_| {})
+    }
+
+    /// Convert all relative links and image links into absolute ones
+    /// using the specified root url and its subpath as the url prefix.
+    ///
+    /// The leading `./` and `../` segments of the relative urls are resolved against the prefix,
+    /// for example the `../b` url with the `https://github.com/user/repo/blob/master/` root
+    /// and the `crates/a` subpath becomes `https://github.com/user/repo/blob/master/crates/b`.
+    ///
+    /// Returns an error if an url points above the root.
+    pub fn try_with_absolute_urls(
+        self,
+        root: &str,
+        subpath: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        let subpath = subpath.trim_matches('/');
+        let subpath = if subpath.is_empty() {
+            String::new()
+        } else {
+            [subpath, "/"].concat()
+        };
+        self.try_map_absolute_urls(root, &subpath)
+    }
+
+    /// Converts all relative links and image links into absolute ones
+    /// resolved against the root url and its subpath.
+    ///
+    /// Returns an error if an url points above the root.
+    fn try_map_absolute_urls(
+        self,
+        root: &str,
+        subpath: &str,
+    ) -> Result<Self, DisallowUrlsWithPrefixError> {
+        use std::string::ToString;
+
+        let mut escaped = None;
+        let data = self.map_absolute_urls(root, subpath, |url| {
+            if escaped.is_none() {
+                escaped = Some(url.to_string());
+            }
+        });
+        match escaped {
+            Some(url) => Err(DisallowUrlsWithPrefixError::EscapesRoot {
+                url,
+                root: root.to_string(),
+            }),
+            None => Ok(data),
+        }
+    }
+
+    /// Converts all relative links and image links into absolute ones
+    /// resolved against the root url and its subpath.
+    ///
+    /// The urls pointing above the root are prefixed as is
+    /// and passed to the `on_escape` function.
+    fn map_absolute_urls<F>(self, root: &str, subpath: &str, mut on_escape: F) -> Self
+    where
+        F: FnMut(&str),
+    {
+        use std::format;
+
+        let prefix = [root, subpath].concat();
+        let mut absolute_url = |url: &str| -> Option<String> {
+            if is_absolute_url(url) || is_fragment(url) {
+                return None;
+            }
+            // The last prefix segment is incomplete, so the url can not be resolved against it.
+            if !prefix.ends_with('/') {
+                return Some([prefix.as_str(), url].concat());
+            }
+            match resolve_relative_url(subpath, url) {
+                Some(path) => Some([root, &path].concat()),
+                None => {
+                    on_escape(url);
+                    Some([prefix.as_str(), url].concat())
+                }
+            }
+        };
+
+        let note = format!("with_absolute_urls(prefix = \"{}\")", prefix);
+        let data = self.map_links(
+            |url| absolute_url(url).map_or(Cow::from(url), Cow::from),
+            note.clone(),
+        );
+        data.map_image_links(
+            |url| absolute_url(url).map_or(Cow::from(url), Cow::from),
+            note,
+        )
+    }
+
+    /// Converts all links with function `func` applied to each link address.
+    pub fn map_links<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        use pulldown_cmark::CowStr;
+
+        self.map_links_full(
+            |link| {
+                let new_url = func(link.dest_url.as_ref());
+                if new_url.as_ref() == link.dest_url.as_ref() {
+                    return None;
+                }
+                let dest_url = CowStr::from(new_url.into_owned());
+                Some(LinkMeta {
+                    link_type: link.link_type,
+                    dest_url,
+                    title: link.title.into_static(),
+                    id: link.id.into_static(),
+                })
+            },
+            note,
+        )
+    }
+
+    /// Converts all links with function `func` applied to each link type, address, title and id.
+    ///
+    /// The link is left unchanged if `func` returns `None` or the same link.
+    pub fn map_links_full<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        F: FnMut(LinkMeta<'_>) -> Option<LinkMeta<'static>>,
+    {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::Tag;
+
+        let note = note.into();
+        self.map(|node| {
+            let link = match node.event() {
+                Some(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                })) => LinkMeta {
+                    link_type: *link_type,
+                    dest_url: dest_url.clone(),
+                    title: title.clone(),
+                    id: id.clone(),
+                },
+                _ => return node,
+            };
+            match func(link.clone()) {
+                Some(new_link) if new_link != link => {
+                    let event = Event::Start(Tag::Link {
+                        link_type: new_link.link_type,
+                        dest_url: new_link.dest_url,
+                        title: new_link.title,
+                        id: new_link.id,
+                    });
+                    node.into_modified(event, note.clone())
+                }
+                _ => node,
+            }
+        })
+    }
+
+    /// Converts all image links with function `func` applied to each image address.
+    pub fn map_image_links<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{CowStr, Tag};
+
+        fn map_image_link<'a, F>(tag: &Tag<'a>, mut func: F) -> Option<Tag<'a>>
+        where
+            for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+        {
+            if let Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            } = tag
+            {
+                let new_url = func(dest_url.as_ref());
+                if dest_url.as_ref() != new_url.as_ref() {
+                    return Some(Tag::Image {
+                        link_type: *link_type,
+                        dest_url: CowStr::from(new_url.into_owned()),
+                        title: title.clone(),
+                        id: id.clone(),
+                    });
+                }
+            }
+            None
+        }
+
+        let note = note.into();
+        self.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(tag)) => map_image_link(tag, &mut func).map(Event::Start),
+                _ => None,
+            };
+            match event {
+                Some(event) => node.into_modified(event, note.clone()),
+                None => node,
+            }
+        })
+    }
+
+    /// Converts all fragment-only links, like `#usage`,
+    /// with function `func` applied to each link address.
+    ///
+    /// Unlike `map_links` the other link addresses are left unchanged.
+    pub fn map_fragment_links<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map_links(
+            |url| {
+                if is_fragment(url) {
+                    func(url)
+                } else {
+                    Cow::from(url)
+                }
+            },
+            note,
+        )
+    }
+}
+
+pub(crate) fn is_absolute_url(url: &str) -> bool {
+    is_url_with_scheme(url)
+}
+
+pub(crate) fn is_fragment(url: &str) -> bool {
+    url.starts_with('#')
+}
+
+#[allow(clippy::match_like_matches_macro)] // requires minimum rustc version 1.42.0
+fn is_url_with_scheme(url: &str) -> bool {
+    if let Some(scheme) = url.split("//").next() {
+        if scheme.is_empty() {
+            return true;
+        } else if scheme.ends_with(':') && scheme.len() >= 2 {
+            let scheme = &scheme[..scheme.len() - 1];
+            if let b'a'..=b'z' | b'A'..=b'Z' = scheme.as_bytes()[0] {
+                return scheme.as_bytes()[1..].iter().all(|ch| {
+                    if let b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'+' | b'.' | b'-' = ch {
+                        true
+                    } else {
+                        false
+                    }
+                });
+            }
+        }
+    }
+    false
+}
+
+/// Returns the url resolved against the `/`-separated subpath
+/// or `None` if it points above the subpath root.
+///
+/// Only the leading `./` and `../` url segments are resolved.
+pub(crate) fn resolve_relative_url(subpath: &str, url: &str) -> Option<String> {
+    let mut segments: Vec<_> = subpath.split('/').filter(|part| !part.is_empty()).collect();
+    let mut rest = url;
+    loop {
+        if let Some(tail) = rest.strip_prefix("./") {
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("../") {
+            let _ = segments.pop()?;
+            rest = tail;
+        } else if rest == "." {
+            rest = "";
+        } else if rest == ".." {
+            let _ = segments.pop()?;
+            rest = "";
+        } else {
+            break;
+        }
+    }
+    let mut path = segments.join("/");
+    if !path.is_empty() {
+        path.push('/');
+    }
+    path.push_str(rest);
+    Some(path)
+}
+
+/// Returns the rest of the url after the specified prefix if the url starts with it.
+///
+/// The url scheme and host are compared case-insensitively, the rest of the url as is.
+/// The prefix ending with `/` also matches the url without it
+/// if the url ends there or continues with a query or a fragment.
+pub(crate) fn strip_url_prefix<'a>(url: &'a str, prefix: &str) -> Option<&'a str> {
+    fn split_origin(url: &str) -> Option<(&str, &str)> {
+        let host_start = url.find("//")? + 2;
+        let host_end = url[host_start..]
+            .find(['/', '?', '#'])
+            .map_or(url.len(), |idx| host_start + idx);
+        Some(url.split_at(host_end))
+    }
+
+    let (url_origin, url_rest, prefix_rest) = match (split_origin(url), split_origin(prefix)) {
+        (Some((url_origin, url_rest)), Some((prefix_origin, prefix_rest))) => {
+            if !url_origin.eq_ignore_ascii_case(prefix_origin) {
+                return None;
+            }
+            (url_origin, url_rest, prefix_rest)
+        }
+        _ => return url.strip_prefix(prefix),
+    };
+    debug_assert!(!url_origin.is_empty());
+    if let Some(rest) = url_rest.strip_prefix(prefix_rest) {
+        return Some(rest);
+    }
+    let rest = url_rest.strip_prefix(prefix_rest.strip_suffix('/')?)?;
+    if rest.is_empty() || rest.starts_with(['?', '#']) {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn without_trailing_slash(value: &str) -> &str {
+    match value.as_bytes().last() {
+        Some(b'/') => &value[..value.len() - 1],
+        _ => value,
+    }
+}
+
+pub(crate) fn blob_path_prefix(repository_url: &str, branch: &str) -> String {
+    [
+        without_trailing_slash(repository_url),
+        "/blob/",
+        branch,
+        "/",
+    ]
+    .concat()
+}
+
+/// Returns the docs url prefix for the specified package documentation url.
+///
+/// The docs.rs urls are extended with the version wildcard and the crate name,
+/// other urls are used as-is since their site structure is unknown.
+pub(crate) fn docs_path_prefix(package_name: &str, documentation_url: &str) -> String {
+    docs_path_prefix_with_version(&package_name.replace('-', "_"), documentation_url, "*")
+}
+
+/// Returns the docs url prefix for the specified library crate documentation url
+/// with the specified docs.rs version or version wildcard.
+pub(crate) fn docs_path_prefix_with_version(
+    crate_name: &str,
+    documentation_url: &str,
+    version: &str,
+) -> String {
+    let url = without_trailing_slash(documentation_url);
+    if is_docs_rs_url(url) {
+        [url, "/", version, "/", crate_name, "/"].concat()
+    } else {
+        [url, "/"].concat()
+    }
+}
+
+fn is_docs_rs_url(url: &str) -> bool {
+    url.strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .and_then(|url| url.split('/').next())
+        .is_some_and(|host| host == "docs.rs")
+}
+
+impl CMarkData {
+    /// Remove the specified fenced code block tag.
+    pub fn remove_codeblock_tag(self, tag: &str) -> Self {
+        self.remove_codeblock_tags(&[tag])
+    }
+
+    /// Remove the specified fenced code block tags.
+    ///
+    /// Tags may be separated by commas, whitespace or both,
+    /// the separators of the remaining tags are preserved.
+    pub fn remove_codeblock_tags(self, tags: &[&str]) -> Self {
+        self.remove_codeblock_tags_matching(
+            |tag| tags.contains(&tag),
+            Cow::from(std::format!("remove_codeblock_tags(tags = {:?})", tags)),
+        )
+    }
+
+    /// Remove fenced code block tags the predicate returns `true` for.
+    ///
+    /// The separators of the remaining tags are preserved.
+    pub fn remove_codeblock_tags_matching<F>(
+        self,
+        predicate: F,
+        note: impl Into<Cow<'static, str>>,
+    ) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.remove_codeblock_tags_matching_with(false, predicate, note.into())
+    }
+
+    fn remove_codeblock_tags_matching_with<F>(
+        self,
+        keep_language: bool,
+        mut predicate: F,
+        note: Cow<'static, str>,
+    ) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        use crate::CMarkItemAsModified;
+
+        self.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(tag)) => {
+                    remove_codeblock_tag_tags(tag, keep_language, &mut predicate).map(Event::Start)
+                }
+                _ => None,
+            };
+            match event {
+                Some(event) => node.into_modified(event, note.clone()),
+                None => node,
+            }
+        })
+    }
+}
+
+impl CMarkData {
+    /// Remove emphasis, strong emphasis and strikethrough, keeping their text.
+    ///
+    /// It allows to ignore bold and italic differences instead of rewriting the text.
+    pub fn remove_emphasis(self) -> Self {
+        self.remove_emphasis_kinds(EmphasisKinds::ALL)
+    }
+
+    /// Remove the specified kinds of emphasis, keeping their text.
+    ///
+    /// The neighboring text nodes are concatenated after that.
+    pub fn remove_emphasis_kinds(self, kinds: EmphasisKinds) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let note = if kinds == EmphasisKinds::ALL {
+            Cow::from("remove_emphasis()")
+        } else {
+            Cow::from(std::format!("remove_emphasis_kinds(kinds = {:?})", kinds))
+        };
+        self.map(|node| {
+            let is_removed = match node.event() {
+                Some(Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis)) => {
+                    kinds.contains(EmphasisKinds::EMPHASIS)
+                }
+                Some(Event::Start(Tag::Strong) | Event::End(TagEnd::Strong)) => {
+                    kinds.contains(EmphasisKinds::STRONG)
+                }
+                Some(Event::Start(Tag::Strikethrough) | Event::End(TagEnd::Strikethrough)) => {
+                    kinds.contains(EmphasisKinds::STRIKETHROUGH)
+                }
+                _ => false,
+            };
+            if is_removed {
+                node.into_removed(note.clone())
+            } else {
+                node
+            }
+        })
+        .concat_texts()
+    }
+
+    /// Replace links with their text.
+    ///
+    /// It allows to compare only link texts, for example for intra-doc links
+    /// that have no reasonable url in the readme.
+    /// Images inside links are kept, use `flatten_images` to replace them with their alt text.
+    pub fn flatten_links(self) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        self.map(|node| match node.event() {
+            Some(Event::Start(Tag::Link { .. }) | Event::End(TagEnd::Link)) => {
+                node.into_removed(Cow::from("flatten_links()"))
+            }
+            _ => node,
+        })
+    }
+
+    /// Replace images with their alt text.
+    pub fn flatten_images(self) -> Self {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        self.map(|node| match node.event() {
+            Some(Event::Start(Tag::Image { .. }) | Event::End(TagEnd::Image)) => {
+                node.into_removed(Cow::from("flatten_images()"))
+            }
+            _ => node,
+        })
+    }
+
+    /// Convert inline HTML line breaks like `<br>`, `<br/>` or `<br />` to hard breaks.
+    ///
+    /// Readmes often use `<br>` for hard line breaks while docs use two trailing spaces
+    /// or a trailing backslash.
+    /// A soft break following the converted line break is removed,
+    /// as it is a part of the hard break in the Markdown syntax.
+    pub fn normalize_hard_breaks(self) -> Self {
+        use crate::{CMarkItemAsModified, CMarkItemAsRemoved};
+
+        let note = || Cow::from("normalize_hard_breaks()");
+        let mut is_after_break = false;
+        self.map(|node| {
+            let is_break = matches!(
+                node.event(),
+                Some(Event::InlineHtml(html)) if is_html_line_break(html)
+            );
+            let is_soft_break_after_break =
+                is_after_break && matches!(node.event(), Some(Event::SoftBreak));
+            is_after_break = is_break;
+            if is_break {
+                node.into_modified(Event::HardBreak, note())
+            } else if is_soft_break_after_break {
+                node.into_removed(note())
+            } else {
+                node
+            }
+        })
+    }
+
+    /// Remove HTML blocks and inline HTML.
+    ///
+    /// Readmes often contain HTML like `<p align="center">` wrappers or `<img>` tags
+    /// that have no counterpart in the docs.
+    pub fn remove_html(self) -> Self {
+        self.remove_html_with(|_| true, Cow::from("remove_html()"))
+    }
+
+    /// Remove HTML blocks and inline HTML the predicate returns `true` for.
+    ///
+    /// The predicate is called with the whole HTML block text for HTML blocks
+    /// and with the inline HTML text, like `<img src="logo.png">`, for inline HTML.
+    pub fn remove_html_matching<F>(self, predicate: F) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.remove_html_with(predicate, Cow::from("remove_html_matching()"))
+    }
+
+    fn remove_html_with<F>(self, mut predicate: F, note: Cow<'static, str>) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        use crate::CMarkItemAsRemoved;
+        use core::mem::take;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let mut result = Vec::new();
+        let mut block = Vec::new();
+        let mut block_html = String::new();
+
+        for node in self.0.into_iter() {
+            match node.event() {
+                Some(Event::Start(Tag::HtmlBlock)) => block.push(node),
+                Some(Event::End(TagEnd::HtmlBlock)) if !block.is_empty() => {
+                    block.push(node);
+                    if predicate(&take(&mut block_html)) {
+                        result.push(take(&mut block).into_removed(note.clone()));
+                    } else {
+                        result.append(&mut block);
+                    }
+                }
+                Some(Event::Html(html)) if !block.is_empty() => {
+                    block_html += html;
+                    block.push(node);
+                }
+                Some(Event::Html(html) | Event::InlineHtml(html)) if predicate(html) => {
+                    result.push(node.into_removed(note.clone()));
+                }
+                _ if !block.is_empty() => block.push(node),
+                _ => result.push(node),
+            }
+        }
+        result.append(&mut block);
+
+        Self(result)
+    }
+
+    /// Remove HTML comments, like `<!-- omit in toc -->` or `<!-- markdownlint-disable -->`.
+    ///
+    /// HTML events consisting only of comments are removed,
+    /// and comments are trimmed from events that mix comments with other HTML.
+    /// Comments may span several HTML events,
+    /// and HTML blocks consisting only of comments are removed entirely.
+    pub fn remove_html_comments(self) -> Self {
+        use crate::{CMarkItemAsModified, CMarkItemAsRemoved};
+        use core::mem::take;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let note = || Cow::from("remove_html_comments()");
+        let mut result = Vec::new();
+        let mut block = Vec::new();
+        let mut in_comment = false;
+
+        for node in self.0.into_iter() {
+            let node = match node.event() {
+                Some(event @ (Event::Html(html) | Event::InlineHtml(html))) => {
+                    let (html, is_in_comment) = strip_html_comments(html, in_comment);
+                    in_comment = is_in_comment;
+                    match html {
+                        Some(html) if html.trim().is_empty() => node.into_removed(note()),
+                        Some(html) => {
+                            let html = CowStr::Boxed(html.into_boxed_str());
+                            let event = match event {
+                                Event::Html(_) => Event::Html(html),
+                                _ => Event::InlineHtml(html),
+                            };
+                            node.into_modified(event, note())
+                        }
+                        None => node,
+                    }
+                }
+                _ => node,
+            };
+
+            match node.event() {
+                Some(Event::Start(Tag::HtmlBlock)) => block.push(node),
+                Some(Event::End(TagEnd::HtmlBlock)) if !block.is_empty() => {
+                    block.push(node);
+                    let is_empty = block[1..block.len() - 1]
+                        .iter()
+                        .all(|node| node.event().is_none());
+                    if is_empty {
+                        result.push(take(&mut block).into_removed(note()));
+                    } else {
+                        result.append(&mut block);
+                    }
+                }
+                _ if !block.is_empty() => block.push(node),
+                _ => result.push(node),
+            }
+        }
+        result.append(&mut block);
+
+        Self(result)
+    }
+
+    /// Remove fenced code blocks with the specified tag including their contents.
+    ///
+    /// Unlike `remove_codeblock_tag`, which removes only the tag,
+    /// it allows to keep code blocks that intentionally exist only in the readme or only in docs.
+    pub fn remove_codeblocks_with_tag(self, tag: &str) -> Self {
+        use crate::tags::CodeblockTags;
+        use crate::CMarkItemAsRemoved;
+        use core::mem::take;
+        use pulldown_cmark::{CodeBlockKind, Tag, TagEnd};
+
+        let note = || Cow::from(std::format!("remove_codeblocks_with_tag(tag = {:?})", tag));
+        let mut result = Vec::new();
+        let mut codeblock = Vec::new();
+
+        for node in self.0.into_iter() {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tags))))
+                    if codeblock.is_empty() && CodeblockTags::parse(tags).contains(tag) =>
+                {
+                    codeblock.push(node);
+                }
+                Some(Event::End(TagEnd::CodeBlock)) if !codeblock.is_empty() => {
+                    codeblock.push(node);
+                    result.push(take(&mut codeblock).into_removed(note()));
+                }
+                _ if !codeblock.is_empty() => codeblock.push(node),
+                _ => result.push(node),
+            }
+        }
+
+        if !codeblock.is_empty() {
+            result.push(codeblock.into_removed(note()));
+        }
+
+        Self(result)
+    }
+
+    /// Remove the contents of all fenced and indented code blocks,
+    /// leaving only their start and end tags.
+    ///
+    /// It allows to check the code blocks placement and tags, but not the code itself,
+    /// for example if the readme contains a shortened version of the docs example.
+    pub fn remove_codeblock_contents(self) -> Self {
+        self.remove_codeblock_contents_if(|_| true, Cow::from("remove_codeblock_contents()"))
+    }
+
+    /// Remove the contents of fenced code blocks with the specified tag,
+    /// leaving only their start and end tags.
+    ///
+    /// See `remove_codeblock_contents` for details.
+    pub fn remove_codeblock_contents_for_tag(self, tag: &str) -> Self {
+        use crate::tags::CodeblockTags;
+        use pulldown_cmark::CodeBlockKind;
+
+        self.remove_codeblock_contents_if(
+            |kind| match kind {
+                CodeBlockKind::Fenced(tags) => CodeblockTags::parse(tags).contains(tag),
+                CodeBlockKind::Indented => false,
+            },
+            Cow::from(std::format!(
+                "remove_codeblock_contents_for_tag(tag = {:?})",
+                tag
+            )),
+        )
+    }
+
+    /// Strip trailing whitespace of every code block line and trailing blank lines
+    /// of all code blocks.
+    ///
+    /// The adjacent code block text events are concatenated before trimming,
+    /// whitespace-only code block texts are removed
+    /// and texts outside code blocks are left unchanged.
+    pub fn trim_codeblock_text(self) -> Self {
+        self.trim_codeblock_text_with(false, "trim_codeblock_text()")
+    }
+
+    /// Strip trailing whitespace of every code block line, trailing blank lines
+    /// and the leading blank line of all code blocks.
+    ///
+    /// See `trim_codeblock_text` for details.
+    pub fn trim_codeblock_text_and_leading_blank_line(self) -> Self {
+        self.trim_codeblock_text_with(true, "trim_codeblock_text_and_leading_blank_line()")
+    }
+
+    fn trim_codeblock_text_with(self, is_leading_line_trimmed: bool, note: &'static str) -> Self {
+        use crate::{CMarkItemAsModified, CMarkItemAsRemoved};
+        use core::mem::take;
+        use pulldown_cmark::{CowStr, Tag, TagEnd};
+
+        let trim = |nodes: Vec<Arc<CMarkItem>>| -> Vec<Arc<CMarkItem>> {
+            let text: String = nodes
+                .iter()
+                .filter_map(|node| match node.event() {
+                    Some(Event::Text(text)) => Some(text.as_ref()),
+                    _ => None,
+                })
+                .collect();
+            let trimmed = trim_codeblock_text(&text, is_leading_line_trimmed);
+            if trimmed == text {
+                return nodes;
+            }
+            if trimmed.is_empty() {
+                return std::vec![nodes.into_removed(Cow::from(note))];
+            }
+            let event = Event::Text(CowStr::Boxed(trimmed.into_boxed_str()));
+            std::vec![nodes.into_modified(event, Cow::from(note))]
+        };
+
+        let mut result = Vec::new();
+        let mut texts = Vec::new();
+        let mut is_codeblock = false;
+
+        for node in self.0.into_iter() {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(_))) => is_codeblock = true,
+                Some(Event::End(TagEnd::CodeBlock)) => is_codeblock = false,
+                Some(Event::Text(_)) if is_codeblock => {
+                    texts.push(node);
+                    continue;
+                }
+                _ => {}
+            }
+            if !texts.is_empty() {
+                result.append(&mut trim(take(&mut texts)));
+            }
+            result.push(node);
+        }
+        if !texts.is_empty() {
+            result.append(&mut trim(texts));
+        }
+
+        Self(result)
+    }
+
+    fn remove_codeblock_contents_if<F>(self, mut predicate: F, note: Cow<'static, str>) -> Self
+    where
+        F: FnMut(&pulldown_cmark::CodeBlockKind<'_>) -> bool,
+    {
+        use crate::CMarkItemAsRemoved;
+        use core::mem::take;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let mut result = Vec::new();
+        let mut contents = Vec::new();
+        let mut is_codeblock = false;
+
+        for node in self.0.into_iter() {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(kind))) if !is_codeblock && predicate(kind) => {
+                    is_codeblock = true;
+                    result.push(node);
+                }
+                Some(Event::End(TagEnd::CodeBlock)) if is_codeblock => {
+                    if !contents.is_empty() {
+                        result.push(take(&mut contents).into_removed(note.clone()));
+                    }
+                    is_codeblock = false;
+                    result.push(node);
+                }
+                _ if is_codeblock => contents.push(node),
+                _ => result.push(node),
+            }
+        }
+
+        if !contents.is_empty() {
+            result.push(contents.into_removed(note));
+        }
+
+        Self(result)
+    }
+}
+
+/// Returns the code block text with the trailing whitespace of every line
+/// and the trailing blank lines removed.
+fn trim_codeblock_text(text: &str, is_leading_line_trimmed: bool) -> String {
+    let mut lines: Vec<_> = text.lines().map(str::trim_end).collect();
+    while lines.last() == Some(&"") {
+        let _ = lines.pop();
+    }
+    if is_leading_line_trimmed && lines.first() == Some(&"") {
+        let _ = lines.remove(0);
+    }
+    let mut result = lines.join("\n");
+    if !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Returns the code block tag without the tags the predicate returns `true` for
+/// if any of them is found.
+///
+/// If `keep_language` is `true`, the first tag is never removed
+/// if it is the code block language rather than a rustdoc test tag or an error code.
+fn remove_codeblock_tag_tags<'a, F>(
+    event_tag: &pulldown_cmark::Tag<'a>,
+    keep_language: bool,
+    predicate: &mut F,
+) -> Option<pulldown_cmark::Tag<'a>>
+where
+    F: FnMut(&str) -> bool,
+{
+    use crate::tags::CodeblockTags;
+    use crate::{codeblock_rust_test_tags, is_codeblock_rust_error_code_tag};
+    use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+
+    if let Tag::CodeBlock(CodeBlockKind::Fenced(ref node_tags)) = event_tag {
+        let node_tags = CodeblockTags::parse(node_tags);
+        let is_language = |tag: &str| {
+            keep_language
+                && !codeblock_rust_test_tags().contains(&tag)
+                && !is_codeblock_rust_error_code_tag(tag)
+        };
+        let skipped = usize::from(node_tags.iter().next().is_some_and(is_language));
+        if node_tags.iter().skip(skipped).any(&mut *predicate) {
+            let mut idx = 0;
+            let node_tags = node_tags.retain(|node_tag| {
+                idx += 1;
+                idx <= skipped || !predicate(node_tag)
+            });
+            let node_tags = CowStr::Boxed(node_tags.into_boxed_str());
+            return Some(Tag::CodeBlock(CodeBlockKind::Fenced(node_tags)));
+        }
+    }
+    None
+}
+
+impl CMarkData {
+    /// Separate fenced code block tags with commas only.
+    ///
+    /// Rustdoc accepts tags separated by commas, whitespace or both,
+    /// so `rust no_run` and `rust, no_run` are the same as `rust,no_run`.
+    pub fn normalize_codeblock_tags(self) -> Self {
+        use crate::tags::CodeblockTags;
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+
+        self.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(node_tags)))) => {
+                    let canonical = CodeblockTags::parse(node_tags).to_canonical();
+                    if canonical != node_tags.as_ref() {
+                        Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                            CowStr::Boxed(canonical.into_boxed_str()),
+                        ))))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            match event {
+                Some(event) => node.into_modified(event, Cow::from("normalize_codeblock_tags()")),
+                None => node,
+            }
+        })
+    }
+}
+
+impl CMarkData {
+    /// Remove fenced code block tags that are used by `cargo test`.
+    ///
+    /// Error code tags like `E0277` in `compile_fail,E0277` are removed too.
+    /// The first tag is kept if it is the code block language
+    /// rather than a test tag or an error code.
+    ///
+    /// See <https://doc.rust-lang.org/rustdoc/documentation-tests.html> for more details.
+    pub fn remove_codeblock_rust_test_tags(self) -> Self {
+        self.remove_codeblock_rust_test_tags_with(&[])
+    }
+
+    /// Remove fenced code block tags that are used by `cargo test`
+    /// and the specified project-specific tags.
+    ///
+    /// See `remove_codeblock_rust_test_tags` for details.
+    pub fn remove_codeblock_rust_test_tags_with(self, extra: &[&str]) -> Self {
+        use crate::{codeblock_rust_test_tags, is_codeblock_rust_error_code_tag};
+
+        let note = if extra.is_empty() {
+            Cow::from("remove_codeblock_rust_test_tags()")
+        } else {
+            Cow::from(std::format!(
+                "remove_codeblock_rust_test_tags_with(extra = {:?})",
+                extra
+            ))
+        };
+        self.remove_codeblock_tags_matching_with(
+            true,
+            |tag| {
+                codeblock_rust_test_tags().contains(&tag)
+                    || is_codeblock_rust_error_code_tag(tag)
+                    || extra.contains(&tag)
+            },
+            note,
+        )
+    }
+
+    /// Use the specified codeblock tag, if they are not specified
+    ///
+    /// Untagged code blocks often contain console output or diagrams,
+    /// use `use_default_codeblock_tag_if` with `looks_like_rust` to keep them untagged.
+    pub fn use_default_codeblock_tag(self, tag: &str) -> Self {
+        use crate::CMarkItemAsModified;
+
+        self.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(node_tag)) => {
+                    map_default_codeblock_tag(node_tag, tag).map(Event::Start)
+                }
+                _ => None,
+            };
+            match event {
+                Some(event) => node.into_modified(
+                    event,
+                    Cow::from(std::format!("use_default_codeblock_tag(tag = \"{}\")", tag)),
+                ),
+                None => node,
+            }
+        })
+    }
+}
+
+impl CMarkData {
+    /// Convert indented code blocks to fenced code blocks with the specified info string.
+    ///
+    /// Rustdoc ignores indents in consecutive doc-comments and doc-attributes,
+    /// so four-space indented examples in docs are parsed as indented code blocks
+    /// and can not match fenced code blocks in the readme.
+    /// The code block text is left unchanged,
+    /// so it can be followed by `remove_hidden_rust_code` if the tag is `rust`.
+    ///
+    /// See <https://github.com/rust-lang/rust/issues/70732> for more details.
+    pub fn convert_indented_codeblocks_to_fenced(self, tag: &str) -> Self {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+        use std::string::ToString;
+
+        self.map(|node| match node.event() {
+            Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Indented))) => {
+                let info = CowStr::Boxed(tag.to_string().into_boxed_str());
+                let event = Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)));
+                node.into_modified(
+                    event,
+                    Cow::from(std::format!(
+                        "convert_indented_codeblocks_to_fenced(tag = {:?}), \
+                        see https://github.com/rust-lang/rust/issues/70732",
+                        tag
+                    )),
+                )
+            }
+            _ => node,
+        })
+    }
+}
+
+impl CMarkData {
+    /// Converts the info strings of all fenced code blocks,
+    /// like `rust,ignore`, with function `func` applied to each of them.
+    ///
+    /// The code blocks with unchanged info strings are left as is.
+    pub fn map_codeblock_tags<F>(self, mut func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+
+        let note = note.into();
+        self.map(|node| {
+            let info = match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => info,
+                _ => return node,
+            };
+            let new_info = func(info);
+            if new_info.as_ref() == info.as_ref() {
+                return node;
+            }
+            let new_info = CowStr::Boxed(new_info.into_owned().into_boxed_str());
+            let event = Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(new_info)));
+            node.into_modified(event, note.clone())
+        })
+    }
+
+    /// Replace the fenced code block tags that match the first value of an alias pair
+    /// with the second value of that pair, like `console` with `sh`.
+    ///
+    /// The other tags and the tag separators are preserved.
+    pub fn map_codeblock_tag_aliases(self, aliases: &[(&str, &str)]) -> Self {
+        use crate::tags::CodeblockTags;
+
+        let note = std::format!("map_codeblock_tag_aliases(aliases = {:?})", aliases);
+        self.map_codeblock_tags(
+            |info| {
+                let tags = CodeblockTags::parse(info);
+                if !aliases.iter().any(|(from, _)| tags.contains(from)) {
+                    return Cow::from(info);
+                }
+                Cow::from(tags.map(|tag| {
+                    aliases
+                        .iter()
+                        .find(|(from, _)| *from == tag)
+                        .map_or(tag, |(_, to)| to)
+                }))
+            },
+            note,
+        )
+    }
+
+    /// Use the specified codeblock tag for fenced code blocks without tags
+    /// if the specified predicate returns true when passing the code block text to it.
+    pub fn use_default_codeblock_tag_if<F>(self, tag: &str, mut predicate: F) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{Tag, TagEnd};
+
+        let mut is_default = Vec::new();
+        let mut text: Option<String> = None;
+        for event in self.iter_events() {
+            match event {
+                Event::Start(Tag::CodeBlock(_)) => text = Some(String::new()),
+                Event::Text(node_text) => {
+                    if let Some(text) = &mut text {
+                        text.push_str(node_text);
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    is_default.extend(text.take().map(|text| predicate(&text)));
+                }
+                _ => {}
+            }
+        }
+
+        let mut is_default = is_default.into_iter();
+        self.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(node_tag @ Tag::CodeBlock(_))) => {
+                    if is_default.next().unwrap_or(false) {
+                        map_default_codeblock_tag(node_tag, tag).map(Event::Start)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            match event {
+                Some(event) => node.into_modified(
+                    event,
+                    Cow::from(std::format!(
+                        "use_default_codeblock_tag_if(tag = \"{}\")",
+                        tag
+                    )),
+                ),
+                None => node,
+            }
+        })
+    }
+
+    /// Replace the specified fenced code block tag with another one.
+    ///
+    /// It allows to correct tags in bulk, for example to replace `sh` with `bash`.
+    pub fn retag_codeblocks(self, from: &str, to: &str) -> Self {
+        use crate::tags::CodeblockTags;
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+
+        self.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(node_tags)))) => {
+                    let tags = CodeblockTags::parse(node_tags);
+                    if tags.contains(from) {
+                        Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                            CowStr::Boxed(tags.replace(from, to).into_boxed_str()),
+                        ))))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            match event {
+                Some(event) => node.into_modified(
+                    event,
+                    Cow::from(std::format!(
+                        "retag_codeblocks(from = {:?}, to = {:?})",
+                        from,
+                        to
+                    )),
+                ),
+                None => node,
+            }
+        })
+    }
+}
+
+fn map_default_codeblock_tag<'a>(
+    event_tag: &pulldown_cmark::Tag<'a>,
+    tag: &str,
+) -> Option<pulldown_cmark::Tag<'a>> {
+    use crate::tags::CodeblockTags;
+    use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+    use std::string::ToString;
+
+    if let Tag::CodeBlock(CodeBlockKind::Fenced(ref node_tag)) = event_tag {
+        if CodeblockTags::parse(node_tag).iter().next().is_none() {
+            return Some(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::Boxed(
+                tag.to_string().into_boxed_str(),
+            ))));
+        }
+    }
+    None
+}
+
+impl CMarkData {
+    /// Use rust fenced codeblock highlight as default.
+    pub fn use_default_codeblock_rust_tag(self) -> Self {
+        self.use_default_codeblock_tag("rust")
+    }
+
+    /// Remove hidden rust code from rust fenced codeblocks.
+    ///
+    /// Lines starting with `# ` and lines consisting of `#` are removed,
+    /// and lines starting with `##` are unescaped to start with `#`.
+    /// Only code blocks whose first tag other than rustdoc test attributes is `rust` are changed,
+    /// so code blocks in other languages, like `bash` comments, are preserved.
+    ///
+    /// Hidden lines are a rustdoc feature, so this transformation is intended for docs only.
+    ///
+    /// See <https://doc.rust-lang.org/rustdoc/documentation-tests.html#hiding-portions-of-the-example> for more details.
+    pub fn remove_hidden_rust_code(self) -> Self {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{CodeBlockKind, CowStr, Tag, TagEnd};
+
+        let mut is_rust_codeblock = false;
+
+        self.map(|node| {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tags)))) => {
+                    is_rust_codeblock = is_rust_codeblock_tags(tags);
+                }
+                Some(Event::End(TagEnd::CodeBlock)) => is_rust_codeblock = false,
+                Some(Event::Text(text)) if is_rust_codeblock => {
+                    let text: Vec<_> = text.split('\n').filter_map(visible_rust_line).collect();
+                    let text = text.join("\n");
+                    let event = Event::Text(CowStr::Boxed(text.into_boxed_str()));
+                    return node.into_modified(event, Cow::from("remove_hidden_rust_code()"));
+                }
+                _ => {}
+            };
+            node
+        })
+    }
+
+    /// Replace lines of rust fenced codeblocks matching any of the specified wildcard patterns
+    /// with the mask, preserving the line indentation.
+    ///
+    /// Apply it to both readme and docs to compare code blocks that intentionally differ
+    /// in some lines, like `$crate::` paths and `#[macro_use]` attributes in macro examples,
+    /// while keeping the other lines strict.
+    /// Patterns are matched against lines without leading and trailing whitespace.
+    /// They support only `*` and `?` wildcards, and `[` and `]` match themselves,
+    /// like in `#[macro_use]*`.
+    pub fn mask_codeblock_lines_matching(self, patterns: &[&str], mask: &str) -> Self {
+        use crate::{CMarkItemAsModified, WildcardPattern};
+        use pulldown_cmark::{CodeBlockKind, CowStr, Tag, TagEnd};
+
+        let compiled: Vec<_> = patterns
+            .iter()
+            .map(|pattern| WildcardPattern::with_literal_brackets(pattern))
+            .collect();
+        let mut is_rust_codeblock = false;
+
+        self.map(|node| {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tags)))) => {
+                    is_rust_codeblock = is_rust_codeblock_tags(tags);
+                }
+                Some(Event::End(TagEnd::CodeBlock)) => is_rust_codeblock = false,
+                Some(Event::Text(text)) if is_rust_codeblock => {
+                    let mut is_masked = false;
+                    let lines: Vec<_> = text
+                        .split('\n')
+                        .map(|line| {
+                            let trimmed = line.trim();
+                            if !trimmed.is_empty()
+                                && compiled.iter().any(|pattern| pattern.matches(trimmed))
+                            {
+                                is_masked = true;
+                                let indent = &line[..line.len() - line.trim_start().len()];
+                                Cow::from([indent, mask].concat())
+                            } else {
+                                Cow::from(line)
+                            }
+                        })
+                        .collect();
+                    if is_masked {
+                        let text = lines.join("\n");
+                        let event = Event::Text(CowStr::Boxed(text.into_boxed_str()));
+                        return node.into_modified(
+                            event,
+                            Cow::from(std::format!(
+                                "mask_codeblock_lines_matching(patterns = {:?}, mask = {:?})",
+                                patterns,
+                                mask
+                            )),
+                        );
+                    }
+                }
+                _ => {}
+            };
+            node
+        })
+    }
+}
+
+/// Returns `true` if the first tag other than rustdoc test attributes is `rust`.
+pub(crate) fn is_rust_codeblock_tags(tags: &str) -> bool {
+    use crate::tags::CodeblockTags;
+    use crate::{codeblock_rust_test_tags, is_codeblock_rust_error_code_tag};
+
+    CodeblockTags::parse(tags).iter().find(|tag| {
+        !codeblock_rust_test_tags().contains(tag) && !is_codeblock_rust_error_code_tag(tag)
+    }) == Some("rust")
+}
+
+/// Returns the capitalized GitHub alert kind name, like `Note` or `Warning`.
+fn github_alert_name(kind: pulldown_cmark::BlockQuoteKind) -> &'static str {
+    use pulldown_cmark::BlockQuoteKind;
+
+    match kind {
+        BlockQuoteKind::Note => "Note",
+        BlockQuoteKind::Tip => "Tip",
+        BlockQuoteKind::Important => "Important",
+        BlockQuoteKind::Warning => "Warning",
+        BlockQuoteKind::Caution => "Caution",
+    }
+}
+
+/// Returns `true` if the HTML is a `<br>`, `<br/>` or `<br />` line break.
+fn is_html_line_break(html: &str) -> bool {
+    let html = html.trim();
+    let Some(tag) = html
+        .strip_prefix('<')
+        .and_then(|html| html.strip_suffix('>'))
+    else {
+        return false;
+    };
+    let tag = tag.trim_end();
+    let tag = tag.strip_suffix('/').unwrap_or(tag).trim_end();
+    tag.eq_ignore_ascii_case("br")
+}
+
+/// Returns the HTML without comments, or `None` if the HTML is unchanged,
+/// and whether the HTML ends inside an unterminated comment.
+fn strip_html_comments(html: &str, mut in_comment: bool) -> (Option<String>, bool) {
+    let mut result = String::new();
+    let mut is_changed = in_comment;
+    let mut rest = html;
+    loop {
+        if in_comment {
+            match rest.find("-->") {
+                Some(end) => {
+                    rest = &rest[end + 3..];
+                    in_comment = false;
+                }
+                None => break,
+            }
+        } else {
+            match rest.find("<!--") {
+                Some(start) => {
+                    result += &rest[..start];
+                    rest = &rest[start + 4..];
+                    in_comment = true;
+                    is_changed = true;
+                }
+                None => {
+                    result += rest;
+                    break;
+                }
+            }
+        }
+    }
+    (is_changed.then_some(result), in_comment)
+}
+
+/// Returns the line as rustdoc shows it, or `None` if the line is hidden.
+///
+/// Leading whitespace is ignored, so hidden lines of indented code are hidden too.
+fn visible_rust_line(line: &str) -> Option<Cow<'_, str>> {
+    let trimmed = line.trim();
+    if trimmed.starts_with("##") {
+        Some(Cow::from(line.replacen("##", "#", 1)))
+    } else if trimmed == "#" || trimmed.starts_with("# ") || trimmed.starts_with("#\t") {
+        None
+    } else {
+        Some(Cow::from(line))
+    }
+}
+
+/// The link properties passed to and returned from the `CMarkData::map_links_full` function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkMeta<'a> {
+    /// The link type, like inline, reference or autolink.
+    pub link_type: LinkType,
+    /// The link destination url.
+    pub dest_url: CowStr<'a>,
+    /// The link title, like `title` in `[text](url "title")`, or an empty string.
+    pub title: CowStr<'a>,
+    /// The link reference id, like `id` in `[text][id]`, or an empty string.
+    pub id: CowStr<'a>,
+}
+
+/// A set of emphasis kinds used by the `CMarkData::remove_emphasis_kinds` function.
+///
+/// Kinds can be combined with `|`, like `EmphasisKinds::EMPHASIS | EmphasisKinds::STRONG`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EmphasisKinds(u8);
+
+impl EmphasisKinds {
+    /// No emphasis kinds.
+    pub const NONE: Self = Self(0);
+    /// Emphasis, like `*text*` or `_text_`.
+    pub const EMPHASIS: Self = Self(1);
+    /// Strong emphasis, like `**text**` or `__text__`.
+    pub const STRONG: Self = Self(2);
+    /// Strikethrough, like `~~text~~`.
+    pub const STRIKETHROUGH: Self = Self(4);
+    /// All emphasis kinds.
+    pub const ALL: Self = Self(7);
+
+    /// Returns `true` if all the specified kinds are in the set.
+    pub fn contains(self, kinds: Self) -> bool {
+        self.0 & kinds.0 == kinds.0
+    }
+}
+
+impl core::ops::BitOr for EmphasisKinds {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// An error which can occur when incrementing levels of the sixth level headings.
+#[derive(Clone, Debug, Error)]
+#[error("The heading `{heading}` of the sixth level can not be incremented.")]
+pub struct HeadingLevelOverflowError {
+    /// Heading plain text.
+    pub heading: String,
+    /// Heading start node, its spans point to the heading source.
+    pub node: Arc<CMarkItem>,
+}
+
+/// An error which can occur when checking for disallowed link prefixes.
+#[derive(Clone, Debug, Error)]
+pub enum DisallowUrlsWithPrefixError {
+    /// A prefix found
+    #[error("The url `{url}` use a prohibited prefix `{prefix}`.")]
+    PrefixFound {
+        /// Full url
+        url: String,
+        /// Disallowed prefix
+        prefix: String,
+    },
+    /// A url matching a disallowing predicate found
+    #[error("The url `{url}` is prohibited: {description}.")]
+    MatchFound {
+        /// Full url
+        url: String,
+        /// Description of the disallowed urls
+        description: String,
+    },
+    /// A relative url pointing above the root found
+    #[error("The relative url `{url}` points above the root `{root}`.")]
+    EscapesRoot {
+        /// Relative url
+        url: String,
+        /// Root url
+        root: String,
+    },
+}
+
+pub(crate) fn increase_heading_level(
+    level: pulldown_cmark::HeadingLevel,
+) -> pulldown_cmark::HeadingLevel {
+    use pulldown_cmark::HeadingLevel;
+
+    match level {
+        HeadingLevel::H1 => HeadingLevel::H2,
+        HeadingLevel::H2 => HeadingLevel::H3,
+        HeadingLevel::H3 => HeadingLevel::H4,
+        HeadingLevel::H4 => HeadingLevel::H5,
+        HeadingLevel::H5 | HeadingLevel::H6 => HeadingLevel::H6,
+    }
+}
+
+/// Returns the plain text of the heading started by the first of the specified nodes.
+///
+/// Inline code, emphasis and other inline markup are flattened to their text.
+pub(crate) fn heading_text(nodes: &[Arc<CMarkItem>]) -> Option<String> {
+    use pulldown_cmark::Tag;
+
+    match nodes.iter().find_map(|node| node.event()) {
+        Some(Event::Start(Tag::Heading { .. })) => tag_text(nodes),
+        _ => None,
+    }
+}
+
+/// Returns the plain text of the tag started by the first of the specified nodes.
+///
+/// Inline code, emphasis and other inline markup are flattened to their text.
+pub(crate) fn tag_text(nodes: &[Arc<CMarkItem>]) -> Option<String> {
+    let mut events = nodes.iter().filter_map(|node| node.event());
+    match events.next() {
+        Some(Event::Start(_)) => {}
+        _ => return None,
+    }
+
+    let mut text = String::new();
+    let mut depth = 1;
+    for event in events {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(text);
+                }
+            }
+            Event::Text(value) | Event::Code(value) => text += value,
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn heading_level(level: pulldown_cmark::HeadingLevel) -> u32 {
+    use pulldown_cmark::HeadingLevel;
+
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+#[test]
+fn test_is_url_with_scheme() {
+    assert!(!is_url_with_scheme("Foo"));
+    assert!(!is_url_with_scheme("crate::Foo"));
+    assert!(is_url_with_scheme("//Foo"));
+    assert!(!is_url_with_scheme("://Foo"));
+    assert!(is_url_with_scheme("a://Foo"));
+    assert!(is_url_with_scheme("Z://Foo"));
+    assert!(!is_url_with_scheme("0://Foo"));
+    assert!(is_url_with_scheme("aa://Foo"));
+    assert!(is_url_with_scheme("a0://Foo"));
+    assert!(is_url_with_scheme("a+://Foo"));
+    assert!(is_url_with_scheme("a.://Foo"));
+    assert!(is_url_with_scheme("a-://Foo"));
+    assert!(!is_url_with_scheme("a?://Foo"));
+    assert!(is_url_with_scheme("http://Foo"));
+    assert!(is_url_with_scheme("https://Foo"));
+}
+
+#[test]
+fn test_resolve_relative_url() {
+    let resolve = |subpath, url| resolve_relative_url(subpath, url);
+    assert_eq!(resolve("", "a/b.rs").as_deref(), Some("a/b.rs"));
+    assert_eq!(resolve("", "./a/b.rs").as_deref(), Some("a/b.rs"));
+    assert_eq!(resolve("", "././a").as_deref(), Some("a"));
+    assert_eq!(resolve("", "."), Some(String::new()));
+    assert_eq!(resolve("", "../a"), None);
+    assert_eq!(
+        resolve("crates/foo", "./a").as_deref(),
+        Some("crates/foo/a")
+    );
+    assert_eq!(
+        resolve("crates/foo", "../bar/a").as_deref(),
+        Some("crates/bar/a")
+    );
+    assert_eq!(resolve("crates/foo", "../../a").as_deref(), Some("a"));
+    assert_eq!(resolve("crates/foo", "..").as_deref(), Some("crates/"));
+    assert_eq!(resolve("crates/foo", "./.././../a").as_deref(), Some("a"));
+    assert_eq!(resolve("crates/foo", "./../../../a"), None);
+    assert_eq!(
+        resolve("crates/foo", "a/../b").as_deref(),
+        Some("crates/foo/a/../b")
+    );
+}
+
+#[test]
+fn test_with_absolute_urls_relative_paths() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::Tag;
+    use std::string::ToString;
+
+    let urls = |data: CMarkData| {
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                    Some(dest_url.to_string())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let root = "https://github.com/x/y/blob/master/";
+    let text = "[a](./examples/demo.rs) [b](../other/README.md) ![c](./.././c.png)\n";
+
+    assert_eq!(
+        urls(
+            readme_data(text)
+                .try_with_absolute_urls(root, "crates/foo")
+                .unwrap()
+        ),
+        [
+            "https://github.com/x/y/blob/master/crates/foo/examples/demo.rs",
+            "https://github.com/x/y/blob/master/crates/other/README.md",
+            "https://github.com/x/y/blob/master/crates/c.png",
+        ]
+    );
+    assert_eq!(
+        urls(
+            readme_data(text)
+                .use_absolute_blob_urls_with_subpath("https://github.com/x/y", "crates/foo")
+                .unwrap()
+        ),
+        urls(
+            readme_data(text)
+                .try_with_absolute_urls(root, "crates/foo")
+                .unwrap()
+        ),
+    );
+    assert_eq!(
+        urls(
+            readme_data("[a](./examples/demo.rs)\n")
+                .use_absolute_blob_urls("https://github.com/x/y")
+                .unwrap()
+        ),
+        ["https://github.com/x/y/blob/master/examples/demo.rs"]
+    );
+    assert!(matches!(
+        readme_data(text).try_with_absolute_urls(root, ""),
+        Err(DisallowUrlsWithPrefixError::EscapesRoot { url, root: err_root })
+            if url == "../other/README.md" && err_root == root
+    ));
+    assert!(matches!(
+        readme_data(text).use_absolute_blob_urls("https://github.com/x/y"),
+        Err(DisallowUrlsWithPrefixError::EscapesRoot { url, root: err_root })
+            if url == "../other/README.md" && err_root == root
+    ));
+    assert_eq!(
+        urls(readme_data(text).with_absolute_urls(root)),
+        [
+            "https://github.com/x/y/blob/master/examples/demo.rs",
+            "https://github.com/x/y/blob/master/../other/README.md",
+            "https://github.com/x/y/blob/master/./.././c.png",
+        ]
+    );
+    assert_eq!(
+        urls(readme_data("[a](../../../../../a)\n").with_absolute_urls(root)),
+        ["https://github.com/x/y/blob/master/../../../../../a"]
+    );
+}
+
+#[test]
+fn test_strip_url_prefix() {
+    let prefix = "https://docs.rs/foo/*/foo/";
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foo/struct.Foo.html", prefix),
+        Some("struct.Foo.html")
+    );
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foo/#features", prefix),
+        Some("#features")
+    );
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foo#features", prefix),
+        Some("#features")
+    );
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foo?search=a", prefix),
+        Some("?search=a")
+    );
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foo", prefix),
+        Some("")
+    );
+    assert_eq!(
+        strip_url_prefix("HTTPS://Docs.RS/foo/*/foo/index.html", prefix),
+        Some("index.html")
+    );
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foobar", prefix),
+        None
+    );
+    assert_eq!(strip_url_prefix("https://docs.rs/FOO/*/foo/", prefix), None);
+    assert_eq!(
+        strip_url_prefix("https://docs.rs.evil/foo/*/foo/", prefix),
+        None
+    );
+    assert_eq!(strip_url_prefix("#features", prefix), None);
+    assert_eq!(
+        strip_url_prefix("https://docs.rs/foo/*/foo/", "https://DOCS.rs/foo/*/foo"),
+        Some("/")
+    );
+    assert_eq!(
+        strip_url_prefix("https://docs.rs#a", "https://docs.rs/"),
+        Some("#a")
+    );
+    assert_eq!(strip_url_prefix("src/lib.rs", "src/"), Some("lib.rs"));
+    assert_eq!(strip_url_prefix("SRC/lib.rs", "src/"), None);
+}
+
+#[test]
+fn test_collapse_whitespace() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::CowStr;
+
+    let data = readme_data(
+        "Text.  More   text\twith `code  span` and  \nhard break.\n\n```\nkeep   this\n```\n",
+    )
+    .collapse_whitespace();
+    let text = |text| Event::Text(CowStr::Borrowed(text));
+    let events: Vec<_> = data
+        .iter_events()
+        .filter(|event| matches!(event, Event::Text(_) | Event::Code(_) | Event::HardBreak))
+        .cloned()
+        .collect();
+    assert_eq!(
+        events,
+        [
+            text("Text. More text with"),
+            Event::Code(CowStr::Borrowed("code  span")),
+            text("and"),
+            Event::HardBreak,
+            text("hard break."),
+            text("keep   this\n"),
+        ]
+    );
+}
+
+#[test]
+fn test_remove_codeblocks_with_tag() {
+    use crate::test_utils::readme_data;
+
+    let events = |text: &str| {
+        let data = readme_data(text).remove_codeblocks_with_tag("no_sync");
+        data.iter_events()
+            .map(|event| std::format!("{:?}", event))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(events("```sh,no_sync\ncargo test\n```\n"), [""; 0]);
+    assert_eq!(
+        events("```rust no_sync\nfn a() {}\n```\n\nText\n"),
+        events("Text\n")
+    );
+    assert_eq!(
+        events("Text\n\n```no_sync\n```\n\n```rust\nfn b() {}\n```\n"),
+        events("Text\n\n```rust\nfn b() {}\n```\n")
+    );
+    assert_eq!(
+        events("- Item\n\n  ```text,no_sync\n  # Not a heading\n  ```\n\n  Text\n"),
+        events("- Item\n\n  Text\n")
+    );
+    assert_eq!(events("```rust,no_sync_other\n```\n").len(), 2);
+    assert_eq!(events("```no_sync_extra\nText\n```\n").len(), 3);
+
+    let text = "```console,no_sync\n$ cargo test\n$ cargo run\n```\n\nText\n\n\
+        ```no_sync\nfn a() {}\n```\n";
+    let data = readme_data(text).remove_codeblocks_with_tag("no_sync");
+    assert_eq!(data.to_markdown(), "Text\n");
+    let removed: Vec<_> = data
+        .iter()
+        .filter(|node| matches!(node.as_ref(), CMarkItem::Removed { .. }))
+        .collect();
+    assert_eq!(removed.len(), 2);
+    let ranges = |node: &Arc<CMarkItem>| {
+        let spans = node.spans();
+        let start = spans.iter().map(|span| span.range.start).min().unwrap();
+        let end = spans.iter().map(|span| span.range.end).max().unwrap();
+        &text[start..end]
+    };
+    assert_eq!(
+        ranges(removed[0]),
+        "```console,no_sync\n$ cargo test\n$ cargo run\n```"
+    );
+    assert_eq!(ranges(removed[1]), "```no_sync\nfn a() {}\n```");
+}
+
+#[test]
+fn test_use_absolute_blob_urls_with_subpath() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::Tag;
+    use std::string::ToString;
+
+    let urls = |subpath| {
+        let data = readme_data("[a](LICENSE) [b](#usage) [c](https://example.com/)\n")
+            .use_absolute_blob_urls_with_subpath("https://github.com/user/repo/", subpath)
+            .unwrap();
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link { dest_url, .. }) => Some(dest_url.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let expected = |license: &str| {
+        [
+            license.to_string(),
+            "#usage".to_string(),
+            "https://example.com/".to_string(),
+        ]
+    };
+    assert_eq!(
+        urls(""),
+        expected("https://github.com/user/repo/blob/master/LICENSE")
+    );
+    assert_eq!(
+        urls("crates/foo/"),
+        expected("https://github.com/user/repo/blob/master/crates/foo/LICENSE")
+    );
+}
+
+#[test]
+fn test_remove_hidden_rust_code() {
+    use crate::test_utils::readme_data;
+
+    let texts = |text: &str| {
+        let data = readme_data(text).remove_hidden_rust_code();
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Text(text) => Some(std::string::ToString::to_string(text)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        texts("```rust\n# use a::b;\n#\nlet x = 1;\n    # hidden\n## escaped\n#[attr]\n```\n"),
+        ["let x = 1;\n# escaped\n#[attr]\n"]
+    );
+    assert_eq!(texts("```no_run,rust\n# hidden\nshown\n```\n"), ["shown\n"]);
+    assert_eq!(
+        texts("```bash\n# Install the tool\ncargo install foo\n```\n"),
+        ["# Install the tool\ncargo install foo\n"]
+    );
+    assert_eq!(
+        texts("```rust\n# hidden\n```\n\n```sh\n# comment\n```\n"),
+        ["", "# comment\n"]
+    );
+    assert_eq!(texts("```\n# comment\n```\n"), ["# comment\n"]);
+    assert_eq!(
+        texts("```rust\n# hidden\nshown\n```\n\n```toml\n# comment\n[dependencies]\n```\n"),
+        ["shown\n", "# comment\n[dependencies]\n"]
+    );
+    assert_eq!(
+        texts("```rust\n# hidden\n```\n\n# Heading\n\n\\# text\n"),
+        ["", "Heading", "# text"]
+    );
+}
+
+#[test]
+fn test_visible_rust_line() {
+    let line = |line| visible_rust_line(line).map(Cow::into_owned);
+    assert_eq!(line("let x = 1;").as_deref(), Some("let x = 1;"));
+    assert_eq!(line("# use foo;"), None);
+    assert_eq!(line("    # use foo;"), None);
+    assert_eq!(line("\t# use foo;"), None);
+    assert_eq!(line("#\tuse foo;"), None);
+    assert_eq!(line("#"), None);
+    assert_eq!(line("    #"), None);
+    assert_eq!(
+        line("#[derive(Debug)]").as_deref(),
+        Some("#[derive(Debug)]")
+    );
+    assert_eq!(line("    #[test]").as_deref(), Some("    #[test]"));
+    assert_eq!(
+        line("#![allow(unused)]").as_deref(),
+        Some("#![allow(unused)]")
+    );
+    assert_eq!(line("## attribute").as_deref(), Some("# attribute"));
+    assert_eq!(line("    ##[attr]").as_deref(), Some("    #[attr]"));
+    assert_eq!(line("###").as_deref(), Some("##"));
+    assert_eq!(line("").as_deref(), Some(""));
+}
+
+#[test]
+fn test_canonicalize_emphasis_nesting() {
+    use crate::test_utils::readme_data;
+
+    let events = |text: &str| {
+        let data = readme_data(text).canonicalize_emphasis_nesting();
+        assert_eq!(data.validate_structure(), Ok(()));
+        data.iter_events()
+            .map(|event| std::format!("{:?}", event))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(events("***both***"), events("**_both_**"));
+    assert_eq!(events("_**both**_"), events("**_both_**"));
+    assert_eq!(events("(***both***)"), events("(**_both_**)"));
+    assert_eq!(events("\"***both***\"."), events("\"__*both*__\"."));
+    assert_ne!(events("***a** b*"), events("**_a_ b**"));
+    assert_eq!(events("***a* b**"), events("**_a_ b**"));
+}
+
+#[test]
+fn test_first_section_and_append() {
+    use std::path::{Path, PathBuf};
+
+    let data = |text: &str, path: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(path),
+            text.into(),
+        )))
+    };
+    let architecture = data(
+        "Intro.\n\n## Overview\n\nText.\n\n### Details\n\nMore.\n\n## Internals\n\nHidden.\n",
+        "ARCHITECTURE.md",
+    )
+    .first_section();
+    assert_eq!(
+        architecture.to_markdown(),
+        "## Overview\n\nText.\n\n### Details\n\nMore.\n"
+    );
+    assert_eq!(architecture.iter().count(), 14);
+
+    let docs = data("# Title\n\nText\n", "lib.rs").append(architecture);
+    assert_eq!(docs.validate_structure(), Ok(()));
+    assert_eq!(
+        docs.to_markdown(),
+        "# Title\n\nText\n\n## Overview\n\nText.\n\n### Details\n\nMore.\n"
+    );
+
+    let readme = CMarkData::from_markdown_file(
+        Path::new("README.md"),
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+    )
+    .unwrap();
+    assert!(readme.iter().next().is_some());
+}
+
+#[test]
+#[should_panic(expected = "appended data has unbalanced events")]
+fn test_append_unbalanced() {
+    use crate::CMarkItem;
+
+    let unbalanced = CMarkData::from_items(std::vec![CMarkItem::new(
+        Event::Start(pulldown_cmark::Tag::Paragraph),
+        Cow::from("test"),
+    )]);
+    let _ = CMarkData::default().append(unbalanced);
+}
+
+#[test]
+fn test_mask_codeblock_lines_matching() {
+    use crate::test_utils::readme_data;
+    use crate::{compare_sync, CMarkDocs, CMarkReadme};
+
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        readme_data(concat!(
+            "```rust\n",
+            "use my_crate::log;\n",
+            "macro_rules! info {\n",
+            "    ($msg:expr) => { my_crate::log($msg) };\n",
+            "}\n",
+            "```\n\n",
+            "```sh\n",
+            "echo $crate::a\n",
+            "```\n",
+        )),
+        (),
+        (),
+    );
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        readme_data(concat!(
+            "```rust\n",
+            "#[macro_use] extern crate my_crate;\n",
+            "macro_rules! info {\n",
+            "    ($msg:expr) => { $crate::log($msg) };\n",
+            "}\n",
+            "```\n\n",
+            "```sh\n",
+            "echo $crate::a\n",
+            "```\n",
+        )),
+        (),
+        (),
+    );
+    assert!(compare_sync(&readme, &docs).is_err());
+
+    let patterns = [
+        "#[macro_use]*",
+        "use my_crate::*",
+        "*$crate::*",
+        "*my_crate::log(*",
+    ];
+    let readme = readme.mask_codeblock_lines_matching(&patterns, "/* masked */");
+    let docs = docs.mask_codeblock_lines_matching(&patterns, "/* masked */");
+    assert_eq!(
+        readme.data().to_markdown(),
+        concat!(
+            "```rust\n",
+            "/* masked */\n",
+            "macro_rules! info {\n",
+            "    /* masked */\n",
+            "}\n",
+            "```\n\n",
+            "```sh\n",
+            "echo $crate::a\n",
+            "```\n",
+        )
+    );
+    assert!(compare_sync(&readme, &docs).is_ok());
+
+    assert_eq!(
+        readme_data("```rust\n#[macro_use]\n#[macro_export]\n[a]\n```\n")
+            .mask_codeblock_lines_matching(&["#[macro_use]*", "[?]"], "//")
+            .to_markdown(),
+        "```rust\n//\n#[macro_export]\n//\n```\n"
+    );
+}
+
+#[test]
+fn test_with_absolute_image_urls() {
+    use crate::test_utils::{modified_notes, readme_file};
+    use crate::CMarkReadme;
+
+    let readme = CMarkReadme::from_file(readme_file(
+        "![diagram](docs/diagram.png) ![logo](https://example.com/logo.png)\n",
+    ))
+    .use_absolute_blob_urls("https://github.com/user/project")
+    .unwrap();
+    assert_eq!(
+        readme.data().to_markdown(),
+        "![diagram](https://github.com/user/project/blob/master/docs/diagram.png) \
+            ![logo](https://example.com/logo.png)\n"
+    );
+    assert_eq!(
+        modified_notes(readme.data()),
+        ["with_absolute_urls(prefix = \"https://github.com/user/project/blob/master/\")"]
+    );
+}
+
+#[test]
+fn test_remove_sections() {
+    use crate::test_utils::{readme_data, removed_notes};
+
+    let data = || {
+        readme_data(
+            "# Foo\n\nText.\n\n## License\n\nMIT.\n\n### Details\n\nMore.\n\n\
+                ## Usage\n\nUse it.\n\n## Changelog\n\nNone.\n\n## Contribution\n\nWelcome.\n",
+        )
+    };
+    let sections = [
+        ("Contribution", 2),
+        ("License", 2),
+        ("Changelog", 2),
+        ("Other", 2),
+    ];
+    let removed = data().remove_sections(&sections);
+    assert_eq!(
+        removed.to_markdown(),
+        data()
+            .remove_section("License", 2)
+            .remove_section("Changelog", 2)
+            .remove_section("Contribution", 2)
+            .to_markdown()
+    );
+    assert_eq!(
+        removed.to_markdown(),
+        "# Foo\n\nText.\n\n## Usage\n\nUse it.\n"
+    );
+    assert_eq!(
+        removed_notes(&removed),
+        [
+            "remove_section(name = \"License\", level = 2)",
+            "remove_section(name = \"Changelog\", level = 2)",
+            "remove_section(name = \"Contribution\", level = 2)",
+        ]
+    );
+
+    let mut reversed = sections;
+    reversed.reverse();
+    assert_eq!(data().remove_sections(&reversed), removed);
+    assert_eq!(data().remove_sections(&[]), data());
+    assert_eq!(
+        data().remove_sections(&[("Details", 3), ("License", 2)]),
+        data().remove_section("License", 2)
+    );
+}
+
+#[test]
+fn test_cmark_data_iteration() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::Tag;
+
+    let data =
+        readme_data("# Foo\n\nText.\n\n## Bar\n\n### Baz\n\nMore text.\n").remove_section("Baz", 3);
+
+    let items: Vec<_> = data.iter().collect();
+    assert_eq!(data.len(), items.len());
+    assert!(!data.is_empty());
+    assert!(CMarkData::default().is_empty());
+    assert_eq!(data.iter().len(), data.len());
+    let mut reversed: Vec<_> = data.iter().rev().collect();
+    reversed.reverse();
+    assert_eq!(reversed, items);
+    for (idx, item) in items.iter().enumerate() {
+        assert!(Arc::ptr_eq(data.get(idx).unwrap(), item));
+    }
+    assert!(data.get(data.len()).is_none());
+
+    let mut iter = data.iter();
+    assert!(iter.next_back().is_some());
+    assert_eq!(iter.len(), data.len() - 1);
+    iter.by_ref().for_each(drop);
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
 
-        self.map(|node| {
-            let event = match node.event() {
-                Some(Event::Start(node_tag)) => {
-                    map_default_codeblock_tag(node_tag, tag).map(Event::Start)
-                }
-                _ => None,
-            };
-            match event {
-                Some(event) => node.into_modified(
-                    event,
-                    Cow::from(std::format!("use_default_codeblock_tag(tag = \"{}\")", tag)),
-                ),
-                None => node,
-            }
-        })
+    let indexed: Vec<_> = data.iter_events_indexed().collect();
+    assert_eq!(indexed.len(), data.iter_events().count());
+    assert!(indexed.len() < data.len());
+    for (idx, event) in &indexed {
+        assert_eq!(data.get(*idx).unwrap().event(), Some(*event));
     }
+
+    let last_heading = data.rposition_heading(1..=6).unwrap();
+    assert!(matches!(
+        data.get(last_heading).unwrap().event(),
+        Some(Event::Start(Tag::Heading { level, .. })) if heading_level(*level) == 2
+    ));
+    assert_eq!(data.rposition_heading(..2), Some(0));
+    assert_eq!(data.rposition_heading(3..), None);
 }
 
-fn map_default_codeblock_tag<'a>(
-    event_tag: &pulldown_cmark::Tag<'a>,
-    tag: &str,
-) -> Option<pulldown_cmark::Tag<'a>> {
-    use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+#[test]
+fn test_keep_sections() {
+    use crate::test_utils::{readme_data, removed_notes};
+
+    let data = || {
+        readme_data(
+            "# Foo\n\nText.\n\n## Usage\n\nUse it.\n\n### Details\n\nMore.\n\n\
+                ## License\n\nMIT.\n\n## Usage\n\nAgain.\n",
+        )
+    };
+
+    let kept = data().keep_sections(&["Usage"], 2);
+    assert_eq!(
+        kept.to_markdown(),
+        "# Foo\n\nText.\n\n## Usage\n\nUse it.\n\n### Details\n\nMore.\n\n\
+            ## Usage\n\nAgain.\n"
+    );
+    assert_eq!(
+        removed_notes(&kept),
+        ["keep_sections(names = [\"Usage\"], level = 2)"]
+    );
+
+    let preamble = data().keep_sections(&[], 2);
+    assert_eq!(preamble.to_markdown(), "# Foo\n\nText.\n");
+    assert_eq!(removed_notes(&preamble).len(), 3);
+
+    assert_eq!(
+        data().keep_sections(&["License", "Usage"], 2).to_markdown(),
+        data().to_markdown()
+    );
+}
+
+#[test]
+fn test_remove_section_with_inline_markup() {
+    use crate::test_utils::readme_data;
+
+    let text = "# Foo\n\n## The `assert_sync` function\n\nText.\n\n\
+        ## *Experimental* API\n\nText.\n\n## Usage:\n\nText.\n\n\
+        ## `Documentation`\n\nText.\n";
+
+    assert_eq!(
+        readme_data(text)
+            .remove_section("The assert_sync function", 2)
+            .remove_section("Experimental API", 2)
+            .remove_section("Usage:", 2)
+            .remove_documentation_section()
+            .to_markdown(),
+        "# Foo\n"
+    );
+    assert_eq!(
+        readme_data(text).remove_section("Usage", 2).to_markdown(),
+        readme_data(text).to_markdown()
+    );
+    assert_eq!(
+        readme_data(text)
+            .remove_section("The `assert_sync` function", 2)
+            .to_markdown(),
+        readme_data(text).to_markdown()
+    );
+}
+
+#[test]
+fn test_remove_section_by_predicate() {
+    use crate::test_utils::{readme_data, removed_notes};
+
+    let data = || {
+        readme_data(
+            "# Foo\n\n## Benchmarks\n\nFast.\n\n### Benchmark results\n\nVery fast.\n\n\
+                ## Usage\n\nUse it.\n\n### Performance\n\nFast.\n\n## *Benchmark* results\n\n\
+                Fast.\n",
+        )
+    };
+
+    let mut calls = Vec::new();
+    let removed = data().remove_section_by_predicate(|text, level| {
+        calls.push((String::from(text), level));
+        text.starts_with("Benchmark") || text == "Performance"
+    });
+    assert_eq!(removed.to_markdown(), "# Foo\n\n## Usage\n\nUse it.\n");
+    assert_eq!(
+        calls,
+        [
+            (String::from("Foo"), 1),
+            (String::from("Benchmarks"), 2),
+            (String::from("Usage"), 2),
+            (String::from("Performance"), 3),
+            (String::from("Benchmark results"), 2),
+        ]
+    );
+    assert_eq!(
+        removed_notes(&removed),
+        ["remove_section_by_predicate()"; 3]
+    );
+    assert_eq!(data().remove_section_by_predicate(|_, _| false), data());
+}
+
+#[test]
+fn test_extract_section() {
+    use crate::test_utils::{docs_data, removed_notes};
+
+    let data = || {
+        docs_data(
+            "# Foo\n\nText.\n\n## Usage\n\nUse it.\n\n### Details\n\nMore.\n\n\
+                ## License\n\nMIT.\n",
+        )
+    };
+
+    assert!(data().has_section("Usage", 2));
+    assert!(!data().has_section("Usage", 1));
+    assert!(!data().has_section("Other", 2));
+
+    let section = data().extract_section("Usage", 2);
+    assert_eq!(
+        section.to_markdown(),
+        "## Usage\n\nUse it.\n\n### Details\n\nMore.\n"
+    );
+    assert_eq!(
+        removed_notes(&section),
+        [
+            "extract_section(name = \"Usage\", level = 2)",
+            "extract_section(name = \"Usage\", level = 2)",
+        ]
+    );
+
+    assert_eq!(
+        data().extract_section_body("Usage", 2).to_markdown(),
+        "Use it.\n\n### Details\n\nMore.\n"
+    );
+    assert_eq!(
+        data().extract_section_body("License", 2).to_markdown(),
+        "MIT.\n"
+    );
+
+    let missing = data().extract_section("Other", 2);
+    assert_eq!(missing.iter_events().count(), 0);
+    assert_eq!(missing.len(), 1);
+    assert!(CMarkData::default().extract_section("Usage", 2).is_empty());
+}
+
+#[test]
+fn test_remove_sections_from() {
+    use crate::test_utils::{readme_data, removed_notes};
+
+    let data =
+        || readme_data("# Foo\n\nText.\n\n## License\n\nMIT.\n\n## Contribution\n\nWelcome.\n");
+
+    let removed = data().remove_sections_from("License", 2);
+    assert_eq!(removed.to_markdown(), "# Foo\n\nText.\n");
+    assert_eq!(
+        removed_notes(&removed),
+        ["remove_sections_from(name = \"License\", level = 2)"]
+    );
+
+    assert_eq!(data().remove_sections_from("License", 3), data());
+    assert_eq!(data().remove_sections_from("Changelog", 2), data());
+}
+
+#[test]
+fn test_normalize_heading_levels() {
+    use crate::test_utils::{modified_notes, readme_data};
+    use pulldown_cmark::HeadingLevel;
+
+    assert_eq!(
+        readme_data("# Foo\n\n## Bar\n\nText.\n")
+            .normalize_heading_levels(HeadingLevel::H2)
+            .to_markdown(),
+        "## Foo\n\n### Bar\n\nText.\n"
+    );
+    assert_eq!(
+        readme_data("### Foo\n\n#### Bar\n\n##### Baz\n")
+            .normalize_heading_levels(HeadingLevel::H2)
+            .to_markdown(),
+        "## Foo\n\n### Bar\n\n#### Baz\n"
+    );
+    assert_eq!(
+        readme_data("## Foo\n\n### Bar\n").normalize_heading_levels(HeadingLevel::H2),
+        readme_data("## Foo\n\n### Bar\n")
+    );
+    assert_eq!(
+        readme_data("Text.\n").normalize_heading_levels(HeadingLevel::H2),
+        readme_data("Text.\n")
+    );
+
+    let clamped = readme_data("# Foo\n\n##### Bar\n\n###### Baz\n")
+        .normalize_heading_levels(HeadingLevel::H3);
+    assert_eq!(
+        clamped.to_markdown(),
+        "### Foo\n\n###### Bar\n\n###### Baz\n"
+    );
+    assert_eq!(
+        modified_notes(&clamped),
+        [
+            "normalize_heading_levels(top_level = h3)",
+            "normalize_heading_levels(top_level = h3)",
+            "normalize_heading_levels(top_level = h3) clamped to h6",
+            "normalize_heading_levels(top_level = h3) clamped to h6",
+            "normalize_heading_levels(top_level = h3) clamped to h6",
+            "normalize_heading_levels(top_level = h3) clamped to h6",
+        ]
+    );
+}
+
+#[test]
+fn test_increment_heading_levels_checked() {
+    use crate::test_utils::docs_data;
     use std::string::ToString;
 
-    if let Tag::CodeBlock(CodeBlockKind::Fenced(ref node_tag)) = event_tag {
-        if node_tag.as_ref() == "" {
-            return Some(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::Boxed(
-                tag.to_string().into_boxed_str(),
-            ))));
-        }
-    }
-    None
+    assert_eq!(
+        docs_data("# Foo\n\n##### Bar\n")
+            .increment_heading_levels_checked()
+            .unwrap(),
+        docs_data("# Foo\n\n##### Bar\n").increment_heading_levels()
+    );
+    let err = docs_data("# Foo\n\n##### Bar\n\n###### The `baz`\n")
+        .increment_heading_levels_checked()
+        .unwrap_err();
+    assert_eq!(err.heading, "The baz");
+    assert_eq!(
+        err.to_string(),
+        "The heading `The baz` of the sixth level can not be incremented."
+    );
+    let spans = err.node.spans();
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].range.start, "# Foo\n\n##### Bar\n\n".len());
 }
 
-impl CMarkData {
-    /// Use rust fenced codeblock highlight as default.
-    pub fn use_default_codeblock_rust_tag(self) -> Self {
-        self.use_default_codeblock_tag("rust")
-    }
+#[test]
+fn test_map_headings() {
+    use crate::test_utils::{modified_notes, readme_data};
 
-    /// Remove hidden rust code from rust fenced codeblocks.
-    ///
-    /// See <https://doc.rust-lang.org/rustdoc/documentation-tests.html#hiding-portions-of-the-example> for more details.
-    pub fn remove_hidden_rust_code(self) -> Self {
-        use crate::CMarkItemAsModified;
-        use pulldown_cmark::{CodeBlockKind, CowStr, Tag};
+    let text = "# Foo\n\n## Example\n\nText.\n\n## The `foo` *function*\n\n### Example\n";
 
-        let mut is_rust_codeblock = false;
+    let mut calls = Vec::new();
+    let mapped = readme_data(text).map_headings(
+        |text, level| {
+            calls.push((String::from(text), level));
+            match text {
+                "Example" => Some(String::from("Examples")),
+                "The foo function" => Some(String::from("Function")),
+                "Foo" => Some(String::from("Foo")),
+                _ => None,
+            }
+        },
+        "map_headings()",
+    );
+    assert_eq!(
+        calls,
+        [
+            (String::from("Foo"), 1),
+            (String::from("Example"), 2),
+            (String::from("The foo function"), 2),
+            (String::from("Example"), 3),
+        ]
+    );
+    assert_eq!(
+        mapped.to_markdown(),
+        "# Foo\n\n## Examples\n\nText.\n\n## Function\n\n### Examples\n"
+    );
+    assert_eq!(modified_notes(&mapped), ["map_headings()"; 3]);
 
-        self.map(|node| {
-            match node.event() {
-                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tags)))) => {
-                    is_rust_codeblock |= tags.split(',').any(|tag| tag == "rust")
-                }
-                Some(Event::Text(text)) => {
-                    if is_rust_codeblock {
-                        let text: Vec<_> = text
-                            .split('\n')
-                            .filter(|line| *line != "#" && !line.starts_with("# "))
-                            .collect();
-                        let text = text.join("\n");
-                        let event = Event::Text(CowStr::Boxed(text.into_boxed_str()));
-                        return node.into_modified(event, Cow::from("remove_hidden_rust_code()"));
-                    }
+    assert_eq!(
+        readme_data(text)
+            .rename_section("Example", "Examples", 2)
+            .rename_section("The foo function", "Function", 2)
+            .to_markdown(),
+        "# Foo\n\n## Examples\n\nText.\n\n## Function\n\n### Example\n"
+    );
+}
+
+#[test]
+fn test_remove_codeblock_contents() {
+    use crate::test_utils::{readme_data, removed_items, static_events};
+
+    let readme = "Text.\n\n```rust\nfoo();\n```\n\n```toml\na = 1\n```\n\n    indented\n";
+    let docs = "Text.\n\n```rust\n# setup();\nfoo();\nbar();\n```\n\n\
+        ```toml\nb = 2\n```\n\n    other\n";
+
+    assert_eq!(
+        static_events(&readme_data(readme).remove_codeblock_contents()),
+        static_events(&readme_data(docs).remove_codeblock_contents())
+    );
+    assert_ne!(
+        static_events(&readme_data(readme).remove_codeblock_contents_for_tag("rust")),
+        static_events(&readme_data(docs).remove_codeblock_contents_for_tag("rust"))
+    );
+    assert_eq!(
+        static_events(
+            &readme_data("```rust\nfoo();\n```\n").remove_codeblock_contents_for_tag("rust")
+        ),
+        static_events(
+            &readme_data("```rust\nbar();\n```\n").remove_codeblock_contents_for_tag("rust")
+        )
+    );
+
+    let removed = readme_data(docs).remove_codeblock_contents_for_tag("rust");
+    let spans: Vec<_> = removed_items(&removed)
+        .into_iter()
+        .flat_map(|node| node.spans())
+        .collect();
+    assert!(!spans.is_empty());
+    assert!(spans.iter().all(|span| span
+        .note
+        .ends_with("remove_codeblock_contents_for_tag(tag = \"rust\")")));
+    assert_eq!(spans[0].range.start, "Text.\n\n```rust\n".len());
+}
+
+#[test]
+fn test_trim_codeblock_text() {
+    use crate::test_utils::{readme_data, static_events};
+    use pulldown_cmark::{CodeBlockKind, CowStr, Tag, TagEnd};
+
+    assert_eq!(trim_codeblock_text("a  \nb\t\n\n \n", false), "a\nb\n");
+    assert_eq!(trim_codeblock_text("a", false), "a\n");
+    assert_eq!(trim_codeblock_text("\n\na\n", false), "\n\na\n");
+    assert_eq!(trim_codeblock_text("\n\na\n", true), "\na\n");
+    assert_eq!(trim_codeblock_text(" \n", true), "");
+    assert_eq!(
+        static_events(&readme_data("Text  \n\n```rust\nfoo();  \n\n\n```\n").trim_codeblock_text()),
+        static_events(&readme_data("Text  \n\n```rust\nfoo();\n```\n"))
+    );
+    assert_eq!(
+        static_events(&readme_data("```rust\n  \n\n```\n").trim_codeblock_text()),
+        static_events(&readme_data("```rust\n```\n"))
+    );
+    assert_eq!(
+        static_events(&readme_data("```\n\n \n```\n").trim_codeblock_text_and_leading_blank_line()),
+        static_events(&readme_data("```\n```\n"))
+    );
+
+    let split = CMarkData::from_items(std::vec![
+        CMarkItem::new(
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::Borrowed(
+                "rust"
+            )))),
+            Cow::from("test")
+        ),
+        CMarkItem::new(Event::Text(CowStr::Borrowed("foo();")), Cow::from("test")),
+        CMarkItem::new(Event::Text(CowStr::Borrowed(" \n\n")), Cow::from("test")),
+        CMarkItem::new(Event::End(TagEnd::CodeBlock), Cow::from("test")),
+        CMarkItem::new(Event::Text(CowStr::Borrowed("text ")), Cow::from("test")),
+    ]);
+    let trimmed = split.trim_codeblock_text();
+    assert_eq!(trimmed.len(), 4);
+    assert_eq!(
+        static_events(&trimmed),
+        [
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::Borrowed(
+                "rust"
+            )))),
+            Event::Text(CowStr::Borrowed("foo();\n")),
+            Event::End(TagEnd::CodeBlock),
+            Event::Text(CowStr::Borrowed("text ")),
+        ]
+    );
+    assert_eq!(
+        static_events(
+            &readme_data("```\n\nfoo\n```\n").trim_codeblock_text_and_leading_blank_line()
+        ),
+        static_events(&readme_data("```\nfoo\n```\n"))
+    );
+}
+
+#[test]
+fn test_map_codeblock_tags() {
+    use crate::test_utils::{modified_notes, readme_data};
+
+    let text = "```toml,ignore\na = 1\n```\n\n```console\n$ ls\n```\n\n```rust\n```\n\n    code\n";
+
+    let mapped = readme_data(text).map_codeblock_tags(
+        |info| match info {
+            "toml,ignore" => Cow::from("toml"),
+            _ => Cow::from(info),
+        },
+        "map_codeblock_tags()",
+    );
+    assert_eq!(modified_notes(&mapped).len(), 1);
+    assert_eq!(
+        mapped.to_markdown(),
+        readme_data(text.replace("toml,ignore", "toml").as_str()).to_markdown()
+    );
+    assert_eq!(
+        modified_notes(
+            &readme_data(text).map_codeblock_tags(|info| Cow::from(String::from(info)), "same()")
+        )
+        .len(),
+        0
+    );
+
+    let aliased = readme_data("```console no_run\n$ ls\n```\n\n```consoles\n```\n")
+        .map_codeblock_tag_aliases(&[("console", "sh"), ("no_run", "ignore")]);
+    assert_eq!(modified_notes(&aliased).len(), 1);
+    assert_eq!(
+        aliased.to_markdown(),
+        readme_data("```sh ignore\n$ ls\n```\n\n```consoles\n```\n").to_markdown()
+    );
+}
+
+#[test]
+fn test_codeblock_tag_separators() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::{CodeBlockKind, Tag};
+
+    let infos = |data: CMarkData| {
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    Some(std::string::ToString::to_string(info))
                 }
-                _ => {}
-            };
-            node
-        })
-    }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let texts = |data: CMarkData| {
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Text(text) => Some(std::string::ToString::to_string(text)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let text = "```rust ignore\n# a\nb\n```\n\n```rust, ignore\n# a\nb\n```\n\n\
+                ```ignore,rust no_run\n# a\nb\n```\n";
+    assert_eq!(
+        infos(readme_data(text).remove_codeblock_tag("ignore")),
+        ["rust", "rust", "rust no_run"]
+    );
+    assert_eq!(
+        infos(readme_data(text).remove_codeblock_tags(&["ignore", "no_run"])),
+        ["rust", "rust", "rust"]
+    );
+    assert_eq!(
+        texts(readme_data(text).remove_hidden_rust_code()),
+        ["b\n", "b\n", "b\n"]
+    );
+    assert_eq!(
+        infos(readme_data(text).use_default_codeblock_tag("sh")),
+        ["rust ignore", "rust, ignore", "ignore,rust no_run"]
+    );
+    assert_eq!(
+        infos(readme_data("```\na\n```\n\n```,\nb\n```\n").use_default_codeblock_tag("sh")),
+        ["sh", "sh"]
+    );
 }
 
-/// An error which can occur when checking for disallowed link prefixes.
-#[derive(Clone, Debug, Error)]
-pub enum DisallowUrlsWithPrefixError {
-    /// A prefix found
-    #[error("The url `{url}` use a prohibited prefix `{prefix}`.")]
-    PrefixFound {
-        /// Full url
-        url: String,
-        /// Disallowed prefix
-        prefix: String,
-    },
+#[test]
+fn test_remove_codeblock_rust_test_tags() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::{CodeBlockKind, Tag};
+
+    let infos = |text: &str| {
+        readme_data(text)
+            .remove_codeblock_rust_test_tags()
+            .use_default_codeblock_rust_tag()
+            .iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    Some(std::string::ToString::to_string(info))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(infos("```rust,compile_fail,E0502\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```compile_fail,E0277\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```compile_fail E0277 rust\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```E0277\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```ignore\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```should_panic,rust,no_run\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```sh,E027\na\n```\n"), ["sh,E027"]);
+    assert!(is_rust_codeblock_tags("compile_fail,E0277,rust"));
+
+    assert_eq!(infos("```rust,edition2021\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```rust,edition2024\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```rust standalone_crate\na\n```\n"), ["rust"]);
+    assert_eq!(infos("```rust,custom\na\n```\n"), ["rust"]);
+
+    let infos_with = |text: &str, extra: &[&str]| {
+        readme_data(text)
+            .remove_codeblock_rust_test_tags_with(extra)
+            .iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    Some(std::string::ToString::to_string(info))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(infos_with("```sh,ignore,sh\na\n```\n", &["sh"]), ["sh"]);
+    assert_eq!(infos_with("```ignore,sh\na\n```\n", &["sh"]), [""]);
+
+    let data = readme_data("```rust,no_run,my_tag\na\n```\n")
+        .remove_codeblock_rust_test_tags_with(&["my_tag"]);
+    assert!(data.iter_events().any(|event| matches!(
+        event,
+        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if info.as_ref() == "rust"
+    )));
 }
 
-fn increase_heading_level(level: pulldown_cmark::HeadingLevel) -> pulldown_cmark::HeadingLevel {
-    use pulldown_cmark::HeadingLevel;
+#[test]
+fn test_convert_indented_codeblocks_to_fenced() {
+    use crate::test_utils::{modified_notes, readme_data, static_events};
+    use pulldown_cmark::{CodeBlockKind, Tag};
 
-    match level {
-        HeadingLevel::H1 => HeadingLevel::H2,
-        HeadingLevel::H2 => HeadingLevel::H3,
-        HeadingLevel::H3 => HeadingLevel::H4,
-        HeadingLevel::H4 => HeadingLevel::H5,
-        HeadingLevel::H5 | HeadingLevel::H6 => HeadingLevel::H6,
+    let converted = readme_data("Text\n\n    # use a::b;\n    let x = 1;\n")
+        .convert_indented_codeblocks_to_fenced("rust");
+    assert_eq!(
+        modified_notes(&converted)
+            .iter()
+            .filter(|note| note.contains("rust-lang/rust/issues/70732"))
+            .count(),
+        1
+    );
+    assert_eq!(
+        static_events(&converted),
+        static_events(&readme_data(
+            "Text\n\n```rust\n# use a::b;\nlet x = 1;\n```\n"
+        ))
+    );
+    assert_eq!(
+        static_events(&converted.remove_hidden_rust_code()),
+        static_events(&readme_data("Text\n\n```rust\nlet x = 1;\n```\n"))
+    );
+
+    let fenced = readme_data("```\n    a\n```\n").convert_indented_codeblocks_to_fenced("rust");
+    assert!(fenced
+        .iter_events()
+        .any(|event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag.is_empty())));
+}
+
+#[test]
+fn test_remove_html_comments() {
+    use crate::test_utils::{readme_data, removed_items, static_events};
+
+    let comments =
+        readme_data("<!-- markdownlint-disable -->\n\n# Title\n\nText\n").remove_html_comments();
+    assert_eq!(removed_items(&comments).len(), 1);
+    assert_eq!(
+        static_events(&comments),
+        static_events(&readme_data("# Title\n\nText\n"))
+    );
+
+    assert_eq!(
+        static_events(
+            &readme_data("<!--\nmulti-line\ncomment\n-->\n\nText\n").remove_html_comments()
+        ),
+        static_events(&readme_data("Text\n"))
+    );
+    assert_eq!(
+        static_events(
+            &readme_data("Text <!-- omit in toc --> more\n")
+                .remove_html_comments()
+                .concat_texts()
+        ),
+        static_events(&readme_data("Text  more\n"))
+    );
+    assert_eq!(
+        static_events(
+            &readme_data("Text <!-- a\nb --> more\n")
+                .remove_html_comments()
+                .concat_texts()
+        ),
+        static_events(&readme_data("Text  more\n"))
+    );
+    assert_eq!(
+        static_events(&readme_data("<div><!-- a -->\n<!--\nb\n--></div>\n").remove_html_comments()),
+        static_events(&readme_data("<div>\n</div>\n"))
+    );
+    assert_eq!(
+        static_events(&readme_data("<div>\n\nText\n").remove_html_comments()),
+        static_events(&readme_data("<div>\n\nText\n"))
+    );
+}
+
+#[test]
+fn test_remove_html() {
+    use crate::test_utils::{readme_data, removed_items, static_events};
+
+    let text = "<p align=\"center\">\n<img src=\"logo.png\">\n</p>\n\n\
+                # Title\n\nText <sub>note</sub>\n";
+
+    let html = readme_data(text).remove_html();
+    let removed = removed_items(&html);
+    assert_eq!(removed.len(), 3);
+    assert_eq!(removed[0].spans().len(), 5);
+    assert_eq!(
+        static_events(&html.concat_texts()),
+        static_events(&readme_data("# Title\n\nText note\n"))
+    );
+
+    assert_eq!(
+        static_events(&readme_data(text).remove_html_matching(|html| html.contains("<img"))),
+        static_events(&readme_data("# Title\n\nText <sub>note</sub>\n"))
+    );
+    let sub = readme_data(text)
+        .remove_html_matching(|html| html.starts_with("<sub") || html.starts_with("</sub"));
+    assert!(sub
+        .iter_events()
+        .any(|event| matches!(event, Event::Html(_))));
+    assert!(!sub
+        .iter_events()
+        .any(|event| matches!(event, Event::InlineHtml(_))));
+}
+
+#[test]
+fn test_normalize_hard_breaks() {
+    use crate::test_utils::{readme_data, static_events};
+
+    let events = |text: &str| static_events(&readme_data(text).normalize_hard_breaks());
+
+    let docs = events("First  \nsecond\n");
+    assert!(docs.contains(&Event::HardBreak));
+    assert_eq!(events("First<br>\nsecond\n"), docs);
+    assert_eq!(events("First<br/>\nsecond\n"), docs);
+    assert_eq!(events("First<BR />\nsecond\n"), docs);
+    assert_eq!(events("First\\\nsecond\n"), docs);
+    assert_ne!(events("First<bra>\nsecond\n"), docs);
+    assert_ne!(events("First<br>text</br>\nsecond\n"), docs);
+
+    assert!(is_html_line_break(" <br> "));
+    assert!(is_html_line_break("<Br / >"));
+    assert!(is_html_line_break("<br  />"));
+    assert!(!is_html_line_break("<brx>"));
+}
+
+#[test]
+fn test_flatten_links() {
+    use crate::test_utils::{readme_data, removed_notes, static_events};
+
+    let text = "See [`CMarkDocs`](CMarkDocs) and [docs][docs].\n\n\
+                [![Logo](logo.png)](https://example.com)\n\n\
+                [docs]: https://docs.rs/\n";
+
+    let links = readme_data(text).flatten_links();
+    assert_eq!(removed_notes(&links), ["flatten_links()"; 6]);
+    assert_eq!(
+        static_events(&links.concat_texts()),
+        static_events(
+            &readme_data("See `CMarkDocs` and docs.\n\n![Logo](logo.png)\n").concat_texts()
+        )
+    );
+    assert_eq!(
+        static_events(
+            &readme_data(text)
+                .flatten_links()
+                .flatten_images()
+                .concat_texts()
+        ),
+        static_events(&readme_data("See `CMarkDocs` and docs.\n\nLogo\n").concat_texts())
+    );
+}
+
+#[test]
+fn test_remove_emphasis() {
+    use crate::test_utils::{github_readme_data, removed_notes, static_events};
+
+    let data = github_readme_data;
+    let text = "The **codemap** feature is *enabled* by ~~default~~.\n";
+
+    let removed = data(text).remove_emphasis();
+    assert_eq!(removed_notes(&removed), ["remove_emphasis()"; 6]);
+    assert_eq!(
+        static_events(&removed),
+        static_events(&data("The codemap feature is enabled by default.\n"))
+    );
+    assert_eq!(
+        static_events(
+            &data(text).remove_emphasis_kinds(EmphasisKinds::EMPHASIS | EmphasisKinds::STRONG)
+        ),
+        static_events(&data("The codemap feature is enabled by ~~default~~.\n"))
+    );
+    assert_eq!(
+        static_events(&data(text).remove_emphasis_kinds(EmphasisKinds::NONE)),
+        static_events(&data(text))
+    );
+    assert!(EmphasisKinds::ALL.contains(EmphasisKinds::STRONG | EmphasisKinds::STRIKETHROUGH));
+    assert!(!EmphasisKinds::EMPHASIS.contains(EmphasisKinds::STRONG));
+}
+
+#[test]
+fn test_remove_thematic_breaks() {
+    use crate::test_utils::{readme_data, static_events};
+
+    let readme = "# Crate\n\n\
+        [![Crate](https://img.shields.io/crates/v/crate.svg)](https://crates.io/crates/crate)\n\n\
+        ---\n\nDescription.\n\n***\n\nFooter.\n";
+    let docs = "# Crate\n\nDescription.\n\nFooter.\n";
+
+    assert_eq!(
+        static_events(
+            &readme_data(readme)
+                .remove_badges_paragraph()
+                .remove_thematic_breaks()
+        ),
+        static_events(&readme_data(docs))
+    );
+    assert_eq!(
+        static_events(
+            &readme_data(readme)
+                .remove_badges_paragraph()
+                .remove_first_thematic_breaks(1)
+        ),
+        static_events(&readme_data("# Crate\n\nDescription.\n\n***\n\nFooter.\n"))
+    );
+    assert_eq!(
+        static_events(&readme_data(readme).remove_first_thematic_breaks(0)),
+        static_events(&readme_data(readme))
+    );
+}
+
+#[test]
+fn test_normalize_github_alerts() {
+    use crate::test_utils::{github_readme_data, static_events};
+
+    let data = github_readme_data;
+
+    assert_eq!(
+        static_events(&data("> [!NOTE]\n> Useful text.\n").normalize_github_alerts()),
+        static_events(&data("> Note: Useful text.\n"))
+    );
+    assert_eq!(
+        static_events(&data("> [!WARNING]\n> Be careful.\n").normalize_github_alerts()),
+        static_events(&data("> Warning: Be careful.\n"))
+    );
+    assert_eq!(
+        static_events(&data("> [!WARNING]\n> `unsafe` code.\n").normalize_github_alerts()),
+        static_events(&data("> Warning: `unsafe` code.\n"))
+    );
+    assert_eq!(
+        static_events(&data("> [!TIP]\n> Text.\n").strip_github_alerts()),
+        static_events(&data("> Text.\n"))
+    );
+    assert_eq!(
+        static_events(
+            &data("> [!CAUTION]\n> Text.\n")
+                .normalize_github_alerts_with(|kind| std::format!("{:?}! ", kind))
+        ),
+        static_events(&data("> Caution! Text.\n"))
+    );
+
+    assert_eq!(
+        static_events(&data("> [!NOTE]\n> - First.\n> - Second.\n").normalize_github_alerts()),
+        static_events(&data("> Note:\n>\n> - First.\n> - Second.\n"))
+    );
+    assert_eq!(
+        static_events(&data("> [!TIP]\n> ```\n> code\n> ```\n").normalize_github_alerts()),
+        static_events(&data("> Tip:\n>\n> ```\n> code\n> ```\n"))
+    );
+
+    let plain = data("> Note: plain blockquote.\n").normalize_github_alerts();
+    assert!(plain
+        .iter()
+        .all(|node| matches!(node.as_ref(), CMarkItem::Parsed { .. })));
+}
+
+#[test]
+fn test_remove_front_matter() {
+    use crate::test_utils::{readme_file, removed_items, static_events};
+    use pulldown_cmark::Options;
+
+    let data = |text: &str, options: Options| {
+        CMarkData::from_text_source_with_options(TextSource::File(readme_file(text)), options)
+    };
+    let removed_spans = |data: &CMarkData| {
+        removed_items(data)
+            .into_iter()
+            .map(|node| node.spans().len())
+            .collect::<Vec<_>>()
+    };
+    let text = "---\ntitle: Crate\nlayout: default\n---\n\n# Crate\n\nText.\n";
+    let expected = static_events(&data("# Crate\n\nText.\n", Options::empty()));
+
+    let metadata = data(text, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    let removed = metadata.clone().remove_metadata_blocks();
+    assert_eq!(removed_spans(&removed), [3]);
+    assert_eq!(static_events(&removed), expected);
+    assert_eq!(static_events(&metadata.remove_front_matter()), expected);
+
+    let degenerate = data(text, Options::empty()).remove_front_matter();
+    assert_eq!(removed_spans(&degenerate), [6]);
+    assert_eq!(static_events(&degenerate), expected);
+
+    let not_front_matter = "---\n\nTitle\n---\n\nText.\n";
+    assert_eq!(
+        static_events(&data(not_front_matter, Options::empty()).remove_front_matter()),
+        static_events(&data(not_front_matter, Options::empty()))
+    );
+    assert_eq!(
+        static_events(&data("# Crate\n", Options::empty()).remove_front_matter()),
+        static_events(&data("# Crate\n", Options::empty()))
+    );
+}
+
+#[test]
+fn test_disallow_urls_matching() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let is_insecure = |url: &str| url.starts_with("http://");
+    assert!(readme_data("[a](https://example.com) ![b](logo.png)\n")
+        .disallow_urls_matching(is_insecure, "insecure links")
+        .is_ok());
+    let err = readme_data("[a](https://example.com) ![b](http://example.com/logo.png)\n")
+        .disallow_urls_matching(is_insecure, "insecure links")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The url `http://example.com/logo.png` is prohibited: insecure links."
+    );
+}
+
+#[test]
+fn test_disallow_urls_with_prefix() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let disallow = |text: &str| {
+        readme_data(text)
+            .disallow_absolute_docs_links("foo", "https://docs.rs/foo")
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    };
+    assert_eq!(
+        disallow("[a](#features) [b](https://docs.rs/foobar/*/foobar/) [c](features.html)\n"),
+        Ok(())
+    );
+    for url in [
+        "https://docs.rs/foo/*/foo/#features",
+        "https://docs.rs/foo/*/foo#features",
+        "https://docs.rs/foo/*/foo",
+        "https://Docs.RS/foo/*/foo/struct.Foo.html",
+    ] {
+        assert_eq!(
+            disallow(&std::format!("[a]({})\n", url)),
+            Err(std::format!(
+                "The url `{}` use a prohibited prefix `https://docs.rs/foo/*/foo/`.",
+                url
+            ))
+        );
     }
+    assert!(
+        readme_data("[a](https://github.com/user/repo/blob/master#readme)\n")
+            .disallow_absolute_blob_links("https://GitHub.com/user/repo")
+            .is_err()
+    );
 }
 
-fn heading_level(level: pulldown_cmark::HeadingLevel) -> u32 {
-    use pulldown_cmark::HeadingLevel;
+#[test]
+fn test_use_absolute_blob_urls_with_branch() {
+    use crate::test_utils::readme_data;
 
-    match level {
-        HeadingLevel::H1 => 1,
-        HeadingLevel::H2 => 2,
-        HeadingLevel::H3 => 3,
-        HeadingLevel::H4 => 4,
-        HeadingLevel::H5 => 5,
-        HeadingLevel::H6 => 6,
+    let data =
+        || readme_data("[a](LICENSE) [b](https://github.com/user/repo/blob/main/src/lib.rs)\n");
+    assert_eq!(
+        data()
+            .use_absolute_blob_urls("https://github.com/user/repo/")
+            .unwrap()
+            .to_markdown(),
+        "[a](https://github.com/user/repo/blob/master/LICENSE) \
+            [b](https://github.com/user/repo/blob/main/src/lib.rs)\n"
+    );
+    assert_eq!(
+        data()
+            .use_absolute_blob_urls_with_branch("https://github.com/user/repo", "main")
+            .unwrap()
+            .to_markdown(),
+        "[a](https://github.com/user/repo/blob/main/LICENSE) \
+            [b](https://github.com/user/repo/blob/main/src/lib.rs)\n"
+    );
+    assert!(data()
+        .disallow_absolute_blob_links("https://github.com/user/repo/")
+        .is_ok());
+    assert!(data()
+        .disallow_absolute_blob_links_with_branch("https://github.com/user/repo/", "main")
+        .is_err());
+    assert!(data()
+        .disallow_absolute_blob_links_with_branch("https://github.com/user/repo", "trunk")
+        .is_ok());
+}
+
+#[test]
+fn test_use_absolute_docs_urls_with_version() {
+    use crate::test_utils::readme_data;
+
+    assert_eq!(
+        readme_data("[Bar](struct.Bar.html)\n")
+            .use_absolute_docs_urls("foo-bar", "https://docs.rs/foo-bar")
+            .to_markdown(),
+        "[Bar](https://docs.rs/foo-bar/*/foo_bar/struct.Bar.html)\n"
+    );
+    assert_eq!(
+        readme_data("[Bar](struct.Bar.html)\n")
+            .use_absolute_docs_urls_with_version("foo-bar", "https://docs.rs/foo-bar/", "1.2.3")
+            .to_markdown(),
+        "[Bar](https://docs.rs/foo-bar/1.2.3/foo_bar/struct.Bar.html)\n"
+    );
+
+    let manifest = Manifest::from_cargo_toml_content(
+        "[package]\nname = \"foo\"\nversion = \"1.2.3\"\ndocumentation = \"https://docs.rs/foo\"\n",
+    )
+    .unwrap();
+    for url in [
+        "https://docs.rs/foo/*/foo/struct.Bar.html",
+        "https://docs.rs/foo/1.2.3/foo/struct.Bar.html",
+    ] {
+        assert!(readme_data(&std::format!("[Bar]({})\n", url))
+            .disallow_absolute_package_docs_links(&manifest)
+            .is_err());
     }
+    assert!(
+        readme_data("[Bar](https://docs.rs/foo/1.0.0/foo/struct.Bar.html)\n")
+            .disallow_absolute_package_docs_links(&manifest)
+            .is_ok()
+    );
 }
 
 #[test]
-fn test_is_url_with_scheme() {
-    assert!(!is_url_with_scheme("Foo"));
-    assert!(!is_url_with_scheme("crate::Foo"));
-    assert!(is_url_with_scheme("//Foo"));
-    assert!(!is_url_with_scheme("://Foo"));
-    assert!(is_url_with_scheme("a://Foo"));
-    assert!(is_url_with_scheme("Z://Foo"));
-    assert!(!is_url_with_scheme("0://Foo"));
-    assert!(is_url_with_scheme("aa://Foo"));
-    assert!(is_url_with_scheme("a0://Foo"));
-    assert!(is_url_with_scheme("a+://Foo"));
-    assert!(is_url_with_scheme("a.://Foo"));
-    assert!(is_url_with_scheme("a-://Foo"));
-    assert!(!is_url_with_scheme("a?://Foo"));
-    assert!(is_url_with_scheme("http://Foo"));
-    assert!(is_url_with_scheme("https://Foo"));
+fn test_map_links_full() {
+    use crate::test_utils::{modified_notes, readme_data, static_events};
+
+    let mut link_types = Vec::new();
+    let inline = readme_data("[a][r] [b](https://b.c \"title\")\n\n[r]: https://a.b\n")
+        .map_links_full(
+            |link| {
+                link_types.push(link.link_type);
+                Some(LinkMeta {
+                    link_type: LinkType::Inline,
+                    dest_url: link.dest_url.into_static(),
+                    title: CowStr::Borrowed(""),
+                    id: CowStr::Borrowed(""),
+                })
+            },
+            "inline_links()",
+        );
+    assert_eq!(link_types, [LinkType::Reference, LinkType::Inline]);
+    assert_eq!(
+        static_events(&inline),
+        static_events(&readme_data("[a](https://a.b) [b](https://b.c)\n"))
+    );
+    assert_eq!(modified_notes(&inline).len(), 2);
+
+    let same = readme_data("[a](https://a.b)\n").map_links_full(
+        |link| {
+            Some(LinkMeta {
+                link_type: link.link_type,
+                dest_url: link.dest_url.into_static(),
+                title: link.title.into_static(),
+                id: link.id.into_static(),
+            })
+        },
+        "same()",
+    );
+    assert_eq!(modified_notes(&same).len(), 0);
+}
+
+#[test]
+fn test_use_default_codeblock_tag_if() {
+    use crate::looks_like_rust;
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::{CodeBlockKind, Tag};
+
+    let infos = |data: CMarkData| {
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    Some(std::string::ToString::to_string(info))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let text = concat!(
+        "```\n$ cargo test\n   Compiling foo v0.1.0\ntest result: ok.\n```\n\n",
+        "```\nfoo\n├── Cargo.toml\n└── src\n    └── lib.rs\n```\n\n",
+        "```\nuse foo::Bar;\nlet bar = Bar::new();\n```\n\n",
+        "```text\nfn main() {}\n```\n",
+    );
+
+    assert_eq!(
+        infos(readme_data(text).use_default_codeblock_tag_if("rust", looks_like_rust)),
+        ["", "", "rust", "text"]
+    );
+    assert_eq!(
+        infos(readme_data(text).use_default_codeblock_tag("rust")),
+        ["rust", "rust", "rust", "text"]
+    );
+    assert_eq!(
+        infos(
+            readme_data(text)
+                .use_default_codeblock_tag_if("rust", looks_like_rust)
+                .use_default_codeblock_tag("text")
+        ),
+        ["text", "text", "rust", "text"]
+    );
+}
+
+#[test]
+fn test_retag_codeblocks() {
+    use crate::test_utils::readme_data;
+    use pulldown_cmark::{CodeBlockKind, Tag};
+
+    let infos = |data: CMarkData| {
+        data.iter_events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    Some(std::string::ToString::to_string(info))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let text =
+        "```sh\n$ cargo build\n```\n\n```sh,ignore\nls\n```\n\n```shell\nls\n```\n\n```\nls\n```\n";
+
+    assert_eq!(
+        infos(readme_data(text).retag_codeblocks("sh", "bash")),
+        ["bash", "bash,ignore", "shell", ""]
+    );
+    assert_eq!(
+        infos(readme_data(text).retag_codeblocks("zsh", "bash")),
+        ["sh", "sh,ignore", "shell", ""]
+    );
 }
@@ -1,13 +1,19 @@
 use std::borrow::Cow;
 use std::path::Path;
+use std::string::String;
 use std::sync::Arc;
+use std::vec::Vec;
 
 use pulldown_cmark::Event;
 use thiserror::Error;
 
 use crate::{
-    CMarkData, CMarkDataIter, Config, DisallowUrlsWithPrefixError, File, FileDocs,
-    FileDocsFromFileError, FileFromPathError, Manifest, Package,
+    CMarkData, CMarkDataIter, CMarkTransform, Config, DanglingRelativeLinksError,
+    DisallowUrlsWithPrefixError, DocsParseLimits, DocsUrlLayout, DocsUrlVersion, EmphasisKinds,
+    File, FileDocs, FileDocsFromFileError, FileFromPathError, HeadingLevelOverflowError, LinkMeta,
+    Manifest, Package, ParseProfile, PipelineError, PipelineSpec, RemovalBudget,
+    RemovalBudgetError, ReservedHeadingWarning, SkippedDocs, TextSource, TitleSource,
+    TryCMarkTransform, UnresolvedRustPath, UrlRewriteRules,
 };
 
 /// Parsed documentation Markdown with optionally specified package path and package manifest.
@@ -16,6 +22,8 @@ pub struct CMarkDocs<P, M> {
     data: CMarkData,
     package_path: P,
     manifest: M,
+    skipped_docs: Vec<SkippedDocs>,
+    file_docs: Option<Arc<FileDocs>>,
 }
 
 impl<'a> CMarkDocs<&'a Path, &'a Manifest> {
@@ -23,6 +31,7 @@ impl<'a> CMarkDocs<&'a Path, &'a Manifest> {
     ///
     /// First it reads docs file by path specified in the package manifest.
     /// Then it parses it with default configuration.
+    #[allow(clippy::result_large_err)]
     pub fn from_package_with_default_config(
         package: &'a Package,
     ) -> Result<Self, CMarkDocsFromPackageError> {
@@ -32,21 +41,116 @@ impl<'a> CMarkDocs<&'a Path, &'a Manifest> {
     /// Creates docs from package and the specified config.
     ///
     /// First it reads docs file by path specified in the package manifest.
-    /// Then it parses it with the specified configuration.
+    /// Then it parses it with the specified configuration
+    /// and the `ParseProfile::rustdoc` profile.
+    #[allow(clippy::result_large_err)]
     pub fn from_package_and_config(
         package: &'a Package,
         config: &Config<'_>,
+    ) -> Result<Self, CMarkDocsFromPackageError> {
+        Self::from_package_and_config_with_parse_profile(package, config, ParseProfile::rustdoc())
+    }
+
+    /// Creates docs from package, the specified config and Markdown parser profile.
+    #[allow(clippy::result_large_err)]
+    pub fn from_package_and_config_with_parse_profile(
+        package: &'a Package,
+        config: &Config<'_>,
+        profile: ParseProfile,
     ) -> Result<Self, CMarkDocsFromPackageError> {
         let path = package.manifest().default_relative_target_path();
         let file = Arc::new(File::from_path(path.to_path_buf(), Some(package.path()))?);
-        let package_path = package.path();
-        let manifest = package.manifest();
-        Ok(Self::from_file_and_config_and_package_path_and_manifest(
-            file,
-            config,
-            package_path,
-            manifest,
-        )?)
+        let file_docs = FileDocs::from_file(file, config)?;
+        Ok(Self::from_package_and_file_docs(
+            package, file_docs, profile,
+        ))
+    }
+
+    /// Creates docs from package and the specified config
+    /// with the docs file parsing guards.
+    ///
+    /// See `FileDocs::from_file_with_limits` for details.
+    #[allow(clippy::result_large_err)]
+    pub fn from_package_and_config_with_limits(
+        package: &'a Package,
+        config: &Config<'_>,
+        limits: &DocsParseLimits,
+    ) -> Result<Self, CMarkDocsFromPackageError> {
+        let path = package.manifest().default_relative_target_path();
+        let file = Arc::new(File::from_path(path.to_path_buf(), Some(package.path()))?);
+        let file_docs = FileDocs::from_file_with_limits(file, config, limits)?;
+        Ok(Self::from_package_and_file_docs(
+            package,
+            file_docs,
+            ParseProfile::rustdoc(),
+        ))
+    }
+
+    /// Creates docs from package for every specified config.
+    ///
+    /// The docs file is read and parsed only once,
+    /// so it allows to check several readme variants against the documentation
+    /// built with different configs, like localized docs enabled by a feature:
+    ///
+    /// ```rust,no_run
+    /// use readme_sync::{assert_sync_with_label, CMarkDocs, CMarkReadme, Config, Package};
+    ///
+    /// let package = Package::from_path(env!("CARGO_MANIFEST_DIR").into()).unwrap();
+    /// let configs = [Config::new(), Config::new().with_features(["docs-zh"])];
+    /// let docs = CMarkDocs::from_package_and_configs(&package, &configs).unwrap();
+    ///
+    /// let readme = CMarkReadme::from_package(&package).unwrap();
+    /// assert_sync_with_label(&readme, &docs[0], "en");
+    /// let readme = CMarkReadme::from_package_and_readme_name(&package, "README.zh-CN.md").unwrap();
+    /// assert_sync_with_label(&readme, &docs[1], "zh-CN");
+    /// ```
+    #[allow(clippy::result_large_err)]
+    pub fn from_package_and_configs(
+        package: &'a Package,
+        configs: &[Config<'_>],
+    ) -> Result<Vec<Self>, CMarkDocsFromPackageError> {
+        let path = package.manifest().default_relative_target_path();
+        let file = Arc::new(File::from_path(path.to_path_buf(), Some(package.path()))?);
+        Ok(FileDocs::from_file_with_configs(file, configs)?
+            .into_iter()
+            .map(|file_docs| {
+                Self::from_package_and_file_docs(package, file_docs, ParseProfile::rustdoc())
+            })
+            .collect())
+    }
+
+    fn from_package_and_file_docs(
+        package: &'a Package,
+        file_docs: FileDocs,
+        profile: ParseProfile,
+    ) -> Self {
+        let file_docs = Arc::new(file_docs);
+        let skipped_docs = file_docs.skipped_docs_summary().to_vec();
+        let data = CMarkData::from_text_source_with_parse_profile(
+            TextSource::FileDocs(Arc::clone(&file_docs)),
+            profile,
+        );
+        Self {
+            skipped_docs,
+            file_docs: Some(file_docs),
+            ..Self::from_data_chunks_package_pach_and_manifest(
+                data,
+                package.path(),
+                package.manifest(),
+            )
+        }
+    }
+
+    /// Applies the docs transformations of the specified pipeline.
+    ///
+    /// Relative urls are resolved against the package directory in the repository,
+    /// which is the closest parent directory of the package containing `.git`.
+    pub fn apply_pipeline(
+        self,
+        pipeline: &PipelineSpec,
+    ) -> Result<CMarkDocs<&'a Path, &'a Manifest>, PipelineError> {
+        let subpath = crate::cmark_readme::package_repository_subpath(self.package_path);
+        self.apply_pipeline_with_repository_subpath(pipeline, &subpath)
     }
 }
 
@@ -54,6 +158,7 @@ impl CMarkDocs<(), ()> {
     /// Creates docs from file and the specified config.
     ///
     /// The method parses a file with the specified configuration.
+    #[allow(clippy::result_large_err)]
     pub fn from_file_and_config(
         file: Arc<File>,
         config: &Config<'_>,
@@ -69,6 +174,8 @@ impl<'a, P, M> CMarkDocs<P, M> {
             data: self.data,
             package_path,
             manifest: self.manifest,
+            skipped_docs: self.skipped_docs,
+            file_docs: self.file_docs,
         }
     }
 
@@ -78,10 +185,13 @@ impl<'a, P, M> CMarkDocs<P, M> {
             data: self.data,
             package_path: self.package_path,
             manifest,
+            skipped_docs: self.skipped_docs,
+            file_docs: self.file_docs,
         }
     }
 
     /// Creates docs from file, config, package path and manifest.
+    #[allow(clippy::result_large_err)]
     pub fn from_file_and_config_and_package_path_and_manifest(
         file: Arc<File>,
         config: &Config<'_>,
@@ -102,8 +212,13 @@ impl<'a, P, M> CMarkDocs<P, M> {
         package_path: P,
         manifest: M,
     ) -> Self {
-        let data = CMarkData::from_file_docs(file_docs);
-        Self::from_data_chunks_package_pach_and_manifest(data, package_path, manifest)
+        let skipped_docs = file_docs.skipped_docs_summary().to_vec();
+        let data = CMarkData::from_file_docs(Arc::clone(&file_docs));
+        Self {
+            skipped_docs,
+            file_docs: Some(file_docs),
+            ..Self::from_data_chunks_package_pach_and_manifest(data, package_path, manifest)
+        }
     }
 
     /// Creates docs from CMark items, package path and manifest.
@@ -116,6 +231,8 @@ impl<'a, P, M> CMarkDocs<P, M> {
             data,
             package_path,
             manifest,
+            skipped_docs: Vec::new(),
+            file_docs: None,
         }
     }
 
@@ -139,6 +256,36 @@ impl<'a, P, M> CMarkDocs<P, M> {
         &self.manifest
     }
 
+    /// Returns documentation lines excluded by `cfg_attr` predicates evaluated to false.
+    ///
+    /// It is empty for docs created from CMark items.
+    /// See `FileDocs::skipped_docs_summary` for details.
+    pub fn skipped_docs_summary(&self) -> &[SkippedDocs] {
+        &self.skipped_docs
+    }
+
+    /// Returns the parsed documentation the docs were created from.
+    ///
+    /// It is `None` for docs created from CMark items.
+    pub fn file_docs(&self) -> Option<&Arc<FileDocs>> {
+        self.file_docs.as_ref()
+    }
+
+    /// Returns the extracted documentation text before any transformations.
+    ///
+    /// It is the same text as `FileDocs::docs` returns,
+    /// and it is `None` for docs created from CMark items.
+    pub fn raw_extracted_text(&self) -> Option<&str> {
+        self.file_docs.as_deref().map(FileDocs::docs)
+    }
+
+    /// Renders the current events, with all the applied transformations, as Markdown text.
+    ///
+    /// See `render_markdown` for the rendering details.
+    pub fn effective_text(&self) -> String {
+        self.data.to_markdown()
+    }
+
     /// Iterate over `CMarkItem`s.
     pub fn iter(&self) -> CMarkDataIter<'_> {
         self.data.iter()
@@ -179,6 +326,39 @@ impl<'a, P, M> CMarkDocs<P, M> {
         self.map(|data| data.concat_texts())
     }
 
+    /// Append events of the other data, for example parsed from another Markdown file,
+    /// to the end of the docs.
+    ///
+    /// The appended events keep their text sources, so diagnostics refer to their files.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either docs or the other data fails the `validate_structure` check.
+    pub fn append(self, other: CMarkData) -> CMarkDocs<P, M> {
+        self.map(|data| data.append(other))
+    }
+
+    /// Replace whitespace runs in text events with single spaces
+    /// and trim whitespace at the start and at the end of text events.
+    pub fn collapse_whitespace(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.collapse_whitespace())
+    }
+
+    /// Use the same nesting order for strong emphasis and emphasis wrapping the same content.
+    pub fn canonicalize_emphasis_nesting(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.canonicalize_emphasis_nesting())
+    }
+
+    /// Returns self if the transformations performed the numbers of removals
+    /// expected by the budget, otherwise returns an error.
+    pub fn verify_removal_budget(
+        self,
+        budget: &RemovalBudget,
+    ) -> Result<CMarkDocs<P, M>, RemovalBudgetError> {
+        self.data.verify_removal_budget(budget)?;
+        Ok(self)
+    }
+
     /// Increment levels of all headings.
     ///
     /// In readme, the first level heading is usually used only for the project title.
@@ -186,10 +366,30 @@ impl<'a, P, M> CMarkDocs<P, M> {
     /// Rustdoc automatically adds the header of a crate name and the first level headers are used for text sections.
     ///
     /// So it is necessary to increase the level of all headings in the documentation in order to synchronize the headings.
+    ///
+    /// The sixth level headings are left as is with a warning note,
+    /// use `increment_heading_levels_checked` to reject them instead.
     pub fn increment_heading_levels(self) -> CMarkDocs<P, M> {
         self.map(|data| data.increment_heading_levels())
     }
 
+    /// Increment levels of all headings,
+    /// but returns an error if there is a sixth level heading.
+    ///
+    /// See `CMarkData::increment_heading_levels_checked` for details.
+    pub fn increment_heading_levels_checked(
+        self,
+    ) -> Result<CMarkDocs<P, M>, HeadingLevelOverflowError> {
+        self.map_result(|data| data.increment_heading_levels_checked())
+    }
+
+    /// Shift levels of all headings so the minimum used heading level equals the specified one.
+    ///
+    /// See `CMarkData::normalize_heading_levels` for details.
+    pub fn normalize_heading_levels(self, top_level: pulldown_cmark::HeadingLevel) -> Self {
+        self.map(|data| data.normalize_heading_levels(top_level))
+    }
+
     /// Add a first level heading with the specified text.
     ///
     /// This function could be useful after heading level incremented.
@@ -197,11 +397,244 @@ impl<'a, P, M> CMarkDocs<P, M> {
         self.map(|data| data.add_title(text))
     }
 
+    /// Keep only the first `count` top-level blocks, like paragraphs, code blocks and lists.
+    ///
+    /// A heading ends the leading blocks immediately. Other events are marked as removed.
+    pub fn take_leading_blocks(self, count: usize) -> Self {
+        self.map(|data| data.take_leading_blocks(count))
+    }
+
+    /// Remove the first level heading if it is the first block.
+    pub fn remove_title(self) -> Self {
+        self.map(|data| data.remove_title())
+    }
+
+    /// Apply the specified transformation.
+    pub fn apply<T: CMarkTransform + ?Sized>(self, transform: &T) -> Self {
+        self.map(|data| data.apply(transform))
+    }
+
+    /// Apply the specified transformation that may fail.
+    pub fn try_apply<T: TryCMarkTransform + ?Sized>(self, transform: &T) -> Result<Self, T::Error> {
+        self.map_result(|data| data.try_apply(transform))
+    }
+
     /// Remove section with the specified heading text and level and its subsections.
     pub fn remove_section(self, heading: &str, level: u32) -> Self {
         self.map(|data| data.remove_section(heading, level))
     }
 
+    /// Remove all sections and their subsections with headings matching the specified predicate.
+    ///
+    /// See `CMarkData::remove_section_by_predicate` for details.
+    pub fn remove_section_by_predicate<F>(self, predicate: F) -> Self
+    where
+        F: FnMut(&str, u32) -> bool,
+    {
+        self.map(|data| data.remove_section_by_predicate(predicate))
+    }
+
+    /// Replace the text of headings with the text returned by function `func`.
+    ///
+    /// See `CMarkData::map_headings` for details.
+    pub fn map_headings<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        F: FnMut(&str, u32) -> Option<String>,
+    {
+        self.map(|data| data.map_headings(func, note))
+    }
+
+    /// Replace the text of headings with the specified text and level.
+    pub fn rename_section(self, from: &str, to: &str, level: u32) -> Self {
+        self.map(|data| data.rename_section(from, to, level))
+    }
+
+    /// Remove the contents of all fenced and indented code blocks,
+    /// leaving only their start and end tags.
+    ///
+    /// See `CMarkData::remove_codeblock_contents` for details.
+    pub fn remove_codeblock_contents(self) -> Self {
+        self.map(|data| data.remove_codeblock_contents())
+    }
+
+    /// Remove the contents of fenced code blocks with the specified tag,
+    /// leaving only their start and end tags.
+    pub fn remove_codeblock_contents_for_tag(self, tag: &str) -> Self {
+        self.map(|data| data.remove_codeblock_contents_for_tag(tag))
+    }
+
+    /// Strip trailing whitespace of every code block line and trailing blank lines
+    /// of all code blocks.
+    ///
+    /// See `CMarkData::trim_codeblock_text` for details.
+    pub fn trim_codeblock_text(self) -> Self {
+        self.map(|data| data.trim_codeblock_text())
+    }
+
+    /// Strip trailing whitespace of every code block line, trailing blank lines
+    /// and the leading blank line of all code blocks.
+    pub fn trim_codeblock_text_and_leading_blank_line(self) -> Self {
+        self.map(|data| data.trim_codeblock_text_and_leading_blank_line())
+    }
+
+    /// Converts the info strings of all fenced code blocks with function `func`.
+    ///
+    /// See `CMarkData::map_codeblock_tags` for details.
+    pub fn map_codeblock_tags<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map(|data| data.map_codeblock_tags(func, note))
+    }
+
+    /// Replace the fenced code block tags that match the first value of an alias pair
+    /// with the second value of that pair.
+    pub fn map_codeblock_tag_aliases(self, aliases: &[(&str, &str)]) -> Self {
+        self.map(|data| data.map_codeblock_tag_aliases(aliases))
+    }
+
+    /// Remove all tables.
+    pub fn remove_tables(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_tables())
+    }
+
+    /// Remove the table with the specified zero-based index.
+    pub fn remove_table_at(self, index: usize) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_table_at(index))
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// starting with the capitalized alert kind followed by a colon, like `> Note: text`.
+    pub fn normalize_github_alerts(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.normalize_github_alerts())
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// without any alert marker.
+    pub fn strip_github_alerts(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.strip_github_alerts())
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// starting with the text returned by the specified function.
+    ///
+    /// See `CMarkData::normalize_github_alerts_with` for details.
+    pub fn normalize_github_alerts_with<F>(self, func: F) -> CMarkDocs<P, M>
+    where
+        F: FnMut(pulldown_cmark::BlockQuoteKind) -> String,
+    {
+        self.map(|data| data.normalize_github_alerts_with(func))
+    }
+
+    /// Remove metadata blocks, like YAML front matter.
+    pub fn remove_metadata_blocks(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_metadata_blocks())
+    }
+
+    /// Remove all thematic breaks, like `---` or `***`.
+    pub fn remove_thematic_breaks(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_thematic_breaks())
+    }
+
+    /// Remove the specified number of the first thematic breaks.
+    pub fn remove_first_thematic_breaks(self, count: usize) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_first_thematic_breaks(count))
+    }
+
+    /// Remove emphasis, strong emphasis and strikethrough, keeping their text.
+    pub fn remove_emphasis(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_emphasis())
+    }
+
+    /// Remove the specified kinds of emphasis, keeping their text.
+    ///
+    /// See `CMarkData::remove_emphasis_kinds` for details.
+    pub fn remove_emphasis_kinds(self, kinds: EmphasisKinds) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_emphasis_kinds(kinds))
+    }
+
+    /// Replace links with their text.
+    ///
+    /// See `CMarkData::flatten_links` for details.
+    pub fn flatten_links(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.flatten_links())
+    }
+
+    /// Replace images with their alt text.
+    pub fn flatten_images(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.flatten_images())
+    }
+
+    /// Convert inline HTML line breaks like `<br>` to hard breaks.
+    ///
+    /// See `CMarkData::normalize_hard_breaks` for details.
+    pub fn normalize_hard_breaks(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.normalize_hard_breaks())
+    }
+
+    /// Remove HTML blocks and inline HTML.
+    ///
+    /// See `CMarkData::remove_html` for details.
+    pub fn remove_html(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_html())
+    }
+
+    /// Remove HTML blocks and inline HTML the predicate returns `true` for.
+    ///
+    /// See `CMarkData::remove_html_matching` for details.
+    pub fn remove_html_matching<F>(self, predicate: F) -> CMarkDocs<P, M>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map(|data| data.remove_html_matching(predicate))
+    }
+
+    /// Remove HTML comments, like `<!-- omit in toc -->` or `<!-- markdownlint-disable -->`.
+    ///
+    /// See `CMarkData::remove_html_comments` for details.
+    pub fn remove_html_comments(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_html_comments())
+    }
+
+    /// Remove everything from the first section with the specified heading text and level
+    /// to the end of the document.
+    ///
+    /// See `CMarkData::remove_sections_from` for details.
+    pub fn remove_sections_from(self, heading: &str, level: u32) -> Self {
+        self.map(|data| data.remove_sections_from(heading, level))
+    }
+
+    /// Keep only the first section with the specified heading text and level
+    /// and its subsections.
+    ///
+    /// See `CMarkData::extract_section` for details.
+    pub fn extract_section(self, heading: &str, level: u32) -> Self {
+        self.map(|data| data.extract_section(heading, level))
+    }
+
+    /// Keep only the contents of the first section with the specified heading text and level
+    /// and its subsections without the section heading.
+    ///
+    /// See `CMarkData::extract_section_body` for details.
+    pub fn extract_section_body(self, heading: &str, level: u32) -> Self {
+        self.map(|data| data.extract_section_body(heading, level))
+    }
+
+    /// Keep only the sections with the specified heading texts and level and their subsections.
+    ///
+    /// See `CMarkData::keep_sections` for details.
+    pub fn keep_sections(self, headings: &[&str], level: u32) -> Self {
+        self.map(|data| data.keep_sections(headings, level))
+    }
+
+    /// Remove sections with the specified heading texts and levels and their subsections
+    /// in a single pass.
+    ///
+    /// See `CMarkData::remove_sections` for details.
+    pub fn remove_sections(self, sections: &[(&str, u32)]) -> Self {
+        self.map(|data| data.remove_sections(sections))
+    }
+
     /// Remove the specified fenced code block tag.
     pub fn remove_codeblock_tag(self, tag: &str) -> CMarkDocs<P, M> {
         self.map(|data| data.remove_codeblock_tag(tag))
@@ -212,6 +645,62 @@ impl<'a, P, M> CMarkDocs<P, M> {
         self.map(|data| data.remove_codeblock_tags(tags))
     }
 
+    /// Remove fenced code block tags the predicate returns `true` for.
+    ///
+    /// See `CMarkData::remove_codeblock_tags_matching` for details.
+    pub fn remove_codeblock_tags_matching<F>(
+        self,
+        predicate: F,
+        note: impl Into<Cow<'static, str>>,
+    ) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map(|data| data.remove_codeblock_tags_matching(predicate, note))
+    }
+
+    /// Remove fenced code blocks with the specified tag including their contents.
+    pub fn remove_codeblocks_with_tag(self, tag: &str) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_codeblocks_with_tag(tag))
+    }
+
+    /// Separate fenced code block tags with commas only.
+    ///
+    /// Rustdoc accepts tags separated by commas, whitespace or both,
+    /// so `rust no_run` and `rust, no_run` are the same as `rust,no_run`.
+    pub fn normalize_codeblock_tags(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.normalize_codeblock_tags())
+    }
+
+    /// Use the specified codeblock tag for fenced code blocks without tags
+    /// if the specified predicate returns true when passing the code block text to it.
+    pub fn use_default_codeblock_tag_if<F>(self, tag: &str, predicate: F) -> CMarkDocs<P, M>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map(|data| data.use_default_codeblock_tag_if(tag, predicate))
+    }
+
+    /// Replace the specified fenced code block tag with another one.
+    pub fn retag_codeblocks(self, from: &str, to: &str) -> CMarkDocs<P, M> {
+        self.map(|data| data.retag_codeblocks(from, to))
+    }
+
+    /// Replace lines of rust fenced codeblocks matching any of the specified wildcard patterns
+    /// with the mask, preserving the line indentation.
+    ///
+    /// See `CMarkData::mask_codeblock_lines_matching` for details.
+    pub fn mask_codeblock_lines_matching(self, patterns: &[&str], mask: &str) -> CMarkDocs<P, M> {
+        self.map(|data| data.mask_codeblock_lines_matching(patterns, mask))
+    }
+
+    /// Replaces texts of fenced code blocks tagged `toml` with their canonical formatting.
+    ///
+    /// See `CMarkData::normalize_toml_codeblocks` for details.
+    pub fn normalize_toml_codeblocks(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.normalize_toml_codeblocks())
+    }
+
     /// Remove fenced code block tags that are used by `cargo test`.
     ///
     /// See <https://doc.rust-lang.org/rustdoc/documentation-tests.html> for more details.
@@ -219,11 +708,28 @@ impl<'a, P, M> CMarkDocs<P, M> {
         self.map(|data| data.remove_codeblock_rust_test_tags())
     }
 
+    /// Remove fenced code block tags that are used by `cargo test`
+    /// and the specified project-specific tags.
+    ///
+    /// See `CMarkData::remove_codeblock_rust_test_tags_with` for details.
+    pub fn remove_codeblock_rust_test_tags_with(self, extra: &[&str]) -> CMarkDocs<P, M> {
+        self.map(|data| data.remove_codeblock_rust_test_tags_with(extra))
+    }
+
     /// Use the specified codeblock tag, if they are not specified
+    ///
+    /// See `use_default_codeblock_tag_if` to keep untagged console output or diagrams untagged.
     pub fn use_default_codeblock_tag(self, tag: &str) -> CMarkDocs<P, M> {
         self.map(|data| data.use_default_codeblock_tag(tag))
     }
 
+    /// Convert indented code blocks to fenced code blocks with the specified info string.
+    ///
+    /// See `CMarkData::convert_indented_codeblocks_to_fenced` for details.
+    pub fn convert_indented_codeblocks_to_fenced(self, tag: &str) -> CMarkDocs<P, M> {
+        self.map(|data| data.convert_indented_codeblocks_to_fenced(tag))
+    }
+
     /// Use rust fenced codeblock highlight as default.
     pub fn use_default_codeblock_rust_tag(self) -> CMarkDocs<P, M> {
         self.map(|data| data.use_default_codeblock_rust_tag())
@@ -236,6 +742,13 @@ impl<'a, P, M> CMarkDocs<P, M> {
         self.map(|data| data.remove_hidden_rust_code())
     }
 
+    /// Returns warnings about top-level headings named like rustdoc-generated sections.
+    ///
+    /// See `CMarkData::lint_reserved_headings` for details.
+    pub fn lint_reserved_headings(&self) -> Vec<ReservedHeadingWarning> {
+        self.data.lint_reserved_headings()
+    }
+
     /// Returns self if absolute docs links to the specified repository not found,
     /// otherwise returns an error.
     pub fn disallow_absolute_docs_links(
@@ -246,6 +759,21 @@ impl<'a, P, M> CMarkDocs<P, M> {
         self.map_result(|data| data.disallow_absolute_docs_links(package_name, documentation_url))
     }
 
+    /// Rewrite links with Rust path destinations, like intra-doc links,
+    /// to the rustdoc item urls with the specified docs url prefix.
+    ///
+    /// Returns the docs and the links that look like Rust paths but are ambiguous.
+    /// See `CMarkData::rewrite_rust_path_links` for details.
+    pub fn rewrite_rust_path_links_with_crate_name(
+        mut self,
+        crate_name: &str,
+        docs_prefix: &str,
+    ) -> (CMarkDocs<P, M>, Vec<UnresolvedRustPath>) {
+        let (data, unresolved) = self.data.rewrite_rust_path_links(crate_name, docs_prefix);
+        self.data = data;
+        (self, unresolved)
+    }
+
     /// Convert all relative links into absolute ones using
     /// the specified package documentation url as the root address.
     pub fn use_absolute_docs_urls(
@@ -255,6 +783,82 @@ impl<'a, P, M> CMarkDocs<P, M> {
     ) -> CMarkDocs<P, M> {
         self.map(|data| data.use_absolute_docs_urls(package_name, documentation_url))
     }
+
+    /// Converts all fragment-only links, like `#usage`,
+    /// with function `func` applied to each link address.
+    pub fn map_fragment_links<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map(|data| data.map_fragment_links(func, note))
+    }
+
+    /// Replaces rustdoc heading anchors in fragment links with the GitHub ones.
+    ///
+    /// See `CMarkData::use_github_heading_anchors` for details.
+    pub fn use_github_heading_anchors(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.use_github_heading_anchors())
+    }
+
+    /// Replaces GitHub heading anchors in fragment links with the rustdoc ones.
+    ///
+    /// See `CMarkData::use_rustdoc_heading_anchors` for details.
+    pub fn use_rustdoc_heading_anchors(self) -> CMarkDocs<P, M> {
+        self.map(|data| data.use_rustdoc_heading_anchors())
+    }
+
+    /// Rewrites link urls with the specified rules.
+    pub fn rewrite_urls(self, rules: &UrlRewriteRules) -> CMarkDocs<P, M> {
+        self.map(|data| data.rewrite_urls(rules))
+    }
+
+    /// Returns self if no link or image urls match the specified predicate,
+    /// otherwise returns an error with the first matching url and the description.
+    pub fn disallow_urls_matching<F>(
+        self,
+        predicate: F,
+        description: &str,
+    ) -> Result<CMarkDocs<P, M>, DisallowUrlsWithPrefixError>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map_result(|data| data.disallow_urls_matching(predicate, description))
+    }
+
+    /// Returns self if absolute docs links with the specified docs url prefix not found,
+    /// otherwise returns an error.
+    pub fn disallow_absolute_docs_links_with_prefix(
+        self,
+        prefix: &str,
+    ) -> Result<CMarkDocs<P, M>, DisallowUrlsWithPrefixError> {
+        self.map_result(|data| data.disallow_urls_with_prefix(prefix))
+    }
+
+    /// Convert all relative links into absolute ones using the specified docs url prefix.
+    pub fn use_absolute_docs_urls_with_prefix(self, prefix: &str) -> CMarkDocs<P, M> {
+        self.map(|data| data.use_absolute_docs_urls_with_prefix(prefix))
+    }
+}
+
+impl<'a, M> CMarkDocs<&'a Path, M> {
+    /// Returns self if all relative link and image urls point to existing files
+    /// in the directory of the docs source file, otherwise returns an error listing the missing ones.
+    ///
+    /// Urls are resolved against the package directory if the docs source file is unknown.
+    /// See `CMarkData::dangling_relative_links` for details.
+    pub fn disallow_dangling_relative_links(
+        self,
+    ) -> Result<CMarkDocs<&'a Path, M>, DanglingRelativeLinksError> {
+        let base_dir = match &self.file_docs {
+            Some(file_docs) => self
+                .package_path
+                .join(file_docs.file().path())
+                .parent()
+                .map_or_else(|| self.package_path.to_path_buf(), Path::to_path_buf),
+            None => self.package_path.to_path_buf(),
+        };
+        self.map_result(|data| data.disallow_dangling_relative_links(&base_dir))
+    }
 }
 
 impl<'a, P> CMarkDocs<P, &'a Manifest> {
@@ -262,38 +866,90 @@ impl<'a, P> CMarkDocs<P, &'a Manifest> {
     ///
     /// This function could be useful after heading level incremented.
     pub fn add_package_title(self) -> CMarkDocs<P, &'a Manifest> {
-        let name = self.manifest.package.name.clone();
-        self.add_title(&name)
+        self.add_title_from(TitleSource::PackageName)
     }
 
-    /// Returns self if absolute docs links to the manifest repository not found,
+    /// Add a first level heading with the title from the specified source.
+    ///
+    /// Use the source matching the `TitlePolicy` verified for the readme,
+    /// so the readme and docs titles stay comparable.
+    pub fn add_title_from(self, source: TitleSource) -> CMarkDocs<P, &'a Manifest> {
+        let title = source.title(&self.manifest.package.name);
+        self.add_title(&title)
+    }
+
+    /// Rewrite links with Rust path destinations, like intra-doc links,
+    /// to the rustdoc item urls using the manifest library name and documentation url.
+    ///
+    /// Returns the docs and the links that look like Rust paths but are ambiguous,
+    /// so they can be mapped manually with `map_links`.
+    /// See `CMarkData::rewrite_rust_path_links` and `Manifest::docs_url_prefix` for details.
+    pub fn rewrite_rust_path_links(self) -> (CMarkDocs<P, &'a Manifest>, Vec<UnresolvedRustPath>) {
+        let crate_name = self.manifest.lib_name();
+        let docs_prefix = self.manifest.docs_url_prefix();
+        self.rewrite_rust_path_links_with_crate_name(&crate_name, &docs_prefix)
+    }
+
+    /// Returns self if absolute docs links to the manifest package documentation not found,
     /// otherwise returns an error.
+    ///
+    /// Both the version wildcard and the manifest version documentation urls are disallowed.
+    /// See `Manifest::docs_url_prefix_with_version` for the documentation url prefix details.
     pub fn disallow_absolute_package_docs_links(
         self,
     ) -> Result<CMarkDocs<P, &'a Manifest>, DisallowAbsolutePackageDocsLinksError> {
-        let name = self.manifest.package.name.clone();
-        let documentation = self
-            .manifest
-            .package
-            .documentation
-            .clone()
-            .ok_or(DisallowAbsolutePackageDocsLinksError::DocsUrlNotFound)?;
-        Ok(self.disallow_absolute_docs_links(&name, &documentation)?)
+        let manifest = self.manifest;
+        Ok(self.map_result(|data| data.disallow_absolute_package_docs_links(manifest))?)
+    }
+
+    /// Returns self if absolute docs links to the manifest package documentation
+    /// with the specified layout not found, otherwise returns an error.
+    ///
+    /// Use the same layout as in `use_absolute_package_docs_urls_with_layout`.
+    /// See `Manifest::docs_url_prefix_with_layout` for the documentation url prefix details.
+    pub fn disallow_absolute_package_docs_links_with_layout(
+        self,
+        layout: &DocsUrlLayout,
+    ) -> Result<CMarkDocs<P, &'a Manifest>, DisallowAbsolutePackageDocsLinksError> {
+        let prefix = self.manifest.docs_url_prefix_with_layout(layout);
+        Ok(self.disallow_absolute_docs_links_with_prefix(&prefix)?)
     }
 
     /// Convert all relative links into absolute ones
     /// using the manifest package documentation url as the root address.
+    ///
+    /// See `Manifest::docs_url_prefix` for the documentation url prefix details.
+    /// It never fails, the `Result` is kept for compatibility.
     pub fn use_absolute_package_docs_urls(
         self,
     ) -> Result<CMarkDocs<P, &'a Manifest>, UseAbsolutePackageDocsUrlsError> {
-        let name = self.manifest.package.name.clone();
-        let documentation = self
-            .manifest
-            .package
-            .documentation
-            .clone()
-            .ok_or(UseAbsolutePackageDocsUrlsError::DocsUrlNotFound)?;
-        Ok(self.use_absolute_docs_urls(&name, &documentation))
+        Ok(self.use_absolute_package_docs_urls_with_version(DocsUrlVersion::Wildcard))
+    }
+
+    /// Convert all relative links into absolute ones
+    /// using the manifest package documentation url and the specified version as the root address.
+    ///
+    /// Use `DocsUrlVersion::Manifest` to match the readme links to the concrete
+    /// docs.rs version generated by rustdoc.
+    /// See `Manifest::docs_url_prefix_with_version` for the documentation url prefix details.
+    pub fn use_absolute_package_docs_urls_with_version(
+        self,
+        version: DocsUrlVersion,
+    ) -> CMarkDocs<P, &'a Manifest> {
+        let prefix = self.manifest.docs_url_prefix_with_version(version);
+        self.use_absolute_docs_urls_with_prefix(&prefix)
+    }
+
+    /// Convert all relative links into absolute ones
+    /// using the manifest package documentation url with the specified layout as the root address.
+    ///
+    /// See `Manifest::docs_url_prefix_with_layout` for the documentation url prefix details.
+    pub fn use_absolute_package_docs_urls_with_layout(
+        self,
+        layout: DocsUrlLayout,
+    ) -> CMarkDocs<P, &'a Manifest> {
+        let prefix = self.manifest.docs_url_prefix_with_layout(&layout);
+        self.use_absolute_docs_urls_with_prefix(&prefix)
     }
 
     /// Converts all links with function `func` applied to each link address.
@@ -303,10 +959,42 @@ impl<'a, P> CMarkDocs<P, &'a Manifest> {
     {
         self.map(|data| data.map_links(func, note))
     }
+
+    /// Converts all links with function `func` applied to each link type, address, title and id.
+    ///
+    /// See `CMarkData::map_links_full` for details.
+    pub fn map_links_full<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        F: FnMut(LinkMeta<'_>) -> Option<LinkMeta<'static>>,
+    {
+        self.map(|data| data.map_links_full(func, note))
+    }
+
+    /// Converts all image links with function `func` applied to each image address.
+    pub fn map_image_links<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map(|data| data.map_image_links(func, note))
+    }
+
+    /// Applies the docs transformations of the specified pipeline
+    /// with the specified package directory relative to the repository root.
+    ///
+    /// See `PipelineSpec::apply_to_docs` for details.
+    pub fn apply_pipeline_with_repository_subpath(
+        self,
+        pipeline: &PipelineSpec,
+        repository_subpath: &str,
+    ) -> Result<CMarkDocs<P, &'a Manifest>, PipelineError> {
+        let manifest = self.manifest;
+        self.map_result(|data| pipeline.apply_to_docs(data, manifest, repository_subpath))
+    }
 }
 
 /// An error which can occur when creating docs from package.
 #[derive(Debug, Error)]
+#[allow(clippy::large_enum_variant)]
 pub enum CMarkDocsFromPackageError {
     /// File reading failed.
     #[error(transparent)]
@@ -323,6 +1011,8 @@ pub enum DisallowAbsolutePackageDocsLinksError {
     #[error(transparent)]
     DisallowUrlsWithPrefixError(#[from] DisallowUrlsWithPrefixError),
     /// Manifest does not contain `package.documentation` field.
+    ///
+    /// It is no longer returned since the docs url is derived from the package name if absent.
     #[error("Manifest does not contain package.documentation field")]
     DocsUrlNotFound,
 }
@@ -331,6 +1021,36 @@ pub enum DisallowAbsolutePackageDocsLinksError {
 /// using the manifest package documentation url as the root address.
 #[derive(Clone, Copy, Debug, Error)]
 pub enum UseAbsolutePackageDocsUrlsError {
+    /// Manifest does not contain `package.documentation` field.
+    ///
+    /// It is no longer returned since the docs url is derived from the package name if absent.
     #[error("Manifest does not contain package.documentation field")]
     DocsUrlNotFound,
 }
+
+#[test]
+fn test_docs_texts() {
+    use std::path::PathBuf;
+
+    let file = Arc::new(File::from_path_and_text(
+        PathBuf::from("lib.rs"),
+        "//! # Title\n//!\n//! Some  *text*.\n".into(),
+    ));
+    let docs = CMarkDocs::from_file_and_config(file, &Config::default())
+        .unwrap()
+        .remove_title();
+    assert_eq!(
+        docs.raw_extracted_text(),
+        Some("# Title\n\nSome  *text*.\n")
+    );
+    assert_eq!(
+        docs.file_docs().map(|file_docs| file_docs.docs()),
+        docs.raw_extracted_text()
+    );
+    assert_eq!(docs.effective_text(), "Some  *text*.\n");
+
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(docs.into_data(), (), ());
+    assert!(docs.file_docs().is_none());
+    assert!(docs.raw_extracted_text().is_none());
+    assert_eq!(docs.effective_text(), "Some  *text*.\n");
+}
@@ -1,12 +1,18 @@
-use std::path::Path;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::string::String;
 use std::sync::Arc;
+use std::vec::Vec;
 
 use pulldown_cmark::Event;
 use thiserror::Error;
 
 use crate::{
-    CMarkData, CMarkDataIter, DisallowUrlsWithPrefixError, File, FileFromPathError, Manifest,
-    Package,
+    BadgeWarning, CMarkData, CMarkDataIter, CMarkItem, CMarkTransform, DanglingRelativeLinksError,
+    DisallowUrlsWithPrefixError, EmphasisKinds, File, FileFromPathError, LinkMeta, Manifest,
+    Package, ParseProfile, PipelineError, PipelineSpec, RawImageUrlsError, RemovalBudget,
+    RemovalBudgetError, TableSummary, TextSource, TitleError, TitlePolicy, TomlReadError,
+    TryCMarkTransform, UrlRewriteRules,
 };
 
 /// Parsed readme Markdown with optionally specified package path and package manifest.
@@ -21,17 +27,75 @@ impl<'a> CMarkReadme<&'a Path, &'a Manifest> {
     /// Creates readme from package.
     ///
     /// It reads readme file by path specified in the package manifest.
+    ///
+    /// Returns `CMarkReadmeFromPackageError::UnsupportedFormat` error
+    /// if the readme file has a known non-Markdown extension, like `.adoc`, `.rst` or `.txt`,
+    /// or if it has no extension and its content does not look like Markdown.
+    ///
+    /// The readme is parsed with the `ParseProfile::github` profile.
     pub fn from_package(package: &'a Package) -> Result<Self, CMarkReadmeFromPackageError> {
+        Self::from_package_impl(package, true, ParseProfile::github())
+    }
+
+    /// Creates readme from package using the specified Markdown parser profile.
+    pub fn from_package_with_parse_profile(
+        package: &'a Package,
+        profile: ParseProfile,
+    ) -> Result<Self, CMarkReadmeFromPackageError> {
+        Self::from_package_impl(package, true, profile)
+    }
+
+    /// Creates readme from package parsing the readme file as Markdown regardless of its format.
+    pub fn from_package_forcing_markdown(
+        package: &'a Package,
+    ) -> Result<Self, CMarkReadmeFromPackageError> {
+        Self::from_package_impl(package, false, ParseProfile::github())
+    }
+
+    /// Creates readme from the package readme file with the specified name,
+    /// like an alternate localized readme `README.zh-CN.md`.
+    ///
+    /// The file name is relative to the package root.
+    /// Returns `CMarkReadmeFromPackageError::NamedNotFound` error if the file does not exist.
+    pub fn from_package_and_readme_name(
+        package: &'a Package,
+        file_name: &str,
+    ) -> Result<Self, CMarkReadmeFromPackageError> {
+        let path = Path::new(file_name);
+        if !package.path().join(path).is_file() {
+            return Err(CMarkReadmeFromPackageError::NamedNotFound(
+                path.to_path_buf(),
+            ));
+        }
+        Self::from_package_and_path(package, path, true, ParseProfile::github())
+    }
+
+    fn from_package_impl(
+        package: &'a Package,
+        check_format: bool,
+        profile: ParseProfile,
+    ) -> Result<Self, CMarkReadmeFromPackageError> {
         let path = package
             .relative_readme_path()
             .ok_or(CMarkReadmeFromPackageError::NotFound)?;
+        Self::from_package_and_path(package, path, check_format, profile)
+    }
+
+    fn from_package_and_path(
+        package: &'a Package,
+        path: &Path,
+        check_format: bool,
+        profile: ParseProfile,
+    ) -> Result<Self, CMarkReadmeFromPackageError> {
         let file = Arc::new(File::from_path(path.to_path_buf(), Some(package.path()))?);
-        let package_path = package.path();
-        let manifest = package.manifest();
-        Ok(Self::from_file_and_package_path_and_manifest(
-            file,
-            package_path,
-            manifest,
+        if check_format {
+            check_markdown_format(path, file.text())?;
+        }
+        let data = CMarkData::from_text_source_with_parse_profile(TextSource::File(file), profile);
+        Ok(Self::from_data_and_package_path_and_manifest(
+            data,
+            package.path(),
+            package.manifest(),
         ))
     }
 }
@@ -41,6 +105,36 @@ impl CMarkReadme<(), ()> {
     pub fn from_file(file: Arc<File>) -> Self {
         Self::from_file_and_package_path_and_manifest(file, (), ())
     }
+
+    /// Creates readme from the extracted `.crate` archive created by `cargo package`.
+    ///
+    /// The `.crate` archive is a gzipped tarball and could be extracted with `tar -xzf`.
+    /// The readme is located by the packaged manifest and the file is named as the archive member,
+    /// like `name-version/README.md`.
+    ///
+    /// It allows to check the readme rendered by crates.io
+    /// and to detect readme files dropped by `include` and `exclude` rules or packaged stale.
+    pub fn from_packaged_dir(path: &Path) -> Result<Self, CMarkReadmeFromPackageError> {
+        let manifest = Manifest::from_package_path(path)?;
+        let readme_path = manifest
+            .relative_readme_path(path)
+            .ok_or(CMarkReadmeFromPackageError::NotFound)?;
+        let text = std::fs::read_to_string(path.join(readme_path)).map_err(|err| {
+            FileFromPathError::IoError {
+                path: path.join(readme_path),
+                err,
+            }
+        })?;
+        check_markdown_format(readme_path, &text)?;
+
+        let member_root = path.file_name().map_or_else(PathBuf::new, PathBuf::from);
+        let file = File::from_path_and_text(member_root.join(readme_path), text);
+        let data = CMarkData::from_text_source_with_parse_profile(
+            TextSource::File(Arc::new(file)),
+            ParseProfile::github(),
+        );
+        Ok(Self::from_data_and_package_path_and_manifest(data, (), ()))
+    }
 }
 
 impl<'a, P, M> CMarkReadme<P, M> {
@@ -145,6 +239,27 @@ impl<'a, P, M> CMarkReadme<P, M> {
         self.map(|data| data.concat_texts())
     }
 
+    /// Replace whitespace runs in text events with single spaces
+    /// and trim whitespace at the start and at the end of text events.
+    pub fn collapse_whitespace(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.collapse_whitespace())
+    }
+
+    /// Use the same nesting order for strong emphasis and emphasis wrapping the same content.
+    pub fn canonicalize_emphasis_nesting(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.canonicalize_emphasis_nesting())
+    }
+
+    /// Returns self if the transformations performed the numbers of removals
+    /// expected by the budget, otherwise returns an error.
+    pub fn verify_removal_budget(
+        self,
+        budget: &RemovalBudget,
+    ) -> Result<CMarkReadme<P, M>, RemovalBudgetError> {
+        self.data.verify_removal_budget(budget)?;
+        Ok(self)
+    }
+
     /// Removes first paragraph that contains only images and image-links,
     /// if the specified predicate returns true when passing image urls to it.
     pub fn remove_images_only_paragraph<F>(self, predicate: F) -> CMarkReadme<P, M>
@@ -154,16 +269,295 @@ impl<'a, P, M> CMarkReadme<P, M> {
         self.map(|data| data.remove_images_only_paragraph(predicate))
     }
 
+    /// Returns badges that reference other repositories or crates than the specified manifest.
+    ///
+    /// See `CMarkData::lint_badges` for the checked badges.
+    pub fn lint_badges(&self, manifest: &Manifest) -> Vec<BadgeWarning> {
+        self.data.lint_badges(manifest)
+    }
+
     /// Removes first paragraph that contains only badges.
     pub fn remove_badges_paragraph(self) -> CMarkReadme<P, M> {
         self.map(|data| data.remove_badges_paragraph())
     }
 
+    /// Remove badges embedded in headings.
+    ///
+    /// See `CMarkData::remove_badges_from_headings` for details.
+    pub fn remove_badges_from_headings(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_badges_from_headings())
+    }
+
+    /// Returns the start nodes of headings without any text.
+    pub fn empty_headings(&self) -> Vec<Arc<CMarkItem>> {
+        self.data.empty_headings()
+    }
+
+    /// Remove tables that contain only badges.
+    pub fn remove_badges_table(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_badges_table())
+    }
+
+    /// Remove tables for which the specified predicate returns `true`.
+    pub fn remove_table_matching<F>(self, predicate: F) -> CMarkReadme<P, M>
+    where
+        F: Fn(&TableSummary) -> bool,
+    {
+        self.map(|data| data.remove_table_matching(predicate))
+    }
+
+    /// Remove all tables.
+    pub fn remove_tables(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_tables())
+    }
+
+    /// Remove the table with the specified zero-based index.
+    pub fn remove_table_at(self, index: usize) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_table_at(index))
+    }
+
+    /// Keep only the first `count` top-level blocks, like paragraphs, code blocks and lists.
+    ///
+    /// A heading ends the leading blocks immediately. Other events are marked as removed.
+    pub fn take_leading_blocks(self, count: usize) -> Self {
+        self.map(|data| data.take_leading_blocks(count))
+    }
+
+    /// Remove the first level heading if it is the first block.
+    pub fn remove_title(self) -> Self {
+        self.map(|data| data.remove_title())
+    }
+
+    /// Apply the specified transformation.
+    pub fn apply<T: CMarkTransform + ?Sized>(self, transform: &T) -> Self {
+        self.map(|data| data.apply(transform))
+    }
+
+    /// Apply the specified transformation that may fail.
+    pub fn try_apply<T: TryCMarkTransform + ?Sized>(self, transform: &T) -> Result<Self, T::Error> {
+        self.map_result(|data| data.try_apply(transform))
+    }
+
     /// Remove section with the specified heading text and level and its subsections.
     pub fn remove_section(self, heading: &str, level: u32) -> Self {
         self.map(|data| data.remove_section(heading, level))
     }
 
+    /// Remove all sections and their subsections with headings matching the specified predicate.
+    ///
+    /// See `CMarkData::remove_section_by_predicate` for details.
+    pub fn remove_section_by_predicate<F>(self, predicate: F) -> Self
+    where
+        F: FnMut(&str, u32) -> bool,
+    {
+        self.map(|data| data.remove_section_by_predicate(predicate))
+    }
+
+    /// Shift levels of all headings so the minimum used heading level equals the specified one.
+    ///
+    /// See `CMarkData::normalize_heading_levels` for details.
+    pub fn normalize_heading_levels(self, top_level: pulldown_cmark::HeadingLevel) -> Self {
+        self.map(|data| data.normalize_heading_levels(top_level))
+    }
+
+    /// Replace the text of headings with the text returned by function `func`.
+    ///
+    /// See `CMarkData::map_headings` for details.
+    pub fn map_headings<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        F: FnMut(&str, u32) -> Option<String>,
+    {
+        self.map(|data| data.map_headings(func, note))
+    }
+
+    /// Replace the text of headings with the specified text and level.
+    pub fn rename_section(self, from: &str, to: &str, level: u32) -> Self {
+        self.map(|data| data.rename_section(from, to, level))
+    }
+
+    /// Remove the contents of all fenced and indented code blocks,
+    /// leaving only their start and end tags.
+    ///
+    /// See `CMarkData::remove_codeblock_contents` for details.
+    pub fn remove_codeblock_contents(self) -> Self {
+        self.map(|data| data.remove_codeblock_contents())
+    }
+
+    /// Remove the contents of fenced code blocks with the specified tag,
+    /// leaving only their start and end tags.
+    pub fn remove_codeblock_contents_for_tag(self, tag: &str) -> Self {
+        self.map(|data| data.remove_codeblock_contents_for_tag(tag))
+    }
+
+    /// Strip trailing whitespace of every code block line and trailing blank lines
+    /// of all code blocks.
+    ///
+    /// See `CMarkData::trim_codeblock_text` for details.
+    pub fn trim_codeblock_text(self) -> Self {
+        self.map(|data| data.trim_codeblock_text())
+    }
+
+    /// Strip trailing whitespace of every code block line, trailing blank lines
+    /// and the leading blank line of all code blocks.
+    pub fn trim_codeblock_text_and_leading_blank_line(self) -> Self {
+        self.map(|data| data.trim_codeblock_text_and_leading_blank_line())
+    }
+
+    /// Converts the info strings of all fenced code blocks with function `func`.
+    ///
+    /// See `CMarkData::map_codeblock_tags` for details.
+    pub fn map_codeblock_tags<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map(|data| data.map_codeblock_tags(func, note))
+    }
+
+    /// Replace the fenced code block tags that match the first value of an alias pair
+    /// with the second value of that pair.
+    pub fn map_codeblock_tag_aliases(self, aliases: &[(&str, &str)]) -> Self {
+        self.map(|data| data.map_codeblock_tag_aliases(aliases))
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// starting with the capitalized alert kind followed by a colon, like `> Note: text`.
+    pub fn normalize_github_alerts(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.normalize_github_alerts())
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// without any alert marker.
+    pub fn strip_github_alerts(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.strip_github_alerts())
+    }
+
+    /// Convert GitHub alert blockquotes, like `> [!NOTE]`, to plain blockquotes
+    /// starting with the text returned by the specified function.
+    ///
+    /// See `CMarkData::normalize_github_alerts_with` for details.
+    pub fn normalize_github_alerts_with<F>(self, func: F) -> CMarkReadme<P, M>
+    where
+        F: FnMut(pulldown_cmark::BlockQuoteKind) -> String,
+    {
+        self.map(|data| data.normalize_github_alerts_with(func))
+    }
+
+    /// Remove metadata blocks, like YAML front matter.
+    pub fn remove_metadata_blocks(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_metadata_blocks())
+    }
+
+    /// Remove Jekyll-style front matter, like `---\ntitle: Crate\n---`, at the start of the readme.
+    ///
+    /// See `CMarkData::remove_front_matter` for details.
+    pub fn remove_front_matter(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_front_matter())
+    }
+
+    /// Remove all thematic breaks, like `---` or `***`.
+    pub fn remove_thematic_breaks(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_thematic_breaks())
+    }
+
+    /// Remove the specified number of the first thematic breaks.
+    pub fn remove_first_thematic_breaks(self, count: usize) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_first_thematic_breaks(count))
+    }
+
+    /// Remove emphasis, strong emphasis and strikethrough, keeping their text.
+    pub fn remove_emphasis(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_emphasis())
+    }
+
+    /// Remove the specified kinds of emphasis, keeping their text.
+    ///
+    /// See `CMarkData::remove_emphasis_kinds` for details.
+    pub fn remove_emphasis_kinds(self, kinds: EmphasisKinds) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_emphasis_kinds(kinds))
+    }
+
+    /// Replace links with their text.
+    ///
+    /// See `CMarkData::flatten_links` for details.
+    pub fn flatten_links(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.flatten_links())
+    }
+
+    /// Replace images with their alt text.
+    pub fn flatten_images(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.flatten_images())
+    }
+
+    /// Convert inline HTML line breaks like `<br>` to hard breaks.
+    ///
+    /// See `CMarkData::normalize_hard_breaks` for details.
+    pub fn normalize_hard_breaks(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.normalize_hard_breaks())
+    }
+
+    /// Remove HTML blocks and inline HTML.
+    ///
+    /// See `CMarkData::remove_html` for details.
+    pub fn remove_html(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_html())
+    }
+
+    /// Remove HTML blocks and inline HTML the predicate returns `true` for.
+    ///
+    /// See `CMarkData::remove_html_matching` for details.
+    pub fn remove_html_matching<F>(self, predicate: F) -> CMarkReadme<P, M>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map(|data| data.remove_html_matching(predicate))
+    }
+
+    /// Remove HTML comments, like `<!-- omit in toc -->` or `<!-- markdownlint-disable -->`.
+    ///
+    /// See `CMarkData::remove_html_comments` for details.
+    pub fn remove_html_comments(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_html_comments())
+    }
+
+    /// Remove everything from the first section with the specified heading text and level
+    /// to the end of the document.
+    ///
+    /// See `CMarkData::remove_sections_from` for details.
+    pub fn remove_sections_from(self, heading: &str, level: u32) -> Self {
+        self.map(|data| data.remove_sections_from(heading, level))
+    }
+
+    /// Keep only the first section with the specified heading text and level
+    /// and its subsections.
+    ///
+    /// See `CMarkData::extract_section` for details.
+    pub fn extract_section(self, heading: &str, level: u32) -> Self {
+        self.map(|data| data.extract_section(heading, level))
+    }
+
+    /// Keep only the contents of the first section with the specified heading text and level
+    /// and its subsections without the section heading.
+    ///
+    /// See `CMarkData::extract_section_body` for details.
+    pub fn extract_section_body(self, heading: &str, level: u32) -> Self {
+        self.map(|data| data.extract_section_body(heading, level))
+    }
+
+    /// Keep only the sections with the specified heading texts and level and their subsections.
+    ///
+    /// See `CMarkData::keep_sections` for details.
+    pub fn keep_sections(self, headings: &[&str], level: u32) -> Self {
+        self.map(|data| data.keep_sections(headings, level))
+    }
+
+    /// Remove sections with the specified heading texts and levels and their subsections
+    /// in a single pass.
+    ///
+    /// See `CMarkData::remove_sections` for details.
+    pub fn remove_sections(self, sections: &[(&str, u32)]) -> Self {
+        self.map(|data| data.remove_sections(sections))
+    }
+
     /// Remove the specified fenced code block tag.
     pub fn remove_codeblock_tag(self, tag: &str) -> CMarkReadme<P, M> {
         self.map(|data| data.remove_codeblock_tag(tag))
@@ -174,11 +568,80 @@ impl<'a, P, M> CMarkReadme<P, M> {
         self.map(|data| data.remove_codeblock_tags(tags))
     }
 
+    /// Remove fenced code block tags the predicate returns `true` for.
+    ///
+    /// See `CMarkData::remove_codeblock_tags_matching` for details.
+    pub fn remove_codeblock_tags_matching<F>(
+        self,
+        predicate: F,
+        note: impl Into<Cow<'static, str>>,
+    ) -> Self
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map(|data| data.remove_codeblock_tags_matching(predicate, note))
+    }
+
+    /// Remove fenced code blocks with the specified tag including their contents.
+    pub fn remove_codeblocks_with_tag(self, tag: &str) -> CMarkReadme<P, M> {
+        self.map(|data| data.remove_codeblocks_with_tag(tag))
+    }
+
+    /// Separate fenced code block tags with commas only.
+    ///
+    /// Rustdoc accepts tags separated by commas, whitespace or both,
+    /// so `rust no_run` and `rust, no_run` are the same as `rust,no_run`.
+    pub fn normalize_codeblock_tags(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.normalize_codeblock_tags())
+    }
+
+    /// Use the specified codeblock tag for fenced code blocks without tags
+    /// if the specified predicate returns true when passing the code block text to it.
+    pub fn use_default_codeblock_tag_if<F>(self, tag: &str, predicate: F) -> CMarkReadme<P, M>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map(|data| data.use_default_codeblock_tag_if(tag, predicate))
+    }
+
+    /// Replace the specified fenced code block tag with another one.
+    pub fn retag_codeblocks(self, from: &str, to: &str) -> CMarkReadme<P, M> {
+        self.map(|data| data.retag_codeblocks(from, to))
+    }
+
+    /// Replace lines of rust fenced codeblocks matching any of the specified wildcard patterns
+    /// with the mask, preserving the line indentation.
+    ///
+    /// See `CMarkData::mask_codeblock_lines_matching` for details.
+    pub fn mask_codeblock_lines_matching(self, patterns: &[&str], mask: &str) -> CMarkReadme<P, M> {
+        self.map(|data| data.mask_codeblock_lines_matching(patterns, mask))
+    }
+
+    /// Replaces texts of fenced code blocks tagged `toml` with their canonical formatting.
+    ///
+    /// See `CMarkData::normalize_toml_codeblocks` for details.
+    pub fn normalize_toml_codeblocks(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.normalize_toml_codeblocks())
+    }
+
     /// Remove sections with heading `Documentation` and level 2.
     pub fn remove_documentation_section(self) -> Self {
         self.map(|data| data.remove_documentation_section())
     }
 
+    /// Returns self if no link or image urls match the specified predicate,
+    /// otherwise returns an error with the first matching url and the description.
+    pub fn disallow_urls_matching<F>(
+        self,
+        predicate: F,
+        description: &str,
+    ) -> Result<CMarkReadme<P, M>, DisallowUrlsWithPrefixError>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.map_result(|data| data.disallow_urls_matching(predicate, description))
+    }
+
     /// Returns self if absolute blob links to the specified repository not found,
     /// otherwise returns an error.
     pub fn disallow_absolute_blob_links(
@@ -190,39 +653,392 @@ impl<'a, P, M> CMarkReadme<P, M> {
 
     /// Convert all relative links into absolute ones using
     /// the repository url as the root address.
-    pub fn use_absolute_blob_urls(self, repository_url: &str) -> CMarkReadme<P, M> {
-        self.map(|data| data.use_absolute_blob_urls(repository_url))
+    ///
+    /// Returns an error if an url points above the repository root.
+    pub fn use_absolute_blob_urls(
+        self,
+        repository_url: &str,
+    ) -> Result<CMarkReadme<P, M>, DisallowUrlsWithPrefixError> {
+        self.map_result(|data| data.use_absolute_blob_urls(repository_url))
     }
-}
 
-impl<'a, P> CMarkReadme<P, &'a Manifest> {
-    /// Returns self if absolute blob links to the manifest repository not found,
+    /// Returns self if absolute blob links to the specified repository branch not found,
     /// otherwise returns an error.
-    pub fn disallow_absolute_repository_blob_links(
+    pub fn disallow_absolute_blob_links_with_branch(
         self,
-    ) -> Result<CMarkReadme<P, &'a Manifest>, DisallowAbsoluteRepositoryBlobLinksError> {
+        repository_url: &str,
+        branch: &str,
+    ) -> Result<CMarkReadme<P, M>, DisallowUrlsWithPrefixError> {
+        self.map_result(|data| {
+            data.disallow_absolute_blob_links_with_branch(repository_url, branch)
+        })
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the repository url and the specified branch as the root address.
+    ///
+    /// Returns an error if an url points above the repository root.
+    pub fn use_absolute_blob_urls_with_branch(
+        self,
+        repository_url: &str,
+        branch: &str,
+    ) -> Result<CMarkReadme<P, M>, DisallowUrlsWithPrefixError> {
+        self.map_result(|data| data.use_absolute_blob_urls_with_branch(repository_url, branch))
+    }
+
+    /// Convert all relative links into absolute ones using
+    /// the repository url and the specified repository subpath as the root address.
+    ///
+    /// See `CMarkData::use_absolute_blob_urls_with_subpath` for details.
+    pub fn use_absolute_blob_urls_with_subpath(
+        self,
+        repository_url: &str,
+        subpath: &str,
+    ) -> Result<CMarkReadme<P, M>, DisallowUrlsWithPrefixError> {
+        self.map_result(|data| data.use_absolute_blob_urls_with_subpath(repository_url, subpath))
+    }
+
+    /// Converts all fragment-only links, like `#usage`,
+    /// with function `func` applied to each link address.
+    pub fn map_fragment_links<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map(|data| data.map_fragment_links(func, note))
+    }
+
+    /// Replaces rustdoc heading anchors in fragment links with the GitHub ones.
+    ///
+    /// See `CMarkData::use_github_heading_anchors` for details.
+    pub fn use_github_heading_anchors(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.use_github_heading_anchors())
+    }
+
+    /// Replaces GitHub heading anchors in fragment links with the rustdoc ones.
+    ///
+    /// See `CMarkData::use_rustdoc_heading_anchors` for details.
+    pub fn use_rustdoc_heading_anchors(self) -> CMarkReadme<P, M> {
+        self.map(|data| data.use_rustdoc_heading_anchors())
+    }
+
+    /// Rewrites link urls with the specified rules.
+    pub fn rewrite_urls(self, rules: &UrlRewriteRules) -> CMarkReadme<P, M> {
+        self.map(|data| data.rewrite_urls(rules))
+    }
+
+    /// Converts all links with function `func` applied to each link type, address, title and id.
+    ///
+    /// See `CMarkData::map_links_full` for details.
+    pub fn map_links_full<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        F: FnMut(LinkMeta<'_>) -> Option<LinkMeta<'static>>,
+    {
+        self.map(|data| data.map_links_full(func, note))
+    }
+
+    /// Converts all image links with function `func` applied to each image address.
+    pub fn map_image_links<F>(self, func: F, note: impl Into<Cow<'static, str>>) -> Self
+    where
+        for<'b> F: FnMut(&'b str) -> Cow<'b, str>,
+    {
+        self.map(|data| data.map_image_links(func, note))
+    }
+}
+
+impl<'a, M> CMarkReadme<&'a Path, M> {
+    /// Returns self if all relative link and image urls point to existing files
+    /// in the package directory, otherwise returns an error listing the missing ones.
+    ///
+    /// See `CMarkData::dangling_relative_links` for details.
+    pub fn disallow_dangling_relative_links(
+        self,
+    ) -> Result<CMarkReadme<&'a Path, M>, DanglingRelativeLinksError> {
+        let package_path = self.package_path;
+        self.map_result(|data| data.disallow_dangling_relative_links(package_path))
+    }
+}
+
+impl<'a> CMarkReadme<&'a Path, &'a Manifest> {
+    /// Convert all relative links into absolute ones
+    /// using the manifest repository url and the readme location as the root address.
+    ///
+    /// The branch is taken from the manifest, see `Manifest::repository_branch`.
+    /// See `use_absolute_repository_blob_urls_with_repo_root` for details.
+    pub fn use_absolute_repository_blob_urls(
+        self,
+    ) -> Result<CMarkReadme<&'a Path, &'a Manifest>, UseAbsoluteRepositoryBlobUrlsError> {
+        let branch = self.manifest.repository_branch();
+        self.use_absolute_repository_blob_urls_with_branch(branch)
+    }
+
+    /// Convert all relative links into absolute ones
+    /// using the manifest repository url, the specified branch
+    /// and the readme location as the root address.
+    ///
+    /// See `use_absolute_repository_blob_urls_with_repo_root` for details.
+    pub fn use_absolute_repository_blob_urls_with_branch(
+        self,
+        branch: &str,
+    ) -> Result<CMarkReadme<&'a Path, &'a Manifest>, UseAbsoluteRepositoryBlobUrlsError> {
         let repository = self
             .manifest
             .package
             .repository
             .clone()
-            .ok_or(DisallowAbsoluteRepositoryBlobLinksError::DocsUrlNotFound)?;
-        Ok(self.disallow_absolute_blob_links(&repository)?)
+            .ok_or(UseAbsoluteRepositoryBlobUrlsError::DocsUrlNotFound)?;
+        let subpath = self.repository_subpath(None);
+        Ok(self.map_result(|data| {
+            data.use_absolute_blob_urls_with_branch_and_subpath(&repository, branch, &subpath)
+        })?)
     }
 
     /// Convert all relative links into absolute ones
-    /// using the manifest repository url as the root address.
-    pub fn use_absolute_repository_blob_urls(
+    /// using the manifest repository url and the readme location as the root address.
+    ///
+    /// Relative links are resolved against the readme file directory
+    /// relative to the repository root, so both a repository-level readme
+    /// and a package-level readme in a workspace subdirectory get correct urls.
+    /// If the repository root is not specified, the closest parent directory
+    /// of the package containing `.git` is used, or the package directory if there is none.
+    pub fn use_absolute_repository_blob_urls_with_repo_root(
         self,
-    ) -> Result<CMarkReadme<P, &'a Manifest>, UseAbsoluteRepositoryBlobUrlsError> {
+        repo_root: Option<&Path>,
+    ) -> Result<CMarkReadme<&'a Path, &'a Manifest>, UseAbsoluteRepositoryBlobUrlsError> {
         let repository = self
             .manifest
             .package
             .repository
             .clone()
             .ok_or(UseAbsoluteRepositoryBlobUrlsError::DocsUrlNotFound)?;
-        Ok(self.use_absolute_blob_urls(&repository))
+        let subpath = self.repository_subpath(repo_root);
+        let branch = self.manifest.repository_branch();
+        Ok(self.map_result(|data| {
+            data.use_absolute_blob_urls_with_branch_and_subpath(&repository, branch, &subpath)
+        })?)
+    }
+
+    /// Convert all relative image urls into absolute raw content urls
+    /// of the manifest GitHub repository.
+    ///
+    /// Relative image urls are resolved against the readme location in the repository,
+    /// see `use_absolute_repository_blob_urls_with_repo_root` for details.
+    /// The branch is taken from the manifest, see `Manifest::repository_branch`.
+    /// Apply it before `use_absolute_repository_blob_urls`.
+    /// See `CMarkData::use_absolute_raw_urls_for_images_with_subpath` for details.
+    pub fn use_absolute_repository_raw_urls_for_images(
+        self,
+    ) -> Result<CMarkReadme<&'a Path, &'a Manifest>, RawImageUrlsError> {
+        let repository = self
+            .manifest
+            .package
+            .repository
+            .clone()
+            .ok_or(RawImageUrlsError::RepositoryUrlNotFound)?;
+        let subpath = self.repository_subpath(None);
+        let branch = self.manifest.repository_branch();
+        self.map_result(|data| {
+            data.use_absolute_raw_urls_for_images_with_subpath(&repository, branch, &subpath)
+        })
+    }
+
+    /// Applies the readme transformations of the specified pipeline.
+    ///
+    /// Relative urls are resolved against the readme location in the repository,
+    /// see `use_absolute_repository_blob_urls_with_repo_root` for details.
+    pub fn apply_pipeline(
+        self,
+        pipeline: &PipelineSpec,
+    ) -> Result<CMarkReadme<&'a Path, &'a Manifest>, PipelineError> {
+        let subpath = self.repository_subpath(None);
+        self.apply_pipeline_with_repository_subpath(pipeline, &subpath)
+    }
+
+    /// Returns the readme file directory relative to the specified repository root
+    /// or to the closest parent directory of the package containing `.git`.
+    fn repository_subpath(&self, repo_root: Option<&Path>) -> String {
+        let readme_path = self
+            .manifest
+            .relative_readme_path(self.package_path)
+            .unwrap_or_else(|| Path::new("README.md"));
+        let repo_root = match repo_root {
+            Some(repo_root) => Some(repo_root.to_path_buf()),
+            None => find_repository_root(self.package_path),
+        };
+        match repo_root {
+            Some(repo_root) => {
+                readme_repository_subpath(self.package_path, readme_path, &repo_root)
+            }
+            None => String::new(),
+        }
+    }
+}
+
+impl<'a, P> CMarkReadme<P, &'a Manifest> {
+    /// Verifies the first first-level heading text of the readme
+    /// against the policy for the manifest package name.
+    ///
+    /// The returned error contains the heading node to report its location.
+    pub fn verify_title(&self, policy: TitlePolicy) -> Result<(), TitleError> {
+        self.data.verify_title(&policy, &self.manifest.package.name)
+    }
+
+    /// Returns self if absolute blob links to the manifest repository not found,
+    /// otherwise returns an error.
+    ///
+    /// The branch is taken from the manifest, see `Manifest::repository_branch`.
+    pub fn disallow_absolute_repository_blob_links(
+        self,
+    ) -> Result<CMarkReadme<P, &'a Manifest>, DisallowAbsoluteRepositoryBlobLinksError> {
+        let branch = self.manifest.repository_branch();
+        self.disallow_absolute_repository_blob_links_with_branch(branch)
     }
+
+    /// Returns self if absolute blob links to the specified branch
+    /// of the manifest repository not found, otherwise returns an error.
+    pub fn disallow_absolute_repository_blob_links_with_branch(
+        self,
+        branch: &str,
+    ) -> Result<CMarkReadme<P, &'a Manifest>, DisallowAbsoluteRepositoryBlobLinksError> {
+        let repository = self
+            .manifest
+            .package
+            .repository
+            .clone()
+            .ok_or(DisallowAbsoluteRepositoryBlobLinksError::DocsUrlNotFound)?;
+        Ok(self.disallow_absolute_blob_links_with_branch(&repository, branch)?)
+    }
+
+    /// Applies the readme transformations of the specified pipeline
+    /// with the specified readme file directory relative to the repository root.
+    ///
+    /// See `PipelineSpec::apply_to_readme` for details.
+    pub fn apply_pipeline_with_repository_subpath(
+        self,
+        pipeline: &PipelineSpec,
+        repository_subpath: &str,
+    ) -> Result<CMarkReadme<P, &'a Manifest>, PipelineError> {
+        let manifest = self.manifest;
+        self.map_result(|data| pipeline.apply_to_readme(data, manifest, repository_subpath))
+    }
+}
+
+/// Readme file extensions that are parsed as Markdown.
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdown", "mkd", "mkdn", "mdwn"];
+
+/// Readme file extensions of other known markup and plain text formats.
+const NON_MARKDOWN_EXTENSIONS: &[&str] = &[
+    "adoc", "asciidoc", "asc", "rst", "txt", "text", "org", "textile", "rdoc", "pod", "html",
+    "htm", "wiki", "creole",
+];
+
+/// Returns an error if the readme file is unlikely to be a Markdown file.
+fn check_markdown_format(path: &Path, text: &str) -> Result<(), CMarkReadmeFromPackageError> {
+    use std::string::ToString;
+
+    let unsupported = |extension: Option<String>, reason: &str| {
+        Err(CMarkReadmeFromPackageError::UnsupportedFormat {
+            path: path.to_path_buf(),
+            extension,
+            reason: reason.to_string(),
+        })
+    };
+
+    match path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+    {
+        Some(ext) if MARKDOWN_EXTENSIONS.contains(&ext.as_str()) => Ok(()),
+        Some(ext) if NON_MARKDOWN_EXTENSIONS.contains(&ext.as_str()) => {
+            unsupported(Some(ext), "the file extension is of a non-Markdown format")
+        }
+        Some(_) => Ok(()),
+        None if text.trim().is_empty() || looks_like_markdown(text) => Ok(()),
+        None => unsupported(
+            None,
+            "the file has no extension and no Markdown headings, code fences, links or lists found",
+        ),
+    }
+}
+
+/// Returns `true` if the text contains common Markdown syntax.
+fn looks_like_markdown(text: &str) -> bool {
+    text.lines().map(str::trim_start).any(|line| {
+        let heading_marks = line.len() - line.trim_start_matches('#').len();
+        (1..=6).contains(&heading_marks)
+            && line[heading_marks..].starts_with(|ch: char| ch.is_whitespace())
+            || line.starts_with("```")
+            || line.starts_with("~~~")
+            || line.starts_with("- ")
+            || line.starts_with("* ")
+            || line.contains("](")
+    })
+}
+
+/// Returns the closest directory containing `.git` starting from the specified one.
+fn find_repository_root(path: &Path) -> Option<PathBuf> {
+    let path = absolute_path(path);
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Returns the package directory relative to the closest parent directory containing `.git`
+/// with `/` separators.
+///
+/// Returns an empty string if there is no such directory.
+pub(crate) fn package_repository_subpath(package_path: &Path) -> String {
+    match find_repository_root(package_path) {
+        Some(repo_root) => repository_subpath(package_path, &repo_root),
+        None => String::new(),
+    }
+}
+
+/// Returns the readme file directory relative to the repository root with `/` separators.
+///
+/// Returns an empty string if the readme is outside of the repository root.
+fn readme_repository_subpath(package_path: &Path, readme_path: &Path, repo_root: &Path) -> String {
+    let readme_path = absolute_path(&package_path.join(readme_path));
+    repository_subpath(readme_path.parent().unwrap_or(&readme_path), repo_root)
+}
+
+/// Returns the directory relative to the repository root with `/` separators.
+///
+/// Returns an empty string if the directory is outside of the repository root.
+fn repository_subpath(dir: &Path, repo_root: &Path) -> String {
+    use std::path::Component;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    match absolute_path(dir).strip_prefix(absolute_path(repo_root)) {
+        Ok(subpath) => subpath
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => String::new(),
+    }
+}
+
+/// Returns canonical path if it exists, or lexically normalized path otherwise.
+fn absolute_path(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    path.canonicalize().unwrap_or_else(|_| {
+        let mut result = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    let _ = result.pop();
+                }
+                component => result.push(component),
+            }
+        }
+        result
+    })
 }
 
 /// An error which can occur when creating readme from package.
@@ -234,6 +1050,26 @@ pub enum CMarkReadmeFromPackageError {
     /// Readme file not found.
     #[error("CMarkReadme not found.")]
     NotFound,
+    /// Readme file with the specified name not found.
+    #[error("Readme file `{}` not found.", .0.display())]
+    NamedNotFound(PathBuf),
+    /// Manifest reading failed.
+    #[error(transparent)]
+    ManifestError(#[from] TomlReadError),
+    /// Readme file is not a Markdown file.
+    #[error(
+        "Readme file `{path}` is not a Markdown file: {reason}. \
+         Only Markdown readmes can be sync-checked, \
+         use `CMarkReadme::from_package_forcing_markdown` to parse it as Markdown anyway."
+    )]
+    UnsupportedFormat {
+        /// Readme file path.
+        path: PathBuf,
+        /// Readme file extension, if any.
+        extension: Option<String>,
+        /// The reason why the file is considered as non-Markdown.
+        reason: String,
+    },
 }
 
 /// An error which can occur when checking for disallowed repository blob links.
@@ -249,8 +1085,171 @@ pub enum DisallowAbsoluteRepositoryBlobLinksError {
 
 /// An error which can occur when converting relative links into absolute ones,
 /// using the manifest repository url as the root address.
-#[derive(Clone, Copy, Debug, Error)]
+#[derive(Clone, Debug, Error)]
 pub enum UseAbsoluteRepositoryBlobUrlsError {
+    /// An url points above the repository root.
+    #[error(transparent)]
+    DisallowUrlsWithPrefixError(#[from] DisallowUrlsWithPrefixError),
     #[error("Manifest does not contain package.documentation field")]
     DocsUrlNotFound,
 }
+
+#[test]
+fn test_readme_repository_subpath() {
+    let subpath = |package: &str, readme: &str| {
+        readme_repository_subpath(Path::new(package), Path::new(readme), Path::new("/repo"))
+    };
+    assert_eq!(subpath("/repo", "README.md"), "");
+    assert_eq!(subpath("/repo/crates/foo", "../../README.md"), "");
+    assert_eq!(subpath("/repo/crates/foo", "README.md"), "crates/foo");
+    assert_eq!(
+        subpath("/repo/crates/foo", "./docs/README.md"),
+        "crates/foo/docs"
+    );
+    assert_eq!(
+        subpath("/repo/crates/foo", "../bar/README.md"),
+        "crates/bar"
+    );
+    assert_eq!(subpath("/other", "README.md"), "");
+}
+
+#[test]
+fn test_check_markdown_format() {
+    let check = |path: &str, text: &str| check_markdown_format(Path::new(path), text);
+
+    assert!(check("README.md", "Plain text.").is_ok());
+    assert!(check("docs/Readme.Markdown", "").is_ok());
+    assert!(check("README.unknown", "Plain text.").is_ok());
+    assert!(check("README", "# Title\n\nText.").is_ok());
+    assert!(check("README", "Text with [link](https://example.com).").is_ok());
+    assert!(check("README", "Text.\n\n```rust\nfn main() {}\n```").is_ok());
+    assert!(check("README", "").is_ok());
+
+    match check("readme.adoc", "= Title") {
+        Err(CMarkReadmeFromPackageError::UnsupportedFormat {
+            path, extension, ..
+        }) => {
+            assert_eq!(path, Path::new("readme.adoc"));
+            assert_eq!(extension.as_deref(), Some("adoc"));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match check("README", "Title\n\n#hashtag is not a heading.") {
+        Err(CMarkReadmeFromPackageError::UnsupportedFormat {
+            extension, reason, ..
+        }) => {
+            assert_eq!(extension, None);
+            assert!(reason.contains("no extension"));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_from_packaged_dir() {
+    use std::fs;
+
+    let root = std::env::temp_dir().join(std::format!(
+        "readme-sync-test-packaged-dir-{}",
+        std::process::id()
+    ));
+    let dir = root.join("foo-0.1.0");
+    fs::create_dir_all(dir.join("docs")).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nreadme = \"docs/README.md\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("docs/README.md"), "# Foo\n\nPackaged readme.\n").unwrap();
+
+    let readme = CMarkReadme::from_packaged_dir(&dir).unwrap();
+    let path = match readme.data().iter().next().map(|node| node.spans()) {
+        Some(spans) => match spans[0].text_source {
+            TextSource::File(file) => file.path().to_path_buf(),
+            TextSource::FileDocs(_) => panic!("readme should be parsed from file"),
+        },
+        None => panic!("readme should not be empty"),
+    };
+    assert_eq!(path, Path::new("foo-0.1.0/docs/README.md"));
+    assert_eq!(readme.data().to_markdown(), "# Foo\n\nPackaged readme.\n");
+
+    fs::remove_file(dir.join("docs/README.md")).unwrap();
+    assert!(matches!(
+        CMarkReadme::from_packaged_dir(&dir),
+        Err(CMarkReadmeFromPackageError::FileError(_))
+    ));
+    assert!(matches!(
+        CMarkReadme::from_packaged_dir(&root),
+        Err(CMarkReadmeFromPackageError::ManifestError(_))
+    ));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_use_absolute_repository_blob_urls_in_subdirectory() {
+    use crate::test_utils::readme_file;
+    use crate::PipelineStep;
+    use std::fs;
+
+    let root = std::env::temp_dir().join(std::format!(
+        "readme-sync-test-repository-blob-urls-{}",
+        std::process::id()
+    ));
+    let dir = root.join("crates/foo");
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\
+            repository = \"https://github.com/user/repo\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("README.md"), "[a](./a.md) [b](../b.md)\n").unwrap();
+    let package = Package::from_path(dir.clone()).unwrap();
+    let expected = "[a](https://github.com/user/repo/blob/master/crates/foo/a.md) \
+        [b](https://github.com/user/repo/blob/master/crates/b.md)\n";
+
+    let readme = CMarkReadme::from_package(&package).unwrap();
+    assert_eq!(
+        readme
+            .clone()
+            .use_absolute_repository_blob_urls()
+            .unwrap()
+            .data()
+            .to_markdown(),
+        expected
+    );
+    let pipeline = PipelineSpec {
+        readme: std::vec![PipelineStep::UseAbsoluteRepositoryBlobUrls],
+        ..PipelineSpec::empty()
+    };
+    assert_eq!(
+        readme
+            .clone()
+            .apply_pipeline(&pipeline)
+            .unwrap()
+            .data()
+            .to_markdown(),
+        expected
+    );
+    assert_eq!(
+        CMarkReadme::from_file_and_package_path_and_manifest(
+            readme_file("![a](./a.png) ![b](/b.png)\n"),
+            package.path(),
+            package.manifest(),
+        )
+        .use_absolute_repository_raw_urls_for_images()
+        .unwrap()
+        .data()
+        .to_markdown(),
+        "![a](https://raw.githubusercontent.com/user/repo/master/crates/foo/a.png) \
+            ![b](https://raw.githubusercontent.com/user/repo/master/b.png)\n"
+    );
+    assert!(matches!(
+        readme.use_absolute_repository_blob_urls_with_repo_root(Some(&dir)),
+        Err(UseAbsoluteRepositoryBlobUrlsError::DisallowUrlsWithPrefixError(_))
+    ));
+
+    fs::remove_dir_all(&root).unwrap();
+}
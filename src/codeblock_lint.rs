@@ -0,0 +1,221 @@
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem};
+
+/// The default maximum number of lines of a code block with several paragraphs
+/// that is not considered suspicious.
+pub const DEFAULT_MAX_CODEBLOCK_LINES: usize = 40;
+
+/// A fenced code block that is likely missing its closing fence
+/// and swallowed the following Markdown text.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error("code block may be missing its closing fence, {reason}")]
+pub struct CodeblockWarning {
+    /// The reason the code block is suspicious.
+    pub reason: SuspiciousCodeblock,
+    /// The code block start node.
+    pub node: Arc<CMarkItem>,
+}
+
+/// A reason a fenced code block is suspicious.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum SuspiciousCodeblock {
+    /// The code block contains a Markdown heading-like line.
+    #[error("it contains the heading-like line {0:?}")]
+    HeadingLine(String),
+    /// The code block contains another code fence opener.
+    #[error("it contains the code fence opener {0:?}")]
+    FenceOpener(String),
+    /// The code block is long and contains several blank-line-separated paragraphs.
+    #[error("it has {lines} lines in {paragraphs} blank-line-separated paragraphs")]
+    LongWithParagraphs {
+        /// The number of code block lines.
+        lines: usize,
+        /// The number of blank-line-separated paragraphs.
+        paragraphs: usize,
+    },
+}
+
+impl CMarkData {
+    /// Returns warnings about fenced code blocks that are likely missing their closing fences.
+    ///
+    /// Code blocks longer than `DEFAULT_MAX_CODEBLOCK_LINES` lines with several paragraphs
+    /// are considered suspicious.
+    /// See `lint_suspicious_codeblocks_with_max_lines` for details.
+    pub fn lint_suspicious_codeblocks(&self) -> Vec<CodeblockWarning> {
+        self.lint_suspicious_codeblocks_with_max_lines(DEFAULT_MAX_CODEBLOCK_LINES)
+    }
+
+    /// Returns warnings about fenced code blocks that are likely missing their closing fences.
+    ///
+    /// A code block is suspicious if it contains a Markdown heading-like line
+    /// or another code fence opener,
+    /// or if it is longer than `max_lines` lines and contains several blank-line-separated paragraphs.
+    /// Heading-like lines are ignored in Rust code blocks as they are rustdoc hidden lines there,
+    /// and in code blocks of languages using `#` line comments, like `sh`, `toml` or `python`.
+    /// At most one warning is returned for every code block.
+    pub fn lint_suspicious_codeblocks_with_max_lines(
+        &self,
+        max_lines: usize,
+    ) -> Vec<CodeblockWarning> {
+        use pulldown_cmark::{CodeBlockKind, Event, Tag, TagEnd};
+
+        let mut warnings = Vec::new();
+        let mut codeblock: Option<(&Arc<CMarkItem>, bool, String)> = None;
+        for node in self.iter() {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tags)))) => {
+                    codeblock = Some((node, is_hash_comment_codeblock_tags(tags), String::new()));
+                }
+                Some(Event::Text(text)) => {
+                    if let Some((_, _, codeblock_text)) = &mut codeblock {
+                        codeblock_text.push_str(text);
+                    }
+                }
+                Some(Event::End(TagEnd::CodeBlock)) => {
+                    if let Some((node, hash_comments, text)) = codeblock.take() {
+                        if let Some(reason) =
+                            suspicious_codeblock_reason(&text, hash_comments, max_lines)
+                        {
+                            warnings.push(CodeblockWarning {
+                                reason,
+                                node: Arc::clone(node),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        warnings
+    }
+}
+
+/// Returns the reason the fenced code block text is suspicious if any.
+fn suspicious_codeblock_reason(
+    text: &str,
+    hash_comments: bool,
+    max_lines: usize,
+) -> Option<SuspiciousCodeblock> {
+    for line in text.lines() {
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        if !hash_comments && is_heading_like(line) {
+            return Some(SuspiciousCodeblock::HeadingLine(String::from(line)));
+        }
+        if indent <= 3 && (trimmed.starts_with("```") || trimmed.starts_with("~~~")) {
+            return Some(SuspiciousCodeblock::FenceOpener(String::from(trimmed)));
+        }
+    }
+
+    let lines = text.lines().count();
+    let paragraphs = text
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .count();
+    if lines > max_lines && paragraphs > 1 {
+        return Some(SuspiciousCodeblock::LongWithParagraphs { lines, paragraphs });
+    }
+    None
+}
+
+/// Code block languages where `#` starts a line comment or a rustdoc hidden line.
+const HASH_COMMENT_LANGUAGES: &[&str] = &[
+    "rust",
+    "sh",
+    "bash",
+    "zsh",
+    "fish",
+    "shell",
+    "console",
+    "toml",
+    "yaml",
+    "yml",
+    "python",
+    "py",
+    "ruby",
+    "rb",
+    "perl",
+    "r",
+    "dockerfile",
+    "makefile",
+    "make",
+    "cmake",
+    "nix",
+    "ini",
+    "conf",
+    "powershell",
+    "ps1",
+    "gitignore",
+];
+
+/// Returns `true` if the code block language uses `#` line comments or rustdoc hidden lines.
+///
+/// Rustdoc test tags and error codes are skipped when looking for the language.
+fn is_hash_comment_codeblock_tags(tags: &str) -> bool {
+    use crate::tags::CodeblockTags;
+    use crate::{codeblock_rust_test_tags, is_codeblock_rust_error_code_tag};
+
+    CodeblockTags::parse(tags)
+        .iter()
+        .find(|tag| {
+            !codeblock_rust_test_tags().contains(tag) && !is_codeblock_rust_error_code_tag(tag)
+        })
+        .is_some_and(|language| {
+            HASH_COMMENT_LANGUAGES
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(language))
+        })
+}
+
+/// Returns `true` if the line looks like an ATX heading: `#{1,6}` followed by a space.
+fn is_heading_like(line: &str) -> bool {
+    let level = line.bytes().take_while(|&byte| byte == b'#').count();
+    (1..=6).contains(&level) && line[level..].starts_with(' ')
+}
+
+#[test]
+fn test_lint_suspicious_codeblocks() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let warnings = |text: &str, max_lines| -> Vec<String> {
+        readme_data(text)
+            .lint_suspicious_codeblocks_with_max_lines(max_lines)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    };
+
+    let unclosed = "# Usage\n\n```json\n{ \"foo\": 1 }\n\n## License\n\nMIT\n";
+    assert_eq!(
+        warnings(unclosed, 40),
+        ["code block may be missing its closing fence, \
+            it contains the heading-like line \"## License\""]
+    );
+    assert_eq!(
+        warnings("```sh\ncargo build\n```\n\n```text\n```rust\n```\n", 40),
+        ["code block may be missing its closing fence, \
+            it contains the code fence opener \"```rust\""]
+    );
+    assert_eq!(
+        warnings("```\nfirst\n\nsecond\nthird\n```\n", 3),
+        ["code block may be missing its closing fence, \
+            it has 4 lines in 2 blank-line-separated paragraphs"]
+    );
+    assert!(warnings("```\nfirst\n\nsecond\nthird\n```\n", 40).is_empty());
+    assert!(warnings(
+        "```rust\n# use std::fmt;\n#[derive(Debug)]\nstruct A;\n```\n",
+        40
+    )
+    .is_empty());
+    assert!(warnings("    ## Indented\n\n```\n#hashtag\n```\n", 40).is_empty());
+    assert!(warnings("```sh\n# Build it\ncargo build\n```\n", 40).is_empty());
+    assert!(warnings("```toml\n# Settings\n[dependencies]\n```\n", 40).is_empty());
+    assert!(warnings("```Python\n# Run it\nmain()\n```\n", 40).is_empty());
+    assert_eq!(warnings("```\n# Title\n```\n", 40).len(), 1);
+}
@@ -1,7 +1,10 @@
+use core::ops::Range;
 use std::collections::HashMap;
+use std::string::String;
 use std::sync::Arc;
+use std::vec::Vec;
 
-use crate::{File, FileDocs};
+use crate::{File, FileDocs, TextSource};
 
 /// Storage for codemap and corresponding codemap files.
 #[derive(Debug, Default)]
@@ -9,6 +12,16 @@ pub struct CodemapFiles {
     codemap: codemap::CodeMap,
     files: HashMap<Arc<File>, Arc<codemap::File>>,
     file_docs: HashMap<Arc<FileDocs>, Arc<codemap::File>>,
+    max_line_len: Option<usize>,
+    focuses: HashMap<TextSource, Vec<usize>>,
+    excerpts: HashMap<usize, Vec<ExcerptSegment>>,
+}
+
+/// A part of the original text kept in the codemap file text with trimmed long lines.
+#[derive(Clone, Debug)]
+struct ExcerptSegment {
+    source: Range<usize>,
+    target_start: usize,
 }
 
 impl CodemapFiles {
@@ -17,6 +30,50 @@ impl CodemapFiles {
         Self::default()
     }
 
+    /// Creates a new codemap files storage
+    /// that trims source lines longer than the specified length.
+    ///
+    /// Lines are trimmed to a window around the first focus offset within them
+    /// or around the line start.
+    pub fn with_max_line_len(max_line_len: usize) -> Self {
+        Self {
+            max_line_len: Some(max_line_len),
+            ..Self::default()
+        }
+    }
+
+    /// Adds an offset in the specified text source that trimmed lines should contain.
+    ///
+    /// It affects only files inserted after it.
+    pub fn add_focus(&mut self, text_source: &TextSource, offset: usize) {
+        if let TextSource::FileDocs(file_docs) = text_source {
+            if let Some(file_offset) = file_docs.remap_offset_to_file(offset) {
+                self.focuses
+                    .entry(TextSource::File(Arc::clone(file_docs.file())))
+                    .or_default()
+                    .push(file_offset);
+            }
+        }
+        self.focuses
+            .entry(text_source.clone())
+            .or_default()
+            .push(offset);
+    }
+
+    /// Returns the codemap span of the specified text range of the codemap file.
+    ///
+    /// The range is mapped into the trimmed text if the file lines were trimmed.
+    pub fn subspan(&self, file: &Arc<codemap::File>, range: &Range<usize>) -> codemap::Span {
+        let (start, end) = match self.excerpts.get(&(Arc::as_ptr(file) as usize)) {
+            Some(segments) => (
+                map_offset(segments, range.start),
+                map_offset(segments, range.end),
+            ),
+            None => (range.start, range.end),
+        };
+        file.span.subspan(start as u64, end.max(start) as u64)
+    }
+
     /// Returns codemap.
     pub fn codemap(&self) -> &codemap::CodeMap {
         &self.codemap
@@ -34,12 +91,13 @@ impl CodemapFiles {
 
     /// Inserts the given file into the storage if it is not present, then returns a reference to the appropriate file from codemap.
     pub fn get_or_insert_codemap_file(&mut self, file: &Arc<File>) -> &Arc<codemap::File> {
-        use std::string::ToString;
-
         let codemap = &mut self.codemap;
+        let excerpts = &mut self.excerpts;
+        let (max_line_len, focuses) = (self.max_line_len, &self.focuses);
         self.files.entry(Arc::clone(file)).or_insert_with(|| {
             let path = file.path().to_string_lossy().into_owned();
-            codemap.add_file(path, file.text().to_string())
+            let focuses = focuses.get(&TextSource::File(Arc::clone(file)));
+            add_file(codemap, excerpts, path, file.text(), max_line_len, focuses)
         })
     }
 
@@ -48,14 +106,96 @@ impl CodemapFiles {
         &mut self,
         file_docs: &Arc<FileDocs>,
     ) -> &Arc<codemap::File> {
-        use std::string::ToString;
-
         let codemap = &mut self.codemap;
+        let excerpts = &mut self.excerpts;
+        let (max_line_len, focuses) = (self.max_line_len, &self.focuses);
         self.file_docs
             .entry(Arc::clone(file_docs))
             .or_insert_with(|| {
                 let path = file_docs.file().path().to_string_lossy().into_owned() + "/parsed";
-                codemap.add_file(path, file_docs.docs().to_string())
+                let focuses = focuses.get(&TextSource::FileDocs(Arc::clone(file_docs)));
+                add_file(
+                    codemap,
+                    excerpts,
+                    path,
+                    file_docs.docs(),
+                    max_line_len,
+                    focuses,
+                )
             })
     }
 }
+
+/// Adds the text to the codemap, trimming lines longer than `max_line_len` if it is specified.
+fn add_file(
+    codemap: &mut codemap::CodeMap,
+    excerpts: &mut HashMap<usize, Vec<ExcerptSegment>>,
+    path: String,
+    text: &str,
+    max_line_len: Option<usize>,
+    focuses: Option<&Vec<usize>>,
+) -> Arc<codemap::File> {
+    use std::string::ToString;
+
+    let max_line_len = match max_line_len {
+        Some(max_line_len) if text.split('\n').any(|line| line.len() > max_line_len) => {
+            max_line_len
+        }
+        _ => return codemap.add_file(path, text.to_string()),
+    };
+
+    let mut trimmed = String::new();
+    let mut segments = Vec::new();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let line_end = line_start + content.len();
+        if content.len() > max_line_len {
+            let focus = focuses
+                .and_then(|focuses| {
+                    focuses
+                        .iter()
+                        .find(|&&offset| line_start <= offset && offset <= line_end)
+                })
+                .map_or(0, |offset| offset - line_start);
+            let (start, end) = crate::compare::excerpt_window(content, focus, max_line_len);
+            if start > 0 {
+                trimmed += "...";
+            }
+            segments.push(ExcerptSegment {
+                source: line_start + start..line_start + end,
+                target_start: trimmed.len(),
+            });
+            trimmed += &content[start..end];
+            trimmed += &std::format!("... {} more bytes", content.len() - (end - start));
+            segments.push(ExcerptSegment {
+                source: line_end..line_start + line.len(),
+                target_start: trimmed.len(),
+            });
+            trimmed += &line[content.len()..];
+        } else {
+            segments.push(ExcerptSegment {
+                source: line_start..line_start + line.len(),
+                target_start: trimmed.len(),
+            });
+            trimmed += line;
+        }
+        line_start += line.len();
+    }
+
+    let file = codemap.add_file(path, trimmed);
+    let _ = excerpts.insert(Arc::as_ptr(&file) as usize, segments);
+    file
+}
+
+/// Maps the original text offset into the trimmed text,
+/// offsets in the omitted parts are mapped to the end of the previous kept part.
+fn map_offset(segments: &[ExcerptSegment], offset: usize) -> usize {
+    let idx = segments.partition_point(|segment| segment.source.start <= offset);
+    match idx.checked_sub(1).map(|idx| &segments[idx]) {
+        Some(segment) => {
+            segment.target_start + (offset - segment.source.start).min(segment.source.len())
+        }
+        None => 0,
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::vec::Vec;
 
 use codemap_diagnostic::SpanLabel;
@@ -46,14 +47,35 @@ impl<'a> CodemapSpans<'a> {
     }
 }
 
-trait FileSubSpan {
-    fn subspan(&self, range: &core::ops::Range<usize>) -> codemap::Span;
-}
+/// Sorts span labels by file name, start and end positions,
+/// merges span labels with identical ranges and keeps at most `max_count` of them.
+///
+/// If a range has both primary and secondary labels, the primary one is kept.
+/// Returns the number of omitted span labels.
+pub(crate) fn normalize_span_labels(
+    codemap_files: &CodemapFiles,
+    span_labels: &mut Vec<SpanLabel>,
+    max_count: usize,
+) -> usize {
+    use codemap_diagnostic::SpanStyle;
+    use std::string::String;
 
-impl FileSubSpan for codemap::File {
-    fn subspan(&self, range: &core::ops::Range<usize>) -> codemap::Span {
-        self.span.subspan(range.start as u64, range.end as u64)
-    }
+    let codemap = codemap_files.codemap();
+    let key = |label: &SpanLabel| {
+        let file = codemap.find_file(label.span.low());
+        (
+            String::from(file.name()),
+            label.span.low() - file.span.low(),
+            label.span.high() - file.span.low(),
+            label.style != SpanStyle::Primary,
+        )
+    };
+    span_labels.sort_by_cached_key(key);
+    span_labels.dedup_by(|next, prev| next.span == prev.span);
+
+    let omitted = span_labels.len().saturating_sub(max_count);
+    span_labels.truncate(max_count);
+    omitted
 }
 
 impl<'a> Extend<CMarkSpan<'a>> for CodemapSpans<'_> {
@@ -67,10 +89,9 @@ impl<'a> Extend<CMarkSpan<'a>> for CodemapSpans<'_> {
         for item in iter {
             match item.text_source {
                 TextSource::File(file) => {
-                    let span = self
-                        .codemap_files
-                        .get_or_insert_codemap_file(file)
-                        .subspan(item.range);
+                    let codemap_file =
+                        Arc::clone(self.codemap_files.get_or_insert_codemap_file(file));
+                    let span = self.codemap_files.subspan(&codemap_file, item.range);
                     self.span_labels.push(SpanLabel {
                         span,
                         style: SpanStyle::Primary,
@@ -78,10 +99,11 @@ impl<'a> Extend<CMarkSpan<'a>> for CodemapSpans<'_> {
                     });
                 }
                 TextSource::FileDocs(file_docs) => {
-                    let span = self
-                        .codemap_files
-                        .get_or_insert_codemap_docs_file(file_docs)
-                        .subspan(item.range);
+                    let codemap_file = Arc::clone(
+                        self.codemap_files
+                            .get_or_insert_codemap_docs_file(file_docs),
+                    );
+                    let span = self.codemap_files.subspan(&codemap_file, item.range);
                     self.span_labels.push(SpanLabel {
                         span,
                         style: SpanStyle::Primary,
@@ -91,10 +113,9 @@ impl<'a> Extend<CMarkSpan<'a>> for CodemapSpans<'_> {
                     let file = file_docs.file();
                     let file_range = file_docs.remap_to_file(item.range.clone());
                     if let Some(file_range) = file_range {
-                        let span = self
-                            .codemap_files
-                            .get_or_insert_codemap_file(file)
-                            .subspan(&file_range);
+                        let codemap_file =
+                            Arc::clone(self.codemap_files.get_or_insert_codemap_file(file));
+                        let span = self.codemap_files.subspan(&codemap_file, &file_range);
                         self.span_labels.push(SpanLabel {
                             span,
                             style: SpanStyle::Secondary,
@@ -0,0 +1,1571 @@
+use core::fmt::Display;
+use std::borrow::ToOwned;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::CowStr;
+
+use crate::suggested_patch::suggest_readme_patch;
+use crate::{CMarkData, CMarkDocs, CMarkItem, CMarkReadme, CMarkSpan, SuggestedPatch, TextSource};
+
+/// The first readme and docs nodes that are not the same,
+/// with plain text notes about their differences.
+///
+/// It is available without the codemap features,
+/// and its `Display` implementation renders a plain text report.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncMismatch {
+    /// The readme node or `None` if the readme has no more nodes.
+    pub readme_node: Option<Arc<CMarkItem>>,
+    /// The docs node or `None` if the docs have no more nodes.
+    pub docs_node: Option<Arc<CMarkItem>>,
+    /// Readme nodes removed right before the readme node.
+    pub readme_removed: Vec<Arc<CMarkItem>>,
+    /// Docs nodes removed right before the docs node.
+    pub docs_removed: Vec<Arc<CMarkItem>>,
+    /// Notes about the differences between the readme and docs node events.
+    pub notes: Vec<String>,
+    /// The number of matched events before the mismatch.
+    pub matched_count: usize,
+    /// The last matched events before the mismatch.
+    pub previous_events: Vec<pulldown_cmark::Event<'static>>,
+    /// The suggested readme edit resolving the mismatch if it can be safely computed.
+    pub suggested_patch: Option<SuggestedPatch>,
+    /// Whether the mismatched nodes are the last texts of the documents
+    /// and differ only by trailing whitespace.
+    pub trailing_whitespace_only: bool,
+}
+
+/// The maximum number of the previous events stored in `SyncMismatch`.
+const MAX_PREVIOUS_EVENTS: usize = 16;
+
+/// Options of mismatch messages rendering.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct EmitOptions {
+    /// The maximum length in bytes of a single rendered event or source line excerpt.
+    ///
+    /// Longer excerpts are trimmed to a window around the difference if its position is known,
+    /// and the number of omitted bytes is shown instead of the rest.
+    pub max_excerpt_len: usize,
+    /// Whether source excerpts are formatted directly from the file texts instead of a codemap.
+    ///
+    /// The codemap requires copies of whole files, which may take a lot of memory
+    /// for huge generated sources. Lightweight excerpts are formatted without colors.
+    pub lightweight_excerpts: bool,
+}
+
+impl EmitOptions {
+    /// Returns the options with lightweight source excerpts enabled or disabled.
+    pub fn lightweight_excerpts(self, lightweight_excerpts: bool) -> Self {
+        Self {
+            lightweight_excerpts,
+            ..self
+        }
+    }
+}
+
+impl Default for EmitOptions {
+    fn default() -> Self {
+        Self {
+            max_excerpt_len: 240,
+            lightweight_excerpts: false,
+        }
+    }
+}
+
+/// Returns `Ok(())` if the given readme and docs are the same, and `Err(SyncMismatch)` otherwise.
+///
+/// Unlike `check_sync`, it does not require the codemap features
+/// and returns structured mismatch data instead of diagnostic messages.
+#[allow(clippy::result_large_err)]
+pub fn compare_sync<P1, P2, M1, M2>(
+    readme: &CMarkReadme<P1, M1>,
+    docs: &CMarkDocs<P2, M2>,
+) -> Result<(), SyncMismatch> {
+    match find_mismatch(readme.data(), docs.data()) {
+        Some(mismatch) => Err(mismatch),
+        None => Ok(()),
+    }
+}
+
+/// Returns the first mismatch of the specified readme and docs data if any.
+pub(crate) fn find_mismatch(readme: &CMarkData, docs: &CMarkData) -> Option<SyncMismatch> {
+    find_mismatch_with_options(readme, docs, &EmitOptions::default())
+}
+
+/// Returns the first mismatch of the specified readme and docs data if any,
+/// using the specified options for the mismatch notes.
+pub(crate) fn find_mismatch_with_options(
+    readme: &CMarkData,
+    docs: &CMarkData,
+    options: &EmitOptions,
+) -> Option<SyncMismatch> {
+    let mut readme_iter = readme.iter();
+    let mut docs_iter = docs.iter();
+    let mut matched_events = Vec::new();
+
+    loop {
+        let NextItem {
+            node: readme_node,
+            event: readme_event,
+            removed: readme_removed,
+        } = next_node(&mut readme_iter);
+
+        let NextItem {
+            node: docs_node,
+            event: docs_event,
+            removed: docs_removed,
+        } = next_node(&mut docs_iter);
+
+        if readme_node.is_none() && docs_node.is_none() {
+            return None;
+        }
+
+        if readme_event == docs_event {
+            matched_events.push(readme_event.unwrap());
+        } else {
+            let trailing_whitespace_note =
+                trailing_whitespace_note(&readme_event, &docs_event, &readme_iter, &docs_iter);
+            let mut notes = match (readme_event, docs_event) {
+                (Some(readme_event), Some(docs_event)) => {
+                    let text_kind = if is_in_image(&matched_events) {
+                        "image alt text"
+                    } else {
+                        "text"
+                    };
+                    let mut notes = event_diff_notes(
+                        &readme_event,
+                        &docs_event,
+                        text_kind,
+                        options.max_excerpt_len,
+                    );
+                    notes.extend(list_item_code_block_note(
+                        &readme_event,
+                        &docs_event,
+                        &matched_events,
+                    ));
+                    notes.extend(emphasis_nesting_note(
+                        &readme_event,
+                        &docs_event,
+                        &readme_iter,
+                        &docs_iter,
+                    ));
+                    notes
+                }
+                _ => Vec::new(),
+            };
+            if let Some(docs_node) = &docs_node {
+                notes.extend(duplicated_heading_note(docs, docs_node));
+            }
+            if let Some(readme_node) = &readme_node {
+                notes.extend(duplicated_block_note(readme, readme_node, "readme"));
+            }
+            if let Some(docs_node) = &docs_node {
+                notes.extend(duplicated_block_note(docs, docs_node, "docs"));
+            }
+            if let Some(readme_node) = &readme_node {
+                notes.extend(table_note(readme, readme_node, "readme"));
+            }
+            if let Some(docs_node) = &docs_node {
+                notes.extend(table_note(docs, docs_node, "docs"));
+            }
+            let trailing_whitespace_only = trailing_whitespace_note.is_some();
+            if let Some(note) = trailing_whitespace_note {
+                notes.insert(0, note);
+            }
+            let matched_count = matched_events.len();
+            let from = matched_count.saturating_sub(MAX_PREVIOUS_EVENTS);
+            let mut mismatch = SyncMismatch {
+                readme_node,
+                docs_node,
+                readme_removed,
+                docs_removed,
+                notes,
+                matched_count,
+                previous_events: matched_events.split_off(from),
+                suggested_patch: None,
+                trailing_whitespace_only,
+            };
+            mismatch.suggested_patch = suggest_readme_patch(readme, docs, &mismatch);
+            return Some(mismatch);
+        }
+    }
+}
+
+impl SyncMismatch {
+    /// Returns the main message describing the mismatched nodes.
+    pub fn message(&self) -> String {
+        self.message_with_options(&EmitOptions::default())
+    }
+
+    /// Returns the main message describing the mismatched nodes
+    /// using the specified rendering options.
+    pub fn message_with_options(&self, options: &EmitOptions) -> String {
+        use std::format;
+
+        let event_excerpt = |node: &Option<Arc<CMarkItem>>| {
+            let event = node.as_ref()?.event()?;
+            Some(self.event_excerpt(event, options))
+        };
+        match (
+            event_excerpt(&self.readme_node),
+            event_excerpt(&self.docs_node),
+        ) {
+            (Some(readme_event), Some(docs_event)) => format!(
+                "readme node\n`{}`\n does not match docs node\n`{}`",
+                readme_event, docs_event
+            ),
+            (Some(readme_event), None) => format!(
+                "readme node\n`{}`\n does not match any docs node",
+                readme_event
+            ),
+            (None, Some(docs_event)) => format!(
+                "docs node\n`{}`\n does not match any readme node",
+                docs_event
+            ),
+            (None, None) => String::from("readme and docs nodes do not match"),
+        }
+    }
+
+    /// Returns the character position of the first difference
+    /// if both mismatched nodes are events with different texts.
+    pub fn text_difference(&self) -> Option<usize> {
+        let readme_text = get_event_text(self.readme_node.as_ref()?.event()?)?;
+        let docs_text = get_event_text(self.docs_node.as_ref()?.event()?)?;
+        Some(first_difference(readme_text, docs_text))
+    }
+
+    /// Returns the event rendering trimmed around the texts difference if it is known.
+    fn event_excerpt(&self, event: &pulldown_cmark::Event<'_>, options: &EmitOptions) -> String {
+        use std::format;
+
+        let rendered = format!("{}", FmtPrint(event));
+        let focus = match (self.text_difference(), get_event_text(event)) {
+            (Some(pos), Some(text)) => rendered.find('"').map_or(0, |quote| {
+                // The escaped text before the difference, without its quotes.
+                let before = format!("{:?}", &text[..byte_offset(text, pos)]);
+                quote + before.len() - 1
+            }),
+            _ => 0,
+        };
+        excerpt(&rendered, focus, options.max_excerpt_len)
+    }
+
+    /// Returns a note listing the last matched events before the mismatch.
+    pub fn previous_events_note(&self) -> String {
+        self.previous_events_note_with_options(&EmitOptions::default())
+    }
+
+    /// Returns a note listing the last matched events before the mismatch
+    /// using the specified rendering options.
+    pub fn previous_events_note_with_options(&self, options: &EmitOptions) -> String {
+        use std::format;
+        use std::string::ToString;
+
+        if self.matched_count == 0 {
+            "match failed on first events".to_string()
+        } else {
+            let mut note = "previous events: [\n".to_owned();
+            if self.matched_count > self.previous_events.len() {
+                note += "    ...\n";
+            }
+            for event in &self.previous_events {
+                let event = format!("{}", FmtPrint(event));
+                note += &format!("    {}\n", excerpt(&event, 0, options.max_excerpt_len));
+            }
+            note += "]";
+            note
+        }
+    }
+}
+
+impl Display for SyncMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "error: {}", self.message())?;
+        let nodes = [&self.readme_node, &self.docs_node];
+        for span in nodes
+            .iter()
+            .filter_map(|node| node.as_ref())
+            .flat_map(|node| node.spans())
+        {
+            writeln!(f, "  --> {}", span_location(&span))?;
+        }
+        for (removed, node_type) in [
+            (&self.readme_removed, "readme"),
+            (&self.docs_removed, "docs"),
+        ] {
+            if !removed.is_empty() {
+                writeln!(
+                    f,
+                    "note: some {} nodes were removed before these",
+                    node_type
+                )?;
+                for span in removed.iter().flat_map(|node| node.spans()) {
+                    writeln!(f, "  --> {}", span_location(&span))?;
+                }
+            }
+        }
+        for note in &self.notes {
+            writeln!(f, "note: {}", note)?;
+        }
+        write!(f, "note: {}", self.previous_events_note())
+    }
+}
+
+impl std::error::Error for SyncMismatch {}
+
+/// Returns the `path:line:column` location of the span start in the original file.
+fn span_location(span: &CMarkSpan<'_>) -> String {
+    use std::format;
+
+    let (path, text, offset) = match span.text_source {
+        TextSource::File(file) => (file.path().display(), file.text(), span.range.start),
+        TextSource::FileDocs(file_docs) => match file_docs.remap_to_file(span.range.clone()) {
+            Some(range) => (
+                file_docs.file().path().display(),
+                file_docs.file().text(),
+                range.start,
+            ),
+            None => (
+                file_docs.file().path().display(),
+                file_docs.docs(),
+                span.range.start,
+            ),
+        },
+    };
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    format!("{}:{}:{}", path, line, column)
+}
+
+struct NextItem {
+    node: Option<Arc<CMarkItem>>,
+    event: Option<pulldown_cmark::Event<'static>>,
+    removed: Vec<Arc<CMarkItem>>,
+}
+
+fn next_node(iter: &mut crate::CMarkDataIter<'_>) -> NextItem {
+    let mut removed = Vec::new();
+    loop {
+        if let Some(node) = iter.next() {
+            if let Some(event) = node.event() {
+                return NextItem {
+                    node: Some(Arc::clone(node)),
+                    event: Some(event.clone()),
+                    removed,
+                };
+            } else {
+                removed.push(Arc::clone(node));
+            }
+        } else {
+            return NextItem {
+                node: None,
+                event: None,
+                removed,
+            };
+        }
+    }
+}
+
+/// Returns a note if the mismatched events are texts that differ only by trailing whitespace
+/// and the rest of both documents contains the same closing tags only.
+fn trailing_whitespace_note(
+    readme_event: &Option<pulldown_cmark::Event<'_>>,
+    docs_event: &Option<pulldown_cmark::Event<'_>>,
+    readme_iter: &crate::CMarkDataIter<'_>,
+    docs_iter: &crate::CMarkDataIter<'_>,
+) -> Option<String> {
+    use pulldown_cmark::Event;
+    use std::format;
+
+    let (readme_text, docs_text) = match (readme_event, docs_event) {
+        (Some(Event::Text(readme_text)), Some(Event::Text(docs_text))) => (readme_text, docs_text),
+        _ => return None,
+    };
+    let content = readme_text.trim_end();
+    if content != docs_text.trim_end() {
+        return None;
+    }
+
+    let rest = |iter: &crate::CMarkDataIter<'_>| -> Option<Vec<Event<'static>>> {
+        iter.clone()
+            .filter_map(|node| node.event())
+            .map(|event| match event {
+                Event::End(_) => Some(event.clone().into_static()),
+                _ => None,
+            })
+            .collect()
+    };
+    if rest(readme_iter)? != rest(docs_iter)? {
+        return None;
+    }
+
+    Some(format!(
+        "the documents differ only by trailing whitespace at end of file: \
+            readme ends with \"{}\", docs end with \"{}\"",
+        readme_text[content.len()..].escape_debug(),
+        docs_text[content.len()..].escape_debug(),
+    ))
+}
+
+/// Returns `true` if the innermost unclosed tag of the specified events is an image.
+fn is_in_image(events: &[pulldown_cmark::Event<'_>]) -> bool {
+    use pulldown_cmark::Tag;
+
+    matches!(innermost_tag(events), Some(Tag::Image { .. }))
+}
+
+/// Returns the innermost unclosed tag of the specified events.
+fn innermost_tag<'a, 'b>(
+    events: &'a [pulldown_cmark::Event<'b>],
+) -> Option<&'a pulldown_cmark::Tag<'b>> {
+    use pulldown_cmark::Event;
+
+    let mut depth = 0;
+    for event in events.iter().rev() {
+        match event {
+            Event::End(_) => depth += 1,
+            Event::Start(_) if depth > 0 => depth -= 1,
+            Event::Start(tag) => return Some(tag),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns a note about the doc comments indentation
+/// if a list item code block is parsed only on one side.
+fn list_item_code_block_note(
+    readme_event: &pulldown_cmark::Event<'_>,
+    docs_event: &pulldown_cmark::Event<'_>,
+    matched_events: &[pulldown_cmark::Event<'_>],
+) -> Option<String> {
+    use pulldown_cmark::{Event, Tag};
+    use std::string::ToString;
+
+    let is_code_block = |event: &Event<'_>| matches!(event, Event::Start(Tag::CodeBlock(_)));
+    let is_text = |event: &Event<'_>| {
+        matches!(
+            event,
+            Event::Text(_) | Event::SoftBreak | Event::Start(Tag::Paragraph)
+        )
+    };
+    let is_code_block_pair = (is_code_block(readme_event) && is_text(docs_event))
+        || (is_text(readme_event) && is_code_block(docs_event));
+    if !is_code_block_pair || !matches!(innermost_tag(matched_events), Some(Tag::Item)) {
+        return None;
+    }
+    Some(
+        concat!(
+            "Possible issue: ",
+            "a list item code block is parsed as a code block only on one side. ",
+            "Indented code blocks in list items must be indented ",
+            "by four spaces relative to the list item text. ",
+            "Rustdoc removes the common leading whitespace of the doc comment lines, ",
+            "including the space after `//!` and `///`, ",
+            "so the list item text starts at the column after the list marker and its space. ",
+            "See <https://doc.rust-lang.org/reference/comments.html#doc-comments> for more details.",
+        )
+        .to_string(),
+    )
+}
+
+/// Returns notes about the differences between the readme and docs events.
+///
+/// The text kind is used to describe the text events differences,
+/// for example "text" or "image alt text".
+/// The events excerpts are limited to `max_excerpt_len` characters.
+fn event_diff_notes(
+    readme_event: &pulldown_cmark::Event<'_>,
+    docs_event: &pulldown_cmark::Event<'_>,
+    text_kind: &str,
+    max_excerpt_len: usize,
+) -> Vec<String> {
+    use pulldown_cmark::{CodeBlockKind, Event, Tag};
+    use std::string::ToString;
+    use std::{format, vec};
+
+    let readme_event_name = get_event_name(readme_event);
+    let docs_event_name = get_event_name(docs_event);
+    if readme_event_name != docs_event_name {
+        return vec![
+            std::format!("readme node event name is \"{}\"", readme_event_name),
+            std::format!("docs   node event name is \"{}\"", docs_event_name),
+        ];
+    }
+
+    let readme_tag = get_event_start_tag(readme_event);
+    let docs_tag = get_event_start_tag(docs_event);
+    if let (Some(readme_tag), Some(docs_tag)) = (readme_tag, docs_tag) {
+        let readme_tag_name = get_start_tag_name(readme_tag);
+        let docs_tag_name = get_start_tag_name(docs_tag);
+        if readme_tag_name != docs_tag_name {
+            let mut notes = vec![
+                std::format!(
+                    "readme node event start tag name is \"{}\"",
+                    readme_tag_name
+                ),
+                std::format!("docs   node event start tag name is \"{}\"", docs_tag_name),
+            ];
+            if let (Event::Start(Tag::BlockQuote(Some(_))), _)
+            | (_, Event::Start(Tag::BlockQuote(Some(_)))) = (readme_event, docs_event)
+            {
+                notes.push(
+                    concat!(
+                        "Possible issue: ",
+                        "GitHub alert blockquotes like `> [!NOTE]` differ from plain blockquotes. ",
+                        "Use `normalize_github_alerts` or `strip_github_alerts` ",
+                        "to compare them with plain blockquotes.",
+                    )
+                    .to_string(),
+                );
+            }
+            if let Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) = docs_event {
+                notes.push(
+                    concat!(
+                        "Possible issue: ",
+                        "Rustdoc ignore indents in the consecutive ",
+                        "doc-comments and doc-attributes. ",
+                        "However, the four-space indents should be ",
+                        "interpreted as Indented code blocks in CMark. ",
+                        "Issue: https://github.com/rust-lang/rust/issues/70732. ",
+                        "Use `convert_indented_codeblocks_to_fenced` ",
+                        "to compare them with fenced code blocks.",
+                    )
+                    .to_string(),
+                );
+            }
+            return notes;
+        }
+    }
+
+    let readme_tag = get_event_end_tag(readme_event);
+    let docs_tag = get_event_end_tag(docs_event);
+    if let (Some(readme_tag), Some(docs_tag)) = (readme_tag, docs_tag) {
+        let readme_tag_name = get_end_tag_name(readme_tag);
+        let docs_tag_name = get_end_tag_name(docs_tag);
+        if readme_tag_name != docs_tag_name {
+            return vec![
+                std::format!("readme node event end tag name is \"{}\"", readme_tag_name),
+                std::format!("docs   node event end tag name is \"{}\"", docs_tag_name),
+            ];
+        }
+    }
+
+    let readme_text = get_event_text(readme_event);
+    let docs_text = get_event_text(docs_event);
+    if let (Some(readme_text), Some(docs_text)) = (readme_text, docs_text) {
+        if readme_text != docs_text {
+            const OFFSET: usize = 32;
+            const LEN: usize = 32;
+
+            let pos = first_difference(readme_text, docs_text);
+            let start = pos.saturating_sub(OFFSET);
+            let end = pos + LEN;
+
+            return vec![
+                std::format!(
+                    "readme and docs node {}s differ at character {}",
+                    text_kind,
+                    pos + 1
+                ),
+                std::format!(
+                    "readme node {} part: \"{}\"",
+                    text_kind,
+                    formatted_subslice(readme_text, start, end)
+                ),
+                std::format!(
+                    "docs   node {} part: \"{}\"",
+                    text_kind,
+                    formatted_subslice(docs_text, start, end)
+                ),
+            ];
+        }
+    }
+
+    vec![
+        excerpt(
+            &format!("readme node: {}", FmtPrint(readme_event)),
+            0,
+            max_excerpt_len,
+        ),
+        excerpt(
+            &format!("docs   node: {}", FmtPrint(docs_event)),
+            0,
+            max_excerpt_len,
+        ),
+    ]
+}
+
+/// Returns a note if the docs section containing the specified node
+/// has the same heading as another docs section.
+fn duplicated_heading_note(docs: &CMarkData, docs_node: &Arc<CMarkItem>) -> Option<String> {
+    use crate::cmark_data::heading_text;
+    use pulldown_cmark::{Event, Tag};
+
+    let nodes: Vec<_> = docs.iter().cloned().collect();
+    let headings: Vec<_> = nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, node)| match node.event() {
+            Some(Event::Start(Tag::Heading { level, .. })) => {
+                Some((idx, *level, heading_text(&nodes[idx..])?))
+            }
+            _ => None,
+        })
+        .collect();
+    let node_idx = nodes.iter().position(|node| Arc::ptr_eq(node, docs_node))?;
+    let (_, level, text) = headings.iter().rev().find(|(idx, ..)| *idx <= node_idx)?;
+    let count = headings
+        .iter()
+        .filter(|(_, other_level, other_text)| other_level == level && other_text == text)
+        .count();
+    if count > 1 {
+        Some(std::format!(
+            "Possible issue: docs contain {} `{}` headings, \
+             they may be built from conflicting `cfg_attr` branches. \
+             Use `FileDocs::lint_cfg_conflicts` to check it.",
+            count,
+            text
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns a note with the occurrence index and the locations of the other occurrences
+/// if the block containing the specified node has the same events as other blocks.
+///
+/// Paragraphs, headings and code blocks are considered.
+fn duplicated_block_note(data: &CMarkData, node: &Arc<CMarkItem>, side: &str) -> Option<String> {
+    use crate::{LocationRole, SourceLocation};
+    use pulldown_cmark::{Event, Tag};
+    use std::format;
+
+    let nodes: Vec<_> = data.iter().filter(|node| node.event().is_some()).collect();
+    let mut blocks = Vec::new();
+    for (start, node) in nodes.iter().enumerate() {
+        let kind = match node.event() {
+            Some(Event::Start(Tag::Paragraph)) => "paragraph",
+            Some(Event::Start(Tag::Heading { .. })) => "heading",
+            Some(Event::Start(Tag::CodeBlock(_))) => "code block",
+            _ => continue,
+        };
+        let mut depth = 0;
+        let mut signature = String::new();
+        for (end, node) in nodes.iter().enumerate().skip(start) {
+            let event = node.event()?;
+            signature += &format!("{:?}\n", event);
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                blocks.push((start..=end, kind, signature));
+                break;
+            }
+        }
+    }
+
+    let node_idx = nodes.iter().position(|other| Arc::ptr_eq(other, node))?;
+    let (range, kind, signature) = blocks
+        .iter()
+        .rev()
+        .find(|(range, ..)| range.contains(&node_idx))?;
+    let identical: Vec<_> = blocks
+        .iter()
+        .filter(|(_, _, other)| other == signature)
+        .collect();
+    if identical.len() < 2 {
+        return None;
+    }
+
+    let role = match side {
+        "readme" => LocationRole::ReadmePrimary,
+        _ => LocationRole::DocsPrimary,
+    };
+    let occurrence = identical
+        .iter()
+        .position(|(other, ..)| other == range)
+        .map_or(0, |idx| idx + 1);
+    let others: Vec<_> = identical
+        .iter()
+        .filter(|(other, ..)| other != range)
+        .filter_map(|(other, ..)| {
+            let span = nodes[*other.start()].spans().into_iter().next()?;
+            let location = SourceLocation::from_span(&span, role)?;
+            Some(format!(
+                "{}:{}",
+                location.path.display(),
+                location.start.line
+            ))
+        })
+        .collect();
+    let others = match others.split_last() {
+        None => return None,
+        Some((last, [])) => format!("the other occurrence is at {}", last),
+        Some((last, rest)) => format!(
+            "the other occurrences are at {} and {}",
+            rest.join(", "),
+            last
+        ),
+    };
+    Some(format!(
+        "this is {} occurrence {} of {} of an identical {}; {}",
+        side,
+        occurrence,
+        identical.len(),
+        kind,
+        others
+    ))
+}
+
+/// Returns a note with the column alignments and the first row text
+/// of the table containing the specified node.
+fn table_note(data: &CMarkData, node: &Arc<CMarkItem>, side: &str) -> Option<String> {
+    use crate::TableSummary;
+    use pulldown_cmark::{Event, Tag, TagEnd};
+
+    let nodes: Vec<_> = data.iter().cloned().collect();
+    let node_idx = nodes.iter().position(|other| Arc::ptr_eq(other, node))?;
+    let start = nodes[..=node_idx]
+        .iter()
+        .rposition(|node| matches!(node.event(), Some(Event::Start(Tag::Table(_)))))?;
+    let end = start
+        + nodes[start..]
+            .iter()
+            .position(|node| matches!(node.event(), Some(Event::End(TagEnd::Table))))?;
+    let Some(Event::Start(Tag::Table(alignments))) = nodes[start].event() else {
+        return None;
+    };
+    if node_idx > end {
+        return None;
+    }
+
+    let table = TableSummary::from_items(&nodes[start..=end]);
+    let first_row: Vec<_> = table.head.iter().map(|cell| cell.text.trim()).collect();
+    Some(std::format!(
+        "{} table column alignments are {}, the first row is `{}`",
+        side,
+        FmtPrint(Alignments(alignments)),
+        first_row.join(" | ")
+    ))
+}
+
+/// Returns a note if the readme and docs have the same emphasis and strong emphasis tags
+/// in the swapped nesting order, like `***text***` and `**_text_**`.
+fn emphasis_nesting_note(
+    readme_event: &pulldown_cmark::Event<'_>,
+    docs_event: &pulldown_cmark::Event<'_>,
+    readme_iter: &crate::CMarkDataIter<'_>,
+    docs_iter: &crate::CMarkDataIter<'_>,
+) -> Option<String> {
+    use pulldown_cmark::{Event, Tag, TagEnd};
+    use std::string::ToString;
+
+    // Returns whether the event is a start tag and whether it is a strong emphasis tag.
+    let kind = |event: &Event<'_>| match event {
+        Event::Start(Tag::Emphasis) => Some((true, false)),
+        Event::Start(Tag::Strong) => Some((true, true)),
+        Event::End(TagEnd::Emphasis) => Some((false, false)),
+        Event::End(TagEnd::Strong) => Some((false, true)),
+        _ => None,
+    };
+    let next_kind =
+        |iter: &crate::CMarkDataIter<'_>| iter.clone().find_map(|node| node.event()).and_then(kind);
+
+    let (is_start, is_strong) = kind(readme_event)?;
+    let is_swapped = kind(docs_event) == Some((is_start, !is_strong))
+        && next_kind(readme_iter) == Some((is_start, !is_strong))
+        && next_kind(docs_iter) == Some((is_start, is_strong));
+    if !is_swapped {
+        return None;
+    }
+    Some(
+        concat!(
+            "Possible issue: ",
+            "only the nesting order of strong emphasis and emphasis differs, ",
+            "`***text***` and `**_text_**` are rendered the same but parsed differently. ",
+            "Use `canonicalize_emphasis_nesting` for both readme and docs to ignore it.",
+        )
+        .to_string(),
+    )
+}
+
+/// Returns the character position of the first difference of the specified texts.
+pub(crate) fn first_difference(lhs: &str, rhs: &str) -> usize {
+    lhs.chars()
+        .zip(rhs.chars())
+        .take_while(|(lhs, rhs)| lhs == rhs)
+        .count()
+}
+
+/// Returns the text trimmed to at most `max_len` bytes around the specified byte offset
+/// with ellipses in place of the omitted text and the number of omitted bytes.
+pub(crate) fn excerpt(text: &str, focus: usize, max_len: usize) -> String {
+    use std::format;
+
+    let (start, end) = excerpt_window(text, focus, max_len);
+    if (start, end) == (0, text.len()) {
+        return String::from(text);
+    }
+    format!(
+        "{}{}... {} more bytes",
+        if start > 0 { "..." } else { "" },
+        &text[start..end],
+        text.len() - (end - start)
+    )
+}
+
+/// Returns the byte range of at most `max_len` bytes of the text around the specified offset.
+pub(crate) fn excerpt_window(text: &str, focus: usize, max_len: usize) -> (usize, usize) {
+    let floor_char_boundary = |mut offset: usize| {
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+
+    if text.len() <= max_len {
+        return (0, text.len());
+    }
+    let focus = focus.min(text.len());
+    let start = focus.saturating_sub(max_len / 2).min(text.len() - max_len);
+    let start = floor_char_boundary(start);
+    let end = floor_char_boundary(start + max_len);
+    (start, end)
+}
+
+/// Returns the text part between the specified character positions
+/// with ellipses in place of the omitted text.
+fn formatted_subslice(text: &str, start: usize, end: usize) -> String {
+    use std::format;
+
+    let len = text.chars().count();
+    let skip_before = start > 3;
+    let start = if skip_before { start } else { 0 };
+    let skip_after = len.saturating_sub(end) > 3;
+    let end = if skip_after { end } else { len };
+
+    format!(
+        "{}{}{}",
+        if skip_before { "..." } else { "" },
+        &text[byte_offset(text, start)..byte_offset(text, end.max(start))],
+        if skip_after { "..." } else { "" }
+    )
+}
+
+/// Returns the byte offset of the character at the specified position
+/// or the text length if the position is out of the text.
+pub(crate) fn byte_offset(text: &str, char_pos: usize) -> usize {
+    text.char_indices()
+        .nth(char_pos)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+pub(crate) fn get_event_start_tag<'a>(
+    event: &'a pulldown_cmark::Event<'_>,
+) -> Option<&'a pulldown_cmark::Tag<'a>> {
+    use pulldown_cmark::Event;
+
+    match event {
+        Event::Start(tag) => Some(tag),
+        _ => None,
+    }
+}
+
+fn get_event_end_tag<'a>(
+    event: &'a pulldown_cmark::Event<'_>,
+) -> Option<&'a pulldown_cmark::TagEnd> {
+    use pulldown_cmark::Event;
+
+    match event {
+        Event::End(tag) => Some(tag),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_event_text<'a>(event: &'a pulldown_cmark::Event<'_>) -> Option<&'a str> {
+    use pulldown_cmark::Event;
+
+    match event {
+        Event::Text(text) => Some(text),
+        Event::Code(text) => Some(text),
+        Event::Html(text) => Some(text),
+        Event::FootnoteReference(text) => Some(text),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_event_name<'a>(event: &pulldown_cmark::Event<'_>) -> &'a str {
+    use pulldown_cmark::Event;
+
+    match event {
+        Event::Start(..) => "Start",
+        Event::End(..) => "End",
+        Event::Text(..) => "Text",
+        Event::Code(..) => "Code",
+        Event::InlineMath(..) => "InlineMath",
+        Event::DisplayMath(..) => "DisplayMath",
+        Event::Html(..) => "Html",
+        Event::InlineHtml(..) => "InlineHtml",
+        Event::FootnoteReference(..) => "FootnoteReference",
+        Event::SoftBreak => "SoftBreak",
+        Event::HardBreak => "HardBreak",
+        Event::Rule => "Rule",
+        Event::TaskListMarker(..) => "TaskListMarker",
+    }
+}
+
+pub(crate) fn get_start_tag_name<'a>(tag: &'a pulldown_cmark::Tag<'_>) -> &'a str {
+    use pulldown_cmark::Tag;
+
+    match tag {
+        Tag::Paragraph => "Paragraph",
+        Tag::Heading { .. } => "Heading",
+        Tag::BlockQuote(kind) => blockquote_tag_name(*kind),
+        Tag::CodeBlock(..) => "CodeBlock",
+        Tag::HtmlBlock { .. } => "HtmlBlock",
+        Tag::List(..) => "List",
+        Tag::Item => "Item",
+        Tag::FootnoteDefinition(..) => "FootnoteDefinition",
+        Tag::DefinitionList => "DefinitionList",
+        Tag::DefinitionListTitle => "DefinitionListTitle",
+        Tag::DefinitionListDefinition => "DefinitionListDefinition",
+        Tag::Table(..) => "Table",
+        Tag::TableHead => "TableHead",
+        Tag::TableRow => "TableRow",
+        Tag::TableCell => "TableCell",
+        Tag::Emphasis => "Emphasis",
+        Tag::Strong => "Strong",
+        Tag::Strikethrough => "Strikethrough",
+        Tag::Link { .. } => "Link",
+        Tag::Image { .. } => "Image",
+        Tag::MetadataBlock(..) => "MetadataBlock",
+    }
+}
+
+fn blockquote_tag_name(kind: Option<pulldown_cmark::BlockQuoteKind>) -> &'static str {
+    use pulldown_cmark::BlockQuoteKind;
+
+    match kind {
+        None => "BlockQuote",
+        Some(BlockQuoteKind::Note) => "BlockQuote(Note)",
+        Some(BlockQuoteKind::Tip) => "BlockQuote(Tip)",
+        Some(BlockQuoteKind::Important) => "BlockQuote(Important)",
+        Some(BlockQuoteKind::Warning) => "BlockQuote(Warning)",
+        Some(BlockQuoteKind::Caution) => "BlockQuote(Caution)",
+    }
+}
+
+fn get_end_tag_name(tag: &pulldown_cmark::TagEnd) -> &str {
+    use pulldown_cmark::TagEnd;
+
+    match tag {
+        TagEnd::Paragraph => "Paragraph",
+        TagEnd::Heading { .. } => "Heading",
+        TagEnd::BlockQuote(kind) => blockquote_tag_name(*kind),
+        TagEnd::CodeBlock => "CodeBlock",
+        TagEnd::HtmlBlock => "HtmlBlock",
+        TagEnd::List(..) => "List",
+        TagEnd::Item => "Item",
+        TagEnd::FootnoteDefinition => "FootnoteDefinition",
+        TagEnd::DefinitionList => "DefinitionList",
+        TagEnd::DefinitionListTitle => "DefinitionListTitle",
+        TagEnd::DefinitionListDefinition => "DefinitionListDefinition",
+        TagEnd::Table => "Table",
+        TagEnd::TableHead => "TableHead",
+        TagEnd::TableRow => "TableRow",
+        TagEnd::TableCell => "TableCell",
+        TagEnd::Emphasis => "Emphasis",
+        TagEnd::Strong => "Strong",
+        TagEnd::Strikethrough => "Strikethrough",
+        TagEnd::Link => "Link",
+        TagEnd::Image => "Image",
+        TagEnd::MetadataBlock(..) => "MetadataBlock",
+    }
+}
+
+pub trait Print {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result;
+}
+
+#[derive(Clone, Debug)]
+pub struct FmtPrint<T>(T);
+
+impl<T> Display for FmtPrint<T>
+where
+    T: Print,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        T::fmt(&self.0, f)
+    }
+}
+
+impl<T> Print for &T
+where
+    T: ?Sized + Print,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T> Print for Option<T>
+where
+    T: Print,
+{
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Some(value) => write!(fmt, "Some({})", FmtPrint(value)),
+            None => write!(fmt, "None"),
+        }
+    }
+}
+
+impl<T> Print for [T]
+where
+    T: Print,
+{
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut iter = self.iter();
+        writeln!(fmt, "[")?;
+        if let Some(first) = iter.next() {
+            writeln!(fmt, "{}", FmtPrint(first))?;
+            for item in iter {
+                writeln!(fmt, ", {}", FmtPrint(item))?;
+            }
+        }
+        writeln!(fmt, "]")?;
+        Ok(())
+    }
+}
+
+impl Print for str {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{}", self)
+    }
+}
+
+impl Print for CowStr<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{}", self)
+    }
+}
+
+impl<T1, T2> Print for (T1, T2)
+where
+    T1: Print,
+    T2: Print,
+{
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "({}, {})", FmtPrint(&self.0), FmtPrint(&self.1))
+    }
+}
+
+impl Print for pulldown_cmark::Event<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::Event;
+
+        let event_name = get_event_name(self);
+        write!(fmt, "{}", event_name)?;
+
+        match self {
+            Event::Start(tag) => write!(fmt, "({})", FmtPrint(tag)),
+            Event::End(tag) => write!(fmt, "({})", FmtPrint(tag)),
+            Event::Text(text)
+            | Event::Code(text)
+            | Event::InlineMath(text)
+            | Event::DisplayMath(text)
+            | Event::Html(text)
+            | Event::InlineHtml(text)
+            | Event::FootnoteReference(text) => write!(fmt, "(\"{}\")", &text),
+            Event::SoftBreak => Ok(()),
+            Event::HardBreak => Ok(()),
+            Event::Rule => Ok(()),
+            Event::TaskListMarker(ch) => write!(fmt, "({})", ch),
+        }
+    }
+}
+
+impl Print for pulldown_cmark::Tag<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::{MetadataBlockKind, Tag};
+
+        let tag_name = get_start_tag_name(self);
+        write!(fmt, "{}", tag_name)?;
+
+        match self {
+            Tag::Paragraph => Ok(()),
+            Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            } => write!(
+                fmt,
+                "({}, \"{}\", {}, {})",
+                level,
+                FmtPrint(id.as_deref()),
+                FmtPrint(classes.as_slice()),
+                FmtPrint(attrs.as_slice())
+            ),
+            Tag::BlockQuote(_) => Ok(()),
+            Tag::CodeBlock(kind) => write!(fmt, "({})", FmtPrint(kind)),
+            Tag::HtmlBlock => Ok(()),
+            Tag::List(Some(first)) => write!(fmt, "(Some({}))", first),
+            Tag::List(None) => write!(fmt, "(None)"),
+            Tag::Item => Ok(()),
+            Tag::FootnoteDefinition(label) => write!(fmt, "(\"{}\")", &label),
+            Tag::DefinitionList => Ok(()),
+            Tag::DefinitionListTitle => Ok(()),
+            Tag::DefinitionListDefinition => Ok(()),
+            Tag::Table(alignment) => write!(fmt, "{}", FmtPrint(Alignments(alignment))),
+            Tag::TableHead => Ok(()),
+            Tag::TableRow => Ok(()),
+            Tag::TableCell => Ok(()),
+            Tag::Emphasis => Ok(()),
+            Tag::Strong => Ok(()),
+            Tag::Strikethrough => Ok(()),
+            Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            } => {
+                write!(
+                    fmt,
+                    "({}, \"{}\", \"{}\", \"{}\")",
+                    FmtPrint(link_type),
+                    dest_url,
+                    title,
+                    id
+                )
+            }
+            Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            } => {
+                write!(
+                    fmt,
+                    "({}, \"{}\", \"{}\", \"{}\")",
+                    FmtPrint(link_type),
+                    dest_url,
+                    title,
+                    id
+                )
+            }
+            Tag::MetadataBlock(MetadataBlockKind::YamlStyle) => {
+                write!(fmt, "(\"yaml style\")")
+            }
+            Tag::MetadataBlock(MetadataBlockKind::PlusesStyle) => {
+                write!(fmt, "(\"pluses style\")")
+            }
+        }
+    }
+}
+
+impl Print for pulldown_cmark::TagEnd {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::{MetadataBlockKind, TagEnd};
+
+        let tag_name = get_end_tag_name(self);
+        write!(fmt, "/{}", tag_name)?;
+
+        match self {
+            TagEnd::Paragraph => Ok(()),
+            TagEnd::Heading(level) => write!(fmt, "({})", level),
+            TagEnd::BlockQuote(_) => Ok(()),
+            TagEnd::CodeBlock => Ok(()),
+            TagEnd::HtmlBlock => Ok(()),
+            TagEnd::List(is_ordered) => write!(fmt, "({})", &is_ordered),
+            TagEnd::Item => Ok(()),
+            TagEnd::FootnoteDefinition => Ok(()),
+            TagEnd::DefinitionList => Ok(()),
+            TagEnd::DefinitionListTitle => Ok(()),
+            TagEnd::DefinitionListDefinition => Ok(()),
+            TagEnd::Table => Ok(()),
+            TagEnd::TableHead => Ok(()),
+            TagEnd::TableRow => Ok(()),
+            TagEnd::TableCell => Ok(()),
+            TagEnd::Emphasis => Ok(()),
+            TagEnd::Strong => Ok(()),
+            TagEnd::Strikethrough => Ok(()),
+            TagEnd::Link => Ok(()),
+            TagEnd::Image => Ok(()),
+            TagEnd::MetadataBlock(MetadataBlockKind::YamlStyle) => {
+                write!(fmt, "(\"yaml style\")")
+            }
+            TagEnd::MetadataBlock(MetadataBlockKind::PlusesStyle) => {
+                write!(fmt, "(\"pluses style\")")
+            }
+        }
+    }
+}
+
+impl Print for pulldown_cmark::CodeBlockKind<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::CodeBlockKind;
+
+        match self {
+            CodeBlockKind::Indented => write!(fmt, "Indented"),
+            CodeBlockKind::Fenced(tag) => write!(fmt, "Fenced({})", tag),
+        }
+    }
+}
+
+/// Table column alignments printed in a single line.
+struct Alignments<'a>(&'a [pulldown_cmark::Alignment]);
+
+impl Print for Alignments<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "(")?;
+        for (idx, alignment) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(fmt, ", ")?;
+            }
+            write!(fmt, "{}", FmtPrint(alignment))?;
+        }
+        write!(fmt, ")")
+    }
+}
+
+impl Print for pulldown_cmark::Alignment {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::Alignment;
+
+        match self {
+            Alignment::None => write!(fmt, "None"),
+            Alignment::Left => write!(fmt, "Left"),
+            Alignment::Center => write!(fmt, "Center"),
+            Alignment::Right => write!(fmt, "Right"),
+        }
+    }
+}
+
+impl Print for pulldown_cmark::LinkType {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::LinkType;
+
+        match self {
+            LinkType::Inline => write!(fmt, "Inline"),
+            LinkType::Reference => write!(fmt, "Reference"),
+            LinkType::ReferenceUnknown => write!(fmt, "ReferenceUnknown"),
+            LinkType::Collapsed => write!(fmt, "Collapsed"),
+            LinkType::CollapsedUnknown => write!(fmt, "CollapsedUnknown"),
+            LinkType::Shortcut => write!(fmt, "Shortcut"),
+            LinkType::ShortcutUnknown => write!(fmt, "ShortcutUnknown"),
+            LinkType::Autolink => write!(fmt, "Autolink"),
+            LinkType::Email => write!(fmt, "Email"),
+        }
+    }
+}
+
+impl Print for pulldown_cmark::BlockQuoteKind {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use pulldown_cmark::BlockQuoteKind;
+
+        match self {
+            BlockQuoteKind::Note => write!(fmt, "Note"),
+            BlockQuoteKind::Tip => write!(fmt, "Tip"),
+            BlockQuoteKind::Important => write!(fmt, "Important"),
+            BlockQuoteKind::Warning => write!(fmt, "Warning"),
+            BlockQuoteKind::Caution => write!(fmt, "Caution"),
+        }
+    }
+}
+
+#[test]
+fn test_formatted_subslice() {
+    assert_eq!(formatted_subslice("abcdef", 0, 3), "abcdef");
+    assert_eq!(formatted_subslice("abcdefghij", 5, 6), "...f...");
+    assert_eq!(formatted_subslice("ab—cdef—gh", 5, 6), "...e...");
+    assert_eq!(formatted_subslice("😀😀😀😀😀😀😀😀😀😀", 4, 5), "...😀...");
+    assert_eq!(formatted_subslice("😀😀😀😀😀😀😀😀", 4, 5), "...😀😀😀😀");
+    assert_eq!(formatted_subslice("漢字漢字漢字漢字", 4, 20), "...漢字漢字");
+    assert_eq!(
+        formatted_subslice("e\u{301}e\u{301}e\u{301}", 1, 40),
+        "e\u{301}e\u{301}e\u{301}"
+    );
+    assert_eq!(formatted_subslice("abc", 10, 20), "...");
+}
+
+#[test]
+fn test_event_diff_notes_multibyte() {
+    use pulldown_cmark::Event;
+
+    let messages = |readme: &str, docs: &str| {
+        let readme = Event::Text(readme.to_owned().into());
+        let docs = Event::Text(docs.to_owned().into());
+        event_diff_notes(&readme, &docs, "text", 240)
+    };
+
+    let long = "Lorem ipsum dolor sit amet — consectetur adipiscing elit";
+    assert_eq!(
+        messages(
+            long,
+            "Lorem ipsum dolor sit amet - consectetur adipiscing elit"
+        ),
+        [
+            "readme and docs node texts differ at character 28",
+            "readme node text part: \"Lorem ipsum dolor sit amet — consectetur adipiscing elit\"",
+            "docs   node text part: \"Lorem ipsum dolor sit amet - consectetur adipiscing elit\"",
+        ]
+    );
+
+    let prefix = "😀".repeat(40);
+    let readme = std::format!("{}漢字{}", prefix, "x".repeat(40));
+    let docs = std::format!("{}漢e\u{301}{}", prefix, "x".repeat(40));
+    let notes = messages(&readme, &docs);
+    assert_eq!(
+        notes[0],
+        "readme and docs node texts differ at character 42"
+    );
+    assert_eq!(
+        notes[1],
+        std::format!(
+            "readme node text part: \"...{}漢字{}...\"",
+            "😀".repeat(31),
+            "x".repeat(31)
+        )
+    );
+    assert_eq!(
+        notes[2],
+        std::format!(
+            "docs   node text part: \"...{}漢e\u{301}{}...\"",
+            "😀".repeat(31),
+            "x".repeat(30)
+        )
+    );
+}
+
+#[test]
+fn test_emphasis_nesting_note() {
+    use crate::test_utils::readme_data;
+
+    let has_note = |readme: &str, docs: &str| {
+        find_mismatch(&readme_data(readme), &readme_data(docs))
+            .unwrap()
+            .notes
+            .iter()
+            .any(|note| note.contains("canonicalize_emphasis_nesting"))
+    };
+
+    assert!(has_note("***text***\n", "**_text_**\n"));
+    assert!(has_note("Some ***text***.\n", "Some **_text_**.\n"));
+    assert!(!has_note("**text**\n", "*text*\n"));
+    assert!(!has_note("**text**\n", "text\n"));
+    assert!(!has_note("***text***\n", "**text**\n"));
+}
+
+#[test]
+fn test_compare_sync() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        readme_data("# Title\n\nFirst text\n"),
+        (),
+        (),
+    );
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        readme_data("# Title\n\nOther text\n"),
+        (),
+        (),
+    );
+    assert!(compare_sync(&readme, &readme_as_docs(&readme)).is_ok());
+
+    let mismatch = compare_sync(&readme, &docs).unwrap_err();
+    assert_eq!(mismatch.matched_count, 4);
+    assert_eq!(mismatch.previous_events.len(), 4);
+    let report = mismatch.to_string();
+    assert!(report.starts_with("error: readme node\n`Text(\"First text\")`"));
+    assert!(report.contains("  --> README.md:3:1\n"));
+    assert!(report.contains("note: readme and docs node texts differ at character 1\n"));
+
+    let docs = readme_data("# Title\n\n## Usage\n\nWith std.\n\n## Usage\n\nWithout std.\n");
+    let mismatch = find_mismatch(&readme_data("# Title\n\n## Usage\n\nText.\n"), &docs).unwrap();
+    assert!(mismatch.notes.iter().any(|note| note.contains(
+        "docs contain 2 `Usage` headings, they may be built from conflicting `cfg_attr` branches."
+    )));
+    let mismatch = find_mismatch(
+        &readme_data("![Logo](logo.png)\n"),
+        &readme_data("![Logo image](logo.png)\n"),
+    );
+    assert_eq!(
+        mismatch.unwrap().notes[0],
+        "readme and docs node image alt texts differ at character 5"
+    );
+
+    let has_indentation_note = |readme: &str, docs: &str| {
+        find_mismatch(&readme_data(readme), &readme_data(docs))
+            .unwrap()
+            .notes
+            .iter()
+            .any(|note| note.contains("Indented code blocks in list items"))
+    };
+    let readme = "- Item:\n\n      let a = 1;\n";
+    assert!(has_indentation_note(readme, "- Item:\n\n  let a = 1;\n"));
+    assert!(has_indentation_note("- Item:\n\n  let a = 1;\n", readme));
+    assert!(!has_indentation_note(
+        readme,
+        "- Item:\n\n      let b = 2;\n"
+    ));
+    assert!(!has_indentation_note(
+        "Text.\n\n    code\n",
+        "Text.\n\ncode\n"
+    ));
+}
+
+#[cfg(test)]
+fn readme_as_docs<P, M>(readme: &CMarkReadme<P, M>) -> CMarkDocs<(), ()> {
+    CMarkDocs::from_data_chunks_package_pach_and_manifest(readme.data().clone(), (), ())
+}
+
+#[test]
+fn test_duplicated_block_note() {
+    use crate::File;
+    use std::path::PathBuf;
+
+    let data = |path: &str, text: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(path),
+            text.into(),
+        )))
+    };
+    let code = "```rust\nuse foo::Bar;\n```\n\n";
+    let readme = data(
+        "README.md",
+        &[code, "First.\n\n", code, "Second.\n\n", code].concat(),
+    );
+    let docs = data(
+        "lib.rs",
+        &[
+            code,
+            "First.\n\n",
+            code,
+            "Second.\n\n",
+            "```rust\nuse foo::Baz;\n```\n",
+        ]
+        .concat(),
+    );
+    let mismatch = find_mismatch(&readme, &docs).unwrap();
+    assert!(
+        mismatch.notes.contains(&String::from(
+            "this is readme occurrence 3 of 3 of an identical code block; \
+             the other occurrences are at README.md:1 and README.md:7"
+        )),
+        "{:?}",
+        mismatch.notes
+    );
+    assert!(mismatch
+        .notes
+        .iter()
+        .all(|note| !note.contains("this is docs")));
+
+    let readme = data("README.md", "Same.\n\nOther.\n\nSame.\n");
+    let docs = data("lib.rs", "Same.\n\nOther.\n\nSame text.\n");
+    let mismatch = find_mismatch(&readme, &docs).unwrap();
+    assert!(
+        mismatch.notes.contains(&String::from(
+            "this is readme occurrence 2 of 2 of an identical paragraph; \
+             the other occurrence is at README.md:1"
+        )),
+        "{:?}",
+        mismatch.notes
+    );
+
+    let readme = data("README.md", "Same.\n\nOther.\n");
+    let docs = data("lib.rs", "Same.\n\nOther text.\n");
+    let mismatch = find_mismatch(&readme, &docs).unwrap();
+    assert!(mismatch
+        .notes
+        .iter()
+        .all(|note| !note.contains("occurrence")));
+}
+
+#[test]
+fn test_table_note() {
+    use crate::File;
+    use std::path::PathBuf;
+
+    let data = |path: &str, text: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(path),
+            text.into(),
+        )))
+    };
+    let readme = data(
+        "README.md",
+        "Text.\n\n| Feature | `std` |\n|:--|:-:|\n| codemap | yes |\n",
+    );
+    let docs = data(
+        "lib.rs",
+        "Text.\n\n| Feature | `std` |\n|:--|:-:|\n| codemap | no |\n",
+    );
+    let mismatch = find_mismatch(&readme, &docs).unwrap();
+    assert!(mismatch.notes.contains(&String::from(
+        "readme table column alignments are (Left, Center), the first row is `Feature | std`"
+    )));
+    assert!(mismatch.notes.contains(&String::from(
+        "docs table column alignments are (Left, Center), the first row is `Feature | std`"
+    )));
+
+    let mismatch = find_mismatch(&data("README.md", "Text.\n"), &data("lib.rs", "Other.\n"));
+    assert!(!mismatch
+        .unwrap()
+        .notes
+        .iter()
+        .any(|note| note.contains("table column alignments")));
+}
+
+#[test]
+fn test_event_diff_notes_github_alerts() {
+    use pulldown_cmark::{BlockQuoteKind, Event, Tag};
+
+    let notes = event_diff_notes(
+        &Event::Start(Tag::BlockQuote(Some(BlockQuoteKind::Warning))),
+        &Event::Start(Tag::BlockQuote(None)),
+        "text",
+        240,
+    );
+    assert_eq!(
+        notes[0],
+        "readme node event start tag name is \"BlockQuote(Warning)\""
+    );
+    assert_eq!(
+        notes[1],
+        "docs   node event start tag name is \"BlockQuote\""
+    );
+    assert!(notes[2].contains("normalize_github_alerts"));
+}
+
+#[test]
+fn test_find_mismatch_with_options_excerpt_len() {
+    use crate::test_utils::readme_data;
+
+    let readme = readme_data("![alt](https://example.com/first-image.png)\n");
+    let docs = readme_data("![alt](https://example.com/second-image.png)\n");
+    let notes = |max_excerpt_len| {
+        let options = EmitOptions {
+            max_excerpt_len,
+            ..EmitOptions::default()
+        };
+        find_mismatch_with_options(&readme, &docs, &options)
+            .unwrap()
+            .notes
+    };
+    assert!(notes(240)[0].ends_with("first-image.png\", \"\", \"\"))"));
+    assert_eq!(
+        notes(16),
+        [
+            "readme node: Sta... 64 more bytes",
+            "docs   node: Sta... 65 more bytes"
+        ]
+    );
+}
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::vec::Vec;
 
 use thiserror::Error;
 
@@ -11,6 +12,9 @@ pub struct DocsItem {
     pub text: Cow<'static, str>,
     /// Source file span.
     pub span: Option<DocsSpan>,
+    /// Source file spans of the `cfg_attr` predicates the chunk is built under,
+    /// from the outermost to the innermost.
+    pub cfg_predicates: Vec<DocsSpan>,
 }
 
 /// Documentation text chunk span start and end.
@@ -35,7 +39,11 @@ impl From<&syn::LitStr> for DocsItem {
     fn from(lit_str: &syn::LitStr) -> Self {
         let text = Cow::from(lit_str.value());
         let span = Some(DocsSpan::from(lit_str.span()));
-        Self { text, span }
+        Self {
+            text,
+            span,
+            cfg_predicates: Vec::new(),
+        }
     }
 }
 
@@ -44,6 +52,7 @@ impl From<&'static str> for DocsItem {
         Self {
             text: Cow::from(text),
             span: None,
+            cfg_predicates: Vec::new(),
         }
     }
 }
@@ -66,6 +75,7 @@ impl From<proc_macro2::Span> for DocsSpan {
 }
 
 /// Builds documentation from the specified attribute.
+#[allow(clippy::result_large_err)]
 pub fn build_attr_docs(
     attr: &syn::Attribute,
     config: &Config<'_>,
@@ -74,12 +84,11 @@ pub fn build_attr_docs(
 }
 
 /// Builds documentation from the specified compile-time structured attribute.
+#[allow(clippy::result_large_err)]
 pub fn build_meta_docs(
     meta: &syn::Meta,
     config: &Config<'_>,
 ) -> Result<impl Iterator<Item = DocsItem>, BuildMetaDocsError> {
-    use std::vec::Vec;
-
     if meta.path().is_ident("doc") {
         match meta {
             syn::Meta::NameValue(syn::MetaNameValue { value, .. }) => match value {
@@ -87,7 +96,14 @@ pub fn build_meta_docs(
                     lit: syn::Lit::Str(lit_str),
                     attrs,
                 }) if attrs.is_empty() => {
-                    Ok(std::vec![DocsItem::from(lit_str), DocsItem::from("\n")].into_iter())
+                    // Rustdoc terminates every doc fragment line with a newline,
+                    // so the newline is added only if the fragment does not end with it.
+                    let item = DocsItem::from(lit_str);
+                    if item.text.ends_with('\n') {
+                        Ok(std::vec![item].into_iter())
+                    } else {
+                        Ok(std::vec![item, DocsItem::from("\n")].into_iter())
+                    }
                 }
                 _ => Err(BuildMetaDocsError::NonStringDocInput(meta.clone())),
             },
@@ -111,13 +127,17 @@ pub fn build_meta_docs(
 
                 let predicate_result = eval_cfg_predicate(&predicate, config)?;
                 if predicate_result {
+                    let predicate_span = DocsSpan::from(syn::spanned::Spanned::span(&predicate));
                     let doc: Result<Vec<DocsItem>, BuildMetaDocsError> = it
                         .map(|nested_meta| build_meta_docs(&nested_meta, config))
                         .try_fold(Vec::new(), |mut acc, doc| {
                             acc.extend(doc?);
                             Ok(acc)
                         });
-                    let doc = doc?;
+                    let mut doc = doc?;
+                    for item in &mut doc {
+                        item.cfg_predicates.insert(0, predicate_span);
+                    }
                     Ok(doc.into_iter())
                 } else {
                     Ok(Vec::new().into_iter())
@@ -130,7 +150,69 @@ pub fn build_meta_docs(
     }
 }
 
+/// Returns the predicate spans of the `cfg_attr` attributes evaluated to false
+/// and the numbers of documentation lines excluded by them.
+///
+/// Invalid attributes are ignored since they are reported by `build_meta_docs`.
+pub(crate) fn skipped_meta_docs(meta: &syn::Meta, config: &Config<'_>) -> Vec<(DocsSpan, usize)> {
+    let (predicate, nested) = match cfg_attr_args(meta) {
+        Some(args) => args,
+        None => return Vec::new(),
+    };
+    match eval_cfg_predicate(&predicate, config) {
+        Ok(true) => nested
+            .iter()
+            .flat_map(|meta| skipped_meta_docs(meta, config))
+            .collect(),
+        Ok(false) => {
+            let lines = nested.iter().map(doc_lines_count).sum();
+            if lines > 0 {
+                let span = DocsSpan::from(syn::spanned::Spanned::span(&predicate));
+                std::vec![(span, lines)]
+            } else {
+                Vec::new()
+            }
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Returns the number of documentation lines of the attribute regardless of `cfg_attr` predicates.
+fn doc_lines_count(meta: &syn::Meta) -> usize {
+    match meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                }),
+            ..
+        }) if meta.path().is_ident("doc") => lit_str.value().lines().count().max(1),
+        _ => match cfg_attr_args(meta) {
+            Some((_, nested)) => nested.iter().map(doc_lines_count).sum(),
+            None => 0,
+        },
+    }
+}
+
+/// Returns the predicate and the attributes of the `cfg_attr` attribute.
+fn cfg_attr_args(meta: &syn::Meta) -> Option<(syn::Meta, Vec<syn::Meta>)> {
+    match meta {
+        syn::Meta::List(meta_list) if meta.path().is_ident("cfg_attr") => {
+            let mut args = meta_list
+                .parse_args::<PunctuatedMetaArgs>()
+                .ok()?
+                .0
+                .into_iter();
+            let predicate = args.next()?;
+            Some((predicate, args.collect()))
+        }
+        _ => None,
+    }
+}
+
 /// Evaluates configuration predicate.
+#[allow(clippy::result_large_err)]
 pub fn eval_cfg_predicate(
     meta: &syn::Meta,
     config: &Config<'_>,
@@ -207,6 +289,7 @@ impl syn::parse::Parse for PunctuatedMetaArgs {
 
 /// An error which can occur when building documentation from attribute.
 #[derive(Clone, Debug, Error)]
+#[allow(clippy::large_enum_variant)]
 pub enum BuildAttrDocsError {
     /// Attribute parser error.
     #[error("Attribute parser error: {0}")]
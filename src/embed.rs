@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::{CMarkDocs, CMarkReadme, Config, File, FileDocsFromFileError};
+
+/// Readme and docs created from the texts embedded at compile time.
+pub type EmbeddedReadmeAndDocs = (CMarkReadme<(), ()>, CMarkDocs<(), ()>);
+
+/// Creates readme and docs from the readme and docs source file paths and texts.
+///
+/// The paths are used only to label the texts in diagnostic messages.
+/// It is used by the `embed` macro.
+#[allow(clippy::result_large_err)]
+pub fn readme_and_docs_from_texts(
+    readme_path: &str,
+    readme_text: &str,
+    docs_path: &str,
+    docs_text: &str,
+    config: &Config<'_>,
+) -> Result<EmbeddedReadmeAndDocs, FileDocsFromFileError> {
+    let readme = CMarkReadme::from_file(Arc::new(File::from_path_and_text(
+        PathBuf::from(readme_path),
+        readme_text.into(),
+    )));
+    let docs = CMarkDocs::from_file_and_config(
+        Arc::new(File::from_path_and_text(
+            PathBuf::from(docs_path),
+            docs_text.into(),
+        )),
+        config,
+    )?;
+    Ok((readme, docs))
+}
+
+/// Embeds the readme and the docs source file at compile time
+/// and creates `CMarkReadme<(), ()>` and `CMarkDocs<(), ()>` from them.
+///
+/// It is useful when the tests can not read the package files,
+/// like in sandboxed CI environments that deny filesystem reads outside the target directory.
+/// The files are read by the compiler with `include_str!`
+/// relative to the `CARGO_MANIFEST_DIR` of the crate calling the macro,
+/// and diagnostic messages refer to them by these relative paths.
+///
+/// The macro accepts an optional `Config` expression used to parse the docs,
+/// optionally preceded by the readme and docs file paths,
+/// `README.md` and `src/lib.rs` by default.
+/// It returns `Result<EmbeddedReadmeAndDocs, FileDocsFromFileError>`.
+///
+/// This macro requires non-default feature `macros`.
+///
+/// ```rust
+/// use readme_sync::{embed, Config};
+///
+/// let (readme, docs) = embed!("README.md", "src/lib.rs", Config::new()).unwrap();
+/// let readme = readme.remove_badges_paragraph();
+/// let docs = docs.increment_heading_levels().add_title("readme-sync");
+/// # let _ = (readme, docs);
+/// ```
+#[macro_export]
+macro_rules! embed {
+    () => {
+        $crate::embed!("README.md", "src/lib.rs", $crate::Config::new())
+    };
+    ($readme:literal, $docs:literal) => {
+        $crate::embed!($readme, $docs, $crate::Config::new())
+    };
+    ($readme:literal, $docs:literal, $config:expr) => {
+        $crate::readme_and_docs_from_texts(
+            $readme,
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $readme)),
+            $docs,
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $docs)),
+            &$config,
+        )
+    };
+    ($config:expr) => {
+        $crate::embed!("README.md", "src/lib.rs", $config)
+    };
+}
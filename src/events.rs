@@ -0,0 +1,142 @@
+//! Builders of balanced Markdown events for synthetic content.
+//!
+//! Every function returns `Created` items with the specified note,
+//! with all start events closed by the corresponding end events.
+
+use std::borrow::Cow;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, Tag};
+
+use crate::CMarkItem;
+
+/// Creates a heading with the specified level and text.
+pub fn heading(
+    level: HeadingLevel,
+    text: &str,
+    note: impl Into<Cow<'static, str>>,
+) -> Vec<Arc<CMarkItem>> {
+    let tag = Tag::Heading {
+        level,
+        id: None,
+        classes: Vec::new(),
+        attrs: Vec::new(),
+    };
+    let note = note.into();
+    let inner = text_items(text, note.clone());
+    tagged(tag, inner, note)
+}
+
+/// Creates a paragraph with the specified text.
+pub fn paragraph(text: &str, note: impl Into<Cow<'static, str>>) -> Vec<Arc<CMarkItem>> {
+    let note = note.into();
+    let inner = text_items(text, note.clone());
+    tagged(Tag::Paragraph, inner, note)
+}
+
+/// Creates a fenced code block with the specified info string and code.
+///
+/// The trailing newline is added to the code if it is missing, as the Markdown parser does.
+pub fn codeblock(tag: &str, text: &str, note: impl Into<Cow<'static, str>>) -> Vec<Arc<CMarkItem>> {
+    let note = note.into();
+    let mut text = String::from(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    let tag = Tag::CodeBlock(CodeBlockKind::Fenced(boxed(tag)));
+    let inner = text_items(&text, note.clone());
+    tagged(tag, inner, note)
+}
+
+/// Creates an inline link with the specified text and url.
+///
+/// The link is an inline element, so it should be placed inside of a block,
+/// for example inside of a paragraph.
+pub fn link(text: &str, url: &str, note: impl Into<Cow<'static, str>>) -> Vec<Arc<CMarkItem>> {
+    let note = note.into();
+    let tag = Tag::Link {
+        link_type: LinkType::Inline,
+        dest_url: boxed(url),
+        title: CowStr::Borrowed(""),
+        id: CowStr::Borrowed(""),
+    };
+    let inner = text_items(text, note.clone());
+    tagged(tag, inner, note)
+}
+
+/// Creates a tight bullet list with the specified item texts.
+pub fn list(items: &[&str], note: impl Into<Cow<'static, str>>) -> Vec<Arc<CMarkItem>> {
+    let note = note.into();
+    let inner = items
+        .iter()
+        .flat_map(|text| tagged(Tag::Item, text_items(text, note.clone()), note.clone()))
+        .collect();
+    tagged(Tag::List(None), inner, note)
+}
+
+fn tagged(
+    tag: Tag<'static>,
+    inner: Vec<Arc<CMarkItem>>,
+    note: Cow<'static, str>,
+) -> Vec<Arc<CMarkItem>> {
+    let end = tag.to_end();
+    let mut items = Vec::with_capacity(inner.len() + 2);
+    items.push(CMarkItem::new(Event::Start(tag), note.clone()));
+    items.extend(inner);
+    items.push(CMarkItem::new(Event::End(end), note));
+    items
+}
+
+fn text_items(text: &str, note: Cow<'static, str>) -> Vec<Arc<CMarkItem>> {
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        std::vec![CMarkItem::new(Event::Text(boxed(text)), note)]
+    }
+}
+
+fn boxed(text: &str) -> CowStr<'static> {
+    CowStr::Boxed(String::from(text).into_boxed_str())
+}
+
+#[test]
+fn test_events_builders() {
+    use crate::test_utils::readme_file;
+    use crate::{check_sync, CMarkData, CMarkDocs, CMarkReadme};
+
+    let check = |items: Vec<Arc<CMarkItem>>, text: &str| {
+        let data = CMarkData::from_items(items);
+        assert_eq!(data.validate_structure(), Ok(()));
+
+        let readme = CMarkReadme::from_file(readme_file(text));
+        let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(data, (), ());
+        assert!(check_sync(&readme, &docs).is_ok(), "{:?}", text);
+    };
+
+    check(heading(HeadingLevel::H2, "Usage", "test"), "## Usage\n");
+    check(paragraph("Some text.", "test"), "Some text.\n");
+    check(
+        codeblock("rust,no_run", "fn main() {}", "test"),
+        "```rust,no_run\nfn main() {}\n```\n",
+    );
+    check(codeblock("", "", "test"), "```\n```\n");
+    check(list(&["first", "second"], "test"), "- first\n- second\n");
+
+    let mut items = paragraph("See ", "test");
+    let _ = items.splice(2..2, link("docs", "https://docs.rs/", "test"));
+    check(items, "See [docs](https://docs.rs/)\n");
+
+    let data = CMarkData::from_items(paragraph("Text", "test"))
+        .add_title("Title")
+        .push_section(
+            HeadingLevel::H2,
+            "Section",
+            CMarkData::from_items(list(&["item"], "test")),
+        );
+    check(
+        data.into_items(),
+        "# Title\n\nText\n\n## Section\n\n- item\n",
+    );
+}
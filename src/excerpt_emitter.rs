@@ -0,0 +1,341 @@
+use core::fmt::{self, Write};
+use core::ops::Range;
+use std::string::{String, ToString};
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::{CMarkSpan, LineCol, TextSource};
+
+/// A diagnostic message level, printed like in `codemap_diagnostic`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Level {
+    Error,
+    Note,
+}
+
+/// A source label style, printed like in `codemap_diagnostic`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum SpanStyle {
+    Primary,
+    Secondary,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A diagnostic message which source excerpts are formatted directly from the file texts.
+///
+/// Unlike `codemap_diagnostic::Diagnostic` it does not require copying whole files into a codemap.
+#[derive(Clone, Debug)]
+pub(crate) struct ExcerptDiagnostic {
+    pub level: Level,
+    pub message: String,
+    pub labels: Vec<ExcerptLabel>,
+}
+
+/// A source range highlighted by an `ExcerptDiagnostic`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ExcerptLabel {
+    pub text_source: TextSource,
+    pub range: Range<usize>,
+    pub style: SpanStyle,
+}
+
+impl ExcerptDiagnostic {
+    /// Creates a diagnostic without source labels.
+    pub fn note(message: String) -> Self {
+        Self {
+            level: Level::Note,
+            message,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Returns the primary label or the first label if there are no primary ones.
+    pub fn main_label(&self) -> Option<&ExcerptLabel> {
+        self.labels
+            .iter()
+            .find(|label| label.style == SpanStyle::Primary)
+            .or_else(|| self.labels.first())
+    }
+}
+
+impl ExcerptLabel {
+    /// Returns the text source name used in diagnostic messages.
+    ///
+    /// Parsed documentation is named like in `CodemapFiles`.
+    pub fn name(&self) -> String {
+        match &self.text_source {
+            TextSource::File(file) => file.path().to_string_lossy().into_owned(),
+            TextSource::FileDocs(file_docs) => {
+                file_docs.file().path().to_string_lossy().into_owned() + "/parsed"
+            }
+        }
+    }
+
+    /// Returns the text of the text source.
+    pub fn text(&self) -> &str {
+        match &self.text_source {
+            TextSource::File(file) => file.text(),
+            TextSource::FileDocs(file_docs) => file_docs.docs(),
+        }
+    }
+
+    /// Returns the 1-based line and column of the specified offset in the text source.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        match &self.text_source {
+            TextSource::File(file) => file.line_col(offset),
+            TextSource::FileDocs(_) => {
+                let text = self.text();
+                let mut offset = offset.min(text.len());
+                while !text.is_char_boundary(offset) {
+                    offset -= 1;
+                }
+                let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+                LineCol {
+                    line: text[..line_start].matches('\n').count() + 1,
+                    column: text[line_start..offset].chars().count() + 1,
+                }
+            }
+        }
+    }
+}
+
+/// Returns the labels of the given spans.
+///
+/// Like `CodemapSpans`, documentation spans are labeled both in the parsed documentation
+/// and in its original file.
+pub(crate) fn excerpt_labels_from<'a, I>(iter: I) -> Vec<ExcerptLabel>
+where
+    I: IntoIterator<Item = CMarkSpan<'a>>,
+{
+    let mut labels = Vec::new();
+    for item in iter {
+        labels.push(ExcerptLabel {
+            text_source: item.text_source.clone(),
+            range: item.range.clone(),
+            style: SpanStyle::Primary,
+        });
+        if let TextSource::FileDocs(file_docs) = item.text_source {
+            if let Some(file_range) = file_docs.remap_to_file(item.range.clone()) {
+                labels.push(ExcerptLabel {
+                    text_source: TextSource::File(Arc::clone(file_docs.file())),
+                    range: file_range,
+                    style: SpanStyle::Secondary,
+                });
+            }
+        }
+    }
+    labels
+}
+
+/// Sorts labels by text source name, start and end positions,
+/// merges labels with identical ranges and keeps at most `max_count` of them.
+///
+/// Returns the number of omitted labels, the same way as `normalize_span_labels`.
+pub(crate) fn normalize_excerpt_labels(labels: &mut Vec<ExcerptLabel>, max_count: usize) -> usize {
+    labels.sort_by_cached_key(|label| {
+        (
+            label.name(),
+            label.range.start,
+            label.range.end,
+            label.style != SpanStyle::Primary,
+        )
+    });
+    labels.dedup_by(|next, prev| next.text_source == prev.text_source && next.range == prev.range);
+
+    let omitted = labels.len().saturating_sub(max_count);
+    labels.truncate(max_count);
+    omitted
+}
+
+/// Writes the diagnostics in the `codemap_diagnostic` emitter format without colors.
+///
+/// Source lines longer than `max_line_len` are trimmed to a window around
+/// the first focus offset within them or around the first label start.
+pub(crate) fn write_excerpt_diagnostics(
+    f: &mut impl Write,
+    diags: &[ExcerptDiagnostic],
+    focuses: &[(TextSource, usize)],
+    max_line_len: usize,
+) -> fmt::Result {
+    for diag in diags {
+        writeln!(f, "{}: {}", diag.level, diag.message)?;
+        write_excerpts(f, diag, focuses, max_line_len)?;
+    }
+    Ok(())
+}
+
+fn write_excerpts(
+    f: &mut impl Write,
+    diag: &ExcerptDiagnostic,
+    focuses: &[(TextSource, usize)],
+    max_line_len: usize,
+) -> fmt::Result {
+    let main_label = match diag.main_label() {
+        Some(label) => label,
+        None => return Ok(()),
+    };
+
+    // Labels are grouped by their text sources, the main label source goes first.
+    let mut groups: Vec<(&TextSource, Vec<&ExcerptLabel>)> = Vec::new();
+    for label in core::iter::once(main_label).chain(&diag.labels) {
+        match groups
+            .iter_mut()
+            .find(|(text_source, _)| **text_source == label.text_source)
+        {
+            Some((_, labels)) => {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+            None => groups.push((&label.text_source, std::vec![label])),
+        }
+    }
+
+    let max_line = diag.labels.iter().map(end_line).max().unwrap_or(1);
+    let width = max_line.to_string().len();
+    let pad = " ".repeat(width);
+
+    for (idx, (text_source, labels)) in groups.iter().enumerate() {
+        let first = labels[0];
+        if idx == 0 {
+            let start = first.line_col(first.range.start);
+            writeln!(
+                f,
+                "{}--> {}:{}:{}",
+                pad,
+                first.name(),
+                start.line,
+                start.column
+            )?;
+        } else {
+            writeln!(f, "{} |", pad)?;
+            writeln!(f, "{}::: {}", pad, first.name())?;
+        }
+        writeln!(f, "{} |", pad)?;
+
+        let text = first.text();
+        let mut lines: Vec<usize> = labels
+            .iter()
+            .flat_map(|label| label.line_col(label.range.start).line..=end_line(label))
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+
+        for line in lines {
+            let (line_range, content) = match text.split_inclusive('\n').nth(line - 1) {
+                Some(content) => {
+                    let start = line_start(text, line);
+                    let content = content.strip_suffix('\n').unwrap_or(content);
+                    (start..start + content.len(), content)
+                }
+                None => (text.len()..text.len(), ""),
+            };
+            let markers: Vec<(Range<usize>, char)> = labels
+                .iter()
+                .filter_map(|label| {
+                    let start = label.range.start.max(line_range.start);
+                    let end = label.range.end.min(line_range.end);
+                    let marker = if label.style == SpanStyle::Primary {
+                        '^'
+                    } else {
+                        '-'
+                    };
+                    (start <= end && label.range.start <= line_range.end && end_line(label) >= line)
+                        .then(|| (start - line_range.start..end - line_range.start, marker))
+                })
+                .collect();
+
+            let focus = focuses
+                .iter()
+                .filter(|(other, _)| other == *text_source)
+                .map(|(_, offset)| *offset)
+                .chain(
+                    markers
+                        .first()
+                        .map(|(range, _)| line_range.start + range.start),
+                )
+                .find(|&offset| line_range.start <= offset && offset <= line_range.end)
+                .map_or(0, |offset| offset - line_range.start);
+            let (window_start, window_end) =
+                crate::compare::excerpt_window(content, focus, max_line_len);
+
+            let mut shown = String::new();
+            if window_start > 0 {
+                shown += "...";
+            }
+            let shift = shown.len();
+            shown += &content[window_start..window_end];
+            if window_end - window_start < content.len() {
+                shown += &std::format!(
+                    "... {} more bytes",
+                    content.len() - (window_end - window_start)
+                );
+            }
+            writeln!(f, "{:>width$} | {}", line, shown, width = width)?;
+
+            let mut marker_line: Vec<char> = Vec::new();
+            for (range, marker) in markers {
+                let start = range.start.clamp(window_start, window_end);
+                let end = range.end.clamp(window_start, window_end);
+                let column = shift + content[window_start..start].chars().count();
+                let len = content[start..end].chars().count().max(1);
+                if marker_line.len() < column + len {
+                    marker_line.resize(column + len, ' ');
+                }
+                for ch in &mut marker_line[column..column + len] {
+                    // Primary markers are not overwritten by secondary ones.
+                    if *ch != '^' {
+                        *ch = marker;
+                    }
+                }
+            }
+            let marker_line: String = marker_line.into_iter().collect();
+            writeln!(f, "{} | {}", pad, marker_line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the 1-based line of the last label character.
+fn end_line(label: &ExcerptLabel) -> usize {
+    let last = label.range.end.saturating_sub(1).max(label.range.start);
+    label.line_col(last).line
+}
+
+/// Returns the byte offset of the start of the specified 1-based line.
+fn line_start(text: &str, line: usize) -> usize {
+    text.split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum()
+}
+
+/// Returns a single-line message prefixed with the `file:line:column` location
+/// of the diagnostic main label if any.
+pub(crate) fn excerpt_single_line(diag: &ExcerptDiagnostic) -> String {
+    use std::format;
+
+    let message = diag.message.split_whitespace().collect::<Vec<_>>();
+    let message = format!("{}: {}", diag.level, message.join(" "));
+    match diag.main_label() {
+        Some(label) => {
+            let start = label.line_col(label.range.start);
+            format!(
+                "{}:{}:{}: {}",
+                label.name(),
+                start.line,
+                start.column,
+                message
+            )
+        }
+        None => message,
+    }
+}
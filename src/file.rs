@@ -1,14 +1,18 @@
 use std::io;
 use std::path::{Path, PathBuf};
 use std::string::String;
+use std::vec::Vec;
 
 use thiserror::Error;
 
+use crate::LineCol;
+
 /// File path and its contents.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct File {
     path: PathBuf,
     text: String,
+    line_starts: Vec<usize>,
 }
 
 impl File {
@@ -20,14 +24,21 @@ impl File {
             None => fs::read_to_string(&path),
         };
         match content {
-            Ok(text) => Ok(Self { path, text }),
+            Ok(text) => Ok(Self::from_path_and_text(path, text)),
             Err(err) => Err(FileFromPathError::IoError { err, path }),
         }
     }
 
     /// Creates file from the specified path and text.
     pub fn from_path_and_text(path: PathBuf, text: String) -> Self {
-        Self { path, text }
+        let line_starts = core::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            path,
+            text,
+            line_starts,
+        }
     }
 
     /// Returns file text.
@@ -39,6 +50,26 @@ impl File {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Returns the 1-based line and column of the specified byte offset.
+    ///
+    /// Columns are counted in characters.
+    /// Offsets past the end of the text are clamped to the text length.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_idx = match self.line_starts.binary_search(&offset) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        };
+        let line_start = self.line_starts[line_idx];
+        LineCol {
+            line: line_idx + 1,
+            column: self.text[line_start..offset].chars().count() + 1,
+        }
+    }
 }
 
 /// An error which can occur when reading a file from the specified path.
@@ -54,3 +85,20 @@ pub enum FileFromPathError {
         err: io::Error,
     },
 }
+
+#[test]
+fn test_line_col() {
+    let file = File::from_path_and_text(PathBuf::from("README.md"), "ab\n\nпр\nc".into());
+    let line_col = |offset| {
+        let LineCol { line, column } = file.line_col(offset);
+        (line, column)
+    };
+    assert_eq!(line_col(0), (1, 1));
+    assert_eq!(line_col(2), (1, 3));
+    assert_eq!(line_col(3), (2, 1));
+    assert_eq!(line_col(4), (3, 1));
+    assert_eq!(line_col(6), (3, 2));
+    assert_eq!(line_col(7), (3, 2));
+    assert_eq!(line_col(9), (4, 1));
+    assert_eq!(line_col(100), (4, 2));
+}
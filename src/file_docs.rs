@@ -6,7 +6,7 @@ use std::vec::Vec;
 
 use thiserror::Error;
 
-use crate::{Config, File};
+use crate::{Config, DocsItem, DocsSpan, File};
 
 /// Parsed `.rs` file documentation.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -17,6 +17,64 @@ pub struct FileDocs {
     docs: String,
     /// Text ranges remap from parsed documentation to the original file contents.
     remap: Vec<TextRemap>,
+    /// Parsed documentation chunks built under `cfg_attr` predicates.
+    cfg_chunks: Vec<CfgChunk>,
+    /// Documentation lines excluded by `cfg_attr` predicates evaluated to false.
+    skipped_docs: Vec<SkippedDocs>,
+}
+
+/// A parsed documentation text range and the file ranges
+/// of the `cfg_attr` predicates it is built under.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct CfgChunk {
+    docs: Range<usize>,
+    predicates: Vec<Range<usize>>,
+}
+
+/// Duplicated top-level documentation headings built under different `cfg_attr` predicates.
+///
+/// It usually means that a `Config` enables features of mutually exclusive documentation
+/// branches, like `feature = "std"` and `not(feature = "std")`.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error(
+    "Heading `{heading}` is built from conflicting cfg predicates: {}.",
+    FmtPredicates(.predicates)
+)]
+pub struct ConflictingCfgDocs {
+    /// The duplicated heading text.
+    pub heading: String,
+    /// The predicates of every duplicated heading, or `None` for headings outside of `cfg_attr`.
+    /// Predicates of nested `cfg_attr` attributes are combined with `all()`.
+    pub predicates: Vec<Option<String>>,
+    /// The file spans of every duplicated heading.
+    pub spans: Vec<Range<usize>>,
+}
+
+/// Documentation lines excluded by the `cfg_attr` predicate evaluated to false.
+#[derive(Clone, Debug, Eq, Error, Hash, PartialEq)]
+#[error("{lines} doc line(s) excluded by `cfg_attr({predicate}, ...)`")]
+pub struct SkippedDocs {
+    /// The predicate with its whitespace collapsed.
+    pub predicate: String,
+    /// The number of excluded documentation lines.
+    pub lines: usize,
+}
+
+struct FmtPredicates<'a>(&'a [Option<String>]);
+
+impl core::fmt::Display for FmtPredicates<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (idx, predicate) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            match predicate {
+                Some(predicate) => write!(f, "`{}`", predicate)?,
+                None => write!(f, "no predicate")?,
+            }
+        }
+        Ok(())
+    }
 }
 
 /// The pair of a source and the corresponding target text remap range.
@@ -30,8 +88,83 @@ pub struct TextRemap {
 
 impl FileDocs {
     /// Creates file documentations from the specified file with the specified features.
+    #[allow(clippy::result_large_err)]
     pub fn from_file(file: Arc<File>, config: &Config<'_>) -> Result<Self, FileDocsFromFileError> {
+        let ast = syn::parse_file(file.text())?;
+        Self::from_file_and_ast(file, &ast, config)
+    }
+
+    /// Creates file documentations from the specified file with the specified features
+    /// and the parsing guards.
+    ///
+    /// The file size limit is checked before parsing.
+    /// If the time limit is set, the file is parsed on a helper thread
+    /// and `FileDocsFromFileError::ParseTimeout` is returned if it is not parsed in time.
+    /// In that case the helper thread can not be interrupted
+    /// and is left to finish the parsing in the background.
+    /// The helper thread parsing errors are returned as `FileDocsFromFileError::LimitedParseError`.
+    #[allow(clippy::result_large_err)]
+    pub fn from_file_with_limits(
+        file: Arc<File>,
+        config: &Config<'_>,
+        limits: &DocsParseLimits,
+    ) -> Result<Self, FileDocsFromFileError> {
+        use std::string::ToString;
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let size = file.text().len();
+        if let Some(limit) = limits.max_file_bytes {
+            if size > limit {
+                return Err(FileDocsFromFileError::FileTooLarge { size, limit });
+            }
+        }
+        let millis = match limits.max_parse_millis {
+            Some(millis) => millis,
+            None => return Self::from_file(file, config),
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let thread_file = Arc::clone(&file);
+        let thread_config = static_config(config);
+        let _ = std::thread::spawn(move || {
+            // Parsing errors may contain syntax tree nodes that can not be sent between threads,
+            // so only the error message is reported.
+            let docs = Self::from_file(thread_file, &thread_config).map_err(|err| err.to_string());
+            let _ = sender.send(docs);
+        });
+        match receiver.recv_timeout(Duration::from_millis(millis)) {
+            Ok(Ok(docs)) => Ok(docs),
+            Ok(Err(message)) => Err(FileDocsFromFileError::LimitedParseError(message)),
+            Err(_) => Err(FileDocsFromFileError::ParseTimeout { millis }),
+        }
+    }
+
+    /// Creates file documentations from the specified file for every specified config.
+    ///
+    /// The file is parsed only once,
+    /// so it is cheaper than calling `from_file` for every config,
+    /// for example to get documentations built with different language features.
+    #[allow(clippy::result_large_err)]
+    pub fn from_file_with_configs(
+        file: Arc<File>,
+        configs: &[Config<'_>],
+    ) -> Result<Vec<Self>, FileDocsFromFileError> {
+        let ast = syn::parse_file(file.text())?;
+        configs
+            .iter()
+            .map(|config| Self::from_file_and_ast(Arc::clone(&file), &ast, config))
+            .collect()
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn from_file_and_ast(
+        file: Arc<File>,
+        ast: &syn::File,
+        config: &Config<'_>,
+    ) -> Result<Self, FileDocsFromFileError> {
         use crate::build_attr_docs;
+        use crate::docs_parser::skipped_meta_docs;
 
         let file_text = file.text();
         let line_offsets: Vec<_> = file_text
@@ -39,7 +172,6 @@ impl FileDocs {
             .map(|slice| slice.as_ptr() as usize - file_text.as_ptr() as usize)
             .collect();
 
-        let ast = syn::parse_file(file_text)?;
         let chunks: Result<Vec<_>, _> = ast
             .attrs
             .iter()
@@ -47,19 +179,51 @@ impl FileDocs {
             .collect();
         let chunks = chunks?;
 
-        let (docs, mut remap, _) = chunks.into_iter().flatten().fold(
+        let file_range = |span: DocsSpan| {
+            file_offset(file_text, &line_offsets, span.start.line, span.start.column)
+                ..file_offset(file_text, &line_offsets, span.end.line, span.end.column)
+        };
+        let mut skipped_docs: Vec<SkippedDocs> = Vec::new();
+        for (span, lines) in ast
+            .attrs
+            .iter()
+            .flat_map(|attr| skipped_meta_docs(&attr.meta, config))
+        {
+            let predicate: Vec<_> = file_text[file_range(span)].split_whitespace().collect();
+            let predicate = predicate.join(" ");
+            match skipped_docs
+                .iter_mut()
+                .find(|skipped| skipped.predicate == predicate)
+            {
+                Some(skipped) => skipped.lines += lines,
+                None => skipped_docs.push(SkippedDocs { predicate, lines }),
+            }
+        }
+        skipped_docs.sort_by_key(|skipped| core::cmp::Reverse(skipped.lines));
+
+        let mut items: Vec<_> = chunks.into_iter().flatten().collect();
+        unindent_docs_items(&mut items, |span| {
+            file_text[file_range(span)].starts_with('/')
+        });
+
+        let mut cfg_chunks = Vec::new();
+        let (docs, mut remap, _) = items.into_iter().fold(
             (String::new(), Vec::new(), None),
             |(text, mut remap, last), item| {
-                let range = item.span.map(|span| {
-                    line_offsets[span.start.line] + span.start.column
-                        ..line_offsets[span.end.line] + span.end.column
-                });
+                let range = item.span.map(file_range);
+                let source = text.len()..text.len() + item.text.len();
                 if let Some(range) = range.clone() {
                     remap.push(TextRemap {
-                        source: text.len()..text.len() + item.text.len(),
+                        source: source.clone(),
                         target: range,
                     });
                 }
+                if !item.cfg_predicates.is_empty() {
+                    cfg_chunks.push(CfgChunk {
+                        docs: source,
+                        predicates: item.cfg_predicates.into_iter().map(file_range).collect(),
+                    });
+                }
                 (
                     text + &item.text,
                     remap,
@@ -69,7 +233,13 @@ impl FileDocs {
         );
 
         remap.sort();
-        Ok(FileDocs { file, docs, remap })
+        Ok(FileDocs {
+            file,
+            docs,
+            remap,
+            cfg_chunks,
+            skipped_docs,
+        })
     }
 
     /// Returns file file.
@@ -87,6 +257,14 @@ impl FileDocs {
         &self.remap
     }
 
+    /// Returns documentation lines excluded by `cfg_attr` predicates evaluated to false,
+    /// grouped by the predicates and sorted by the number of lines in descending order.
+    ///
+    /// It helps to find a `Config` missing a feature all the documentation depends on.
+    pub fn skipped_docs_summary(&self) -> &[SkippedDocs] {
+        &self.skipped_docs
+    }
+
     /// Remaps range from parsed documentation to source file content.
     pub fn remap_to_file(&self, range: Range<usize>) -> Option<Range<usize>> {
         let remap_idx = self
@@ -105,6 +283,107 @@ impl FileDocs {
         let remap = &self.remap[remap_idx];
         Some(remap.target.start..remap.target.end)
     }
+
+    /// Returns the file offset corresponding to the specified documentation offset.
+    ///
+    /// Offsets are aligned to the documentation text within its doc comment line if it is found there,
+    /// and `None` is returned for offsets outside of the text remaps.
+    pub fn remap_offset_to_file(&self, offset: usize) -> Option<usize> {
+        let remap = self
+            .remap
+            .iter()
+            .find(|remap| remap.source.contains(&offset))?;
+        let source = &self.docs[remap.source.clone()];
+        let source = source.strip_suffix('\n').unwrap_or(source);
+        let target = &self.file.text()[remap.target.clone()];
+        let start = if target.ends_with(source) {
+            remap.target.end - source.len()
+        } else {
+            remap.target.start
+        };
+        Some((start + offset - remap.source.start).min(remap.target.end))
+    }
+
+    /// Returns duplicated top-level headings built under different `cfg_attr` predicates.
+    ///
+    /// Headings are duplicated if they have the same level and text.
+    /// Headings nested in block quotes, lists and other containers are ignored.
+    pub fn lint_cfg_conflicts(&self) -> Vec<ConflictingCfgDocs> {
+        use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+        let mut headings: Vec<(_, String, Range<usize>)> = Vec::new();
+        let mut depth = 0_usize;
+        let mut heading = None;
+        for (event, range) in Parser::new_ext(&self.docs, Options::ENABLE_TABLES).into_offset_iter()
+        {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) if depth == 0 => {
+                    heading = Some((level, String::new(), range));
+                }
+                Event::End(TagEnd::Heading(_)) if depth == 0 => headings.extend(heading.take()),
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, heading_text, _)) = heading.as_mut() {
+                        *heading_text += &text;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        let mut is_reported = std::vec![false; headings.len()];
+        for (idx, (level, text, _)) in headings.iter().enumerate() {
+            if is_reported[idx] {
+                continue;
+            }
+            let duplicates: Vec<_> = (idx..headings.len())
+                .filter(|&other| headings[other].0 == *level && headings[other].1 == *text)
+                .collect();
+            let predicates: Vec<_> = duplicates
+                .iter()
+                .map(|&other| self.cfg_predicate(headings[other].2.start))
+                .collect();
+            if predicates
+                .iter()
+                .any(|predicate| *predicate != predicates[0])
+            {
+                for &other in &duplicates {
+                    is_reported[other] = true;
+                }
+                conflicts.push(ConflictingCfgDocs {
+                    heading: text.clone(),
+                    predicates,
+                    spans: duplicates
+                        .iter()
+                        .filter_map(|&other| self.remap_to_file(headings[other].2.clone()))
+                        .collect(),
+                });
+            }
+        }
+        conflicts
+    }
+
+    /// Returns the `cfg_attr` predicate of the parsed documentation at the specified offset.
+    fn cfg_predicate(&self, offset: usize) -> Option<String> {
+        use std::format;
+
+        let chunk = self
+            .cfg_chunks
+            .iter()
+            .find(|chunk| chunk.docs.contains(&offset))?;
+        let text = self.file.text();
+        let predicates: Vec<_> = chunk
+            .predicates
+            .iter()
+            .map(|range| &text[range.clone()])
+            .collect();
+        match predicates.as_slice() {
+            [predicate] => Some(String::from(*predicate)),
+            predicates => Some(format!("all({})", predicates.join(", "))),
+        }
+    }
 }
 
 impl PartialOrd for TextRemap {
@@ -119,8 +398,119 @@ impl Ord for TextRemap {
     }
 }
 
+/// Removes the common leading whitespace of the documentation lines like rustdoc does.
+///
+/// Doc comments like `//! text` keep the space after the comment marker,
+/// so if they are mixed with `doc` attributes,
+/// the attributes indentation is increased by one when computing the common indentation.
+/// Whitespace-only lines and the relative indentation of the lines are preserved as is.
+///
+/// See <https://doc.rust-lang.org/reference/comments.html#doc-comments> for more details.
+fn unindent_docs_items<F>(items: &mut [DocsItem], mut is_doc_comment: F)
+where
+    F: FnMut(DocsSpan) -> bool,
+{
+    use std::borrow::Cow;
+
+    let leading_whitespace_len = |line: &str| {
+        line.bytes()
+            .take_while(|&b| b == b' ' || b == b'\t')
+            .count()
+    };
+    let is_blank = |line: &str| line.chars().all(char::is_whitespace);
+
+    // Generated items without spans, like terminating newlines, are not documentation fragments.
+    let kinds: Vec<_> = items
+        .iter()
+        .map(|item| item.span.map(&mut is_doc_comment))
+        .collect();
+    let fragment_kinds: Vec<_> = kinds.iter().flatten().copied().collect();
+    let add = usize::from(
+        fragment_kinds.windows(2).any(|pair| pair[0] != pair[1]) && fragment_kinds.contains(&true),
+    );
+
+    let min_indent = items
+        .iter()
+        .zip(&kinds)
+        .filter_map(|(item, kind)| {
+            let add = if (*kind)? { 0 } else { add };
+            item.text
+                .lines()
+                .filter(|line| !is_blank(line))
+                .map(|line| leading_whitespace_len(line) + add)
+                .min()
+        })
+        .min();
+    let min_indent = match min_indent {
+        Some(min_indent) => min_indent,
+        None => return,
+    };
+
+    for (item, kind) in items.iter_mut().zip(kinds) {
+        let indent = match kind {
+            Some(true) => min_indent,
+            Some(false) => min_indent.saturating_sub(add),
+            None => continue,
+        };
+        if indent > 0 {
+            let text: String = item
+                .text
+                .split_inclusive('\n')
+                .map(|line| {
+                    if is_blank(line) {
+                        line
+                    } else {
+                        &line[indent.min(leading_whitespace_len(line))..]
+                    }
+                })
+                .collect();
+            item.text = Cow::from(text);
+        }
+    }
+}
+
+/// The guards of the documentation file parsing,
+/// useful for very large generated crate roots.
+///
+/// The limits are disabled by default.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DocsParseLimits {
+    /// The maximum file size in bytes.
+    pub max_file_bytes: Option<usize>,
+    /// The maximum parsing duration in milliseconds.
+    pub max_parse_millis: Option<u64>,
+}
+
+/// Returns the config with owned options that can be moved to another thread.
+fn static_config(config: &Config<'_>) -> Config<'static> {
+    use std::borrow::Cow;
+
+    let owned = |value: &Cow<'_, str>| Cow::Owned(String::from(value.as_ref()));
+    Config {
+        idents: config.idents.iter().map(owned).collect(),
+        name_values: config
+            .name_values
+            .iter()
+            .map(|(name, value)| (owned(name), owned(value)))
+            .collect(),
+    }
+}
+
+/// Returns the byte offset of the specified line and character column in the file text.
+///
+/// Span columns are counted in chars, so they differ from byte offsets for non-ASCII lines.
+fn file_offset(file_text: &str, line_offsets: &[usize], line: usize, column: usize) -> usize {
+    let line_start = line_offsets[line];
+    line_start
+        + file_text[line_start..]
+            .char_indices()
+            .nth(column)
+            .map_or(file_text.len() - line_start, |(idx, _)| idx)
+}
+
 /// An error which can occur when creating file documentation form a given file.
 #[derive(Clone, Debug, Error)]
+#[allow(clippy::large_enum_variant)]
 pub enum FileDocsFromFileError {
     /// File parsing error
     #[error("File parser error: {0}")]
@@ -128,4 +518,286 @@ pub enum FileDocsFromFileError {
     /// Attribute or meta parsing error.
     #[error(transparent)]
     AttrError(#[from] crate::BuildAttrDocsError),
+    /// The file is larger than the `DocsParseLimits::max_file_bytes` limit.
+    #[error(
+        "File size of {size} bytes exceeds the {limit} bytes parsing limit, \
+            consider reading the crate docs from an explicit docs file \
+            or parsing only the crate attributes"
+    )]
+    FileTooLarge {
+        /// The file size in bytes.
+        size: usize,
+        /// The size limit in bytes.
+        limit: usize,
+    },
+    /// The file is not parsed within the `DocsParseLimits::max_parse_millis` limit.
+    #[error("File is not parsed within the {millis} ms parsing limit")]
+    ParseTimeout {
+        /// The time limit in milliseconds.
+        millis: u64,
+    },
+    /// File or attribute parsing error within the `DocsParseLimits::max_parse_millis` limit.
+    ///
+    /// Only the error message is kept since the parsing runs in another thread.
+    #[error("{0}")]
+    LimitedParseError(String),
+}
+
+#[test]
+fn test_file_docs_fragments_concatenation() {
+    use std::borrow::ToOwned;
+    use std::path::PathBuf;
+
+    let docs = |text: &str| {
+        let file = File::from_path_and_text(PathBuf::from("lib.rs"), text.into());
+        FileDocs::from_file(Arc::new(file), &Config::default())
+            .unwrap()
+            .docs()
+            .to_owned()
+    };
+
+    assert_eq!(docs("//! a\n//!\n//! b\n"), "a\n\nb\n");
+    assert_eq!(docs("#![doc = \"a\"]\n#![doc = \"b\"]\n"), "a\nb\n");
+    assert_eq!(docs("#![doc = \"a\\n\"]\n#![doc = \"b\"]\n"), "a\nb\n");
+    assert_eq!(docs("#![doc = \"a\\n\\n\"]\n//! b\n"), "a\n\nb\n");
+    assert_eq!(
+        docs("#![doc = \"a\\nb\"]\n//!\n#![doc = \"\"]\n"),
+        "a\nb\n\n\n"
+    );
+    assert_eq!(docs("//! a\n#![no_std]\n// comment\n//! b\n"), "a\nb\n");
+}
+
+#[test]
+fn test_file_docs_non_ascii_spans() {
+    use std::path::PathBuf;
+    use std::vec::Vec;
+
+    let text = "/* Ünïcödé */ #![doc = \"Fïrst\"] #![doc = \"Second\"]\n//! Thïrd\n";
+    let file = File::from_path_and_text(PathBuf::from("lib.rs"), text.into());
+    let docs = FileDocs::from_file(Arc::new(file), &Config::default()).unwrap();
+    assert_eq!(docs.docs(), "Fïrst\nSecond\nThïrd\n");
+    let targets: Vec<_> = docs
+        .remap()
+        .iter()
+        .map(|remap| &text[remap.target.clone()])
+        .collect();
+    assert_eq!(targets, ["\"Fïrst\"", "\"Second\"", "//! Thïrd"]);
+
+    let text = "/* Ünïcödé */ #![cfg_attr(feature = \"ü\", doc = \"Hidden\")]\n";
+    let file = File::from_path_and_text(PathBuf::from("lib.rs"), text.into());
+    let docs = FileDocs::from_file(Arc::new(file), &Config::default()).unwrap();
+    assert_eq!(
+        docs.skipped_docs_summary(),
+        [SkippedDocs {
+            predicate: "feature = \"ü\"".into(),
+            lines: 1
+        }]
+    );
+}
+
+#[test]
+fn test_file_docs_nested_code_blocks_indentation() {
+    use pulldown_cmark::{Event, Parser};
+    use std::borrow::ToOwned;
+    use std::path::PathBuf;
+
+    let events =
+        |text: &str| -> Vec<Event<'static>> { Parser::new(text).map(Event::into_static).collect() };
+    let docs = |text: &str| {
+        let file = File::from_path_and_text(PathBuf::from("lib.rs"), text.into());
+        FileDocs::from_file(Arc::new(file), &Config::default())
+            .unwrap()
+            .docs()
+            .to_owned()
+    };
+
+    // The readme and the equivalent doc comments as rendered by rustdoc.
+    let readme = concat!(
+        "Crate.\n\n",
+        "    top-level code\n\n",
+        "- Fenced:\n\n",
+        "  ```rust\n",
+        "  let a = 1;\n",
+        "  ```\n",
+        "- Indented:\n\n",
+        "      let b = 2;\n",
+        "        let c = 3;\n",
+        "- Nested:\n",
+        "  - Item:\n\n",
+        "        let d = 4;\n",
+    );
+    let sugared = concat!(
+        "//! Crate.\n",
+        "//!\n",
+        "//!     top-level code\n",
+        "//!\n",
+        "//! - Fenced:\n",
+        "//!\n",
+        "//!   ```rust\n",
+        "//!   let a = 1;\n",
+        "//!   ```\n",
+        "//! - Indented:\n",
+        "//!\n",
+        "//!       let b = 2;\n",
+        "//!         let c = 3;\n",
+        "//! - Nested:\n",
+        "//!   - Item:\n",
+        "//!\n",
+        "//!         let d = 4;\n",
+    );
+    let mixed = concat!(
+        "#![doc = \"Crate.\"]\n",
+        "//!\n",
+        "#![doc = \"    top-level code\\n\"]\n",
+        "//! - Fenced:\n",
+        "//!\n",
+        "#![doc = \"  ```rust\\n  let a = 1;\\n  ```\"]\n",
+        "//! - Indented:\n",
+        "//!\n",
+        "//!       let b = 2;\n",
+        "#![doc = \"        let c = 3;\"]\n",
+        "//! - Nested:\n",
+        "#![doc = \"  - Item:\\n\\n        let d = 4;\"]\n",
+    );
+
+    assert_eq!(docs(sugared), readme);
+    assert_eq!(events(&docs(sugared)), events(readme));
+    assert_eq!(events(&docs(mixed)), events(readme));
+}
+
+#[test]
+fn test_skipped_docs_summary() {
+    use std::path::PathBuf;
+    use std::string::ToString;
+
+    let summary = |text: &str, features: &[&str]| {
+        let file = Arc::new(File::from_path_and_text(
+            PathBuf::from("lib.rs"),
+            text.into(),
+        ));
+        let config = Config::new().with_features(features.iter().copied());
+        FileDocs::from_file(file, &config)
+            .unwrap()
+            .skipped_docs_summary()
+            .to_vec()
+    };
+
+    let hidden = concat!(
+        "#![cfg_attr(feature = \"docs\", doc = \"# Crate\")]\n",
+        "#![cfg_attr(feature = \"docs\", doc = \"\")]\n",
+        "#![cfg_attr(feature = \"docs\", doc = \"Line.\\nOther line.\")]\n",
+    );
+    assert_eq!(
+        summary(hidden, &[]),
+        [SkippedDocs {
+            predicate: "feature = \"docs\"".into(),
+            lines: 4,
+        }]
+    );
+    assert_eq!(summary(hidden, &["docs"]), []);
+
+    let partial = concat!(
+        "//! # Crate\n",
+        "#![cfg_attr(feature = \"std\", doc = \"With std.\")]\n",
+        "#![cfg_attr(not(feature = \"std\"), doc = \"Without std.\")]\n",
+        "#![cfg_attr(feature = \"std\", cfg_attr(unix, doc = \"Unix.\\nMore.\"))]\n",
+        "#![cfg_attr(feature = \"std\", allow(unused))]\n",
+    );
+    let skipped = summary(partial, &[]);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(
+        skipped[0].to_string(),
+        "3 doc line(s) excluded by `cfg_attr(feature = \"std\", ...)`"
+    );
+    let skipped = summary(partial, &["std"]);
+    assert_eq!(skipped.len(), 2);
+    assert_eq!(skipped[0].predicate, "unix");
+    assert_eq!(skipped[0].lines, 2);
+    assert_eq!(skipped[1].predicate, "not(feature = \"std\")");
+}
+
+#[test]
+fn test_lint_cfg_conflicts() {
+    use std::path::PathBuf;
+    use std::string::ToString;
+
+    let text = concat!(
+        "//! # Crate\n",
+        "#![cfg_attr(feature = \"std\", doc = \"## Usage\\n\\nWith std.\")]\n",
+        "#![cfg_attr(feature = \"alloc\", cfg_attr(unix, doc = \"## Usage\\n\\nWith alloc.\"))]\n",
+        "//! > ## Usage\n",
+    );
+    let file = Arc::new(File::from_path_and_text(
+        PathBuf::from("lib.rs"),
+        text.into(),
+    ));
+    let lint = |features: &[&str]| {
+        let mut config = Config::new().with_features(features.iter().copied());
+        let _ = config.idents.insert("unix".into());
+        FileDocs::from_file(Arc::clone(&file), &config)
+            .unwrap()
+            .lint_cfg_conflicts()
+    };
+
+    assert_eq!(lint(&["std"]), []);
+    let conflicts = lint(&["std", "alloc"]);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].heading, "Usage");
+    assert_eq!(
+        conflicts[0].predicates,
+        [
+            Some("feature = \"std\"".to_string()),
+            Some("all(feature = \"alloc\", unix)".to_string())
+        ]
+    );
+    assert_eq!(conflicts[0].spans.len(), 2);
+    assert_eq!(
+        conflicts[0].to_string(),
+        "Heading `Usage` is built from conflicting cfg predicates: \
+         `feature = \"std\"`, `all(feature = \"alloc\", unix)`."
+    );
+}
+
+#[test]
+fn test_file_docs_parse_limits() {
+    use std::path::PathBuf;
+    use std::string::ToString;
+
+    let text = std::format!("//! Docs.\n{}", "pub fn foo() {}\n".repeat(10_000));
+    let file = Arc::new(File::from_path_and_text(PathBuf::from("lib.rs"), text));
+    let config = Config::new().with_features(["foo"]);
+
+    let limits = DocsParseLimits {
+        max_file_bytes: Some(1024),
+        max_parse_millis: None,
+    };
+    let err = FileDocs::from_file_with_limits(Arc::clone(&file), &config, &limits).unwrap_err();
+    assert!(matches!(
+        err,
+        FileDocsFromFileError::FileTooLarge { size, limit: 1024 } if size == file.text().len()
+    ));
+    assert!(err.to_string().contains("explicit docs file"));
+
+    let limits = DocsParseLimits {
+        max_file_bytes: Some(file.text().len()),
+        max_parse_millis: Some(60_000),
+    };
+    let docs = FileDocs::from_file_with_limits(Arc::clone(&file), &config, &limits).unwrap();
+    assert_eq!(
+        docs,
+        FileDocs::from_file(Arc::clone(&file), &config).unwrap()
+    );
+
+    let invalid = Arc::new(File::from_path_and_text(
+        PathBuf::from("lib.rs"),
+        "fn {".into(),
+    ));
+    let err = FileDocs::from_file_with_limits(Arc::clone(&invalid), &config, &limits).unwrap_err();
+    assert!(matches!(err, FileDocsFromFileError::LimitedParseError(_)));
+    assert_eq!(
+        err.to_string(),
+        FileDocs::from_file(invalid, &config)
+            .unwrap_err()
+            .to_string()
+    );
 }
@@ -0,0 +1,240 @@
+use core::fmt::Write;
+use std::string::String;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CMarkData, File, FileDocs, PipelineSpec};
+
+/// The 64-bit FNV-1a hasher.
+///
+/// Unlike the `std` hashers it produces the same results across processes and platforms.
+#[derive(Clone, Copy, Debug)]
+struct Fingerprinter(u64);
+
+impl Fingerprinter {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Write for Fingerprinter {
+    fn write_str(&mut self, text: &str) -> core::fmt::Result {
+        self.write_bytes(text.as_bytes());
+        Ok(())
+    }
+}
+
+pub(crate) fn text_fingerprint(text: &str) -> u64 {
+    let mut fingerprinter = Fingerprinter::new();
+    fingerprinter.write_bytes(text.as_bytes());
+    fingerprinter.finish()
+}
+
+fn debug_fingerprint<T: core::fmt::Debug>(items: impl IntoIterator<Item = T>) -> u64 {
+    let mut fingerprinter = Fingerprinter::new();
+    for item in items {
+        // Writing to the fingerprinter never fails.
+        let _ = write!(fingerprinter, "{:?}", item);
+        // The byte never occurs in UTF-8 and separates the items.
+        fingerprinter.write_bytes(&[0xff]);
+    }
+    fingerprinter.finish()
+}
+
+impl CMarkData {
+    /// Returns the fingerprint of the events, ignoring the removed ones.
+    ///
+    /// See `SyncStamp` for the fingerprints stability details.
+    pub fn content_fingerprint(&self) -> u64 {
+        debug_fingerprint(self.iter_events())
+    }
+}
+
+impl FileDocs {
+    /// Returns the fingerprint of the parsed documentation text.
+    ///
+    /// See `SyncStamp` for the fingerprints stability details.
+    pub fn docs_fingerprint(&self) -> u64 {
+        text_fingerprint(self.docs())
+    }
+}
+
+impl File {
+    /// Returns the fingerprint of the file text.
+    ///
+    /// See `SyncStamp` for the fingerprints stability details.
+    pub fn text_fingerprint(&self) -> u64 {
+        text_fingerprint(self.text())
+    }
+}
+
+impl PipelineSpec {
+    /// Returns the fingerprint of the pipeline steps and options.
+    ///
+    /// See `SyncStamp` for the fingerprints stability details.
+    pub fn fingerprint(&self) -> u64 {
+        debug_fingerprint(core::iter::once(self))
+    }
+}
+
+/// Fingerprints of the readme, docs and pipeline of the last successful synchronization check.
+///
+/// It allows to skip the check if nothing has changed since the stamp was saved,
+/// for example in the `target` directory.
+///
+/// Fingerprints are computed with the 64-bit FNV-1a hash, they are stable across processes
+/// and platforms, but they are not cryptographic and may change across readme-sync versions,
+/// so the stamp contains the crate version and does not match stamps of other versions.
+///
+/// Fingerprints are serialized as 16-digit hexadecimal strings,
+/// because formats like TOML do not support integers above `i64::MAX`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SyncStamp {
+    /// The readme-sync version the stamp is created with.
+    pub version: String,
+    /// The readme fingerprint.
+    #[serde(with = "hex_fingerprint")]
+    pub readme_fp: u64,
+    /// The docs fingerprint.
+    #[serde(with = "hex_fingerprint")]
+    pub docs_fp: u64,
+    /// The pipeline fingerprint.
+    #[serde(with = "hex_fingerprint")]
+    pub pipeline_fp: u64,
+}
+
+/// Serializes fingerprints as fixed-width hexadecimal strings.
+mod hex_fingerprint {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::string::String;
+
+    pub(super) fn serialize<S>(fingerprint: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&std::format!("{:016x}", fingerprint))
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        u64::from_str_radix(&text, 16).map_err(Error::custom)
+    }
+}
+
+impl SyncStamp {
+    /// Creates the stamp from the specified fingerprints and the current crate version.
+    pub fn new(readme_fp: u64, docs_fp: u64, pipeline_fp: u64) -> Self {
+        Self {
+            version: String::from(env!("CARGO_PKG_VERSION")),
+            readme_fp,
+            docs_fp,
+            pipeline_fp,
+        }
+    }
+
+    /// Creates the stamp from the readme file, the docs file and the pipeline.
+    pub fn from_files_and_pipeline(
+        readme: &File,
+        docs: &FileDocs,
+        pipeline: &PipelineSpec,
+    ) -> Self {
+        Self::new(
+            readme.text_fingerprint(),
+            docs.docs_fingerprint(),
+            pipeline.fingerprint(),
+        )
+    }
+
+    /// Returns `true` if the stamp is created with the current crate version
+    /// and the specified fingerprints.
+    pub fn matches_current(&self, readme_fp: u64, docs_fp: u64, pipeline_fp: u64) -> bool {
+        *self == Self::new(readme_fp, docs_fp, pipeline_fp)
+    }
+}
+
+#[test]
+fn test_fingerprints() {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    assert_eq!(text_fingerprint(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(text_fingerprint("a"), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(text_fingerprint("foobar"), 0x8594_4171_f739_67e8);
+
+    let file = |text: &str| {
+        Arc::new(File::from_path_and_text(
+            PathBuf::from("lib.rs"),
+            text.into(),
+        ))
+    };
+    let data = |text: &str| CMarkData::from_file(file(text));
+    assert_eq!(
+        data("Text *a*.\n").content_fingerprint(),
+        data("Text _a_.\n").content_fingerprint()
+    );
+    assert_ne!(
+        data("Text *a*.\n").content_fingerprint(),
+        data("Text *b*.\n").content_fingerprint()
+    );
+    assert_ne!(
+        data("a\n\nb\n").content_fingerprint(),
+        data("ab\n").content_fingerprint()
+    );
+
+    let docs = |text: &str| FileDocs::from_file(file(text), &crate::Config::default()).unwrap();
+    assert_eq!(
+        docs("//! Text.\nfn a() {}\n").docs_fingerprint(),
+        docs("//! Text.\nfn b() {}\n").docs_fingerprint()
+    );
+    assert_ne!(
+        file("//! Text.\nfn a() {}\n").text_fingerprint(),
+        file("//! Text.\nfn b() {}\n").text_fingerprint()
+    );
+
+    let pipeline = PipelineSpec::default();
+    let stamp =
+        SyncStamp::from_files_and_pipeline(&file("Text.\n"), &docs("//! Text.\n"), &pipeline);
+    assert!(stamp.matches_current(
+        file("Text.\n").text_fingerprint(),
+        docs("//! Text.\n").docs_fingerprint(),
+        pipeline.fingerprint()
+    ));
+    assert!(!stamp.matches_current(
+        file("Other text.\n").text_fingerprint(),
+        stamp.docs_fp,
+        stamp.pipeline_fp
+    ));
+    let other_version = SyncStamp {
+        version: String::from("0.0.0"),
+        ..stamp.clone()
+    };
+    assert!(!other_version.matches_current(stamp.readme_fp, stamp.docs_fp, stamp.pipeline_fp));
+
+    let stamp = SyncStamp::new(1, 2, 3);
+    let serialized = toml::to_string(&stamp).unwrap();
+    assert_eq!(toml::from_str::<SyncStamp>(&serialized).unwrap(), stamp);
+
+    let stamp = SyncStamp::new(u64::MAX - 5, i64::MAX as u64 + 1, 3);
+    let serialized = toml::to_string(&stamp).unwrap();
+    assert!(serialized.contains("readme_fp = \"fffffffffffffffa\"\n"));
+    assert!(serialized.contains("docs_fp = \"8000000000000000\"\n"));
+    assert!(serialized.contains("pipeline_fp = \"0000000000000003\"\n"));
+    assert_eq!(toml::from_str::<SyncStamp>(&serialized).unwrap(), stamp);
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+
+use crate::{CMarkData, CMarkItem};
+
+/// An issue found by fragment links validation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FragmentLinkIssue {
+    /// The link fragment does not match any heading anchor.
+    Unresolved {
+        /// Link fragment without the leading `#`.
+        fragment: String,
+        /// Link start node.
+        link: Arc<CMarkItem>,
+    },
+    /// The link fragment matches the anchor of a heading
+    /// whose text is shared with other headings of the same document.
+    ///
+    /// Only the first of such headings gets the plain anchor,
+    /// the others get `-1`, `-2`, ... suffixes.
+    Ambiguous {
+        /// Link fragment without the leading `#`.
+        fragment: String,
+        /// Link start node.
+        link: Arc<CMarkItem>,
+        /// Heading start nodes with the same anchor text.
+        headings: Vec<Arc<CMarkItem>>,
+    },
+}
+
+impl FragmentLinkIssue {
+    /// Returns the link fragment without the leading `#`.
+    pub fn fragment(&self) -> &str {
+        match self {
+            Self::Unresolved { fragment, .. } | Self::Ambiguous { fragment, .. } => fragment,
+        }
+    }
+
+    /// Returns the link start node.
+    pub fn link(&self) -> &Arc<CMarkItem> {
+        match self {
+            Self::Unresolved { link, .. } | Self::Ambiguous { link, .. } => link,
+        }
+    }
+}
+
+/// Returns the heading anchor generated from the specified heading text.
+///
+/// Both GitHub and rustdoc lowercase the heading text,
+/// replace spaces with dashes and drop the remaining punctuation.
+/// Duplicate anchors in the same document get `-1`, `-2`, ... suffixes.
+///
+/// It is the GitHub anchor,
+/// see `rustdoc_heading_anchor` for the rustdoc anchor of non-ASCII headings.
+pub fn heading_anchor(text: &str) -> String {
+    let mut anchor = String::new();
+    for ch in text.trim().chars() {
+        if ch.is_alphanumeric() || ch == '-' || ch == '_' {
+            anchor.extend(ch.to_lowercase());
+        } else if ch.is_whitespace() {
+            anchor.push('-');
+        }
+    }
+    anchor
+}
+
+/// Returns the rustdoc heading anchor generated from the specified heading text.
+///
+/// Unlike GitHub, rustdoc lowercases only ASCII letters
+/// and drops non-ASCII whitespace instead of replacing it with dashes.
+pub fn rustdoc_heading_anchor(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|ch| {
+            if ch.is_alphanumeric() || ch == '-' || ch == '_' {
+                Some(ch.to_ascii_lowercase())
+            } else if ch.is_ascii_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+struct HeadingAnchor {
+    anchor: String,
+    base: String,
+    node: Arc<CMarkItem>,
+}
+
+fn heading_anchors(data: &CMarkData) -> Vec<HeadingAnchor> {
+    heading_anchors_with(data, heading_anchor)
+}
+
+fn heading_anchors_with(data: &CMarkData, anchor_fn: fn(&str) -> String) -> Vec<HeadingAnchor> {
+    use crate::cmark_data::heading_text;
+    use pulldown_cmark::Tag;
+    use std::format;
+
+    let items: Vec<_> = data.iter().cloned().collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut anchors = Vec::new();
+
+    for (idx, node) in items.iter().enumerate() {
+        if let Some(Event::Start(Tag::Heading { id, .. })) = node.event() {
+            let base = match id {
+                Some(id) => id.as_ref().into(),
+                None => anchor_fn(&heading_text(&items[idx..]).unwrap_or_default()),
+            };
+            let count = counts.entry(base.clone()).or_insert(0);
+            let anchor = if *count == 0 {
+                base.clone()
+            } else {
+                format!("{}-{}", base, count)
+            };
+            *count += 1;
+            anchors.push(HeadingAnchor {
+                anchor,
+                base,
+                node: Arc::clone(node),
+            });
+        }
+    }
+
+    anchors
+}
+
+fn fragment_links(data: &CMarkData) -> impl Iterator<Item = (&str, &Arc<CMarkItem>)> {
+    use pulldown_cmark::Tag;
+
+    data.iter().filter_map(|node| match node.event() {
+        Some(Event::Start(Tag::Link { dest_url, .. })) => dest_url
+            .strip_prefix('#')
+            .filter(|fragment| !fragment.is_empty())
+            .map(|fragment| (fragment, node)),
+        _ => None,
+    })
+}
+
+fn validate_links<'a>(
+    links: impl Iterator<Item = (&'a str, &'a Arc<CMarkItem>)>,
+    anchors: &[&[HeadingAnchor]],
+    issues: &mut Vec<FragmentLinkIssue>,
+) {
+    use std::string::ToString;
+
+    for (fragment, link) in links {
+        let mut is_resolved = false;
+        for document_anchors in anchors {
+            if document_anchors
+                .iter()
+                .any(|anchor| anchor.anchor == fragment)
+            {
+                is_resolved = true;
+                let headings: Vec<_> = document_anchors
+                    .iter()
+                    .filter(|anchor| anchor.base == fragment)
+                    .map(|anchor| Arc::clone(&anchor.node))
+                    .collect();
+                if headings.len() > 1 {
+                    issues.push(FragmentLinkIssue::Ambiguous {
+                        fragment: fragment.to_string(),
+                        link: Arc::clone(link),
+                        headings,
+                    });
+                }
+                break;
+            }
+        }
+        if !is_resolved {
+            issues.push(FragmentLinkIssue::Unresolved {
+                fragment: fragment.to_string(),
+                link: Arc::clone(link),
+            });
+        }
+    }
+}
+
+impl CMarkData {
+    /// Checks that every `#fragment` link resolves to a heading anchor.
+    ///
+    /// Anchors are generated from heading texts by the `heading_anchor` function.
+    /// If the other document is specified, its fragment links are checked too,
+    /// and the links of both documents may refer to the headings of either document.
+    /// Links that refer to a heading whose text is used by multiple headings
+    /// are reported as ambiguous.
+    pub fn validate_fragment_links(&self, other: Option<&CMarkData>) -> Vec<FragmentLinkIssue> {
+        let self_anchors = heading_anchors(self);
+        let other_anchors = other.map(heading_anchors).unwrap_or_default();
+        let anchors = [self_anchors.as_slice(), other_anchors.as_slice()];
+
+        let mut issues = Vec::new();
+        validate_links(fragment_links(self), &anchors, &mut issues);
+        if let Some(other) = other {
+            let anchors = [other_anchors.as_slice(), self_anchors.as_slice()];
+            validate_links(fragment_links(other), &anchors, &mut issues);
+        }
+        issues
+    }
+}
+
+impl CMarkData {
+    /// Replaces rustdoc heading anchors in fragment links with the GitHub ones.
+    ///
+    /// Anchors are generated from the heading texts of the same document,
+    /// fragments that do not match any heading are left unchanged.
+    pub fn use_github_heading_anchors(self) -> Self {
+        self.convert_heading_anchors(
+            rustdoc_heading_anchor,
+            heading_anchor,
+            "use_github_heading_anchors()",
+        )
+    }
+
+    /// Replaces GitHub heading anchors in fragment links with the rustdoc ones.
+    ///
+    /// Anchors are generated from the heading texts of the same document,
+    /// fragments that do not match any heading are left unchanged.
+    pub fn use_rustdoc_heading_anchors(self) -> Self {
+        self.convert_heading_anchors(
+            heading_anchor,
+            rustdoc_heading_anchor,
+            "use_rustdoc_heading_anchors()",
+        )
+    }
+
+    fn convert_heading_anchors(
+        self,
+        from: fn(&str) -> String,
+        to: fn(&str) -> String,
+        note: &'static str,
+    ) -> Self {
+        use std::borrow::Cow;
+
+        let anchors: Vec<(String, String)> = heading_anchors_with(&self, from)
+            .into_iter()
+            .zip(heading_anchors_with(&self, to))
+            .map(|(from, to)| (from.anchor, to.anchor))
+            .filter(|(from, to)| from != to)
+            .collect();
+        self.map_fragment_links(
+            |url| match anchors.iter().find(|(from, _)| *from == url[1..]) {
+                Some((_, to)) => Cow::from(["#", to].concat()),
+                None => Cow::from(url),
+            },
+            note,
+        )
+    }
+}
+
+/// Asserts that all fragment links of the given readme and docs
+/// resolve to the headings of either of them.
+#[cfg(feature = "codemap-diagnostic")]
+pub fn assert_fragment_links_valid<P1, P2, M1, M2>(
+    readme: &crate::CMarkReadme<P1, M1>,
+    docs: &crate::CMarkDocs<P2, M2>,
+) {
+    use crate::{CodemapFiles, MatchFailed};
+
+    let issues = readme.data().validate_fragment_links(Some(docs.data()));
+    if !issues.is_empty() {
+        let mut codemap_files = CodemapFiles::new();
+        let diags = issues
+            .iter()
+            .map(|issue| fragment_link_diagnostic(&mut codemap_files, issue))
+            .collect();
+        MatchFailed::from_diags(diags, Arc::new(codemap_files)).emit_to_stderr_colored();
+        panic!("{} invalid fragment link(s) found", issues.len());
+    }
+}
+
+#[cfg(feature = "codemap-diagnostic")]
+fn fragment_link_diagnostic(
+    codemap_files: &mut crate::CodemapFiles,
+    issue: &FragmentLinkIssue,
+) -> codemap_diagnostic::Diagnostic {
+    use crate::CodemapSpans;
+    use codemap_diagnostic::{Diagnostic, Level, SpanStyle};
+    use std::format;
+
+    let mut spans = CodemapSpans::span_labels_from(codemap_files, issue.link().spans());
+    let message = match issue {
+        FragmentLinkIssue::Unresolved { fragment, .. } => {
+            format!("fragment link `#{}` does not match any heading", fragment)
+        }
+        FragmentLinkIssue::Ambiguous {
+            fragment, headings, ..
+        } => {
+            let heading_spans = headings.iter().flat_map(|node| node.spans());
+            spans.extend(
+                CodemapSpans::span_labels_from(codemap_files, heading_spans)
+                    .into_iter()
+                    .map(|mut label| {
+                        label.style = SpanStyle::Secondary;
+                        label
+                    }),
+            );
+            format!(
+                "fragment link `#{}` is ambiguous, {} headings have the same text",
+                fragment,
+                headings.len()
+            )
+        }
+    };
+
+    Diagnostic {
+        level: Level::Error,
+        message,
+        code: None,
+        spans,
+    }
+}
+
+#[test]
+fn test_heading_anchor() {
+    assert_eq!(heading_anchor("Feature Flags"), "feature-flags");
+    assert_eq!(heading_anchor("Why `syn`?"), "why-syn");
+    assert_eq!(heading_anchor("snake_case and-dash"), "snake_case-and-dash");
+}
+
+#[test]
+fn test_validate_fragment_links() {
+    use crate::File;
+    use std::path::PathBuf;
+
+    let parse = |path: &str, text: &str| {
+        let file = File::from_path_and_text(PathBuf::from(path), text.into());
+        CMarkData::from_file(Arc::new(file))
+    };
+    let readme = parse(
+        "README.md",
+        "# Usage\n\n[a](#usage) [b](#usage-1) [c](#faq) [d](#license) [e](#missing)\n\n# Usage\n",
+    );
+    let docs = parse(
+        "lib.rs",
+        "# FAQ\n\n[f](#usage-1) [g](#nothing)\n\n# License\n",
+    );
+
+    let issues = readme.validate_fragment_links(Some(&docs));
+    let issues: Vec<_> = issues
+        .iter()
+        .map(|issue| match issue {
+            FragmentLinkIssue::Unresolved { fragment, .. } => ("unresolved", fragment.as_str()),
+            FragmentLinkIssue::Ambiguous { fragment, .. } => ("ambiguous", fragment.as_str()),
+        })
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            ("ambiguous", "usage"),
+            ("unresolved", "missing"),
+            ("unresolved", "nothing")
+        ]
+    );
+    assert_eq!(docs.validate_fragment_links(None).len(), 2);
+}
+
+#[test]
+fn test_convert_heading_anchors() {
+    use crate::File;
+    use std::path::PathBuf;
+
+    let parse = |path: &str, text: &str| {
+        let file = File::from_path_and_text(PathBuf::from(path), text.into());
+        CMarkData::from_file(Arc::new(file))
+    };
+    assert_eq!(heading_anchor("Über Flags"), "über-flags");
+    assert_eq!(heading_anchor("İstanbul"), "i\u{307}stanbul");
+    assert_eq!(rustdoc_heading_anchor("Über Flags"), "Über-flags");
+    assert_eq!(rustdoc_heading_anchor("Why `syn`?"), "why-syn");
+
+    let readme = parse(
+        "README.md",
+        "[see below](#über-flags) [usage](#usage) [x](#über-flags-1) [y](other.md#über-flags)\n\n\
+            # Usage\n\n# Über Flags\n\n# Über Flags\n",
+    );
+    let docs = parse(
+        "lib.rs",
+        "[see below](#Über-flags) [usage](#usage) [x](#Über-flags-1) [y](other.md#über-flags)\n\n\
+            # Usage\n\n# Über Flags\n\n# Über Flags\n",
+    );
+    assert_eq!(
+        readme.clone().use_rustdoc_heading_anchors().to_markdown(),
+        docs.clone().to_markdown()
+    );
+    assert_eq!(
+        docs.use_github_heading_anchors().to_markdown(),
+        readme.clone().to_markdown()
+    );
+
+    let unchanged = readme.clone().use_github_heading_anchors();
+    assert_eq!(unchanged, readme);
+}
@@ -0,0 +1,283 @@
+use core::mem::take;
+use std::borrow::Cow;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+use crate::{CMarkData, CMarkItem};
+
+impl CMarkData {
+    /// Removes badges embedded in headings, like in `# my-crate [![CI](...)](...)`.
+    ///
+    /// Images with urls matching `badge_url_patterns` are removed with their wrapping links.
+    /// The whitespace around removed badges is collapsed and the remaining heading text is merged,
+    /// so badges can be placed both before and after the title text.
+    /// Headings without badges are left as is.
+    /// A heading consisting only of badges becomes empty, see `empty_headings`.
+    pub fn remove_badges_from_headings(self) -> Self {
+        let patterns = crate::badge_url_patterns();
+        let is_badge = |url: &str| patterns.iter().any(|pattern| pattern.matches(url));
+
+        let mut result = Vec::new();
+        let mut heading = Vec::new();
+        for node in self.into_items() {
+            match node.event() {
+                Some(Event::Start(Tag::Heading { .. })) => heading.push(node),
+                Some(Event::End(TagEnd::Heading(_))) if !heading.is_empty() => {
+                    heading.push(node);
+                    result.extend(remove_heading_badges(take(&mut heading), &is_badge));
+                }
+                _ if !heading.is_empty() => heading.push(node),
+                _ => result.push(node),
+            }
+        }
+        result.append(&mut heading);
+        CMarkData::from_items(result)
+    }
+
+    /// Returns the start nodes of headings without any text.
+    pub fn empty_headings(&self) -> Vec<Arc<CMarkItem>> {
+        use crate::cmark_data::heading_text;
+
+        let items: Vec<_> = self.iter().cloned().collect();
+        items
+            .iter()
+            .enumerate()
+            .filter(|(idx, node)| {
+                matches!(node.event(), Some(Event::Start(Tag::Heading { .. })))
+                    && heading_text(&items[*idx..]).is_some_and(|text| text.trim().is_empty())
+            })
+            .map(|(_, node)| Arc::clone(node))
+            .collect()
+    }
+}
+
+const NOTE: &str = "remove_badges_from_headings()";
+
+/// Removes badges from the heading nodes including the heading start and end nodes.
+fn remove_heading_badges<F>(nodes: Vec<Arc<CMarkItem>>, is_badge: &F) -> Vec<Arc<CMarkItem>>
+where
+    F: Fn(&str) -> bool,
+{
+    use crate::CMarkItemAsRemoved;
+
+    let badges = badge_ranges(&nodes, is_badge);
+    if badges.is_empty() {
+        return nodes;
+    }
+
+    // Badges are replaced with removed nodes, so only texts, other inline events
+    // and removed nodes remain between the heading start and end nodes.
+    let mut inner = Vec::new();
+    let mut nodes = nodes.into_iter().enumerate().peekable();
+    let start = nodes.next().map(|(_, node)| node);
+    while let Some((idx, node)) = nodes.next() {
+        match badges.iter().find(|range| range.0 == idx) {
+            Some(&(_, end)) => {
+                let mut badge = std::vec![node];
+                while let Some((_, node)) = nodes.next_if(|(idx, _)| *idx <= end) {
+                    badge.push(node);
+                }
+                inner.push(badge.into_removed(Cow::from(NOTE)));
+            }
+            None => inner.push(node),
+        }
+    }
+    let end = inner.pop();
+
+    let first_live = inner.iter().position(|node| node.event().is_some());
+    let last_live = inner.iter().rposition(|node| node.event().is_some());
+    let mut result: Vec<_> = start.into_iter().collect();
+    let mut run: Vec<(usize, Arc<CMarkItem>)> = Vec::new();
+    for (idx, node) in inner.into_iter().enumerate() {
+        match node.event() {
+            Some(Event::Text(_)) => run.push((idx, node)),
+            None if !run.is_empty() => run.push((idx, node)),
+            None => result.push(node),
+            Some(_) => {
+                result.extend(merge_text_run(take(&mut run), first_live, last_live));
+                result.push(node);
+            }
+        }
+    }
+    result.extend(merge_text_run(run, first_live, last_live));
+    result.extend(end);
+    result
+}
+
+/// Returns the inclusive node index ranges of badges, images with badge urls,
+/// and links containing only a badge.
+fn badge_ranges<F>(nodes: &[Arc<CMarkItem>], is_badge: &F) -> Vec<(usize, usize)>
+where
+    F: Fn(&str) -> bool,
+{
+    let image_end = |start: usize| {
+        nodes[start..]
+            .iter()
+            .position(|node| matches!(node.event(), Some(Event::End(TagEnd::Image))))
+            .map(|len| start + len)
+    };
+    let is_badge_image = |idx: usize| match nodes[idx].event() {
+        Some(Event::Start(Tag::Image { dest_url, .. })) => is_badge(dest_url),
+        _ => false,
+    };
+    let next_live = |idx: usize| (idx..nodes.len()).find(|&idx| nodes[idx].event().is_some());
+
+    let mut ranges = Vec::new();
+    let mut idx = 0;
+    while idx < nodes.len() {
+        if let Some(Event::Start(Tag::Link { .. })) = nodes[idx].event() {
+            let range = next_live(idx + 1)
+                .filter(|&image| is_badge_image(image))
+                .and_then(image_end)
+                .and_then(|end| next_live(end + 1))
+                .filter(|&end| matches!(nodes[end].event(), Some(Event::End(TagEnd::Link))));
+            if let Some(end) = range {
+                ranges.push((idx, end));
+                idx = end + 1;
+                continue;
+            }
+        }
+        if is_badge_image(idx) {
+            if let Some(end) = image_end(idx) {
+                ranges.push((idx, end));
+                idx = end + 1;
+                continue;
+            }
+        }
+        idx += 1;
+    }
+    ranges
+}
+
+/// Merges the run of text nodes separated only by removed nodes into a single text node.
+///
+/// The whitespace around removed nodes is collapsed to a single space
+/// and the whitespace at the heading start and end is trimmed.
+fn merge_text_run(
+    run: Vec<(usize, Arc<CMarkItem>)>,
+    first_live: Option<usize>,
+    last_live: Option<usize>,
+) -> Vec<Arc<CMarkItem>> {
+    use crate::{CMarkItemAsModified, CMarkItemAsRemoved};
+    use pulldown_cmark::CowStr;
+
+    let texts: Vec<_> = run
+        .iter()
+        .filter(|(_, node)| node.event().is_some())
+        .map(|(idx, _)| *idx)
+        .collect();
+    let has_removed = run.iter().any(|(_, node)| node.event().is_none());
+    let (first, last) = match (texts.first(), texts.last()) {
+        (Some(&first), Some(&last))
+            if has_removed || Some(first) == first_live || Some(last) == last_live =>
+        {
+            (first, last)
+        }
+        _ => return run.into_iter().map(|(_, node)| node).collect(),
+    };
+
+    let mut text = String::new();
+    let mut is_after_removed = false;
+    for (_, node) in &run {
+        match node.event() {
+            Some(Event::Text(value)) => {
+                if is_after_removed {
+                    text.truncate(text.trim_end().len());
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text += value.trim_start();
+                } else {
+                    text += value;
+                }
+                is_after_removed = false;
+            }
+            _ => is_after_removed = true,
+        }
+    }
+    if Some(first) == first_live {
+        text = String::from(text.trim_start());
+    }
+    if Some(last) == last_live {
+        text.truncate(text.trim_end().len());
+    } else if is_after_removed && text.ends_with(char::is_whitespace) {
+        text.truncate(text.trim_end().len());
+        text.push(' ');
+    }
+
+    let (text_nodes, removed): (Vec<_>, Vec<_>) = run
+        .into_iter()
+        .map(|(_, node)| node)
+        .partition(|node| node.event().is_some());
+    let mut result = Vec::new();
+    if text.is_empty() {
+        result.push(text_nodes.into_removed(Cow::from(NOTE)));
+    } else {
+        let is_unchanged = text_nodes.len() == 1
+            && matches!(text_nodes[0].event(), Some(Event::Text(value)) if value.as_ref() == text);
+        if is_unchanged {
+            result.extend(text_nodes);
+        } else {
+            let event = Event::Text(CowStr::Boxed(text.into_boxed_str()));
+            result.push(text_nodes.into_modified(event, Cow::from(NOTE)));
+        }
+    }
+    result.extend(removed);
+    result
+}
+
+#[test]
+fn test_remove_badges_from_headings() {
+    use crate::test_utils::readme_data;
+
+    let badge = |name: &str| {
+        std::format!(
+            "[![{0}](https://img.shields.io/{0}.svg)](https://example.com/{0})",
+            name
+        )
+    };
+    let markdown = |text: &str| {
+        readme_data(text)
+            .remove_badges_from_headings()
+            .to_markdown()
+    };
+
+    assert_eq!(
+        markdown(&std::format!(
+            "# my-crate {} {}\n\nText.\n",
+            badge("ci"),
+            badge("crates")
+        )),
+        "# my-crate\n\nText.\n"
+    );
+    assert_eq!(
+        markdown(&std::format!("# {} my-crate\n", badge("ci"))),
+        "# my-crate\n"
+    );
+    assert_eq!(
+        markdown(
+            "## Usage ![ci](https://img.shields.io/ci.svg) `in` tests
+"
+        ),
+        "## Usage `in` tests\n"
+    );
+    assert_eq!(
+        markdown(&std::format!("# Usage {} of *it*\n", badge("ci"))),
+        "# Usage of *it*\n"
+    );
+    let unchanged = "# Logo ![logo](logo.png) [![x](x.png)](x)\n";
+    assert_eq!(
+        readme_data(unchanged).remove_badges_from_headings(),
+        readme_data(unchanged)
+    );
+
+    let data =
+        readme_data(&std::format!("# {}\n\nText.\n", badge("ci"))).remove_badges_from_headings();
+    assert_eq!(data.to_markdown(), "#\n\nText.\n");
+    let empty = data.empty_headings();
+    assert_eq!(empty.len(), 1);
+    assert_eq!(empty[0].spans()[0].range.start, 0);
+}
@@ -0,0 +1,208 @@
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+
+use crate::{CMarkData, CMarkDocs, CMarkItem, CMarkReadme};
+
+/// An image with its alt text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageAlt {
+    /// Image url.
+    pub url: String,
+    /// Image alt text with inline markup flattened to its text.
+    pub alt: String,
+    /// Image start node.
+    pub node: Arc<CMarkItem>,
+}
+
+/// An image alt text issue found by `check_image_alt_texts`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AltTextIssue {
+    /// The readme image has an empty alt text.
+    EmptyReadmeAlt(ImageAlt),
+    /// The docs image has an empty alt text.
+    EmptyDocsAlt(ImageAlt),
+    /// The paired readme and docs images have different alt texts.
+    DifferentAlts {
+        /// The readme image.
+        readme: ImageAlt,
+        /// The docs image.
+        docs: ImageAlt,
+    },
+    /// The readme image has no paired docs image.
+    ReadmeOnly(ImageAlt),
+    /// The docs image has no paired readme image.
+    DocsOnly(ImageAlt),
+}
+
+impl AltTextIssue {
+    /// Returns the issue images.
+    pub fn images(&self) -> Vec<&ImageAlt> {
+        match self {
+            Self::EmptyReadmeAlt(image)
+            | Self::EmptyDocsAlt(image)
+            | Self::ReadmeOnly(image)
+            | Self::DocsOnly(image) => std::vec![image],
+            Self::DifferentAlts { readme, docs } => std::vec![readme, docs],
+        }
+    }
+}
+
+/// Checks that the readme and docs images have the same non-empty alt texts.
+///
+/// Images are paired by their normalized urls first,
+/// and the remaining images are paired by their order within the sections with the same headings.
+pub fn check_image_alt_texts<P1, P2, M1, M2>(
+    readme: &CMarkReadme<P1, M1>,
+    docs: &CMarkDocs<P2, M2>,
+) -> Vec<AltTextIssue> {
+    let mut readme_images = images(readme.data());
+    let mut docs_images = images(docs.data());
+    let mut pairs = Vec::new();
+
+    // Pairs images with the same normalized urls in the order of their appearance.
+    let mut idx = 0;
+    while idx < readme_images.len() {
+        let url = normalize_url(&readme_images[idx].1.url);
+        match docs_images
+            .iter()
+            .position(|(_, image)| normalize_url(&image.url) == url)
+        {
+            Some(docs_idx) => pairs.push((readme_images.remove(idx), docs_images.remove(docs_idx))),
+            None => idx += 1,
+        }
+    }
+
+    // Pairs the remaining images by their order within the sections with the same headings.
+    let mut idx = 0;
+    while idx < readme_images.len() {
+        let section = &readme_images[idx].0;
+        match docs_images.iter().position(|(other, _)| other == section) {
+            Some(docs_idx) => pairs.push((readme_images.remove(idx), docs_images.remove(docs_idx))),
+            None => idx += 1,
+        }
+    }
+
+    let mut issues = Vec::new();
+    for ((_, readme), (_, docs)) in pairs {
+        match (readme.alt.trim().is_empty(), docs.alt.trim().is_empty()) {
+            (false, false) if readme.alt != docs.alt => {
+                issues.push(AltTextIssue::DifferentAlts { readme, docs });
+            }
+            (false, false) => {}
+            (is_readme_empty, is_docs_empty) => {
+                if is_readme_empty {
+                    issues.push(AltTextIssue::EmptyReadmeAlt(readme));
+                }
+                if is_docs_empty {
+                    issues.push(AltTextIssue::EmptyDocsAlt(docs));
+                }
+            }
+        }
+    }
+    issues.extend(
+        readme_images
+            .into_iter()
+            .map(|(_, image)| AltTextIssue::ReadmeOnly(image)),
+    );
+    issues.extend(
+        docs_images
+            .into_iter()
+            .map(|(_, image)| AltTextIssue::DocsOnly(image)),
+    );
+    issues
+}
+
+/// Returns the images with the breadcrumbs of their sections.
+fn images(data: &CMarkData) -> Vec<(String, ImageAlt)> {
+    use crate::baseline::sections;
+    use crate::cmark_data::tag_text;
+    use pulldown_cmark::Tag;
+
+    let mut images = Vec::new();
+    for (section, items) in sections(data) {
+        for (idx, node) in items.iter().enumerate() {
+            if let Some(Event::Start(Tag::Image { dest_url, .. })) = node.event() {
+                images.push((
+                    section.clone(),
+                    ImageAlt {
+                        url: String::from(&**dest_url),
+                        alt: tag_text(&items[idx..]).unwrap_or_default(),
+                        node: Arc::clone(node),
+                    },
+                ));
+            }
+        }
+    }
+    images
+}
+
+/// Returns the url without the scheme, the leading `./` and the trailing `/`.
+fn normalize_url(url: &str) -> &str {
+    let url = url.trim();
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let url = url.strip_prefix("./").unwrap_or(url);
+    url.strip_suffix('/').unwrap_or(url)
+}
+
+#[test]
+fn test_check_image_alt_texts() {
+    use crate::File;
+    use std::path::PathBuf;
+
+    let file = |path: &str, text: &str| {
+        Arc::new(File::from_path_and_text(PathBuf::from(path), text.into()))
+    };
+    let readme = CMarkReadme::from_file(file(
+        "README.md",
+        concat!(
+            "![Logo](http://example.com/logo.png)\n\n",
+            "# Usage\n\n",
+            "![Usage screenshot](assets/usage.png) ![](./empty.png)\n\n",
+            "![Only readme](readme.png)\n\n",
+            "# Other\n\n",
+            "![Other *text*](other.png)\n",
+        ),
+    ));
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        CMarkData::from_file(file(
+            "lib.rs",
+            concat!(
+                "![Logo](https://example.com/logo.png/)\n\n",
+                "# Usage\n\n",
+                "![Usage](https://raw.example.com/assets/usage.png) ![Empty](empty.png)\n\n",
+                "# Other\n\n",
+                "![Other text](other.png) ![Only docs](docs.png)\n",
+            ),
+        )),
+        (),
+        (),
+    );
+
+    let issues: Vec<_> = check_image_alt_texts(&readme, &docs)
+        .iter()
+        .map(|issue| match issue {
+            AltTextIssue::EmptyReadmeAlt(image) => std::format!("empty readme {}", image.url),
+            AltTextIssue::EmptyDocsAlt(image) => std::format!("empty docs {}", image.url),
+            AltTextIssue::DifferentAlts { readme, docs } => {
+                std::format!("different {:?} {:?}", readme.alt, docs.alt)
+            }
+            AltTextIssue::ReadmeOnly(image) => std::format!("readme only {}", image.url),
+            AltTextIssue::DocsOnly(image) => std::format!("docs only {}", image.url),
+        })
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            "empty readme ./empty.png",
+            "different \"Usage screenshot\" \"Usage\"",
+            "readme only readme.png",
+            "docs only docs.png",
+        ]
+    );
+}
@@ -48,7 +48,8 @@
 //!     let readme = readme
 //!         .remove_badges_paragraph()
 //!         .remove_documentation_section()
-//!         .remove_codeblock_tag("no_sync")
+//!         .remove_codeblocks_with_tag("no_sync")
+//!         .collapse_whitespace()
 //!         .disallow_absolute_repository_blob_links()
 //!         .unwrap()
 //!         .use_absolute_repository_blob_urls()
@@ -57,9 +58,11 @@
 //!     let docs = docs
 //!         .increment_heading_levels()
 //!         .add_package_title()
+//!         .remove_codeblocks_with_tag("no_sync")
 //!         .remove_codeblock_rust_test_tags()
 //!         .use_default_codeblock_rust_tag()
 //!         .remove_hidden_rust_code()
+//!         .collapse_whitespace()
 //!         .map_links(
 //!             |link| match link {
 //!                 "CMarkDocs::map_links" => "struct.CMarkDocs.html#method.map_links".into(),
@@ -78,6 +81,42 @@
 //! # */
 //! ```
 //!
+//! Code blocks that intentionally exist only in the readme or only in the docs,
+//! like shell commands in the readme or doctest-heavy examples in the docs,
+//! can be tagged with `no_sync` and removed on both sides
+//! with the `remove_codeblocks_with_tag("no_sync")` modifier.
+//!
+//! The same modifiers, except for the intra-doc links workaround,
+//! form the default pipeline of the `assert_sync_self` function,
+//! so the example above is equivalent to:
+//!
+//! ```rust,no_run
+//! # /*
+//! #[cfg(test)]
+//! #[test]
+//! # */
+//! fn readme_sync_test() {
+//!     use readme_sync::{assert_sync_self, PipelineSpec, PipelineStep};
+//!     use std::path::Path;
+//!
+//!     let mut pipeline = PipelineSpec::default();
+//!     let map_link = PipelineStep::MapLink {
+//!         from: "CMarkDocs::map_links".into(),
+//!         to: "struct.CMarkDocs.html#method.map_links".into(),
+//!     };
+//!     // Insert before the absolute docs urls steps.
+//!     pipeline.docs.insert(pipeline.docs.len() - 2, map_link);
+//!
+//!     assert_sync_self(Path::new(env!("CARGO_MANIFEST_DIR")), Some(pipeline));
+//! }
+//! ```
+//!
+//! Alternatively, the same modifiers can be listed in a `.readme-sync.toml` file
+//! placed in the package or workspace root directory,
+//! so the integration test is reduced to a single
+//! `run_sync_check_with_config_file(env!("CARGO_MANIFEST_DIR"))` call.
+//! See `SyncConfigFile` for the file format.
+//!
 //! Note that both `cargo build` and `cargo test` enable features from dev-dependencies,
 //! so if you want to test your crate without them (for example in `no_std` environment)
 //! you can use `readme-sync` with `default-features = false`.
@@ -86,25 +125,17 @@
 //!
 //! # Feature Flags
 //!
-//! - `codemap` (enabled by default): Enables `codemap` dependency and required
-//!   for `assert_sync` and other diagnostic functions.
-//! - `codemap-diagnostic` (enabled by default): Enables `codemap-diagnostic` dependency
-//!   and required for `assert_sync` and other diagnostic functions.
-//! - `glob` (enabled by default): Enables `gloc` dependency and required
-//!   for badges detection and methods like `CMarkReadme::remove_badges_paragraph`.
+//! - `codemap` (enabled by default): Enables `codemap` dependency and `CodemapFiles`.
+//! - `codemap-diagnostic` (enabled by default): Enables `codemap-diagnostic` dependency,
+//!   colored `MatchFailed` source excerpts and lint assertions like `assert_badges_current`.
+//!   Without it `assert_sync`, `check_sync` and `MatchFailed` are still available
+//!   and print lightweight uncolored source excerpts.
+//! - `glob`: Enables `glob` dependency and conversion of `glob::Pattern` into `WildcardPattern`.
+//!   Badges detection and methods like `CMarkReadme::remove_badges_paragraph`
+//!   use the internal wildcard matcher and do not require it.
+//! - `macros`: Enables the `embed` macro that embeds the readme and docs source texts
+//!   at compile time, for tests in sandboxes that deny reading the package files.
 //! - `platforms`: Enables `platforms` dependency and method `Config::with_target_arch_os_env`.
-//! - `proc-macro2` (enabled by default): Enables `proc-macro2` dependency
-//!   with `span-locations` feature that allows the crate
-//!   to show the errors location for source Rust files.
-//! - `pulldown-cmark` (enabled by default): Enables `pulldown-cmark` dependency
-//!   and required for almost everything except manifest
-//!   and documentation parsing and some utility functions.
-//! - `serde` (enabled by default): Enables `serde` dependency
-//!   and required for manifest deserializing.
-//! - `syn` (enabled by default): Enables `syn` dependency and required for documentation parsing.
-//! - `thiserror` (enabled by default): Enables `thiserror` dependency
-//!   and required by all functions and methods that can return errors.
-//! - `toml` (enabled by default): Enables `toml` dependency and required for manifest parsing.
 //!
 //! # Other crates
 //!
@@ -121,12 +152,16 @@
 //!
 //! Currently intra-doc link resolution is not supported.
 //! References to structures in the documentation can be changed with [`CMarkDocs::map_links`].
+//! Simple Rust paths can also be rewritten to rustdoc item urls
+//! with `CMarkDocs::rewrite_rust_path_links`, which reports the ambiguous ones.
+//! To compare only the link texts, use `flatten_links` for both readme and docs.
 //! The pulldown cmark also requires the link address to be specified.
 //!
-//! ## Why is the example integration test so long and there is no function that would do it all at once?
+//! ## Why is the example integration test so long?
 //!
 //! Readme and documentation transformations are very different
 //! between different crates and the API of this crate is not yet stabilized.
+//! The `assert_sync_self` function covers only the common case.
 //!
 //! At the moment, however, it supports extensive customization.
 //! You can specify the paths to readme and docs, their contents,
@@ -134,6 +169,22 @@
 //!
 //! So any feedback is welcome!
 //!
+//! ## How to prevent readme-sync dependency features enabled for dependencies of my crate?
+//!
+//! Cargo unifies the features of a package used both as a dependency and as a dev-dependency,
+//! so features required by `readme-sync` and its dependencies can be enabled
+//! for the dependencies of your crate during `cargo build` and `cargo test`.
+//! Use `resolver = "2"` in your `Cargo.toml` to keep dev-dependencies features apart
+//! from the normal build, and add `readme-sync` with `default-features = false`
+//! to test your crate with the minimal set of dependency features:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! readme-sync = { version = "0.3", default-features = false }
+//! ```
+//!
+//! The sync checks like `assert_sync` remain available without the default features.
+//!
 //! ## Why use `syn` instead of just parsing documentation comments?
 //!
 //! Because of `cfg` and `cfg_attr` that are useful for documentation tests
@@ -188,48 +239,143 @@
 
 extern crate std;
 
+mod badge_lint;
 mod badges;
+mod baseline;
+mod behavior_report;
+mod build_script;
 mod cmark_data;
 mod cmark_docs;
 mod cmark_item;
 mod cmark_readme;
-mod cmark_util;
+mod codeblock_lint;
+#[cfg(feature = "codemap")]
 mod codemap_files;
+#[cfg(feature = "codemap-diagnostic")]
 mod codemap_spans;
+mod compare;
 mod config;
 mod docs_parser;
+#[cfg(feature = "macros")]
+mod embed;
+pub mod events;
+mod excerpt_emitter;
 mod file;
 mod file_docs;
+mod fingerprint;
+mod fragment_links;
+mod heading_badges;
+mod image_alt_texts;
 mod manifest;
+mod name_mentions;
+mod normalize;
 mod package;
+mod parse_profile;
+mod pipeline;
+mod preflight;
+mod raw_image_urls;
+mod relative_links;
+mod removal_budget;
+mod render;
+mod reserved_headings;
+mod rust_path_links;
+mod source_location;
+mod source_metadata;
+mod strictness;
+mod structure;
+mod suggested_patch;
 mod sync;
+mod sync_config_file;
+mod tables;
 mod tags;
+#[cfg(test)]
+mod test_utils;
 mod text_source;
+mod title;
+mod toml_codeblocks;
+mod transform;
+mod url_rewrite;
 
-pub use badges::badge_url_patterns;
-pub use cmark_data::{CMarkData, CMarkDataIter, DisallowUrlsWithPrefixError};
+#[cfg(feature = "codemap-diagnostic")]
+pub use badge_lint::assert_badges_current;
+pub use badge_lint::{BadgeMismatch, BadgeWarning};
+pub use badges::{badge_url_patterns, WildcardPattern, WildcardPatternError};
+pub use baseline::{
+    check_sync_with_baseline, Baseline, BaselineEntry, BaselineError, BaselineMismatch,
+    BaselineOutcome,
+};
+pub use behavior_report::{behavior_report, BehaviorReport};
+pub use build_script::{emit_cargo_warnings, enforce_from_env, SyncEnforcement, ENFORCE_ENV_VAR};
+pub use cmark_data::{
+    CMarkData, CMarkDataIter, DisallowUrlsWithPrefixError, EmphasisKinds,
+    HeadingLevelOverflowError, LinkMeta,
+};
 pub use cmark_docs::CMarkDocs;
 pub use cmark_item::{
     CMarkItem, CMarkItemAsModified, CMarkItemAsRemoved, CMarkItemWithNote, CMarkSpan,
 };
 pub use cmark_readme::{CMarkReadme, CMarkReadmeFromPackageError};
+pub use codeblock_lint::{CodeblockWarning, SuspiciousCodeblock, DEFAULT_MAX_CODEBLOCK_LINES};
+#[cfg(feature = "codemap")]
 pub use codemap_files::CodemapFiles;
+#[cfg(feature = "codemap-diagnostic")]
 pub use codemap_spans::CodemapSpans;
+pub use compare::{compare_sync, EmitOptions, SyncMismatch};
 pub use config::Config;
 pub use docs_parser::{
     build_attr_docs, build_meta_docs, eval_cfg_predicate, BuildAttrDocsError, BuildMetaDocsError,
     EvalCfgPredicateError,
 };
 pub use docs_parser::{DocsItem, DocsSpan};
+#[cfg(feature = "macros")]
+pub use embed::{readme_and_docs_from_texts, EmbeddedReadmeAndDocs};
 pub use file::{File, FileFromPathError};
-pub use file_docs::{FileDocs, FileDocsFromFileError, TextRemap};
+pub use file_docs::{
+    ConflictingCfgDocs, DocsParseLimits, FileDocs, FileDocsFromFileError, SkippedDocs, TextRemap,
+};
+pub use fingerprint::SyncStamp;
+#[cfg(feature = "codemap-diagnostic")]
+pub use fragment_links::assert_fragment_links_valid;
+pub use fragment_links::{heading_anchor, rustdoc_heading_anchor, FragmentLinkIssue};
+pub use image_alt_texts::{check_image_alt_texts, AltTextIssue, ImageAlt};
 pub use manifest::{
-    BinPathError, Manifest, ManifestBinTarget, ManifestDocsRsMetadata, ManifestLibTarget,
-    ManifestPackage, ManifestReadmePath, TomlParseError, TomlReadError,
+    BinPathError, DocsUrlLayout, DocsUrlVersion, Manifest, ManifestBinTarget,
+    ManifestDocsRsMetadata, ManifestLibTarget, ManifestPackage, ManifestPackageMetadata,
+    ManifestReadmePath, ManifestReadmeSyncMetadata, TomlParseError, TomlReadError,
+    DEFAULT_BLOB_BRANCH,
 };
-pub use package::Package;
-pub use sync::{assert_sync, check_sync, CheckSyncError, MatchFailed};
-pub use tags::codeblock_rust_test_tags;
+#[cfg(feature = "codemap-diagnostic")]
+pub use name_mentions::assert_no_stale_names;
+pub use name_mentions::Mention;
+pub use package::{Package, PackageSearchAttempt, PackageSearchError};
+pub use parse_profile::ParseProfile;
+pub use pipeline::assert_sync_self;
+pub use pipeline::{PipelineError, PipelineSpec, PipelineStep};
+pub use preflight::{PreflightIssue, PreflightSeverity};
+pub use raw_image_urls::RawImageUrlsError;
+pub use relative_links::{DanglingLink, DanglingRelativeLinksError};
+pub use removal_budget::{RemovalBudget, RemovalBudgetError, RemovalMismatch};
+pub use render::render_markdown;
+#[cfg(feature = "codemap-diagnostic")]
+pub use reserved_headings::assert_no_reserved_headings;
+pub use reserved_headings::{ReservedHeadingWarning, RUSTDOC_RESERVED_HEADINGS};
+pub use rust_path_links::UnresolvedRustPath;
+pub use source_location::{LineCol, LocationRole, SourceLocation};
+pub use source_metadata::{FileMetadata, SourceMetadata};
+pub use strictness::SyncStrictness;
+pub use structure::StructureError;
+pub use suggested_patch::SuggestedPatch;
+pub use sync::{
+    assert_markdown_sync, assert_sync, assert_sync_with_label, check_leading_blocks_sync,
+    check_markdown_sync, check_sync, check_sync_with_metadata, check_sync_with_options,
+    CheckSyncError, MatchFailed, SyncOptions,
+};
+pub use sync_config_file::run_sync_check_with_config_file;
+pub use sync_config_file::{SyncConfigFile, SyncConfigFileError, SYNC_CONFIG_FILE_NAME};
+pub use tables::{TableCellSummary, TableSummary};
+pub use tags::{codeblock_rust_test_tags, is_codeblock_rust_error_code_tag, looks_like_rust};
 pub use text_source::TextSource;
-
-use cmark_util::IntoStatic;
+pub use title::{TitleError, TitlePolicy, TitlePredicate, TitleSource};
+pub use toml_codeblocks::InvalidTomlCodeblock;
+pub use transform::{CMarkTransform, IncrementHeadingLevels, RemoveTitle, TryCMarkTransform};
+pub use url_rewrite::UrlRewriteRules;
@@ -7,6 +7,42 @@ use std::vec::Vec;
 use serde::Deserialize;
 use thiserror::Error;
 
+/// The repository branch used in absolute repository blob urls by default.
+pub const DEFAULT_BLOB_BRANCH: &str = "master";
+
+/// The package version used in absolute docs.rs documentation urls.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum DocsUrlVersion {
+    /// The `*` version wildcard, or `latest` if the package documentation url is not specified.
+    #[default]
+    Wildcard,
+    /// The concrete version from the manifest `package.version` field,
+    /// like in the links generated by rustdoc on docs.rs.
+    Manifest,
+}
+
+/// The layout of the package documentation urls.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum DocsUrlLayout {
+    /// The docs.rs layout with the items located at `<documentation>/<version>/<crate_name>/`.
+    ///
+    /// The version is the `*` wildcard or the manifest package version.
+    /// The docs.rs documentation is used if `package.documentation` is not specified.
+    DocsRs(DocsUrlVersion),
+    /// The items are located directly at `<documentation>/`,
+    /// like in docs hosted on GitHub Pages or an internal server.
+    ///
+    /// The latest docs.rs documentation is used if `package.documentation` is not specified.
+    Flat,
+    /// The documentation url prefix template.
+    ///
+    /// The `{name}` placeholder is replaced with the package name,
+    /// the `{lib_name}` placeholder is replaced with the library crate name,
+    /// and the `{version}` placeholder is replaced with the package version,
+    /// for example `https://docs.example.com/{name}/{version}/{lib_name}/`.
+    Custom(String),
+}
+
 /// Package manifest.
 ///
 /// It includes only fields that are necessary for
@@ -46,6 +82,26 @@ pub struct ManifestPackage {
     pub readme: Option<ManifestReadmePath>,
     /// The `repository` field specifies a URL to the source repository for the package.
     pub repository: Option<String>,
+    /// The `[package.metadata]` table used to configure external tools.
+    pub metadata: Option<ManifestPackageMetadata>,
+}
+
+/// Package manifest `[package.metadata]` section.
+///
+/// See <https://doc.rust-lang.org/cargo/reference/manifest.html#the-metadata-table> for more details.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct ManifestPackageMetadata {
+    /// The `[package.metadata.readme-sync]` table.
+    #[serde(rename = "readme-sync")]
+    pub readme_sync: Option<ManifestReadmeSyncMetadata>,
+}
+
+/// Package manifest `[package.metadata.readme-sync]` section.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct ManifestReadmeSyncMetadata {
+    /// The repository branch used in absolute repository blob urls.
+    #[serde(rename = "default-branch")]
+    pub default_branch: Option<String>,
 }
 
 /// Package manifest `[lib]` section.
@@ -142,6 +198,7 @@ impl Manifest {
                 repository: None,
                 documentation: None,
                 readme: None,
+                metadata: None,
             },
             lib: None,
             bin: None,
@@ -151,6 +208,19 @@ impl Manifest {
         }
     }
 
+    /// Returns the repository branch used in absolute repository blob urls.
+    ///
+    /// It is specified by the `default-branch` key of the `[package.metadata.readme-sync]` table
+    /// and defaults to `DEFAULT_BLOB_BRANCH`.
+    pub fn repository_branch(&self) -> &str {
+        self.package
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.readme_sync.as_ref())
+            .and_then(|readme_sync| readme_sync.default_branch.as_deref())
+            .unwrap_or(DEFAULT_BLOB_BRANCH)
+    }
+
     /// Creates manifest from `Cargo.toml` file contents.
     pub fn from_cargo_toml_content(content: &str) -> Result<Self, TomlParseError> {
         Ok(toml::from_str(content)?)
@@ -162,15 +232,30 @@ impl Manifest {
             path: path.to_path_buf(),
             err,
         })?;
-        Self::from_cargo_toml_content(&content).map_err(|err| TomlReadError::ParseError {
-            path: path.to_path_buf(),
-            err,
+        Self::from_cargo_toml_content(&content).map_err(|err| {
+            if is_virtual_manifest(&content) {
+                TomlReadError::VirtualManifest {
+                    path: path.to_path_buf(),
+                }
+            } else {
+                TomlReadError::ParseError {
+                    path: path.to_path_buf(),
+                    err,
+                }
+            }
         })
     }
 
     /// Reads manifest from the `Cargo.toml` file in the specified package path.
     pub fn from_package_path(path: &Path) -> Result<Self, TomlReadError> {
-        Self::from_cargo_toml_path(&path.join("Cargo.toml"))
+        let manifest_path = path.join("Cargo.toml");
+        if !manifest_path.is_file() {
+            return Err(TomlReadError::ManifestNotFound {
+                path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+                candidates: package_path_candidates(path),
+            });
+        }
+        Self::from_cargo_toml_path(&manifest_path)
     }
 
     /// Returns package relative readme path.
@@ -205,6 +290,81 @@ impl Manifest {
         self.lib.as_ref().and_then(|lib| lib.doc).unwrap_or(true)
     }
 
+    /// Returns the library crate name.
+    ///
+    /// It is the library target name if specified,
+    /// otherwise it is the package name with dashes replaced by underscores.
+    pub fn lib_name(&self) -> String {
+        self.lib
+            .as_ref()
+            .and_then(|lib| lib.name.clone())
+            .unwrap_or_else(|| self.package.name.replace('-', "_"))
+    }
+
+    /// Returns the url prefix of the package documentation items.
+    ///
+    /// If `package.documentation` is not specified the latest docs.rs documentation is assumed,
+    /// like crates.io does.
+    /// The docs.rs documentation url is extended with the version wildcard and the crate name,
+    /// and any other documentation url is used as-is with a trailing slash.
+    pub fn docs_url_prefix(&self) -> String {
+        self.docs_url_prefix_with_version(DocsUrlVersion::Wildcard)
+    }
+
+    /// Returns the url prefix of the package documentation items
+    /// with the specified docs.rs documentation version.
+    ///
+    /// See `docs_url_prefix` for details.
+    pub fn docs_url_prefix_with_version(&self, version: DocsUrlVersion) -> String {
+        use crate::cmark_data::docs_path_prefix_with_version;
+        use std::format;
+
+        let version = match version {
+            DocsUrlVersion::Wildcard => None,
+            DocsUrlVersion::Manifest => Some(self.package.version.as_str()),
+        };
+        match &self.package.documentation {
+            Some(documentation) => docs_path_prefix_with_version(
+                &self.lib_name(),
+                documentation,
+                version.unwrap_or("*"),
+            ),
+            None => format!(
+                "https://docs.rs/{}/{}/{}/",
+                self.package.name,
+                version.unwrap_or("latest"),
+                self.lib_name()
+            ),
+        }
+    }
+
+    /// Returns the url prefix of the package documentation items with the specified layout.
+    ///
+    /// See `DocsUrlLayout` for the layouts details.
+    pub fn docs_url_prefix_with_layout(&self, layout: &DocsUrlLayout) -> String {
+        use crate::cmark_data::without_trailing_slash;
+
+        match (layout, &self.package.documentation) {
+            (DocsUrlLayout::Custom(template), _) => template
+                .replace("{name}", &self.package.name)
+                .replace("{lib_name}", &self.lib_name())
+                .replace("{version}", &self.package.version),
+            (DocsUrlLayout::DocsRs(version), None) => self.docs_url_prefix_with_version(*version),
+            (DocsUrlLayout::DocsRs(version), Some(documentation)) => {
+                let version = match version {
+                    DocsUrlVersion::Wildcard => "*",
+                    DocsUrlVersion::Manifest => &self.package.version,
+                };
+                let documentation = without_trailing_slash(documentation);
+                [documentation, "/", version, "/", &self.lib_name(), "/"].concat()
+            }
+            (DocsUrlLayout::Flat, None) => self.docs_url_prefix(),
+            (DocsUrlLayout::Flat, Some(documentation)) => {
+                [without_trailing_slash(documentation), "/"].concat()
+            }
+        }
+    }
+
     /// Returns package relative library file path.
     ///
     /// See <https://doc.rust-lang.org/cargo/commands/cargo-doc.html> for more details.
@@ -352,9 +512,67 @@ pub enum TomlParseError {
     ParseError(#[from] toml::de::Error),
 }
 
+/// The maximum number of package path candidates stored in `TomlReadError::ManifestNotFound`.
+const MAX_PACKAGE_PATH_CANDIDATES: usize = 4;
+
+/// Returns `true` if the manifest contains `[workspace]` section but no `[package]` section.
+fn is_virtual_manifest(content: &str) -> bool {
+    toml::from_str::<toml::Table>(content)
+        .is_ok_and(|table| table.contains_key("workspace") && !table.contains_key("package"))
+}
+
+/// Returns the subdirectories of the specified path that contain `Cargo.toml`.
+fn package_path_candidates(path: &Path) -> Vec<PathBuf> {
+    let mut candidates: Vec<_> = match std::fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join("Cargo.toml").is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    candidates.sort();
+    candidates.truncate(MAX_PACKAGE_PATH_CANDIDATES);
+    candidates
+}
+
+fn package_path_candidates_hint(candidates: &[PathBuf]) -> String {
+    use std::format;
+
+    if candidates.is_empty() {
+        String::new()
+    } else {
+        let candidates: Vec<_> = candidates
+            .iter()
+            .map(|path| format!("`{}`", path.display()))
+            .collect();
+        format!(", did you mean one of: {}", candidates.join(", "))
+    }
+}
+
 /// An error which can occur when reading manifest from the specified file path.
 #[derive(Debug, Error)]
 pub enum TomlReadError {
+    /// The package path does not contain `Cargo.toml`.
+    #[error(
+        "`Cargo.toml` not found in `{path}`{}",
+        package_path_candidates_hint(.candidates)
+    )]
+    ManifestNotFound {
+        /// The canonicalized package path if it exists.
+        path: PathBuf,
+        /// Up to a few subdirectories containing `Cargo.toml`.
+        candidates: Vec<PathBuf>,
+    },
+    /// The manifest is a virtual workspace manifest.
+    #[error(
+        "`{path}` is a virtual workspace manifest without `[package]` section, \
+         pass a workspace member package path instead"
+    )]
+    VirtualManifest {
+        /// Manifest file path.
+        path: PathBuf,
+    },
     /// File reading failed.
     #[error("Failed to read toml at `{path}`: {err}")]
     IoError {
@@ -383,3 +601,130 @@ pub enum BinPathError {
     #[error("Binary `{0}` specified more than once.")]
     SpecifiedMoreThanOnce(String),
 }
+
+#[test]
+fn test_docs_url_prefix() {
+    let prefix = |fields: &str| {
+        Manifest::from_cargo_toml_content(&std::format!(
+            "[package]\nname = \"foo-bar\"\nversion = \"0.1.0\"\n{}",
+            fields
+        ))
+        .unwrap()
+        .docs_url_prefix()
+    };
+
+    assert_eq!(prefix(""), "https://docs.rs/foo-bar/latest/foo_bar/");
+    assert_eq!(
+        prefix("[lib]\nname = \"foo\"\n"),
+        "https://docs.rs/foo-bar/latest/foo/"
+    );
+    for documentation in ["https://docs.rs/foo-bar", "https://docs.rs/foo-bar/"] {
+        assert_eq!(
+            prefix(&std::format!("documentation = \"{}\"", documentation)),
+            "https://docs.rs/foo-bar/*/foo_bar/"
+        );
+    }
+    assert_eq!(
+        prefix("documentation = \"https://docs.rs/foo-bar\"\n[lib]\nname = \"foo\"\n"),
+        "https://docs.rs/foo-bar/*/foo/"
+    );
+    for documentation in [
+        "https://user.github.io/book",
+        "https://user.github.io/book/",
+    ] {
+        assert_eq!(
+            prefix(&std::format!("documentation = \"{}\"", documentation)),
+            "https://user.github.io/book/"
+        );
+    }
+    assert_eq!(prefix("documentation = \"book/\""), "book/");
+
+    let versioned_prefix = |fields: &str| {
+        Manifest::from_cargo_toml_content(&std::format!(
+            "[package]\nname = \"foo-bar\"\nversion = \"1.2.3\"\n{}",
+            fields
+        ))
+        .unwrap()
+        .docs_url_prefix_with_version(DocsUrlVersion::Manifest)
+    };
+    assert_eq!(
+        versioned_prefix(""),
+        "https://docs.rs/foo-bar/1.2.3/foo_bar/"
+    );
+    assert_eq!(
+        versioned_prefix("documentation = \"https://docs.rs/foo-bar\""),
+        "https://docs.rs/foo-bar/1.2.3/foo_bar/"
+    );
+    assert_eq!(
+        versioned_prefix("documentation = \"https://user.github.io/book\""),
+        "https://user.github.io/book/"
+    );
+}
+
+#[test]
+fn test_docs_url_prefix_with_layout() {
+    let prefix = |fields: &str, layout: DocsUrlLayout| {
+        Manifest::from_cargo_toml_content(&std::format!(
+            "[package]\nname = \"foo-bar\"\nversion = \"1.2.3\"\n{}",
+            fields
+        ))
+        .unwrap()
+        .docs_url_prefix_with_layout(&layout)
+    };
+    let pages = "documentation = \"https://user.github.io/foo-bar/\"";
+
+    assert_eq!(
+        prefix(pages, DocsUrlLayout::DocsRs(DocsUrlVersion::Wildcard)),
+        "https://user.github.io/foo-bar/*/foo_bar/"
+    );
+    assert_eq!(
+        prefix(pages, DocsUrlLayout::DocsRs(DocsUrlVersion::Manifest)),
+        "https://user.github.io/foo-bar/1.2.3/foo_bar/"
+    );
+    assert_eq!(
+        prefix("", DocsUrlLayout::DocsRs(DocsUrlVersion::Manifest)),
+        "https://docs.rs/foo-bar/1.2.3/foo_bar/"
+    );
+    assert_eq!(
+        prefix(
+            &std::format!("{}\n[lib]\nname = \"foo\"", pages),
+            DocsUrlLayout::DocsRs(DocsUrlVersion::Wildcard)
+        ),
+        "https://user.github.io/foo-bar/*/foo/"
+    );
+    assert_eq!(
+        prefix(pages, DocsUrlLayout::Flat),
+        "https://user.github.io/foo-bar/"
+    );
+    assert_eq!(
+        prefix("", DocsUrlLayout::Flat),
+        "https://docs.rs/foo-bar/latest/foo_bar/"
+    );
+    assert_eq!(
+        prefix(
+            pages,
+            DocsUrlLayout::Custom("https://docs.example.com/{name}/{version}/{lib_name}/".into())
+        ),
+        "https://docs.example.com/foo-bar/1.2.3/foo_bar/"
+    );
+}
+
+#[test]
+fn test_repository_branch() {
+    let branch = |fields: &str| -> String {
+        Manifest::from_cargo_toml_content(&std::format!(
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n{}",
+            fields
+        ))
+        .unwrap()
+        .repository_branch()
+        .into()
+    };
+
+    assert_eq!(branch(""), "master");
+    assert_eq!(branch("[package.metadata.other]\nkey = 1\n"), "master");
+    assert_eq!(
+        branch("[package.metadata.readme-sync]\ndefault-branch = \"main\"\n"),
+        "main"
+    );
+}
@@ -0,0 +1,165 @@
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+
+use crate::{CMarkData, CMarkItem};
+
+/// A package name mention found in a text, an inline code or a code block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mention {
+    /// The searched name.
+    pub name: String,
+    /// The mentioned text, it may use underscores instead of dashes and vice versa.
+    pub matched: String,
+    /// The byte offset of the mention in the event text.
+    pub offset: usize,
+    /// The trimmed line of the event text containing the mention.
+    pub context: String,
+    /// The text or code node containing the mention.
+    pub node: Arc<CMarkItem>,
+}
+
+impl CMarkData {
+    /// Returns all mentions of the specified package names
+    /// in texts, inline codes and code blocks.
+    ///
+    /// Names are matched case-sensitively at word boundaries,
+    /// where dashes and underscores are considered as parts of words.
+    /// Both dash and underscore variants of every name are searched,
+    /// so `foo-client` also matches `foo_client` in Rust code.
+    pub fn find_package_name_mentions(&self, names: &[&str]) -> Vec<Mention> {
+        let mut mentions = Vec::new();
+        for node in self.iter() {
+            let text = match node.event() {
+                Some(Event::Text(text) | Event::Code(text)) => text,
+                _ => continue,
+            };
+            let start = mentions.len();
+            for name in names {
+                for variant in name_variants(name) {
+                    for (offset, matched) in text.match_indices(variant.as_str()) {
+                        if is_word_boundary(text, offset, offset + matched.len()) {
+                            mentions.push(Mention {
+                                name: String::from(*name),
+                                matched: String::from(matched),
+                                offset,
+                                context: line_context(text, offset),
+                                node: Arc::clone(node),
+                            });
+                        }
+                    }
+                }
+            }
+            mentions[start..].sort_by_key(|mention| mention.offset);
+        }
+        mentions
+    }
+}
+
+/// Asserts that the given readme and docs do not mention any of the specified retired names.
+///
+/// See `CMarkData::find_package_name_mentions` for the matching rules.
+#[cfg(feature = "codemap-diagnostic")]
+pub fn assert_no_stale_names<P1, P2, M1, M2>(
+    readme: &crate::CMarkReadme<P1, M1>,
+    docs: &crate::CMarkDocs<P2, M2>,
+    old_names: &[&str],
+) {
+    use crate::{CodemapFiles, MatchFailed};
+
+    let mentions: Vec<_> = [("readme", readme.data()), ("docs", docs.data())]
+        .into_iter()
+        .flat_map(|(side, data)| {
+            data.find_package_name_mentions(old_names)
+                .into_iter()
+                .map(move |mention| (side, mention))
+        })
+        .collect();
+    if !mentions.is_empty() {
+        let mut codemap_files = CodemapFiles::new();
+        let diags = mentions
+            .iter()
+            .map(|(side, mention)| mention_diagnostic(&mut codemap_files, side, mention))
+            .collect();
+        MatchFailed::from_diags(diags, Arc::new(codemap_files)).emit_to_stderr_colored();
+        panic!("{} stale name mention(s) found", mentions.len());
+    }
+}
+
+#[cfg(feature = "codemap-diagnostic")]
+fn mention_diagnostic(
+    codemap_files: &mut crate::CodemapFiles,
+    side: &str,
+    mention: &Mention,
+) -> codemap_diagnostic::Diagnostic {
+    use crate::CodemapSpans;
+    use codemap_diagnostic::{Diagnostic, Level};
+    use std::format;
+
+    Diagnostic {
+        level: Level::Error,
+        message: format!(
+            "{} mentions retired name `{}` as `{}` in: {}",
+            side, mention.name, mention.matched, mention.context
+        ),
+        code: None,
+        spans: CodemapSpans::span_labels_from(codemap_files, mention.node.spans()),
+    }
+}
+
+/// Returns the name itself and its variants with dashes and underscores swapped.
+fn name_variants(name: &str) -> Vec<String> {
+    let mut variants = std::vec![
+        String::from(name),
+        name.replace('-', "_"),
+        name.replace('_', "-")
+    ];
+    variants.retain(|variant| !variant.is_empty());
+    variants.sort();
+    variants.dedup();
+    variants
+}
+
+fn is_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let is_word_char = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '-';
+    !text[..start].chars().next_back().is_some_and(is_word_char)
+        && !text[end..].chars().next().is_some_and(is_word_char)
+}
+
+fn line_context(text: &str, offset: usize) -> String {
+    let start = text[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+    let end = text[offset..]
+        .find('\n')
+        .map_or(text.len(), |pos| offset + pos);
+    String::from(text[start..end].trim())
+}
+
+#[test]
+fn test_find_package_name_mentions() {
+    use crate::test_utils::readme_data;
+
+    let data = readme_data(concat!(
+        "# foo-client\n\n",
+        "Use `foo-client` or foo-client-ext, not my-foo-client.\n\n",
+        "```toml\n[dependencies]\nfoo-client = \"1.0\"\n```\n\n",
+        "```rust\nuse foo_client::Client;\n```\n",
+    ));
+
+    let mentions: Vec<_> = data
+        .find_package_name_mentions(&["foo-client"])
+        .into_iter()
+        .map(|mention| (mention.matched, mention.context))
+        .collect();
+    assert_eq!(
+        mentions,
+        [
+            ("foo-client".into(), "foo-client".into()),
+            ("foo-client".into(), "foo-client".into()),
+            ("foo-client".into(), "foo-client = \"1.0\"".into()),
+            ("foo_client".into(), "use foo_client::Client;".into()),
+        ]
+    );
+    assert!(data.find_package_name_mentions(&["foo"]).is_empty());
+}
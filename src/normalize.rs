@@ -0,0 +1,223 @@
+use std::borrow::Cow;
+use std::string::String;
+
+use pulldown_cmark::{CowStr, Event, LinkType, Tag};
+
+use crate::CMarkData;
+
+impl CMarkData {
+    /// Replace soft line breaks with spaces and concatenate the adjacent text events.
+    ///
+    /// Paragraphs wrapped at different columns become the same.
+    pub fn collapse_soft_breaks(self) -> Self {
+        use crate::CMarkItemAsModified;
+
+        self.map(|node| match node.event() {
+            Some(Event::SoftBreak) => node.into_modified(
+                Event::Text(CowStr::Borrowed(" ")),
+                Cow::from("collapse_soft_breaks()"),
+            ),
+            _ => node,
+        })
+        .concat_texts()
+    }
+
+    /// Replace hard line breaks with soft line breaks.
+    ///
+    /// Lines ended with a backslash or two spaces become the same as the usual lines.
+    pub fn use_soft_breaks(self) -> Self {
+        use crate::CMarkItemAsModified;
+
+        self.map(|node| match node.event() {
+            Some(Event::HardBreak) => {
+                node.into_modified(Event::SoftBreak, Cow::from("use_soft_breaks()"))
+            }
+            _ => node,
+        })
+    }
+
+    /// Replace typographic quotes, dashes and ellipses in texts with their ASCII equivalents.
+    ///
+    /// It reverts the smart punctuation, so `“quoted” — text…` becomes `"quoted" --- text...`.
+    /// Code blocks, code spans and HTML are left as is.
+    pub fn normalize_punctuation(self) -> Self {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::TagEnd;
+
+        let mut is_codeblock = false;
+        self.map(|node| {
+            match node.event() {
+                Some(Event::Start(Tag::CodeBlock(_))) => is_codeblock = true,
+                Some(Event::End(TagEnd::CodeBlock)) => is_codeblock = false,
+                Some(Event::Text(text)) if !is_codeblock => {
+                    if let Some(normalized) = ascii_punctuation(text) {
+                        return node.into_modified(
+                            Event::Text(CowStr::Boxed(normalized.into_boxed_str())),
+                            Cow::from("normalize_punctuation()"),
+                        );
+                    }
+                }
+                _ => {}
+            }
+            node
+        })
+    }
+
+    /// Use the inline link type for all links and images.
+    ///
+    /// Reference links, collapsed and shortcut links and autolinks
+    /// become the same as the inline links with the same destination.
+    pub fn normalize_link_types(self) -> Self {
+        self.map_link_tags("normalize_link_types()", |link_type, _, id| {
+            *link_type = LinkType::Inline;
+            *id = CowStr::Borrowed("");
+        })
+    }
+
+    /// Remove the titles of all links and images, like `title` in `[text](url "title")`.
+    pub fn remove_link_titles(self) -> Self {
+        self.map_link_tags("remove_link_titles()", |_, title, _| {
+            *title = CowStr::Borrowed("");
+        })
+    }
+
+    /// Remove custom ids, classes and attributes of all headings, like `# Title {#id .class}`.
+    pub fn remove_heading_attributes(self) -> Self {
+        use crate::CMarkItemAsModified;
+
+        self.map(|node| match node.event() {
+            Some(Event::Start(Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            })) if id.is_some() || !classes.is_empty() || !attrs.is_empty() => {
+                let event = Event::Start(Tag::Heading {
+                    level: *level,
+                    id: None,
+                    classes: std::vec::Vec::new(),
+                    attrs: std::vec::Vec::new(),
+                });
+                node.into_modified(event, Cow::from("remove_heading_attributes()"))
+            }
+            _ => node,
+        })
+    }
+
+    fn map_link_tags<F>(self, note: &'static str, mut func: F) -> Self
+    where
+        F: FnMut(&mut LinkType, &mut CowStr<'static>, &mut CowStr<'static>),
+    {
+        use crate::CMarkItemAsModified;
+
+        self.map(|node| {
+            let tag = match node.event() {
+                Some(Event::Start(tag @ (Tag::Link { .. } | Tag::Image { .. }))) => tag.clone(),
+                _ => return node,
+            };
+            let original = tag.clone();
+            let tag = match tag {
+                Tag::Link {
+                    mut link_type,
+                    dest_url,
+                    mut title,
+                    mut id,
+                } => {
+                    func(&mut link_type, &mut title, &mut id);
+                    Tag::Link {
+                        link_type,
+                        dest_url,
+                        title,
+                        id,
+                    }
+                }
+                Tag::Image {
+                    mut link_type,
+                    dest_url,
+                    mut title,
+                    mut id,
+                } => {
+                    func(&mut link_type, &mut title, &mut id);
+                    Tag::Image {
+                        link_type,
+                        dest_url,
+                        title,
+                        id,
+                    }
+                }
+                tag => tag,
+            };
+            if tag == original {
+                node
+            } else {
+                node.into_modified(Event::Start(tag), Cow::from(note))
+            }
+        })
+    }
+}
+
+/// Returns the text with the typographic punctuation replaced by ASCII one if it is changed.
+fn ascii_punctuation(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\u{2018}' | '\u{2019}' => result.push('\''),
+            '\u{201C}' | '\u{201D}' => result.push('"'),
+            '\u{2013}' => result.push_str("--"),
+            '\u{2014}' => result.push_str("---"),
+            '\u{2026}' => result.push_str("..."),
+            _ => result.push(ch),
+        }
+    }
+    (result != text).then_some(result)
+}
+
+#[test]
+fn test_normalize_events() {
+    use crate::test_utils::{readme_data, readme_file};
+    use crate::TextSource;
+    use pulldown_cmark::Options;
+
+    let events = |data: CMarkData| {
+        data.iter_events()
+            .map(|event| event.clone().into_static())
+            .collect::<std::vec::Vec<_>>()
+    };
+
+    assert_eq!(
+        events(
+            readme_data("Some\ntext\\\nhere.\n")
+                .use_soft_breaks()
+                .collapse_soft_breaks()
+        ),
+        events(readme_data("Some text here.\n"))
+    );
+    assert_eq!(
+        events(readme_data("It’s “quoted” – text — here…\n").normalize_punctuation()),
+        events(readme_data("It's \"quoted\" -- text --- here...\n"))
+    );
+    assert_eq!(
+        events(readme_data("```\n“code”\n```\n").normalize_punctuation()),
+        events(readme_data("```\n“code”\n```\n"))
+    );
+    assert_eq!(
+        events(readme_data("[a][r] <https://b.c>\n\n[r]: https://a.b\n").normalize_link_types()),
+        events(readme_data("[a](https://a.b) [https://b.c](https://b.c)\n"))
+    );
+    assert_eq!(
+        events(readme_data("[a](https://a.b \"title\")\n").remove_link_titles()),
+        events(readme_data("[a](https://a.b)\n"))
+    );
+    let heading = CMarkData::from_text_source_with_options(
+        TextSource::File(readme_file("# Title {#id .class}\n")),
+        Options::ENABLE_HEADING_ATTRIBUTES,
+    );
+    assert_ne!(events(heading.clone()), events(readme_data("# Title\n")));
+    assert_eq!(
+        events(heading.remove_heading_attributes()),
+        events(readme_data("# Title\n"))
+    );
+}
@@ -1,4 +1,7 @@
 use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use thiserror::Error;
 
 use crate::{Manifest, TomlReadError};
 
@@ -13,6 +16,9 @@ pub struct Package {
 
 impl Package {
     /// Creates a package from the specified path.
+    ///
+    /// Returns `TomlReadError::ManifestNotFound` if the path does not contain `Cargo.toml`
+    /// and `TomlReadError::VirtualManifest` if the path is a virtual workspace root.
     pub fn from_path(path: PathBuf) -> Result<Self, TomlReadError> {
         Ok(Self {
             manifest: Manifest::from_package_path(&path)?,
@@ -20,6 +26,85 @@ impl Package {
         })
     }
 
+    /// Finds the package without the compile-time `env!("CARGO_MANIFEST_DIR")` macro.
+    ///
+    /// It is intended for build systems other than cargo, like Bazel or Buck,
+    /// that compile tests without cargo environment variables.
+    /// The package is searched in the following order:
+    /// - the `CARGO_MANIFEST_DIR` environment variable read at runtime,
+    /// - the current directory and its ancestors,
+    /// - the current executable directory and its ancestors.
+    ///
+    /// The first directory containing `Cargo.toml` with a `[package]` section is used.
+    /// Under such build systems the test should be run with the working directory
+    /// inside the package, or with `CARGO_MANIFEST_DIR` set explicitly:
+    ///
+    /// ```rust,no_run
+    /// use readme_sync::{assert_sync, CMarkDocs, CMarkReadme, Config, Package};
+    ///
+    /// let package = Package::from_env_or_search().unwrap();
+    /// let readme = CMarkReadme::from_package(&package).unwrap();
+    /// let docs = CMarkDocs::from_package_and_config(&package, &Config::default()).unwrap();
+    /// assert_sync(&readme, &docs);
+    /// ```
+    ///
+    /// Returns `PackageSearchError` with every tried location if the package is not found.
+    pub fn from_env_or_search() -> Result<Self, PackageSearchError> {
+        let mut attempts = Vec::new();
+        let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR");
+        if let Some(manifest_dir) = &manifest_dir {
+            match Self::from_path(PathBuf::from(manifest_dir)) {
+                Ok(package) => return Ok(package),
+                Err(err) => attempts.push(PackageSearchAttempt {
+                    origin: "CARGO_MANIFEST_DIR",
+                    path: PathBuf::from(manifest_dir),
+                    err,
+                }),
+            }
+        }
+
+        let current_dir = std::env::current_dir().ok();
+        let current_exe = std::env::current_exe().ok();
+        let start_dirs = [
+            ("current directory", current_dir.as_deref()),
+            (
+                "executable directory",
+                current_exe.as_deref().and_then(Path::parent),
+            ),
+        ];
+        for (origin, start_dir) in start_dirs {
+            for path in start_dir.into_iter().flat_map(Path::ancestors) {
+                if attempts
+                    .iter()
+                    .any(|attempt: &PackageSearchAttempt| attempt.path == path)
+                {
+                    continue;
+                }
+                let result = if path.join("Cargo.toml").is_file() {
+                    Self::from_path(path.to_path_buf())
+                } else {
+                    Err(TomlReadError::ManifestNotFound {
+                        path: path.to_path_buf(),
+                        candidates: Vec::new(),
+                    })
+                };
+                match result {
+                    Ok(package) => return Ok(package),
+                    Err(err) => attempts.push(PackageSearchAttempt {
+                        origin,
+                        path: path.to_path_buf(),
+                        err,
+                    }),
+                }
+            }
+        }
+
+        Err(PackageSearchError {
+            is_manifest_dir_set: manifest_dir.is_some(),
+            attempts,
+        })
+    }
+
     /// Creates a package from the manifest and package path.
     pub fn from_manifest_and_path(manifest: Manifest, path: PathBuf) -> Self {
         Self { manifest, path }
@@ -45,3 +130,104 @@ impl Package {
         Manifest::default_readme_filename(&self.path)
     }
 }
+
+/// An error which can occur when searching for the package with `Package::from_env_or_search`.
+#[derive(Debug, Error)]
+#[error(
+    "Package not found{}, tried:{}",
+    if *.is_manifest_dir_set { "" } else { " (`CARGO_MANIFEST_DIR` is not set)" },
+    package_search_attempts_list(.attempts)
+)]
+pub struct PackageSearchError {
+    /// Whether the `CARGO_MANIFEST_DIR` environment variable is set.
+    pub is_manifest_dir_set: bool,
+    /// Every tried location in the search order.
+    pub attempts: Vec<PackageSearchAttempt>,
+}
+
+/// A location tried by `Package::from_env_or_search`.
+#[derive(Debug)]
+pub struct PackageSearchAttempt {
+    /// The location origin, like `CARGO_MANIFEST_DIR` or `current directory`.
+    pub origin: &'static str,
+    /// The tried package path.
+    pub path: PathBuf,
+    /// The package reading error.
+    pub err: TomlReadError,
+}
+
+fn package_search_attempts_list(attempts: &[PackageSearchAttempt]) -> std::string::String {
+    use std::format;
+
+    attempts
+        .iter()
+        .map(|attempt| {
+            format!(
+                "\n- {} `{}`: {}",
+                attempt.origin,
+                attempt.path.display(),
+                attempt.err
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_from_path_misuse() {
+    use std::fs;
+    use std::string::ToString;
+
+    let root = std::env::temp_dir().join(std::format!(
+        "readme-sync-test-package-from-path-{}",
+        std::process::id()
+    ));
+    let package = |path: &str| {
+        let path = root.join(path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(
+            path.join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+    };
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"foo\", \"bar\"]\n",
+    )
+    .unwrap();
+    package("foo");
+    package("bar");
+    fs::create_dir_all(root.join("docs")).unwrap();
+
+    let err = Package::from_path(root.clone()).unwrap_err();
+    assert!(
+        matches!(&err, TomlReadError::VirtualManifest { path } if path == &root.join("Cargo.toml")),
+        "{:?}",
+        err
+    );
+    assert!(err.to_string().contains("virtual workspace manifest"));
+
+    fs::remove_file(root.join("Cargo.toml")).unwrap();
+    match Package::from_path(root.clone()).unwrap_err() {
+        TomlReadError::ManifestNotFound { path, candidates } => {
+            assert_eq!(path, root.canonicalize().unwrap());
+            assert_eq!(candidates, [root.join("bar"), root.join("foo")]);
+        }
+        err => panic!("{:?}", err),
+    }
+    let message = Package::from_path(root.clone()).unwrap_err().to_string();
+    assert!(message.contains("did you mean one of"), "{}", message);
+    assert!(message.contains("bar"), "{}", message);
+
+    match Package::from_path(root.join("missing")).unwrap_err() {
+        TomlReadError::ManifestNotFound { path, candidates } => {
+            assert_eq!(path, root.join("missing"));
+            assert!(candidates.is_empty());
+        }
+        err => panic!("{:?}", err),
+    }
+    assert!(Package::from_path(root.join("foo")).is_ok());
+
+    fs::remove_dir_all(&root).unwrap();
+}
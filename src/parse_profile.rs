@@ -0,0 +1,104 @@
+use pulldown_cmark::Options;
+
+/// A named set of Markdown parser extensions.
+///
+/// The readme is parsed with the `github` profile and the docs are parsed with the `rustdoc` profile
+/// by default.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseProfile {
+    options: Options,
+}
+
+impl ParseProfile {
+    /// Returns the profile matching GitHub Flavored Markdown renderer.
+    ///
+    /// It enables tables, strikethrough, task lists, footnotes and blockquote alerts,
+    /// and disables smart punctuation.
+    pub fn github() -> Self {
+        Self::from_options(
+            Options::ENABLE_TABLES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_TASKLISTS
+                | Options::ENABLE_FOOTNOTES
+                | Options::ENABLE_GFM,
+        )
+    }
+
+    /// Returns the profile matching the rustdoc doc comments renderer.
+    ///
+    /// It enables tables, strikethrough, task lists and footnotes,
+    /// and disables smart punctuation and blockquote alerts.
+    pub fn rustdoc() -> Self {
+        Self::from_options(
+            Options::ENABLE_TABLES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_TASKLISTS
+                | Options::ENABLE_FOOTNOTES,
+        )
+    }
+
+    /// Returns the profile with the specified parser options.
+    pub fn from_options(options: Options) -> Self {
+        Self { options }
+    }
+
+    /// Returns the parser options.
+    pub fn options(&self) -> Options {
+        self.options
+    }
+}
+
+#[test]
+fn test_parse_profiles_compatibility() {
+    use pulldown_cmark::{Alignment, BlockQuoteKind, Event, Parser, Tag};
+    use std::format;
+    use std::string::String;
+    use std::vec::Vec;
+
+    let text = concat!(
+        "| a | b |\n|---|---|\n| c | d |\n\n",
+        "~~struck~~ \"quoted\" -- text[^note]\n\n",
+        "[^note]: Footnote.\n\n",
+        "- [x] done\n\n",
+        "> [!NOTE]\n> Alert.\n",
+    );
+    let events = |profile: ParseProfile| -> Vec<String> {
+        Parser::new_ext(text, profile.options())
+            .map(|event| format!("{:?}", event))
+            .collect()
+    };
+    let has = |events: &[String], event: Event<'_>| events.contains(&format!("{:?}", event));
+    let github = events(ParseProfile::github());
+    let rustdoc = events(ParseProfile::rustdoc());
+
+    for events in [&github, &rustdoc] {
+        assert!(has(
+            events,
+            Event::Start(Tag::Table(std::vec![Alignment::None; 2]))
+        ));
+        assert!(has(events, Event::Start(Tag::Strikethrough)));
+        assert!(has(events, Event::Text(" \"quoted\" -- text".into())));
+        assert!(has(events, Event::FootnoteReference("note".into())));
+        assert!(has(
+            events,
+            Event::Start(Tag::FootnoteDefinition("note".into()))
+        ));
+        assert!(has(events, Event::TaskListMarker(true)));
+    }
+    assert!(has(
+        &github,
+        Event::Start(Tag::BlockQuote(Some(BlockQuoteKind::Note)))
+    ));
+    assert!(has(&rustdoc, Event::Start(Tag::BlockQuote(None))));
+    assert!(has(&rustdoc, Event::Text("!NOTE".into())));
+
+    // Apart from the blockquote alert, both profiles produce the same events.
+    let before_alert = |events: &[String]| -> Vec<String> {
+        events
+            .iter()
+            .take_while(|event| !event.contains("BlockQuote"))
+            .cloned()
+            .collect()
+    };
+    assert_eq!(before_alert(&github), before_alert(&rustdoc));
+}
@@ -0,0 +1,384 @@
+use std::borrow::Cow;
+use std::string::String;
+use std::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{CMarkData, DisallowUrlsWithPrefixError, Manifest, WildcardPatternError};
+
+/// A data-driven set of readme and docs transformations.
+///
+/// It allows to describe transformations as data,
+/// for example in the `.readme-sync.toml` configuration file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PipelineSpec {
+    /// Readme transformations.
+    pub readme: Vec<PipelineStep>,
+    /// Docs transformations.
+    pub docs: Vec<PipelineStep>,
+    /// Sections removed from both readme and docs after their transformations.
+    pub ignored_sections: Vec<(String, u32)>,
+    /// Heading text aliases applied to both readme and docs after their transformations.
+    pub section_aliases: Vec<(String, String)>,
+    /// Badge url patterns used in addition to `badge_url_patterns`.
+    pub badge_patterns: Vec<String>,
+}
+
+/// A readme or docs transformation step.
+///
+/// Each step corresponds to the `CMarkReadme` or `CMarkDocs` method with the same name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PipelineStep {
+    /// Concatenate adjacent text events.
+    ConcatTexts,
+    /// Collapse whitespace runs in text events.
+    CollapseWhitespace,
+    /// Use the same nesting order for strong emphasis and emphasis.
+    CanonicalizeEmphasisNesting,
+    /// Increment levels of all headings.
+    IncrementHeadingLevels,
+    /// Add a first level heading with the specified text.
+    AddTitle(String),
+    /// Add a first level heading with the manifest package name.
+    AddPackageTitle,
+    /// Removes first paragraph that contains only badges.
+    RemoveBadgesParagraph,
+    /// Remove section with the specified heading text and level and its subsections.
+    RemoveSection {
+        /// Section heading text.
+        heading: String,
+        /// Section heading level.
+        level: u32,
+    },
+    /// Remove sections with heading `Documentation` and level 2.
+    RemoveDocumentationSection,
+    /// Remove the specified fenced code block tags.
+    RemoveCodeblockTags(Vec<String>),
+    /// Remove fenced code blocks with the specified tag including their contents.
+    RemoveCodeblocksWithTag(String),
+    /// Separate fenced code block tags with commas only.
+    NormalizeCodeblockTags,
+    /// Remove fenced code block tags that are used by `cargo test`.
+    RemoveCodeblockRustTestTags,
+    /// Use the specified codeblock tag, if they are not specified.
+    UseDefaultCodeblockTag(String),
+    /// Use rust fenced codeblock highlight as default.
+    UseDefaultCodeblockRustTag,
+    /// Remove hidden rust code from rust fenced codeblocks.
+    ///
+    /// This step is applicable only to docs.
+    RemoveHiddenRustCode,
+    /// Replace texts of fenced code blocks tagged `toml` with their canonical formatting.
+    NormalizeTomlCodeblocks,
+    /// Replace links with the specified address by another address.
+    MapLink {
+        /// Original link address.
+        from: String,
+        /// New link address.
+        to: String,
+    },
+    /// Fails if absolute blob links to the manifest repository found.
+    DisallowAbsoluteRepositoryBlobLinks,
+    /// Convert all relative links into absolute ones
+    /// using the manifest repository url as the root address.
+    ///
+    /// Fails if a relative link points above the repository root.
+    UseAbsoluteRepositoryBlobUrls,
+    /// Fails if absolute docs links to the manifest documentation found.
+    DisallowAbsolutePackageDocsLinks,
+    /// Convert all relative links into absolute ones
+    /// using the manifest package documentation url as the root address.
+    ///
+    /// Fails if a relative link points above the documentation url origin.
+    UseAbsolutePackageDocsUrls,
+}
+
+/// Checks that the readme and docs of the package in the specified directory are synchronized
+/// using the specified pipeline or the default one.
+///
+/// It is the code path shared by the crate documentation example,
+/// `run_sync_check_with_config_file` and the integration test of this crate.
+///
+/// # Panics
+///
+/// Panics with diagnostic messages if the readme and docs are not the same
+/// or if the package, readme or docs can not be read or transformed.
+/// Missing prerequisites found by `Package::preflight_sync_check_with_pipeline`
+/// are reported all together before reading the readme and docs.
+pub fn assert_sync_self(manifest_dir: &std::path::Path, pipeline: Option<PipelineSpec>) {
+    use crate::{assert_sync, CMarkDocs, CMarkReadme, Config, Package};
+
+    let package = Package::from_path(manifest_dir.to_path_buf())
+        .unwrap_or_else(|err| panic!("Failed to read package: {}", err));
+    let pipeline = pipeline.unwrap_or_default();
+    let issues = package.preflight_sync_check_with_pipeline(&pipeline);
+    if let Some(message) = crate::preflight::preflight_failure_message(&issues) {
+        panic!("{}", message);
+    }
+    let config = Config::from_package_docs_rs_features(&package);
+
+    let readme = CMarkReadme::from_package(&package)
+        .unwrap_or_else(|err| panic!("Failed to read readme: {}", err))
+        .apply_pipeline(&pipeline)
+        .unwrap_or_else(|err| panic!("Failed to transform readme: {}", err));
+    let docs = CMarkDocs::from_package_and_config(&package, &config)
+        .unwrap_or_else(|err| panic!("Failed to read docs: {}", err))
+        .apply_pipeline(&pipeline)
+        .unwrap_or_else(|err| panic!("Failed to transform docs: {}", err));
+
+    assert_sync(&readme, &docs);
+}
+
+impl Default for PipelineSpec {
+    /// Returns the transformations used in the crate documentation example.
+    fn default() -> Self {
+        use std::string::ToString;
+        use std::vec;
+
+        Self {
+            readme: vec![
+                PipelineStep::RemoveBadgesParagraph,
+                PipelineStep::RemoveDocumentationSection,
+                PipelineStep::RemoveCodeblocksWithTag("no_sync".to_string()),
+                PipelineStep::CollapseWhitespace,
+                PipelineStep::DisallowAbsoluteRepositoryBlobLinks,
+                PipelineStep::UseAbsoluteRepositoryBlobUrls,
+            ],
+            docs: vec![
+                PipelineStep::IncrementHeadingLevels,
+                PipelineStep::AddPackageTitle,
+                PipelineStep::RemoveCodeblocksWithTag("no_sync".to_string()),
+                PipelineStep::RemoveCodeblockRustTestTags,
+                PipelineStep::UseDefaultCodeblockRustTag,
+                PipelineStep::RemoveHiddenRustCode,
+                PipelineStep::CollapseWhitespace,
+                PipelineStep::DisallowAbsolutePackageDocsLinks,
+                PipelineStep::UseAbsolutePackageDocsUrls,
+            ],
+            ignored_sections: Vec::new(),
+            section_aliases: Vec::new(),
+            badge_patterns: Vec::new(),
+        }
+    }
+}
+
+impl PipelineSpec {
+    /// Creates a pipeline without any transformations.
+    pub fn empty() -> Self {
+        Self {
+            readme: Vec::new(),
+            docs: Vec::new(),
+            ignored_sections: Vec::new(),
+            section_aliases: Vec::new(),
+            badge_patterns: Vec::new(),
+        }
+    }
+
+    /// Applies readme transformations, ignored sections and section aliases to the readme data.
+    ///
+    /// The repository subpath is the readme file directory relative to the repository root
+    /// with `/` separators, it is used to resolve relative urls into absolute blob urls.
+    /// Returns an error if the readme transformations contain docs-only steps.
+    pub fn apply_to_readme(
+        &self,
+        data: CMarkData,
+        manifest: &Manifest,
+        repository_subpath: &str,
+    ) -> Result<CMarkData, PipelineError> {
+        if self.readme.contains(&PipelineStep::RemoveHiddenRustCode) {
+            return Err(PipelineError::DocsOnlyStep("remove_hidden_rust_code"));
+        }
+        self.apply(&self.readme, data, manifest, repository_subpath)
+    }
+
+    /// Applies docs transformations, ignored sections and section aliases to the docs data.
+    ///
+    /// The repository subpath is the package directory relative to the repository root
+    /// with `/` separators, it is used to resolve relative urls into absolute blob urls.
+    pub fn apply_to_docs(
+        &self,
+        data: CMarkData,
+        manifest: &Manifest,
+        repository_subpath: &str,
+    ) -> Result<CMarkData, PipelineError> {
+        self.apply(&self.docs, data, manifest, repository_subpath)
+    }
+
+    fn apply(
+        &self,
+        steps: &[PipelineStep],
+        data: CMarkData,
+        manifest: &Manifest,
+        repository_subpath: &str,
+    ) -> Result<CMarkData, PipelineError> {
+        let mut data = steps.iter().try_fold(data, |data, step| {
+            self.apply_step(step, data, manifest, repository_subpath)
+        })?;
+        if !self.ignored_sections.is_empty() {
+            let sections: Vec<_> = self
+                .ignored_sections
+                .iter()
+                .map(|(heading, level)| (heading.as_str(), *level))
+                .collect();
+            data = data.remove_sections(&sections);
+        }
+        if !self.section_aliases.is_empty() {
+            data = data.rename_headings(&self.section_aliases);
+        }
+        Ok(data)
+    }
+
+    fn apply_step(
+        &self,
+        step: &PipelineStep,
+        data: CMarkData,
+        manifest: &Manifest,
+        repository_subpath: &str,
+    ) -> Result<CMarkData, PipelineError> {
+        let package = &manifest.package;
+        let repository = || {
+            package
+                .repository
+                .as_deref()
+                .ok_or(PipelineError::RepositoryUrlNotFound)
+        };
+
+        Ok(match step {
+            PipelineStep::ConcatTexts => data.concat_texts(),
+            PipelineStep::CollapseWhitespace => data.collapse_whitespace(),
+            PipelineStep::CanonicalizeEmphasisNesting => data.canonicalize_emphasis_nesting(),
+            PipelineStep::IncrementHeadingLevels => data.increment_heading_levels(),
+            PipelineStep::AddTitle(text) => data.add_title(text),
+            PipelineStep::AddPackageTitle => data.add_title(&package.name),
+            PipelineStep::RemoveBadgesParagraph => self.remove_badges_paragraph(data)?,
+            PipelineStep::RemoveSection { heading, level } => data.remove_section(heading, *level),
+            PipelineStep::RemoveDocumentationSection => data.remove_documentation_section(),
+            PipelineStep::RemoveCodeblockTags(tags) => {
+                let tags: Vec<_> = tags.iter().map(String::as_str).collect();
+                data.remove_codeblock_tags(&tags)
+            }
+            PipelineStep::RemoveCodeblocksWithTag(tag) => data.remove_codeblocks_with_tag(tag),
+            PipelineStep::NormalizeCodeblockTags => data.normalize_codeblock_tags(),
+            PipelineStep::RemoveCodeblockRustTestTags => data.remove_codeblock_rust_test_tags(),
+            PipelineStep::UseDefaultCodeblockTag(tag) => data.use_default_codeblock_tag(tag),
+            PipelineStep::UseDefaultCodeblockRustTag => data.use_default_codeblock_rust_tag(),
+            PipelineStep::RemoveHiddenRustCode => data.remove_hidden_rust_code(),
+            PipelineStep::NormalizeTomlCodeblocks => data.normalize_toml_codeblocks(),
+            PipelineStep::MapLink { from, to } => data.map_links(
+                |link| {
+                    if link == from {
+                        Cow::Owned(to.clone())
+                    } else {
+                        Cow::from(link)
+                    }
+                },
+                std::format!("map_link(from = \"{}\", to = \"{}\")", from, to),
+            ),
+            PipelineStep::DisallowAbsoluteRepositoryBlobLinks => data
+                .disallow_absolute_blob_links_with_branch(
+                    repository()?,
+                    manifest.repository_branch(),
+                )?,
+            PipelineStep::UseAbsoluteRepositoryBlobUrls => data
+                .use_absolute_blob_urls_with_branch_and_subpath(
+                    repository()?,
+                    manifest.repository_branch(),
+                    repository_subpath,
+                )?,
+            PipelineStep::DisallowAbsolutePackageDocsLinks => {
+                data.disallow_absolute_package_docs_links(manifest)?
+            }
+            PipelineStep::UseAbsolutePackageDocsUrls => {
+                data.try_with_absolute_urls(&manifest.docs_url_prefix(), "")?
+            }
+        })
+    }
+
+    fn remove_badges_paragraph(&self, data: CMarkData) -> Result<CMarkData, PipelineError> {
+        use crate::WildcardPattern;
+
+        let mut patterns = crate::badge_url_patterns();
+        for pattern in &self.badge_patterns {
+            patterns.push(WildcardPattern::new(pattern)?);
+        }
+        Ok(data.remove_images_only_paragraph(|image_urls| {
+            image_urls
+                .iter()
+                .any(|url| patterns.iter().any(|pattern| pattern.matches(url)))
+        }))
+    }
+}
+
+/// An error which can occur when applying pipeline transformations.
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    /// A disallowed prefix or a relative url pointing above the root found.
+    #[error(transparent)]
+    DisallowUrlsWithPrefixError(#[from] DisallowUrlsWithPrefixError),
+    /// Manifest does not contain `package.repository` field.
+    #[error("Manifest does not contain package.repository field")]
+    RepositoryUrlNotFound,
+    /// The step is applicable only to docs, but used for readme.
+    #[error("The `{0}` step is applicable only to docs")]
+    DocsOnlyStep(&'static str),
+    /// Badge url pattern is invalid.
+    #[error("Invalid badge url pattern: {0}")]
+    BadgePatternError(#[from] WildcardPatternError),
+}
+
+#[test]
+fn test_use_absolute_urls_escaping_root() {
+    use crate::test_utils::readme_data;
+
+    let mut manifest = Manifest::from_name_and_version("foo".into(), "1.0.0".into());
+    manifest.package.repository = Some("https://github.com/user/foo".into());
+    let pipeline = |step| PipelineSpec {
+        readme: std::vec![step],
+        docs: Vec::new(),
+        ignored_sections: Vec::new(),
+        section_aliases: Vec::new(),
+        badge_patterns: Vec::new(),
+    };
+
+    let blob_urls = pipeline(PipelineStep::UseAbsoluteRepositoryBlobUrls);
+    assert_eq!(
+        blob_urls
+            .apply_to_readme(readme_data("[a](./a.md)\n"), &manifest, "")
+            .unwrap()
+            .to_markdown(),
+        "[a](https://github.com/user/foo/blob/master/a.md)\n"
+    );
+    assert_eq!(
+        blob_urls
+            .apply_to_readme(
+                readme_data("[a](./a.md) [b](../b.md)\n"),
+                &manifest,
+                "crates/foo"
+            )
+            .unwrap()
+            .to_markdown(),
+        "[a](https://github.com/user/foo/blob/master/crates/foo/a.md) \
+            [b](https://github.com/user/foo/blob/master/crates/b.md)\n"
+    );
+    assert!(matches!(
+        blob_urls.apply_to_readme(readme_data("[a](../a.md)\n"), &manifest, ""),
+        Err(PipelineError::DisallowUrlsWithPrefixError(
+            DisallowUrlsWithPrefixError::EscapesRoot { .. }
+        ))
+    ));
+
+    let docs_urls = pipeline(PipelineStep::UseAbsolutePackageDocsUrls);
+    assert_eq!(
+        docs_urls
+            .apply_to_readme(readme_data("[a](./bar/)\n"), &manifest, "")
+            .unwrap()
+            .to_markdown(),
+        "[a](https://docs.rs/foo/latest/foo/bar/)\n"
+    );
+    assert!(matches!(
+        docs_urls.apply_to_readme(readme_data("[a](../bar/)\n"), &manifest, ""),
+        Err(PipelineError::DisallowUrlsWithPrefixError(
+            DisallowUrlsWithPrefixError::EscapesRoot { .. }
+        ))
+    ));
+}
@@ -0,0 +1,307 @@
+use std::path::PathBuf;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{Config, File, FileDocs, Package, PipelineSpec, PipelineStep};
+
+/// The severity of a `PreflightIssue`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PreflightSeverity {
+    /// The synchronization check can not succeed until the issue is fixed.
+    Error,
+    /// The synchronization check may succeed, but likely not as expected.
+    Warning,
+}
+
+/// A missing synchronization check prerequisite found by `Package::preflight_sync_check`.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum PreflightIssue {
+    /// The readme file is not found.
+    #[error("Readme file not found{}", readme_path_suffix(.0))]
+    ReadmeNotFound(Option<PathBuf>),
+    /// The readme file is empty or contains only whitespace.
+    #[error("Readme file `{}` is empty", .0.display())]
+    ReadmeEmpty(PathBuf),
+    /// The library or binary target file used as the docs source is not found.
+    #[error("Target file `{}` not found", .0.display())]
+    TargetNotFound(PathBuf),
+    /// The library or binary target file can not be parsed.
+    #[error("Target file `{}` can not be parsed: {1}", .0.display())]
+    TargetNotParsed(PathBuf, String),
+    /// The library or binary target file has no inner documentation.
+    #[error("Target file `{}` has no crate documentation", .0.display())]
+    DocsEmpty(PathBuf),
+    /// The manifest does not contain the `package.repository` field
+    /// required by the pipeline repository steps.
+    #[error("Manifest does not contain package.repository field used by the `{0}` step")]
+    RepositoryNotSpecified(&'static str),
+    /// The manifest does not contain the `package.documentation` field
+    /// used by the pipeline documentation steps.
+    #[error("Manifest does not contain package.documentation field used by the `{0}` step")]
+    DocumentationNotSpecified(&'static str),
+}
+
+impl PreflightIssue {
+    /// Returns the issue severity.
+    pub fn severity(&self) -> PreflightSeverity {
+        match self {
+            Self::DocumentationNotSpecified(_) => PreflightSeverity::Warning,
+            _ => PreflightSeverity::Error,
+        }
+    }
+
+    /// Returns the issue remediation hint.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::ReadmeNotFound(_) => {
+                "create `README.md` in the package root or specify `package.readme` in `Cargo.toml`"
+            }
+            Self::ReadmeEmpty(_) => "add the crate description to the readme file",
+            Self::TargetNotFound(_) => {
+                "create `src/lib.rs` or specify the library target path in the `[lib]` section"
+            }
+            Self::TargetNotParsed(_, _) => "fix the target file syntax errors",
+            Self::DocsEmpty(_) => "add the crate documentation with `//!` inner doc comments",
+            Self::RepositoryNotSpecified(_) => {
+                "specify `package.repository` in `Cargo.toml` or remove the repository steps"
+            }
+            Self::DocumentationNotSpecified(_) => {
+                "specify `package.documentation` in `Cargo.toml`, \
+                    the latest docs.rs documentation is assumed otherwise"
+            }
+        }
+    }
+}
+
+impl Package {
+    /// Checks the prerequisites of the synchronization check with the default pipeline.
+    ///
+    /// See `preflight_sync_check_with_pipeline` for details.
+    pub fn preflight_sync_check(&self) -> Vec<PreflightIssue> {
+        self.preflight_sync_check_with_pipeline(&PipelineSpec::default())
+    }
+
+    /// Checks the prerequisites of the synchronization check with the specified pipeline.
+    ///
+    /// Unlike the check itself, it does not stop on the first problem
+    /// and returns all found issues at once:
+    /// the readme file is present and non-empty,
+    /// the library or binary target file exists and has the crate documentation,
+    /// and the manifest contains the fields required by the pipeline steps.
+    pub fn preflight_sync_check_with_pipeline(
+        &self,
+        pipeline: &PipelineSpec,
+    ) -> Vec<PreflightIssue> {
+        use std::string::ToString;
+
+        let mut issues = Vec::new();
+
+        match self.relative_readme_path() {
+            Some(path) => match std::fs::read_to_string(self.path().join(path)) {
+                Ok(text) if text.trim().is_empty() => {
+                    issues.push(PreflightIssue::ReadmeEmpty(path.to_path_buf()))
+                }
+                Ok(_) => {}
+                Err(_) => issues.push(PreflightIssue::ReadmeNotFound(Some(path.to_path_buf()))),
+            },
+            None => issues.push(PreflightIssue::ReadmeNotFound(None)),
+        }
+
+        let target = self.manifest().default_relative_target_path().to_path_buf();
+        match File::from_path(target.clone(), Some(self.path())) {
+            Ok(file) => {
+                let config = Config::from_package_docs_rs_features(self);
+                match FileDocs::from_file(Arc::new(file), &config) {
+                    Ok(docs) if docs.docs().trim().is_empty() => {
+                        issues.push(PreflightIssue::DocsEmpty(target))
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        issues.push(PreflightIssue::TargetNotParsed(target, err.to_string()))
+                    }
+                }
+            }
+            Err(_) => issues.push(PreflightIssue::TargetNotFound(target)),
+        }
+
+        let package = &self.manifest().package;
+        let steps = || pipeline.readme.iter().chain(&pipeline.docs);
+        if package.repository.is_none() {
+            if let Some(step) = steps().find_map(repository_step_name) {
+                issues.push(PreflightIssue::RepositoryNotSpecified(step));
+            }
+        }
+        if package.documentation.is_none() {
+            if let Some(step) = steps().find_map(documentation_step_name) {
+                issues.push(PreflightIssue::DocumentationNotSpecified(step));
+            }
+        }
+
+        issues
+    }
+}
+
+fn readme_path_suffix(path: &Option<PathBuf>) -> String {
+    match path {
+        Some(path) => std::format!(" at `{}`", path.display()),
+        None => String::new(),
+    }
+}
+
+/// Returns the step name if it requires the manifest repository url.
+fn repository_step_name(step: &PipelineStep) -> Option<&'static str> {
+    match step {
+        PipelineStep::DisallowAbsoluteRepositoryBlobLinks => {
+            Some("disallow_absolute_repository_blob_links")
+        }
+        PipelineStep::UseAbsoluteRepositoryBlobUrls => Some("use_absolute_repository_blob_urls"),
+        _ => None,
+    }
+}
+
+/// Returns the step name if it uses the manifest documentation url.
+fn documentation_step_name(step: &PipelineStep) -> Option<&'static str> {
+    match step {
+        PipelineStep::DisallowAbsolutePackageDocsLinks => {
+            Some("disallow_absolute_package_docs_links")
+        }
+        PipelineStep::UseAbsolutePackageDocsUrls => Some("use_absolute_package_docs_urls"),
+        _ => None,
+    }
+}
+
+/// Returns the message listing all issues with their severities and hints
+/// if any of them is an error.
+pub(crate) fn preflight_failure_message(issues: &[PreflightIssue]) -> Option<String> {
+    use std::format;
+
+    if !issues
+        .iter()
+        .any(|issue| issue.severity() == PreflightSeverity::Error)
+    {
+        return None;
+    }
+    let list: String = issues
+        .iter()
+        .map(|issue| {
+            let severity = match issue.severity() {
+                PreflightSeverity::Error => "error",
+                PreflightSeverity::Warning => "warning",
+            };
+            format!("\n{}: {}\n  = help: {}", severity, issue, issue.hint())
+        })
+        .collect();
+    Some(format!("Sync check prerequisites are missing:{}", list))
+}
+
+#[test]
+fn test_preflight_sync_check() {
+    use std::fs;
+    use std::string::ToString;
+
+    let root = std::env::temp_dir().join(std::format!(
+        "readme-sync-test-preflight-{}",
+        std::process::id()
+    ));
+    let package = |name: &str, manifest: &str, files: &[(&str, &str)]| {
+        let path = root.join(name);
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::write(
+            path.join("Cargo.toml"),
+            std::format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n{}",
+                name,
+                manifest
+            ),
+        )
+        .unwrap();
+        for (file, text) in files {
+            fs::write(path.join(file), text).unwrap();
+        }
+        Package::from_path(path).unwrap()
+    };
+    let fields = "repository = \"https://github.com/user/foo\"\n\
+        documentation = \"https://docs.rs/foo\"\n";
+    let readme = ("README.md", "# Foo\n\nText.\n");
+    let lib = ("src/lib.rs", "//! Text.\n");
+
+    let complete = package("complete", fields, &[readme, lib]);
+    assert_eq!(complete.preflight_sync_check(), []);
+
+    let no_readme = package("no-readme", fields, &[lib]);
+    assert_eq!(
+        no_readme.preflight_sync_check(),
+        [PreflightIssue::ReadmeNotFound(None)]
+    );
+
+    let missing_readme = package("missing-readme", "readme = \"DOCS.md\"\n", &[lib]);
+    assert_eq!(
+        missing_readme.preflight_sync_check_with_pipeline(&PipelineSpec::empty()),
+        [PreflightIssue::ReadmeNotFound(Some(PathBuf::from(
+            "DOCS.md"
+        )))]
+    );
+
+    let empty_readme = package("empty-readme", fields, &[("README.md", " \n"), lib]);
+    assert_eq!(
+        empty_readme.preflight_sync_check(),
+        [PreflightIssue::ReadmeEmpty(PathBuf::from("README.md"))]
+    );
+
+    let no_lib = package("no-lib", fields, &[readme]);
+    assert_eq!(
+        no_lib.preflight_sync_check(),
+        [PreflightIssue::TargetNotFound(PathBuf::from("src/lib.rs"))]
+    );
+
+    let invalid_lib = package("invalid-lib", fields, &[readme, ("src/lib.rs", "fn {")]);
+    assert!(matches!(
+        invalid_lib.preflight_sync_check().as_slice(),
+        [PreflightIssue::TargetNotParsed(path, _)] if path == &PathBuf::from("src/lib.rs")
+    ));
+
+    let no_docs = package(
+        "no-docs",
+        fields,
+        &[readme, ("src/lib.rs", "pub fn foo() {}\n")],
+    );
+    assert_eq!(
+        no_docs.preflight_sync_check(),
+        [PreflightIssue::DocsEmpty(PathBuf::from("src/lib.rs"))]
+    );
+
+    let no_fields = package("no-fields", "", &[readme, lib]);
+    assert_eq!(
+        no_fields.preflight_sync_check(),
+        [
+            PreflightIssue::RepositoryNotSpecified("disallow_absolute_repository_blob_links"),
+            PreflightIssue::DocumentationNotSpecified("disallow_absolute_package_docs_links"),
+        ]
+    );
+    assert_eq!(
+        no_fields.preflight_sync_check_with_pipeline(&PipelineSpec::empty()),
+        []
+    );
+
+    let new_crate = package("new-crate", "", &[("src/main.rs", "fn main() {}\n")]);
+    let issues = new_crate.preflight_sync_check();
+    assert_eq!(issues.len(), 4);
+    let message = preflight_failure_message(&issues).unwrap();
+    assert!(message.starts_with("Sync check prerequisites are missing:\n"));
+    assert!(message.contains("\nerror: Readme file not found\n  = help: create `README.md`"));
+    assert!(message.contains("\nerror: Target file `src/lib.rs` not found\n"));
+    assert!(message.contains("\nwarning: Manifest does not contain package.documentation"));
+    assert_eq!(
+        preflight_failure_message(&[PreflightIssue::DocumentationNotSpecified("step")]),
+        None
+    );
+    assert_eq!(
+        PreflightIssue::ReadmeEmpty(PathBuf::from("README.md")).to_string(),
+        "Readme file `README.md` is empty"
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
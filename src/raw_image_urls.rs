@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+use std::string::String;
+
+use thiserror::Error;
+
+use crate::CMarkData;
+
+/// An error which can occur when converting relative image urls into raw GitHub content urls.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum RawImageUrlsError {
+    /// Manifest does not contain `package.repository` field.
+    #[error("Manifest does not contain package.repository field")]
+    RepositoryUrlNotFound,
+    /// The repository url is not a GitHub repository url.
+    #[error(
+        "The repository url `{0}` is not a GitHub repository url \
+            like `https://github.com/<owner>/<repo>`"
+    )]
+    NotGitHubUrl(String),
+    /// The image url points above the repository root.
+    #[error("The image url `{0}` points above the repository root")]
+    EscapesRoot(String),
+}
+
+impl CMarkData {
+    /// Convert all relative image urls into absolute raw content urls
+    /// of the specified GitHub repository and branch,
+    /// like `https://raw.githubusercontent.com/<owner>/<repo>/<branch>/<path>`.
+    ///
+    /// Unlike blob urls, raw content urls point to the images themselves,
+    /// so they are rendered by crates.io and docs.rs.
+    /// Links and absolute image urls are left as is,
+    /// so it should be applied before `use_absolute_blob_urls` that also converts images.
+    pub fn use_absolute_raw_urls_for_images(
+        self,
+        repository_url: &str,
+        branch: &str,
+    ) -> Result<Self, RawImageUrlsError> {
+        self.use_absolute_raw_urls_for_images_with_subpath(repository_url, branch, "")
+    }
+
+    /// Convert all relative image urls into absolute raw content urls
+    /// of the specified GitHub repository, branch and repository subpath.
+    ///
+    /// The subpath is the directory of the readme file relative to the repository root
+    /// with `/` separators, for example `crates/foo`.
+    /// Image urls starting with `/` are resolved against the repository root.
+    /// Returns an error if an image url points above the repository root.
+    /// See `use_absolute_raw_urls_for_images` for details.
+    pub fn use_absolute_raw_urls_for_images_with_subpath(
+        self,
+        repository_url: &str,
+        branch: &str,
+        subpath: &str,
+    ) -> Result<Self, RawImageUrlsError> {
+        use crate::cmark_data::{is_absolute_url, is_fragment, resolve_relative_url};
+
+        let prefix = github_raw_prefix(repository_url, branch)
+            .ok_or_else(|| RawImageUrlsError::NotGitHubUrl(String::from(repository_url)))?;
+        let note = std::format!(
+            "use_absolute_raw_urls_for_images(prefix = \"{}\", subpath = \"{}\")",
+            prefix,
+            subpath
+        );
+        let mut escaped = None;
+        let data = self.map_image_links(
+            |url| {
+                if is_absolute_url(url) || is_fragment(url) {
+                    return Cow::from(url);
+                }
+                let path = match url.strip_prefix('/') {
+                    Some(path) => Some(String::from(path)),
+                    None => resolve_relative_url(subpath, url),
+                };
+                match path {
+                    Some(path) => Cow::from([&prefix, path.as_str()].concat()),
+                    None => {
+                        if escaped.is_none() {
+                            escaped = Some(String::from(url));
+                        }
+                        Cow::from(url)
+                    }
+                }
+            },
+            note,
+        );
+        match escaped {
+            Some(url) => Err(RawImageUrlsError::EscapesRoot(url)),
+            None => Ok(data),
+        }
+    }
+}
+
+/// Returns the raw content url prefix for the specified GitHub repository url and branch.
+fn github_raw_prefix(repository_url: &str, branch: &str) -> Option<String> {
+    let path = ["https://", "http://"]
+        .iter()
+        .find_map(|scheme| repository_url.strip_prefix(scheme))?;
+    let path = path
+        .strip_prefix("www.")
+        .unwrap_or(path)
+        .strip_prefix("github.com/")?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let mut segments = path.split('/');
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(owner), Some(repo), None) if !owner.is_empty() && !repo.is_empty() => Some(
+            [
+                "https://raw.githubusercontent.com/",
+                owner,
+                "/",
+                repo,
+                "/",
+                branch,
+                "/",
+            ]
+            .concat(),
+        ),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_use_absolute_raw_urls_for_images() {
+    use crate::test_utils::readme_data;
+
+    assert_eq!(
+        github_raw_prefix("https://github.com/user/repo.git/", "main").as_deref(),
+        Some("https://raw.githubusercontent.com/user/repo/main/")
+    );
+    assert_eq!(github_raw_prefix("https://github.com/user", "main"), None);
+
+    let data =
+        || readme_data("![a](./docs/diagram.png) ![b](https://example.com/b.png) [c](LICENSE)\n");
+    assert_eq!(
+        data()
+            .use_absolute_raw_urls_for_images("https://github.com/user/repo", "master")
+            .unwrap()
+            .use_absolute_blob_urls("https://github.com/user/repo")
+            .unwrap()
+            .to_markdown(),
+        "![a](https://raw.githubusercontent.com/user/repo/master/docs/diagram.png) \
+            ![b](https://example.com/b.png) \
+            [c](https://github.com/user/repo/blob/master/LICENSE)\n"
+    );
+    assert_eq!(
+        data().use_absolute_raw_urls_for_images("https://gitlab.com/user/repo", "main"),
+        Err(RawImageUrlsError::NotGitHubUrl(String::from(
+            "https://gitlab.com/user/repo"
+        )))
+    );
+    assert_eq!(
+        readme_data("![a](./a.png) ![b](../b.png) ![c](/c.png)\n")
+            .use_absolute_raw_urls_for_images_with_subpath(
+                "https://github.com/user/repo",
+                "master",
+                "crates/foo"
+            )
+            .unwrap()
+            .to_markdown(),
+        "![a](https://raw.githubusercontent.com/user/repo/master/crates/foo/a.png) \
+            ![b](https://raw.githubusercontent.com/user/repo/master/crates/b.png) \
+            ![c](https://raw.githubusercontent.com/user/repo/master/c.png)\n"
+    );
+    assert_eq!(
+        readme_data("![a](../a.png)\n")
+            .use_absolute_raw_urls_for_images("https://github.com/user/repo", "master"),
+        Err(RawImageUrlsError::EscapesRoot(String::from("../a.png")))
+    );
+}
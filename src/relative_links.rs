@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, Tag};
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem};
+
+/// A relative link or image url which target does not exist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DanglingLink {
+    /// The link url.
+    pub url: String,
+    /// The resolved path that does not exist.
+    pub path: PathBuf,
+    /// The link or image start node.
+    pub node: Arc<CMarkItem>,
+}
+
+/// An error returned if some relative links point to missing files.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error(
+    "{} relative link(s) point to missing files:{}",
+    links.len(),
+    dangling_links_list(links)
+)]
+pub struct DanglingRelativeLinksError {
+    /// Links with missing targets.
+    pub links: Vec<DanglingLink>,
+}
+
+fn dangling_links_list(links: &[DanglingLink]) -> String {
+    links
+        .iter()
+        .map(|link| std::format!("\n  `{}` -> {}", link.url, link.path.display()))
+        .collect()
+}
+
+impl CMarkData {
+    /// Returns relative link and image urls which targets do not exist
+    /// in the specified base directory.
+    ///
+    /// Absolute urls, urls with schemes like `mailto:` and fragments are skipped.
+    /// The `?query` and `#fragment` suffixes are ignored,
+    /// and urls starting with `/` are resolved against the base directory too.
+    pub fn dangling_relative_links(&self, base_dir: &Path) -> Vec<DanglingLink> {
+        use crate::cmark_data::{is_absolute_url, is_fragment};
+
+        self.iter()
+            .filter_map(|node| match node.event() {
+                Some(Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. })) => {
+                    Some((dest_url, node))
+                }
+                _ => None,
+            })
+            .filter(|(url, _)| !is_absolute_url(url) && !is_fragment(url) && !has_scheme(url))
+            .filter_map(|(url, node)| {
+                let path = url.split(['?', '#']).next().unwrap_or_default();
+                let path = path.trim_start_matches("./").trim_start_matches('/');
+                if path.is_empty() {
+                    return None;
+                }
+                let path = base_dir.join(path);
+                (!path.exists()).then(|| DanglingLink {
+                    url: String::from(url.as_ref()),
+                    path,
+                    node: Arc::clone(node),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns self if all relative link and image urls point to existing files
+    /// in the specified base directory, otherwise returns an error listing the missing ones.
+    ///
+    /// See `dangling_relative_links` for details.
+    pub fn disallow_dangling_relative_links(
+        self,
+        base_dir: &Path,
+    ) -> Result<Self, DanglingRelativeLinksError> {
+        let links = self.dangling_relative_links(base_dir);
+        if links.is_empty() {
+            Ok(self)
+        } else {
+            Err(DanglingRelativeLinksError { links })
+        }
+    }
+}
+
+/// Returns `true` if the url starts with a scheme like `mailto:`.
+fn has_scheme(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() >= 2
+            && scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '.' | '-'))
+    })
+}
+
+#[test]
+fn test_dangling_relative_links() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let data = readme_data(concat!(
+        "[changelog](CHANGELOG.md#unreleased) [lib](./src/lib.rs?plain=1) [src](src/)\n",
+        "[site](https://example.com/missing.md) [top](#top) [mail](mailto:a@b.c)\n",
+        "[old](OLD_CHANGELOG.md#v1) ![logo](./assets/logo.png)\n",
+    ));
+    let err = data.disallow_dangling_relative_links(root).unwrap_err();
+    let urls: Vec<_> = err.links.iter().map(|link| link.url.as_str()).collect();
+    assert_eq!(urls, ["OLD_CHANGELOG.md#v1", "./assets/logo.png"]);
+    assert_eq!(err.links[1].path, root.join("assets/logo.png"));
+    assert!(err
+        .to_string()
+        .starts_with("2 relative link(s) point to missing files:\n  `OLD_CHANGELOG.md#v1` -> "));
+}
@@ -0,0 +1,218 @@
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem};
+
+/// Expected numbers of removals performed by readme or docs transformations.
+///
+/// Every removal is identified by the note of the transformation that performed it.
+/// The expected transformation is specified either by its name, like `remove_section`,
+/// or by its full note, like `remove_section(name = "Documentation", level = 2)`.
+///
+/// ```rust
+/// use readme_sync::RemovalBudget;
+///
+/// let budget = RemovalBudget::new()
+///     .expect("remove_section", 1)
+///     .expect("remove_codeblocks_with_tag", 2)
+///     .strict();
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RemovalBudget {
+    expected: Vec<(String, usize)>,
+    is_strict: bool,
+}
+
+impl RemovalBudget {
+    /// Creates a budget without expected removals.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects the specified number of removals performed by the specified transformation.
+    pub fn expect(mut self, transformation: &str, count: usize) -> Self {
+        self.expected.push((String::from(transformation), count));
+        self
+    }
+
+    /// Also fails on removals performed by transformations not specified in the budget.
+    pub fn strict(mut self) -> Self {
+        self.is_strict = true;
+        self
+    }
+
+    fn position(&self, note: &str) -> Option<usize> {
+        let name = note.split('(').next().unwrap_or(note);
+        self.expected
+            .iter()
+            .position(|(transformation, _)| transformation == note || transformation == name)
+    }
+}
+
+/// A removal budget mismatch of a single transformation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemovalMismatch {
+    /// The expected transformation name or note.
+    /// For transformations not specified in a strict budget it is the removal note.
+    pub transformation: String,
+    /// The expected number of removals, or `None` if the transformation is not expected.
+    pub expected: Option<usize>,
+    /// The removed nodes.
+    pub removed: Vec<Arc<CMarkItem>>,
+}
+
+/// An error which can occur when verifying a removal budget.
+#[derive(Clone, Debug, Error)]
+#[error("Removal budget mismatch: {}.", FmtMismatches(.mismatches))]
+pub struct RemovalBudgetError {
+    /// Mismatched transformations.
+    pub mismatches: Vec<RemovalMismatch>,
+}
+
+struct FmtMismatches<'a>(&'a [RemovalMismatch]);
+
+impl core::fmt::Display for FmtMismatches<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (idx, mismatch) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            match mismatch.expected {
+                Some(expected) => write!(
+                    f,
+                    "`{}` removed {} node(s) instead of {}",
+                    mismatch.transformation,
+                    mismatch.removed.len(),
+                    expected
+                )?,
+                None => write!(
+                    f,
+                    "unexpected `{}` removed {} node(s)",
+                    mismatch.transformation,
+                    mismatch.removed.len()
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RemovalBudgetError {
+    /// Returns diagnostic messages with the removed nodes spans.
+    #[cfg(feature = "codemap-diagnostic")]
+    pub fn to_match_failed(&self) -> crate::MatchFailed {
+        use crate::{CodemapFiles, CodemapSpans, MatchFailed};
+        use codemap_diagnostic::{Diagnostic, Level};
+        use std::format;
+
+        let mut codemap_files = CodemapFiles::new();
+        let diags = self
+            .mismatches
+            .iter()
+            .map(|mismatch| {
+                let message = format!("{}", FmtMismatches(core::slice::from_ref(mismatch)));
+                let spans = mismatch.removed.iter().flat_map(|node| node.spans());
+                Diagnostic {
+                    level: Level::Error,
+                    message,
+                    code: None,
+                    spans: CodemapSpans::span_labels_from(&mut codemap_files, spans),
+                }
+            })
+            .collect();
+        MatchFailed::from_diags(diags, Arc::new(codemap_files))
+    }
+}
+
+impl CMarkData {
+    /// Checks that transformations performed the numbers of removals expected by the budget.
+    ///
+    /// Each removed node counts as a single removal,
+    /// for example a section removed with all its subsections.
+    /// Removals of transformations not specified in the budget are ignored
+    /// unless the budget is strict.
+    pub fn verify_removal_budget(&self, budget: &RemovalBudget) -> Result<(), RemovalBudgetError> {
+        let mut expected: Vec<_> = budget
+            .expected
+            .iter()
+            .map(|(transformation, count)| RemovalMismatch {
+                transformation: transformation.clone(),
+                expected: Some(*count),
+                removed: Vec::new(),
+            })
+            .collect();
+        let mut unexpected: Vec<RemovalMismatch> = Vec::new();
+
+        for node in self.iter() {
+            if let CMarkItem::Removed { note, .. } = node.as_ref() {
+                if let Some(idx) = budget.position(note) {
+                    expected[idx].removed.push(Arc::clone(node));
+                } else if budget.is_strict {
+                    match unexpected
+                        .iter_mut()
+                        .find(|mismatch| mismatch.transformation == *note)
+                    {
+                        Some(mismatch) => mismatch.removed.push(Arc::clone(node)),
+                        None => unexpected.push(RemovalMismatch {
+                            transformation: String::from(note.as_ref()),
+                            expected: None,
+                            removed: std::vec![Arc::clone(node)],
+                        }),
+                    }
+                }
+            }
+        }
+
+        let mismatches: Vec<_> = expected
+            .into_iter()
+            .filter(|mismatch| mismatch.expected != Some(mismatch.removed.len()))
+            .chain(unexpected)
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(RemovalBudgetError { mismatches })
+        }
+    }
+}
+
+#[test]
+fn test_verify_removal_budget() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let data = readme_data(concat!(
+        "# Crate\n\n",
+        "```sh,no_sync\ncargo test\n```\n\n",
+        "## Documentation\n\nText.\n\n",
+        "## Usage\n\n### Documentation\n\nKept.\n",
+    ))
+    .remove_section("Documentation", 2)
+    .remove_codeblocks_with_tag("no_sync");
+
+    let budget = RemovalBudget::new().expect("remove_section", 1);
+    assert!(data.verify_removal_budget(&budget).is_ok());
+    assert!(data
+        .verify_removal_budget(&budget.clone().expect("remove_codeblocks_with_tag", 1))
+        .is_ok());
+
+    let err = data
+        .verify_removal_budget(&budget.clone().strict())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Removal budget mismatch: unexpected `remove_codeblocks_with_tag(tag = \"no_sync\")` \
+         removed 1 node(s)."
+    );
+
+    let budget =
+        RemovalBudget::new().expect("remove_section(name = \"Documentation\", level = 2)", 2);
+    let err = data.verify_removal_budget(&budget).unwrap_err();
+    assert_eq!(err.mismatches.len(), 1);
+    assert_eq!(err.mismatches[0].removed.len(), 1);
+    #[cfg(feature = "codemap-diagnostic")]
+    assert_eq!(err.to_match_failed().to_single_lines().len(), 1);
+}
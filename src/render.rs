@@ -0,0 +1,514 @@
+//! Markdown rendering of event streams.
+
+use std::string::String;
+use std::vec::Vec;
+
+use pulldown_cmark::{
+    Alignment, BlockQuoteKind, CodeBlockKind, CowStr, Event, LinkType, MetadataBlockKind, Tag,
+};
+
+/// Renders the specified events as Markdown text.
+///
+/// Parsing the rendered text produces the same events for the CommonMark subset
+/// and the tables extension, with adjacent text events concatenated.
+/// Text characters that could be interpreted as Markdown syntax are escaped,
+/// and reference link definitions are rendered after all the other blocks.
+pub fn render_markdown<'a, 'b: 'a, I>(events: I) -> String
+where
+    I: IntoIterator<Item = &'a Event<'b>>,
+{
+    let mut renderer = Renderer {
+        events: events.into_iter().collect(),
+        pos: 0,
+        at_line_start: true,
+        definitions: Vec::new(),
+    };
+    let mut text = String::new();
+    while renderer.pos < renderer.events.len() {
+        let blocks = renderer.blocks(false);
+        if !blocks.is_empty() {
+            if !text.is_empty() {
+                text.push_str("\n\n");
+            }
+            text.push_str(&blocks);
+        }
+        // Skips unbalanced end events.
+        renderer.pos += 1;
+    }
+    if !renderer.definitions.is_empty() {
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&renderer.definitions.join("\n"));
+    }
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+struct Renderer<'a, 'b> {
+    events: Vec<&'a Event<'b>>,
+    pos: usize,
+    at_line_start: bool,
+    definitions: Vec<String>,
+}
+
+impl<'a, 'b> Renderer<'a, 'b> {
+    fn peek(&self) -> Option<&'a Event<'b>> {
+        self.events.get(self.pos).copied()
+    }
+
+    fn skip_end(&mut self) {
+        if let Some(Event::End(_)) = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Renders blocks until the end event of the enclosing tag.
+    fn blocks(&mut self, is_tight: bool) -> String {
+        let mut blocks = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Event::End(_)) => break,
+                Some(event) if is_inline(event) => {
+                    self.at_line_start = true;
+                    blocks.push(self.inlines());
+                }
+                Some(_) => blocks.push(self.block()),
+            }
+        }
+        blocks.join(if is_tight { "\n" } else { "\n\n" })
+    }
+
+    fn block(&mut self) -> String {
+        use std::format;
+
+        let event = self.events[self.pos];
+        self.pos += 1;
+        self.at_line_start = true;
+        let text = match event {
+            Event::Start(Tag::Paragraph) => self.inlines(),
+            Event::Start(Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            }) => {
+                let mut text = "#".repeat(*level as usize);
+                let inlines = self.inlines();
+                if !inlines.is_empty() {
+                    text.push(' ');
+                    text.push_str(&inlines);
+                }
+                let attrs: Vec<_> = id
+                    .iter()
+                    .map(|id| format!("#{}", id))
+                    .chain(classes.iter().map(|class| format!(".{}", class)))
+                    .chain(attrs.iter().map(|(key, value)| match value {
+                        Some(value) => format!("{}={}", key, value),
+                        None => format!("{}", key),
+                    }))
+                    .collect();
+                if !attrs.is_empty() {
+                    text.push_str(&format!(" {{{}}}", attrs.join(" ")));
+                }
+                text
+            }
+            Event::Start(Tag::BlockQuote(kind)) => {
+                let mut text = String::from(match kind {
+                    Some(kind) => blockquote_kind_marker(*kind),
+                    None => "",
+                });
+                let blocks = self.blocks(false);
+                if !text.is_empty() && !blocks.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(&blocks);
+                prefix_lines(&text, "> ", "> ", ">")
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let code = self.raw_text();
+                match kind {
+                    CodeBlockKind::Fenced(info) => fenced_code_block(info, &code),
+                    CodeBlockKind::Indented => {
+                        prefix_lines(code.trim_end_matches('\n'), "    ", "    ", "")
+                    }
+                }
+            }
+            Event::Start(Tag::HtmlBlock) => String::from(self.raw_text().trim_end_matches('\n')),
+            Event::Start(Tag::List(start)) => self.list(*start),
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                let blocks = self.blocks(false);
+                prefix_lines(&format!("[^{}]: {}", label, blocks), "", "    ", "")
+            }
+            Event::Start(Tag::DefinitionList) => self.definition_list(),
+            Event::Start(Tag::Table(alignments)) => self.table(alignments),
+            Event::Start(Tag::MetadataBlock(kind)) => {
+                let fence = match kind {
+                    MetadataBlockKind::YamlStyle => "---",
+                    MetadataBlockKind::PlusesStyle => "+++",
+                };
+                format!("{}\n{}{}", fence, self.raw_text(), fence)
+            }
+            Event::Rule => String::from("***"),
+            // Block contents without their block start events are rendered as is.
+            Event::Start(_) => self.blocks(false),
+            _ => String::new(),
+        };
+        self.skip_end();
+        text
+    }
+
+    fn list(&mut self, start: Option<u64>) -> String {
+        use std::format;
+
+        let is_tight = self.is_tight_list();
+        let mut items = Vec::new();
+        let mut number = start;
+        while let Some(Event::Start(Tag::Item)) = self.peek() {
+            self.pos += 1;
+            let marker = match &mut number {
+                Some(number) => {
+                    *number += 1;
+                    format!("{}.", *number - 1)
+                }
+                None => String::from("-"),
+            };
+            let blocks = self.blocks(is_tight);
+            self.skip_end();
+            let indent = " ".repeat(marker.len() + 1);
+            items.push(if blocks.is_empty() {
+                marker
+            } else {
+                prefix_lines(&blocks, &(marker + " "), &indent, "")
+            });
+        }
+        items.join(if is_tight { "\n" } else { "\n\n" })
+    }
+
+    /// Returns `true` if the list starting at the current position has no item paragraphs.
+    fn is_tight_list(&self) -> bool {
+        let mut depth = 0;
+        for event in &self.events[self.pos..] {
+            match event {
+                Event::Start(Tag::Paragraph) if depth == 1 => return false,
+                Event::Start(_) => depth += 1,
+                Event::End(_) if depth == 0 => break,
+                Event::End(_) => depth -= 1,
+                _ => {}
+            }
+        }
+        true
+    }
+
+    fn definition_list(&mut self) -> String {
+        let mut lines = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Event::Start(Tag::DefinitionListTitle)) => {
+                    self.pos += 1;
+                    self.at_line_start = true;
+                    lines.push(self.inlines());
+                }
+                Some(Event::Start(Tag::DefinitionListDefinition)) => {
+                    self.pos += 1;
+                    let blocks = self.blocks(true);
+                    lines.push(prefix_lines(&blocks, ": ", "  ", ""));
+                }
+                _ => break,
+            }
+            self.skip_end();
+        }
+        lines.join("\n")
+    }
+
+    fn table(&mut self, alignments: &[Alignment]) -> String {
+        let mut rows = Vec::new();
+        while let Some(Event::Start(Tag::TableHead | Tag::TableRow)) = self.peek() {
+            self.pos += 1;
+            let mut cells = Vec::new();
+            while let Some(Event::Start(Tag::TableCell)) = self.peek() {
+                self.pos += 1;
+                self.at_line_start = false;
+                cells.push(self.inlines());
+                self.skip_end();
+            }
+            self.skip_end();
+            rows.push(std::format!("| {} |", cells.join(" | ")));
+            if rows.len() == 1 {
+                let delimiters: Vec<_> = alignments
+                    .iter()
+                    .map(|alignment| match alignment {
+                        Alignment::None => "---",
+                        Alignment::Left => ":--",
+                        Alignment::Center => ":-:",
+                        Alignment::Right => "--:",
+                    })
+                    .collect();
+                rows.push(std::format!("| {} |", delimiters.join(" | ")));
+            }
+        }
+        rows.join("\n")
+    }
+
+    /// Concatenates text events until the end event of the enclosing tag.
+    fn raw_text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(Event::Text(part) | Event::Html(part)) = self.peek() {
+            text.push_str(part);
+            self.pos += 1;
+        }
+        text
+    }
+
+    /// Renders inline events until the end event of the enclosing tag or the next block.
+    fn inlines(&mut self) -> String {
+        use std::format;
+
+        let mut text = String::new();
+        while let Some(event) = self.peek() {
+            if !is_inline(event) {
+                break;
+            }
+            self.pos += 1;
+            let part = match event {
+                Event::Text(part) => escape_text(part, self.at_line_start),
+                Event::Code(code) => code_span(code),
+                Event::InlineMath(math) => format!("${}$", math),
+                Event::DisplayMath(math) => format!("$${}$$", math),
+                Event::Html(html) | Event::InlineHtml(html) => String::from(&**html),
+                Event::FootnoteReference(label) => format!("[^{}]", label),
+                Event::SoftBreak => String::from("\n"),
+                Event::HardBreak => String::from("\\\n"),
+                Event::TaskListMarker(true) => String::from("[x] "),
+                Event::TaskListMarker(false) => String::from("[ ] "),
+                Event::Start(Tag::Emphasis) => self.wrapped_inlines("*", "*"),
+                Event::Start(Tag::Strong) => self.wrapped_inlines("**", "**"),
+                Event::Start(Tag::Strikethrough) => self.wrapped_inlines("~~", "~~"),
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => self.link(*link_type, dest_url, title, id),
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => String::from("!") + &self.link(*link_type, dest_url, title, id),
+                _ => String::new(),
+            };
+            self.at_line_start = matches!(event, Event::SoftBreak | Event::HardBreak);
+            text.push_str(&part);
+        }
+        text
+    }
+
+    fn wrapped_inlines(&mut self, prefix: &str, suffix: &str) -> String {
+        self.at_line_start = false;
+        let text = self.inlines();
+        self.skip_end();
+        std::format!("{}{}{}", prefix, text, suffix)
+    }
+
+    fn link(&mut self, link_type: LinkType, url: &str, title: &str, id: &str) -> String {
+        use std::format;
+
+        self.at_line_start = false;
+        let text = self.inlines();
+        self.skip_end();
+        if !id.is_empty() {
+            let definition = format!("[{}]: {}", id, link_destination(url, title));
+            if !self.definitions.contains(&definition) {
+                self.definitions.push(definition);
+            }
+        }
+        match link_type {
+            LinkType::Autolink | LinkType::Email => format!("<{}>", url),
+            LinkType::Reference | LinkType::ReferenceUnknown => format!("[{}][{}]", text, id),
+            LinkType::Collapsed | LinkType::CollapsedUnknown => format!("[{}][]", text),
+            LinkType::Shortcut | LinkType::ShortcutUnknown => format!("[{}]", text),
+            LinkType::Inline => format!("[{}]({})", text, link_destination(url, title)),
+        }
+    }
+}
+
+/// Renders the link destination and the optional link title.
+fn link_destination(url: &str, title: &str) -> String {
+    use std::format;
+
+    let url = if url.is_empty() || url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", url.replace('<', "\\<").replace('>', "\\>"))
+    } else {
+        String::from(url)
+    };
+    if title.is_empty() {
+        url
+    } else {
+        format!("{} \"{}\"", url, title.replace('"', "\\\""))
+    }
+}
+
+fn is_inline(event: &Event<'_>) -> bool {
+    match event {
+        Event::Start(tag) => matches!(
+            tag,
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. }
+        ),
+        Event::End(_) | Event::Rule => false,
+        Event::Text(_)
+        | Event::Code(_)
+        | Event::InlineMath(_)
+        | Event::DisplayMath(_)
+        | Event::Html(_)
+        | Event::InlineHtml(_)
+        | Event::FootnoteReference(_)
+        | Event::SoftBreak
+        | Event::HardBreak
+        | Event::TaskListMarker(_) => true,
+    }
+}
+
+fn blockquote_kind_marker(kind: BlockQuoteKind) -> &'static str {
+    match kind {
+        BlockQuoteKind::Note => "[!NOTE]",
+        BlockQuoteKind::Tip => "[!TIP]",
+        BlockQuoteKind::Important => "[!IMPORTANT]",
+        BlockQuoteKind::Warning => "[!WARNING]",
+        BlockQuoteKind::Caution => "[!CAUTION]",
+    }
+}
+
+/// Prefixes the first line and the following non-empty lines with the specified prefixes.
+///
+/// Empty lines are prefixed with `empty_prefix` instead.
+fn prefix_lines(text: &str, first_prefix: &str, prefix: &str, empty_prefix: &str) -> String {
+    let mut result = String::new();
+    for (idx, line) in text.split('\n').enumerate() {
+        if idx > 0 {
+            result.push('\n');
+        }
+        if idx == 0 {
+            result.push_str(first_prefix);
+        } else if line.is_empty() {
+            result.push_str(empty_prefix);
+        } else {
+            result.push_str(prefix);
+        }
+        result.push_str(line);
+    }
+    result
+}
+
+fn fenced_code_block(info: &CowStr<'_>, code: &str) -> String {
+    let fence_char = if info.contains('`') { '~' } else { '`' };
+    let longest_fence = code
+        .lines()
+        .map(|line| {
+            line.trim_start()
+                .chars()
+                .take_while(|&ch| ch == fence_char)
+                .count()
+        })
+        .max()
+        .unwrap_or(0);
+    let fence: String = core::iter::repeat(fence_char)
+        .take(longest_fence.max(2) + 1)
+        .collect();
+    let mut text = std::format!("{}{}\n{}", fence, info, code);
+    if !code.is_empty() && !code.ends_with('\n') {
+        text.push('\n');
+    }
+    text + &fence
+}
+
+pub(crate) fn code_span(code: &str) -> String {
+    let mut longest_run = 0;
+    let mut run = 0;
+    for ch in code.chars() {
+        run = if ch == '`' { run + 1 } else { 0 };
+        longest_run = longest_run.max(run);
+    }
+    let fence = "`".repeat(longest_run + 1);
+    let is_padded = code.starts_with('`')
+        || code.ends_with('`')
+        || code.len() > 1 && code.starts_with(' ') && code.ends_with(' ');
+    if is_padded {
+        std::format!("{} {} {}", fence, code, fence)
+    } else {
+        std::format!("{}{}{}", fence, code, fence)
+    }
+}
+
+/// Escapes text characters that could be interpreted as Markdown syntax.
+pub(crate) fn escape_text(text: &str, at_line_start: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    for (idx, &ch) in chars.iter().enumerate() {
+        let prev = idx.checked_sub(1).map(|idx| chars[idx]);
+        let next = chars.get(idx + 1).copied();
+        let is_escaped = match ch {
+            '\\' | '`' | '*' | '[' | ']' | '<' | '>' | '|' | '~' => true,
+            '_' => {
+                !prev.is_some_and(char::is_alphanumeric) || !next.is_some_and(char::is_alphanumeric)
+            }
+            '&' => next.is_some_and(|next| next.is_ascii_alphanumeric() || next == '#'),
+            '#' | '-' | '+' | '=' => at_line_start && idx == 0,
+            '.' | ')' => at_line_start && idx > 0 && chars[..idx].iter().all(char::is_ascii_digit),
+            _ => false,
+        };
+        if is_escaped {
+            result.push('\\');
+        }
+        result.push(ch);
+    }
+    result
+}
+
+#[test]
+fn test_render_markdown_round_trip() {
+    use pulldown_cmark::{Options, Parser};
+
+    let texts = [
+        "# Title\n\nText with *emphasis*, **strong** and `code`.\n",
+        "Special characters: \\*, \\_, \\[a\\], a\\<b, snake_case, a &amp; b, &copy;.\n",
+        "\\# Not a heading\n\n1\\. Not a list\n",
+        "- a\n- b\n  - c\n\n1. first\n2. second\n",
+        "- loose\n\n- list\n\n  with paragraphs\n",
+        "> quote\n>\n> - item\n",
+        "```rust\nfn main() {}\n```\n\n    indented\n\n````\n```\n````\n",
+        "[link](https://example.com \"title\") ![image](img.png) <https://example.com>\n",
+        "[reference][id]\n\n[id]: https://example.com\n",
+        "| a | b |\n|:--|--:|\n| `c` | d \\| e |\n",
+        "Line  \nbreak\nsoft\n\n***\n\n<div>\nhtml\n</div>\n",
+        "`` a`b `` and ` `` `\n",
+        "- [x] done\n- [ ] todo\n",
+    ];
+    let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+    // Adjacent text events are concatenated, as it is done by `CMarkData` parsing.
+    let parse = |text: &str| {
+        let mut events: Vec<Event<'static>> = Vec::new();
+        for event in Parser::new_ext(text, options) {
+            match (events.last_mut(), event) {
+                (Some(Event::Text(prev)), Event::Text(text)) => {
+                    *prev = (String::from(&**prev) + &text).into();
+                }
+                (_, event) => events.push(event.into_static()),
+            }
+        }
+        events
+    };
+    for text in texts {
+        let events = parse(text);
+        let rendered = render_markdown(&events);
+        let rendered_events = parse(&rendered);
+        assert_eq!(
+            events, rendered_events,
+            "{:?} rendered as {:?}",
+            text, rendered
+        );
+    }
+    assert_eq!(render_markdown(&[]), "");
+}
@@ -0,0 +1,151 @@
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, HeadingLevel, Tag};
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem};
+
+/// Section names rustdoc adds to the crate page after the crate documentation.
+pub const RUSTDOC_RESERVED_HEADINGS: &[&str] = &[
+    "Re-exports",
+    "Modules",
+    "Macros",
+    "Structs",
+    "Enums",
+    "Constants",
+    "Statics",
+    "Traits",
+    "Functions",
+    "Type Aliases",
+    "Unions",
+    "Attribute Macros",
+    "Derive Macros",
+];
+
+/// A top-level documentation heading colliding with a rustdoc-generated section.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error(
+    "Heading \"{heading}\" collides with the rustdoc-generated section, \
+        consider renaming it, like to \"{suggestion}\""
+)]
+pub struct ReservedHeadingWarning {
+    /// The heading text.
+    pub heading: String,
+    /// The suggested heading text.
+    pub suggestion: String,
+    /// The heading start node.
+    pub node: Arc<CMarkItem>,
+}
+
+impl CMarkData {
+    /// Returns warnings about top-level headings named like rustdoc-generated sections.
+    ///
+    /// Headings are checked by their level before any transformations,
+    /// so the first level headings of the docs are checked even after `increment_heading_levels`.
+    /// Names from `RUSTDOC_RESERVED_HEADINGS` are matched case-insensitively.
+    pub fn lint_reserved_headings(&self) -> Vec<ReservedHeadingWarning> {
+        use crate::cmark_data::heading_text;
+
+        let items: Vec<_> = self.iter().cloned().collect();
+        let mut warnings = Vec::new();
+        for (idx, node) in items.iter().enumerate() {
+            if original_heading_level(node) != Some(HeadingLevel::H1) {
+                continue;
+            }
+            let heading = match heading_text(&items[idx..]) {
+                Some(heading) => heading,
+                None => continue,
+            };
+            let is_reserved = RUSTDOC_RESERVED_HEADINGS
+                .iter()
+                .any(|reserved| reserved.eq_ignore_ascii_case(heading.trim()));
+            if is_reserved {
+                warnings.push(ReservedHeadingWarning {
+                    suggestion: std::format!("{} Overview", heading.trim()),
+                    heading,
+                    node: Arc::clone(node),
+                });
+            }
+        }
+        warnings
+    }
+}
+
+/// Returns the level of the heading started by the node as it was parsed.
+fn original_heading_level(node: &CMarkItem) -> Option<HeadingLevel> {
+    match node {
+        CMarkItem::Parsed { event, .. } | CMarkItem::Created { event, .. } => match event {
+            Event::Start(Tag::Heading { level, .. }) => Some(*level),
+            _ => None,
+        },
+        CMarkItem::Modified { event, nodes, .. } => match event {
+            Event::Start(Tag::Heading { level, .. }) => nodes
+                .iter()
+                .find_map(|node| original_heading_level(node))
+                .or(Some(*level)),
+            _ => None,
+        },
+        CMarkItem::Removed { .. } => None,
+        CMarkItem::Noted { node, .. } => original_heading_level(node),
+    }
+}
+
+/// Asserts that the given docs do not contain top-level headings
+/// named like rustdoc-generated sections.
+///
+/// See `CMarkData::lint_reserved_headings` for details.
+#[cfg(feature = "codemap-diagnostic")]
+pub fn assert_no_reserved_headings<P, M>(docs: &crate::CMarkDocs<P, M>) {
+    use crate::{CodemapFiles, CodemapSpans, MatchFailed};
+    use codemap_diagnostic::{Diagnostic, Level};
+    use std::string::ToString;
+
+    let warnings = docs.lint_reserved_headings();
+    if !warnings.is_empty() {
+        let mut codemap_files = CodemapFiles::new();
+        let diags = warnings
+            .iter()
+            .map(|warning| Diagnostic {
+                level: Level::Error,
+                message: warning.to_string(),
+                code: None,
+                spans: CodemapSpans::span_labels_from(&mut codemap_files, warning.node.spans()),
+            })
+            .collect();
+        MatchFailed::from_diags(diags, Arc::new(codemap_files)).emit_to_stderr_colored();
+        panic!("{} reserved heading(s) found", warnings.len());
+    }
+}
+
+#[test]
+fn test_lint_reserved_headings() {
+    use crate::{CMarkDocs, File};
+    use std::path::PathBuf;
+    use std::string::ToString;
+
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from("lib.rs/parsed"),
+            "# Usage\n\n# structs\n\n## Traits\n\n# Type Aliases\n".into(),
+        ))),
+        (),
+        (),
+    )
+    .increment_heading_levels();
+    let warnings: Vec<_> = docs
+        .lint_reserved_headings()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "Heading \"structs\" collides with the rustdoc-generated section, \
+                consider renaming it, like to \"structs Overview\"",
+            "Heading \"Type Aliases\" collides with the rustdoc-generated section, \
+                consider renaming it, like to \"Type Aliases Overview\"",
+        ]
+    );
+}
@@ -0,0 +1,291 @@
+use std::borrow::Cow;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, Tag};
+
+use crate::{CMarkData, CMarkItem};
+
+/// A link whose destination looks like a Rust path but can not be resolved unambiguously.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnresolvedRustPath {
+    /// The link destination.
+    pub path: String,
+    /// Link start node.
+    pub link: Arc<CMarkItem>,
+}
+
+impl CMarkData {
+    /// Rewrite links with Rust path destinations, like intra-doc links,
+    /// to the rustdoc item urls with the specified docs url prefix.
+    ///
+    /// The item kind is inferred from the path segments names:
+    /// - `Struct` is resolved to `struct.Struct.html`,
+    /// - `Struct::method` is resolved to `struct.Struct.html#method.method`,
+    /// - `Struct::CONSTANT` is resolved to `struct.Struct.html#associatedconstant.CONSTANT`,
+    /// - `Enum::Variant` is resolved to `enum.Enum.html#variant.Variant`,
+    /// - `crate::CONSTANT` is resolved to `constant.CONSTANT.html`,
+    /// - `function()` is resolved to `fn.function.html`,
+    /// - `macro!` is resolved to `macro.macro.html`,
+    /// - leading lowercase segments are resolved to modules.
+    ///
+    /// Rustdoc disambiguators like `struct@`, `enum@`, `trait@`, `fn@`, `macro@` and `mod@`
+    /// are used instead of the inferred item kind if specified.
+    /// Leading `crate`, `self` and the specified crate name segments are skipped.
+    /// A single uppercase segment, like `CONSTANT`, is not rewritten without a disambiguator,
+    /// as it is more likely a file name, like `LICENSE`.
+    ///
+    /// The item urls are prefixed with the docs url prefix,
+    /// like the one returned by `Manifest::docs_url_prefix`,
+    /// or left relative if the prefix is empty.
+    ///
+    /// Returns the links that look like Rust paths but are ambiguous,
+    /// for example single lowercase segments that may be both modules and functions,
+    /// so they can be mapped manually with `map_links`.
+    pub fn rewrite_rust_path_links(
+        self,
+        crate_name: &str,
+        docs_prefix: &str,
+    ) -> (Self, Vec<UnresolvedRustPath>) {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::CowStr;
+
+        let crate_name = crate_name.replace('-', "_");
+        let note = Cow::from(std::format!(
+            "rewrite_rust_path_links(crate_name = \"{}\", docs_prefix = \"{}\")",
+            crate_name,
+            docs_prefix
+        ));
+        let mut unresolved = Vec::new();
+        let items = self.into_items().into_iter().map(|node| {
+            let tag = match node.event() {
+                Some(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                })) => match rust_path_url(dest_url, &crate_name) {
+                    RustPathUrl::NotRustPath => None,
+                    RustPathUrl::Ambiguous => {
+                        unresolved.push(UnresolvedRustPath {
+                            path: String::from(dest_url.as_ref()),
+                            link: Arc::clone(&node),
+                        });
+                        None
+                    }
+                    RustPathUrl::Resolved(url) => Some(Tag::Link {
+                        link_type: *link_type,
+                        dest_url: CowStr::from([docs_prefix, &url].concat()),
+                        title: title.clone(),
+                        id: id.clone(),
+                    }),
+                },
+                _ => None,
+            };
+            match tag {
+                Some(tag) => node.into_modified(Event::Start(tag), note.clone()),
+                None => node,
+            }
+        });
+        let data = Self::from_items(items.collect());
+        (data, unresolved)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum RustPathUrl {
+    NotRustPath,
+    Ambiguous,
+    Resolved(String),
+}
+
+fn rust_path_url(dest_url: &str, crate_name: &str) -> RustPathUrl {
+    use std::format;
+
+    const KINDS: &[&str] = &[
+        "struct",
+        "enum",
+        "trait",
+        "union",
+        "type",
+        "fn",
+        "function",
+        "macro",
+        "mod",
+        "module",
+        "const",
+        "constant",
+        "static",
+        "derive",
+        "attr",
+        "primitive",
+    ];
+
+    let path = dest_url.trim_matches('`');
+    let (kind, path) = match path.split_once('@') {
+        Some((kind, path)) if KINDS.contains(&kind) => (Some(kind), path),
+        Some(_) => return RustPathUrl::NotRustPath,
+        None => (None, path),
+    };
+    let (kind, path) = if let Some(path) = path.strip_suffix("()") {
+        (kind.or(Some("fn")), path)
+    } else if let Some(path) = path.strip_suffix('!') {
+        (kind.or(Some("macro")), path)
+    } else {
+        (kind, path)
+    };
+    let kind = kind.map(|kind| match kind {
+        "const" => "constant",
+        "function" => "fn",
+        "module" => "mod",
+        kind => kind,
+    });
+
+    let mut segments: Vec<&str> = path.split("::").collect();
+    if !segments.iter().all(|segment| is_identifier(segment)) {
+        return RustPathUrl::NotRustPath;
+    }
+    if kind.is_none() && segments.len() == 1 && !is_upper_camel_case(segments[0]) {
+        return if is_lower_snake_case(segments[0]) {
+            RustPathUrl::Ambiguous
+        } else {
+            RustPathUrl::NotRustPath
+        };
+    }
+    if segments.len() > 1 && [crate_name, "crate", "self"].contains(&segments[0]) {
+        let _ = segments.remove(0);
+    }
+
+    let modules_count = segments
+        .iter()
+        .take_while(|segment| is_lower_snake_case(segment))
+        .count();
+    let (modules, items) = segments.split_at(modules_count);
+    let dir = modules_dir(modules);
+
+    let url = match (kind, modules, items) {
+        (Some("mod"), _, []) => format!("{}index.html", dir),
+        (Some("mod"), _, _) => return RustPathUrl::Ambiguous,
+        (Some(kind), _, [item]) => format!("{}{}.{}.html", dir, kind, item),
+        (Some(kind), [parents @ .., item], []) => {
+            format!("{}{}.{}.html", modules_dir(parents), kind, item)
+        }
+        (None, _, [item]) if is_upper_case(item) => format!("{}constant.{}.html", dir, item),
+        (None, _, [item]) => format!("{}struct.{}.html", dir, item),
+        (None, _, [ty, member]) if is_upper_case(member) => {
+            format!("{}struct.{}.html#associatedconstant.{}", dir, ty, member)
+        }
+        (None, _, [ty, member]) if is_upper_camel_case(member) => {
+            format!("{}enum.{}.html#variant.{}", dir, ty, member)
+        }
+        (None, _, [ty, member]) => format!("{}struct.{}.html#method.{}", dir, ty, member),
+        _ => return RustPathUrl::Ambiguous,
+    };
+    RustPathUrl::Resolved(url)
+}
+
+fn modules_dir(modules: &[&str]) -> String {
+    modules.iter().flat_map(|module| [module, "/"]).collect()
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+fn is_lower_snake_case(text: &str) -> bool {
+    text.chars()
+        .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+}
+
+fn is_upper_case(text: &str) -> bool {
+    text.chars()
+        .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit() || ch == '_')
+}
+
+fn is_upper_camel_case(text: &str) -> bool {
+    text.starts_with(|ch: char| ch.is_ascii_uppercase())
+        && text.chars().any(|ch| ch.is_ascii_lowercase())
+        && !text.contains('_')
+}
+
+#[test]
+fn test_rust_path_url() {
+    use std::string::ToString;
+
+    let url = |path: &str| match rust_path_url(path, "my_crate") {
+        RustPathUrl::Resolved(url) => url,
+        RustPathUrl::Ambiguous => "ambiguous".to_string(),
+        RustPathUrl::NotRustPath => "not a path".to_string(),
+    };
+
+    assert_eq!(url("CMarkDocs"), "struct.CMarkDocs.html");
+    assert_eq!(url("`CMarkDocs`"), "struct.CMarkDocs.html");
+    assert_eq!(
+        url("CMarkDocs::map_links"),
+        "struct.CMarkDocs.html#method.map_links"
+    );
+    assert_eq!(
+        url("crate::CMarkDocs::map_links"),
+        "struct.CMarkDocs.html#method.map_links"
+    );
+    assert_eq!(url("my_crate::events::heading()"), "events/fn.heading.html");
+    assert_eq!(url("events::heading"), "ambiguous");
+    assert_eq!(url("Level::Error"), "enum.Level.html#variant.Error");
+    assert_eq!(url("SYNC_CONFIG_FILE_NAME"), "not a path");
+    assert_eq!(
+        url("const@SYNC_CONFIG_FILE_NAME"),
+        "constant.SYNC_CONFIG_FILE_NAME.html"
+    );
+    assert_eq!(
+        url("EmitOptions::MAX_LEN"),
+        "struct.EmitOptions.html#associatedconstant.MAX_LEN"
+    );
+    assert_eq!(
+        url("crate::SYNC_CONFIG_FILE_NAME"),
+        "constant.SYNC_CONFIG_FILE_NAME.html"
+    );
+    assert_eq!(url("trait@Print"), "trait.Print.html");
+    assert_eq!(url("mod@events"), "events/index.html");
+    assert_eq!(url("assert_sync()"), "fn.assert_sync.html");
+    assert_eq!(url("vec!"), "macro.vec.html");
+    assert_eq!(url("events"), "ambiguous");
+    assert_eq!(url("LICENSE"), "not a path");
+    assert_eq!(url("docs/README.md"), "not a path");
+    assert_eq!(url("https://docs.rs"), "not a path");
+    assert_eq!(url("#usage"), "not a path");
+}
+
+#[test]
+fn test_rewrite_rust_path_links() {
+    use crate::File;
+    use std::path::PathBuf;
+
+    let data = CMarkData::from_file(Arc::new(File::from_path_and_text(
+        PathBuf::from("lib.rs"),
+        "[a](CMarkDocs::map_links) [b](events) [c](https://example.com)\n".into(),
+    )));
+    let (data, unresolved) =
+        data.rewrite_rust_path_links("my-crate", "https://docs.rs/my-crate/*/my_crate/");
+    let urls: Vec<_> = data
+        .iter_events()
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. }) => Some(dest_url.as_ref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            "https://docs.rs/my-crate/*/my_crate/struct.CMarkDocs.html#method.map_links",
+            "events",
+            "https://example.com"
+        ]
+    );
+    assert_eq!(unresolved.len(), 1);
+    assert_eq!(unresolved[0].path, "events");
+}
@@ -0,0 +1,168 @@
+use core::ops::Range;
+use std::path::PathBuf;
+use std::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CMarkSpan, SyncMismatch, TextSource};
+
+/// A 1-based line and column in a file.
+///
+/// Columns are counted in characters.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct LineCol {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number.
+    pub column: usize,
+}
+
+/// The role of a source location in a mismatch.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum LocationRole {
+    /// The mismatched readme node.
+    ReadmePrimary,
+    /// The mismatched docs node.
+    DocsPrimary,
+    /// A readme node removed right before the mismatched readme node.
+    ReadmeRemoved,
+    /// A docs node removed right before the mismatched docs node.
+    DocsRemoved,
+}
+
+/// A source file location of a mismatch node usable without codemap.
+///
+/// Docs locations are mapped to the original file containing the documentation.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SourceLocation {
+    /// File path.
+    pub path: PathBuf,
+    /// Byte range in the file.
+    pub byte_range: Range<usize>,
+    /// Start line and column.
+    pub start: LineCol,
+    /// End line and column.
+    pub end: LineCol,
+    /// Location role in the mismatch.
+    pub role: LocationRole,
+}
+
+impl SourceLocation {
+    /// Returns the file location of the specified span.
+    ///
+    /// Returns `None` if the docs span can not be mapped to its file.
+    pub fn from_span(span: &CMarkSpan<'_>, role: LocationRole) -> Option<Self> {
+        let (file, byte_range) = match span.text_source {
+            TextSource::File(file) => (file, span.range.clone()),
+            TextSource::FileDocs(file_docs) => {
+                let start = file_docs.remap_offset_to_file(span.range.start)?;
+                let end = if span.range.end > span.range.start {
+                    file_docs
+                        .remap_offset_to_file(span.range.end - 1)
+                        .map_or(start, |end| end + 1)
+                        .max(start)
+                } else {
+                    start
+                };
+                (file_docs.file(), start..end)
+            }
+        };
+        Some(Self {
+            path: file.path().to_path_buf(),
+            start: file.line_col(byte_range.start),
+            end: file.line_col(byte_range.end),
+            byte_range,
+            role,
+        })
+    }
+}
+
+impl SyncMismatch {
+    /// Returns the file locations of the mismatched and removed nodes.
+    ///
+    /// Locations are deduplicated and ordered by their roles.
+    pub fn locations(&self) -> Vec<SourceLocation> {
+        let nodes = self
+            .readme_node
+            .iter()
+            .map(|node| (node, LocationRole::ReadmePrimary))
+            .chain(
+                self.docs_node
+                    .iter()
+                    .map(|node| (node, LocationRole::DocsPrimary)),
+            )
+            .chain(
+                self.readme_removed
+                    .iter()
+                    .map(|node| (node, LocationRole::ReadmeRemoved)),
+            )
+            .chain(
+                self.docs_removed
+                    .iter()
+                    .map(|node| (node, LocationRole::DocsRemoved)),
+            );
+
+        let mut locations = Vec::new();
+        for (node, role) in nodes {
+            for span in node.spans() {
+                if let Some(location) = SourceLocation::from_span(&span, role) {
+                    if !locations.contains(&location) {
+                        locations.push(location);
+                    }
+                }
+            }
+        }
+        locations
+    }
+}
+
+#[test]
+fn test_mismatch_locations() {
+    use crate::test_utils::readme_file;
+    use crate::{compare_sync, CMarkDocs, CMarkReadme, Config, File};
+    use std::sync::Arc;
+
+    let readme = CMarkReadme::from_file(readme_file("# Title\n\nSome text.\n"));
+    let docs = CMarkDocs::from_file_and_config(
+        Arc::new(File::from_path_and_text(
+            PathBuf::from("src/lib.rs"),
+            "//! # Title\n//!\n//! Other text.\n".into(),
+        )),
+        &Config::default(),
+    )
+    .unwrap();
+
+    let mismatch = compare_sync(&readme, &docs).unwrap_err();
+    let locations = mismatch.locations();
+    assert_eq!(
+        locations,
+        [
+            SourceLocation {
+                path: PathBuf::from("README.md"),
+                byte_range: 9..19,
+                start: LineCol { line: 3, column: 1 },
+                end: LineCol {
+                    line: 3,
+                    column: 11
+                },
+                role: LocationRole::ReadmePrimary,
+            },
+            SourceLocation {
+                path: PathBuf::from("src/lib.rs"),
+                byte_range: 20..31,
+                start: LineCol { line: 3, column: 5 },
+                end: LineCol {
+                    line: 3,
+                    column: 16
+                },
+                role: LocationRole::DocsPrimary,
+            },
+        ]
+    );
+
+    let serialized = toml::to_string(&locations[1]).unwrap();
+    assert_eq!(
+        toml::from_str::<SourceLocation>(&serialized).unwrap(),
+        locations[1]
+    );
+}
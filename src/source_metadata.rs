@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::time::SystemTime;
+
+/// Optional metadata of the readme and docs source files.
+///
+/// It is used by `check_sync_with_metadata` to guess which side of a mismatch is stale.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceMetadata {
+    /// Readme file metadata.
+    pub readme: FileMetadata,
+    /// Docs file metadata.
+    pub docs: FileMetadata,
+}
+
+/// Optional metadata of a single source file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FileMetadata {
+    /// File path used in diagnostic messages.
+    pub path: Option<PathBuf>,
+    /// File last modification time.
+    pub modified: Option<SystemTime>,
+    /// Caller-defined revision label, for example a commit hash.
+    pub revision: Option<String>,
+}
+
+impl SourceMetadata {
+    /// Creates metadata without any information about the files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads modification times of the specified readme and docs files.
+    ///
+    /// The modification time is left unspecified if it can not be read.
+    pub fn from_fs(readme_path: &Path, docs_path: &Path) -> Self {
+        Self {
+            readme: FileMetadata::from_fs(readme_path),
+            docs: FileMetadata::from_fs(docs_path),
+        }
+    }
+
+    /// Returns a heuristic note about which of the files is likely stale.
+    ///
+    /// Returns `None` if there is not enough metadata to say anything.
+    pub(crate) fn freshness_note(&self) -> Option<String> {
+        use std::format;
+
+        let readme = self.readme.label("readme");
+        let docs = self.docs.label("docs");
+        match (self.readme.modified, self.docs.modified) {
+            (Some(readme_modified), Some(docs_modified)) if readme_modified != docs_modified => {
+                let (newer, newer_modified, older, older_modified, stale) =
+                    if readme_modified > docs_modified {
+                        (readme, readme_modified, docs, docs_modified, "docs are")
+                    } else {
+                        (docs, docs_modified, readme, readme_modified, "readme is")
+                    };
+                Some(format!(
+                    "{} was modified more recently ({}) than {} ({}); \
+                     the {} likely stale (a guess based on file modification times)",
+                    newer,
+                    format_date(newer_modified),
+                    older,
+                    format_date(older_modified),
+                    stale,
+                ))
+            }
+            _ if self.readme.revision.is_some() || self.docs.revision.is_some() => Some(format!(
+                "readme source is {}, docs source is {}; \
+                 compare their revisions to find out which one is stale",
+                readme, docs
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl FileMetadata {
+    /// Reads modification time of the specified file.
+    pub fn from_fs(path: &Path) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            modified: std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            revision: None,
+        }
+    }
+
+    fn label(&self, default: &str) -> String {
+        use std::format;
+        use std::string::ToString;
+
+        let name = match &self.path {
+            Some(path) => path.display().to_string(),
+            None => default.to_string(),
+        };
+        match &self.revision {
+            Some(revision) => format!("{} (revision {})", name, revision),
+            None => name,
+        }
+    }
+}
+
+/// Formats the date of the specified time as `YYYY-MM-DD` in UTC.
+fn format_date(time: SystemTime) -> String {
+    use std::time::UNIX_EPOCH;
+
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86400);
+
+    // Converts days since the Unix epoch into a civil date
+    // using the algorithm from <https://howardhinnant.github.io/date_algorithms.html>.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    std::format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[test]
+fn test_freshness_note() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let at = |secs| Some(UNIX_EPOCH + Duration::from_secs(secs));
+    assert_eq!(format_date(UNIX_EPOCH), "1970-01-01");
+    assert_eq!(format_date(at(1_709_164_800).unwrap()), "2024-02-29");
+    assert_eq!(format_date(at(1_714_521_600).unwrap()), "2024-05-01");
+
+    let mut metadata = SourceMetadata::new();
+    assert_eq!(metadata.freshness_note(), None);
+
+    metadata.readme.path = Some("README.md".into());
+    metadata.readme.modified = at(1_714_521_600);
+    metadata.docs.path = Some("src/lib.rs".into());
+    metadata.docs.modified = at(1_710_201_600);
+    assert_eq!(
+        metadata.freshness_note().unwrap(),
+        "README.md was modified more recently (2024-05-01) than src/lib.rs (2024-03-12); \
+         the docs are likely stale (a guess based on file modification times)"
+    );
+
+    metadata.readme.modified = None;
+    metadata.docs.revision = Some("abc123".into());
+    assert_eq!(
+        metadata.freshness_note().unwrap(),
+        "readme source is README.md, docs source is src/lib.rs (revision abc123); \
+         compare their revisions to find out which one is stale"
+    );
+}
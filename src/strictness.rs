@@ -0,0 +1,312 @@
+use core::fmt::{self, Display};
+use std::borrow::Cow;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::{CMarkData, SyncOptions};
+
+/// A preset of the readme and docs comparison options.
+///
+/// Every preset is a composition of the individual `SyncOptions` flags listed by
+/// `SyncStrictness::enabled_options`, it has no other effects.
+/// The `Display` implementation prints the preset name with its options for CI logs.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SyncStrictness {
+    /// Events are compared as is, even the trailing document whitespace.
+    ///
+    /// No options are enabled.
+    Strict,
+    /// Differences invisible in the rendered documents are ignored.
+    ///
+    /// It enables `collapse_soft_breaks`, `collapse_whitespace`, `normalize_link_types`,
+    /// `remove_heading_attributes` and `ignore_trailing_document_whitespace`.
+    Standard,
+    /// Minor differences of the rendered documents are ignored as well.
+    ///
+    /// It enables the `Standard` options and `normalize_punctuation`, `remove_link_titles`,
+    /// `use_soft_breaks` and `remove_titles`.
+    Lenient,
+}
+
+/// The name and the field accessor of a `SyncOptions` flag.
+type SyncOptionFlag = (&'static str, fn(&mut SyncOptions) -> &mut bool);
+
+/// Whether a normalization is enabled and the normalization itself.
+type NormalizeStep = (bool, fn(CMarkData) -> CMarkData);
+
+/// The names and fields of the sync options flags.
+///
+/// The normalization options are applied in this order.
+const SYNC_OPTION_FLAGS: &[SyncOptionFlag] = &[
+    ("use_soft_breaks", |options| &mut options.use_soft_breaks),
+    ("collapse_soft_breaks", |options| {
+        &mut options.collapse_soft_breaks
+    }),
+    ("normalize_punctuation", |options| {
+        &mut options.normalize_punctuation
+    }),
+    ("collapse_whitespace", |options| {
+        &mut options.collapse_whitespace
+    }),
+    ("normalize_link_types", |options| {
+        &mut options.normalize_link_types
+    }),
+    ("remove_link_titles", |options| {
+        &mut options.remove_link_titles
+    }),
+    ("remove_heading_attributes", |options| {
+        &mut options.remove_heading_attributes
+    }),
+    ("remove_titles", |options| &mut options.remove_titles),
+    ("ignore_trailing_document_whitespace", |options| {
+        &mut options.ignore_trailing_document_whitespace
+    }),
+];
+
+impl SyncStrictness {
+    /// Returns the names of the `SyncOptions` flags enabled by the preset.
+    pub fn enabled_options(self) -> &'static [&'static str] {
+        match self {
+            Self::Strict => &[],
+            Self::Standard => &[
+                "collapse_soft_breaks",
+                "collapse_whitespace",
+                "normalize_link_types",
+                "remove_heading_attributes",
+                "ignore_trailing_document_whitespace",
+            ],
+            Self::Lenient => &[
+                "collapse_soft_breaks",
+                "collapse_whitespace",
+                "normalize_link_types",
+                "remove_heading_attributes",
+                "ignore_trailing_document_whitespace",
+                "normalize_punctuation",
+                "remove_link_titles",
+                "use_soft_breaks",
+                "remove_titles",
+            ],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Strict => "strict",
+            Self::Standard => "standard",
+            Self::Lenient => "lenient",
+        }
+    }
+}
+
+impl Display for SyncStrictness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = self.enabled_options();
+        if options.is_empty() {
+            write!(f, "{} (no options)", self.name())
+        } else {
+            write!(f, "{} ({})", self.name(), options.join(", "))
+        }
+    }
+}
+
+impl SyncOptions {
+    /// Creates the default options with the comparison flags of the specified preset.
+    ///
+    /// The flags not listed in `SyncStrictness::enabled_options` are disabled.
+    pub fn from_strictness(strictness: SyncStrictness) -> Self {
+        let mut options = Self::default();
+        for (name, flag) in SYNC_OPTION_FLAGS {
+            *flag(&mut options) = strictness.enabled_options().contains(name);
+        }
+        options
+    }
+
+    /// Returns the data with the enabled normalization options applied.
+    pub(crate) fn normalize<'a>(&self, data: &'a CMarkData) -> Cow<'a, CMarkData> {
+        let steps: [NormalizeStep; 8] = [
+            (self.use_soft_breaks, CMarkData::use_soft_breaks),
+            (self.collapse_soft_breaks, CMarkData::collapse_soft_breaks),
+            (self.normalize_punctuation, CMarkData::normalize_punctuation),
+            (self.collapse_whitespace, CMarkData::collapse_whitespace),
+            (self.normalize_link_types, CMarkData::normalize_link_types),
+            (self.remove_link_titles, CMarkData::remove_link_titles),
+            (
+                self.remove_heading_attributes,
+                CMarkData::remove_heading_attributes,
+            ),
+            (self.remove_titles, CMarkData::remove_title),
+        ];
+        if !steps.iter().any(|(is_enabled, _)| *is_enabled) {
+            return Cow::Borrowed(data);
+        }
+        Cow::Owned(
+            steps
+                .iter()
+                .filter(|(is_enabled, _)| *is_enabled)
+                .fold(data.clone(), |data, (_, step)| step(data)),
+        )
+    }
+
+    /// Returns `true` if the data are the same with these options.
+    fn is_matched(&self, readme: &CMarkData, docs: &CMarkData) -> bool {
+        use crate::compare::find_mismatch;
+
+        match find_mismatch(&self.normalize(readme), &self.normalize(docs)) {
+            Some(mismatch) => {
+                self.ignore_trailing_document_whitespace && mismatch.trailing_whitespace_only
+            }
+            None => true,
+        }
+    }
+}
+
+/// Returns the note naming the disabled sync options that make the data the same if any.
+///
+/// The options that make the data the same alone are preferred,
+/// otherwise the disabled options are enabled together
+/// and the options unnecessary for the match are disabled back one by one.
+pub(crate) fn sync_option_note(
+    readme: &CMarkData,
+    docs: &CMarkData,
+    options: &SyncOptions,
+) -> Option<String> {
+    use std::format;
+
+    let with_options = |flags: &[&SyncOptionFlag]| {
+        let mut options = *options;
+        for (_, flag) in flags {
+            *flag(&mut options) = true;
+        }
+        options
+    };
+    let names = |flags: &[&SyncOptionFlag]| -> Vec<String> {
+        flags
+            .iter()
+            .map(|(name, _)| format!("`{}`", name))
+            .collect()
+    };
+
+    let mut disabled: Vec<_> = SYNC_OPTION_FLAGS
+        .iter()
+        .filter(|(_, flag)| {
+            let mut options = *options;
+            !*flag(&mut options)
+        })
+        .collect();
+    let single: Vec<_> = disabled
+        .iter()
+        .copied()
+        .filter(|flag| with_options(&[flag]).is_matched(readme, docs))
+        .collect();
+    if !single.is_empty() {
+        return Some(format!(
+            "readme and docs are the same if the {} sync option is enabled",
+            names(&single).join(" or ")
+        ));
+    }
+
+    if !with_options(&disabled).is_matched(readme, docs) {
+        return None;
+    }
+    let mut idx = 0;
+    while idx < disabled.len() {
+        let flag = disabled.remove(idx);
+        if !with_options(&disabled).is_matched(readme, docs) {
+            disabled.insert(idx, flag);
+            idx += 1;
+        }
+    }
+    Some(format!(
+        "readme and docs are the same if the {} sync options are enabled",
+        names(&disabled).join(", ")
+    ))
+}
+
+#[test]
+fn test_sync_strictness() {
+    use crate::test_utils::readme_file;
+    use crate::{check_sync_with_options, CMarkDocs, CMarkReadme, CheckSyncError, File};
+    use std::path::PathBuf;
+    use std::string::ToString;
+    use std::sync::Arc;
+
+    let readme = |text: &str| CMarkReadme::from_file(readme_file(text));
+    let docs = |text: &str| {
+        CMarkDocs::from_data_chunks_package_pach_and_manifest(
+            CMarkData::from_file(Arc::new(File::from_path_and_text(
+                PathBuf::from("lib.md"),
+                text.into(),
+            ))),
+            (),
+            (),
+        )
+    };
+    let check = |readme_text: &str, docs_text: &str, strictness| {
+        check_sync_with_options(
+            &readme(readme_text),
+            &docs(docs_text),
+            &SyncOptions::from_strictness(strictness),
+        )
+        .map_err(|CheckSyncError::MatchFailed(err)| err.to_string())
+    };
+
+    assert_eq!(SyncStrictness::Strict.to_string(), "strict (no options)");
+    assert_eq!(
+        SyncStrictness::Standard.to_string(),
+        "standard (collapse_soft_breaks, collapse_whitespace, normalize_link_types, \
+            remove_heading_attributes, ignore_trailing_document_whitespace)"
+    );
+    for strictness in [
+        SyncStrictness::Strict,
+        SyncStrictness::Standard,
+        SyncStrictness::Lenient,
+    ] {
+        for name in strictness.enabled_options() {
+            assert!(SYNC_OPTION_FLAGS.iter().any(|(flag, _)| flag == name));
+        }
+    }
+    let options = SyncOptions::from_strictness(SyncStrictness::Strict);
+    assert!(!options.ignore_trailing_document_whitespace);
+    assert_eq!(
+        SyncOptions {
+            ignore_trailing_document_whitespace: true,
+            ..options
+        },
+        SyncOptions::default()
+    );
+
+    let wrapped = (
+        "Some text\nwrapped [here][r].\n\n[r]: https://a.b\n",
+        "Some text wrapped [here](https://a.b).\n",
+    );
+    assert!(check(wrapped.0, wrapped.1, SyncStrictness::Strict).is_err());
+    assert!(check(wrapped.0, wrapped.1, SyncStrictness::Standard).is_ok());
+
+    let quoted = ("# Foo\n\nIt’s here.\n", "# Foo\n\nIt's here.\n");
+    assert!(check(quoted.0, quoted.1, SyncStrictness::Lenient).is_ok());
+    let message = check(quoted.0, quoted.1, SyncStrictness::Standard).unwrap_err();
+    assert!(
+        message.contains(
+            "readme and docs are the same if the `normalize_punctuation` sync option is enabled"
+        ),
+        "{}",
+        message
+    );
+
+    let titled = ("# Foo\n\nIt’s here.\n", "# Bar\n\nIt's here.\n");
+    assert!(check(titled.0, titled.1, SyncStrictness::Lenient).is_ok());
+    let message = check(titled.0, titled.1, SyncStrictness::Standard).unwrap_err();
+    assert!(
+        message.contains(
+            "readme and docs are the same if the `normalize_punctuation`, `remove_titles` \
+                sync options are enabled"
+        ),
+        "{}",
+        message
+    );
+    assert!(check("# Foo\n", "# Bar\n", SyncStrictness::Standard)
+        .unwrap_err()
+        .contains("`remove_titles` sync option is enabled"));
+    let err = crate::check_sync(&readme("# Foo\n"), &docs("# Bar\n")).unwrap_err();
+    assert!(!err.to_string().contains("sync option"), "{}", err);
+}
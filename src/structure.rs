@@ -0,0 +1,121 @@
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, TagEnd};
+use thiserror::Error;
+
+use crate::CMarkData;
+
+impl CMarkData {
+    /// Checks that every start event is closed by the corresponding end event
+    /// and that every end event closes the corresponding start event.
+    ///
+    /// Removed items are skipped.
+    pub fn validate_structure(&self) -> Result<(), StructureError> {
+        let mut stack: Vec<(usize, TagEnd)> = Vec::new();
+        for (index, node) in self.iter().enumerate() {
+            match node.event() {
+                Some(Event::Start(tag)) => stack.push((index, tag.to_end())),
+                Some(Event::End(end)) => match stack.pop() {
+                    Some((_, expected)) if expected == *end => {}
+                    Some((_, expected)) => {
+                        return Err(StructureError::MismatchedEnd {
+                            index,
+                            expected,
+                            found: *end,
+                        })
+                    }
+                    None => return Err(StructureError::UnexpectedEnd { index, found: *end }),
+                },
+                _ => {}
+            }
+        }
+        match stack.pop() {
+            Some((index, expected)) => Err(StructureError::Unclosed { index, expected }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An error which can occur when checking events structure.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum StructureError {
+    /// An end event does not correspond to the last unclosed start event.
+    #[error("The end event at index {index} is {found:?}, but {expected:?} expected.")]
+    MismatchedEnd {
+        /// The end event item index.
+        index: usize,
+        /// The expected end event tag.
+        expected: TagEnd,
+        /// The found end event tag.
+        found: TagEnd,
+    },
+    /// An end event without the corresponding start event.
+    #[error("The end event at index {index} is {found:?}, but there is no unclosed start event.")]
+    UnexpectedEnd {
+        /// The end event item index.
+        index: usize,
+        /// The found end event tag.
+        found: TagEnd,
+    },
+    /// A start event without the corresponding end event.
+    #[error("The start event at index {index} is not closed by {expected:?}.")]
+    Unclosed {
+        /// The start event item index.
+        index: usize,
+        /// The expected end event tag.
+        expected: TagEnd,
+    },
+}
+
+#[test]
+fn test_validate_structure() {
+    use crate::CMarkItem;
+    use pulldown_cmark::Tag;
+    use std::borrow::Cow;
+
+    let data = |events: Vec<Event<'static>>| {
+        CMarkData::from_items(
+            events
+                .into_iter()
+                .map(|event| CMarkItem::new(event, Cow::from("test")))
+                .collect(),
+        )
+    };
+
+    assert_eq!(data(Vec::new()).validate_structure(), Ok(()));
+    assert_eq!(
+        data(std::vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("text".into()),
+            Event::End(TagEnd::Paragraph),
+        ])
+        .validate_structure(),
+        Ok(())
+    );
+    assert_eq!(
+        data(std::vec![
+            Event::Start(Tag::Paragraph),
+            Event::End(TagEnd::Item)
+        ])
+        .validate_structure(),
+        Err(StructureError::MismatchedEnd {
+            index: 1,
+            expected: TagEnd::Paragraph,
+            found: TagEnd::Item
+        })
+    );
+    assert_eq!(
+        data(std::vec![Event::End(TagEnd::Paragraph)]).validate_structure(),
+        Err(StructureError::UnexpectedEnd {
+            index: 0,
+            found: TagEnd::Paragraph
+        })
+    );
+    assert_eq!(
+        data(std::vec![Event::Start(Tag::Item)]).validate_structure(),
+        Err(StructureError::Unclosed {
+            index: 0,
+            expected: TagEnd::Item
+        })
+    );
+}
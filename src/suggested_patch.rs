@@ -0,0 +1,518 @@
+use core::ops::Range;
+use std::path::PathBuf;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+
+use crate::{CMarkData, CMarkItem, File, SyncMismatch, TextSource};
+
+/// A suggested readme text edit that resolves a readme and docs mismatch.
+///
+/// The replacement text is rendered from the corresponding docs events.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SuggestedPatch {
+    /// The readme file path.
+    pub path: PathBuf,
+    /// The byte range of the readme text to replace.
+    pub range: Range<usize>,
+    /// The text to replace the range with.
+    pub replacement: String,
+    file: Arc<File>,
+}
+
+/// The number of unchanged lines shown around the changed lines in the unified diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+impl SuggestedPatch {
+    /// Returns the specified text with the patch applied.
+    ///
+    /// The text is expected to be the readme file text the patch was suggested for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the patch range is out of the text bounds
+    /// or does not lie on the text character boundaries.
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = String::from(text);
+        text.replace_range(self.range.clone(), &self.replacement);
+        text
+    }
+
+    /// Returns the patch in the unified diff format.
+    pub fn to_unified_diff(&self) -> String {
+        use std::format;
+
+        let old_text = self.file.text();
+        let new_text = self.apply(old_text);
+        let line_start =
+            |text: &str, offset: usize| text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+
+        let mut changed_start = line_start(old_text, self.range.start);
+        let mut changed_old_end = match old_text[self.range.end..].find('\n') {
+            Some(idx) if self.range.is_empty() || !old_text[..self.range.end].ends_with('\n') => {
+                self.range.end + idx + 1
+            }
+            Some(_) => self.range.end,
+            None => old_text.len(),
+        };
+        // Context lines are counted from the changed lines boundaries.
+        for _ in 0..DIFF_CONTEXT_LINES {
+            if changed_start > 0 {
+                changed_start = line_start(old_text, changed_start - 1);
+            }
+            if changed_old_end < old_text.len() {
+                changed_old_end = old_text[changed_old_end..]
+                    .find('\n')
+                    .map_or(old_text.len(), |idx| changed_old_end + idx + 1);
+            }
+        }
+        let changed_new_end = changed_old_end + new_text.len() - old_text.len();
+        let old_lines = diff_lines(&old_text[changed_start..changed_old_end]);
+        let new_lines = diff_lines(&new_text[changed_start..changed_new_end]);
+
+        let common_prefix = old_lines
+            .iter()
+            .zip(&new_lines)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let common_suffix = old_lines[common_prefix..]
+            .iter()
+            .rev()
+            .zip(new_lines[common_prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let context_prefix = common_prefix.saturating_sub(DIFF_CONTEXT_LINES);
+        let context_suffix = common_suffix.saturating_sub(DIFF_CONTEXT_LINES);
+        let old_lines = &old_lines[context_prefix..old_lines.len() - context_suffix];
+        let new_lines = &new_lines[context_prefix..new_lines.len() - context_suffix];
+        let first_line = old_text[..changed_start].matches('\n').count() + context_prefix + 1;
+        let common_prefix = common_prefix - context_prefix;
+        let common_suffix = common_suffix - context_suffix;
+
+        let path = self.path.display();
+        let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(first_line, old_lines.len()),
+            hunk_range(first_line, new_lines.len()),
+        ));
+        let push_line = |diff: &mut String, prefix: char, line: &str| {
+            diff.push(prefix);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        };
+        for line in &old_lines[..common_prefix] {
+            push_line(&mut diff, ' ', line);
+        }
+        for line in &old_lines[common_prefix..old_lines.len() - common_suffix] {
+            push_line(&mut diff, '-', line);
+        }
+        for line in &new_lines[common_prefix..new_lines.len() - common_suffix] {
+            push_line(&mut diff, '+', line);
+        }
+        for line in &old_lines[old_lines.len() - common_suffix..] {
+            push_line(&mut diff, ' ', line);
+        }
+        diff
+    }
+}
+
+/// Splits the text into lines, keeping the line endings.
+fn diff_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// Formats the unified diff hunk line range.
+fn hunk_range(first_line: usize, len: usize) -> String {
+    match len {
+        0 => std::format!("{},0", first_line - 1),
+        1 => std::format!("{}", first_line),
+        _ => std::format!("{},{}", first_line, len),
+    }
+}
+
+/// Returns a readme patch resolving the specified mismatch
+/// if it is a text difference or a single missing or extra block.
+///
+/// Returns `None` if the mismatched readme nodes have no well-defined readme file ranges
+/// or if the change can not be described unambiguously.
+pub(crate) fn suggest_readme_patch(
+    readme: &CMarkData,
+    docs: &CMarkData,
+    mismatch: &SyncMismatch,
+) -> Option<SuggestedPatch> {
+    let readme_items: Vec<_> = readme.iter().cloned().collect();
+    let docs_items: Vec<_> = docs.iter().cloned().collect();
+    let readme_idx = item_index(&readme_items, &mismatch.readme_node);
+    let docs_idx = item_index(&docs_items, &mismatch.docs_node);
+    match (readme_idx, docs_idx) {
+        (Some(readme_idx), Some(docs_idx))
+            if next_event(&readme_items, readme_idx) == next_event(&docs_items, docs_idx) =>
+        {
+            text_patch(
+                &readme_items[readme_idx],
+                &docs_items[docs_idx],
+                &mismatch.previous_events,
+            )
+        }
+        _ => block_patch(&readme_items, readme_idx, &docs_items, docs_idx),
+    }
+}
+
+/// Returns the first event after the specified item index.
+fn next_event(items: &[Arc<CMarkItem>], idx: usize) -> Option<&Event<'static>> {
+    items[idx + 1..].iter().find_map(|item| item.event())
+}
+
+fn text_patch(
+    readme_node: &CMarkItem,
+    docs_node: &CMarkItem,
+    previous_events: &[Event<'static>],
+) -> Option<SuggestedPatch> {
+    use crate::render::{code_span, escape_text};
+
+    let (file, range) = file_range(readme_node)?;
+    let source = &file.text()[range.clone()];
+    let replacement = match (readme_node.event()?, docs_node.event()?) {
+        (Event::Text(readme_text), Event::Text(docs_text)) => {
+            if source != &**readme_text {
+                return None;
+            }
+            if is_code_block_text(previous_events) {
+                let has_fence = docs_text.lines().any(|line| {
+                    let line = line.trim_start();
+                    line.starts_with("```") || line.starts_with("~~~")
+                });
+                if has_fence {
+                    return None;
+                }
+                String::from(&**docs_text)
+            } else {
+                if docs_text.contains('\n') {
+                    return None;
+                }
+                escape_text(docs_text, true)
+            }
+        }
+        (Event::Code(_), Event::Code(docs_code)) => {
+            if !source.starts_with('`') || !source.ends_with('`') {
+                return None;
+            }
+            code_span(docs_code)
+        }
+        _ => return None,
+    };
+    Some(SuggestedPatch {
+        path: file.path().to_path_buf(),
+        range,
+        replacement,
+        file,
+    })
+}
+
+/// Returns `true` if the text event following the specified events is a code block text.
+fn is_code_block_text(previous_events: &[Event<'static>]) -> bool {
+    use pulldown_cmark::Tag;
+
+    matches!(
+        previous_events
+            .iter()
+            .rev()
+            .find(|event| !matches!(event, Event::Text(_))),
+        Some(Event::Start(Tag::CodeBlock(_)))
+    )
+}
+
+fn block_patch(
+    readme_items: &[Arc<CMarkItem>],
+    readme_idx: Option<usize>,
+    docs_items: &[Arc<CMarkItem>],
+    docs_idx: Option<usize>,
+) -> Option<SuggestedPatch> {
+    if readme_idx.is_some_and(|idx| depth(&readme_items[..idx]) != 0)
+        || docs_idx.is_some_and(|idx| depth(&docs_items[..idx]) != 0)
+    {
+        return None;
+    }
+
+    let inserted = match (readme_idx, docs_idx) {
+        (_, None) => None,
+        (None, Some(docs_idx)) => Some(docs_idx..docs_items.len()),
+        (Some(readme_idx), Some(docs_idx)) => {
+            let docs_end = block_end(docs_items, docs_idx);
+            blocks_eq(readme_items, readme_idx, docs_items, docs_end).then_some(docs_idx..docs_end)
+        }
+    };
+    let removed = match (readme_idx, docs_idx) {
+        (None, _) => None,
+        (Some(readme_idx), None) => Some(readme_idx..readme_items.len()),
+        (Some(readme_idx), Some(docs_idx)) => {
+            let readme_end = block_end(readme_items, readme_idx);
+            blocks_eq(readme_items, readme_end, docs_items, docs_idx)
+                .then_some(readme_idx..readme_end)
+        }
+    };
+
+    match (inserted, removed) {
+        (Some(inserted), None) => insertion_patch(readme_items, readme_idx, &docs_items[inserted]),
+        (None, Some(removed)) => removal_patch(readme_items, removed),
+        _ => None,
+    }
+}
+
+fn insertion_patch(
+    readme_items: &[Arc<CMarkItem>],
+    readme_idx: Option<usize>,
+    inserted: &[Arc<CMarkItem>],
+) -> Option<SuggestedPatch> {
+    use crate::render::render_markdown;
+
+    let rendered = render_markdown(inserted.iter().filter_map(|item| item.event()));
+    if rendered.is_empty() {
+        return None;
+    }
+    let (file, offset, replacement) = match readme_idx {
+        Some(readme_idx) => {
+            let (file, range) = file_range(&readme_items[readme_idx])?;
+            let offset = block_line_start(file.text(), range.start)?;
+            (file, offset, rendered + "\n")
+        }
+        None => {
+            let last_item = readme_items
+                .iter()
+                .rev()
+                .find(|item| item.event().is_some())?;
+            let (file, range) = file_range(last_item)?;
+            let separator = if file.text()[..range.end].ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            (file, range.end, String::from(separator) + &rendered)
+        }
+    };
+    Some(SuggestedPatch {
+        path: file.path().to_path_buf(),
+        range: offset..offset,
+        replacement,
+        file,
+    })
+}
+
+fn removal_patch(readme_items: &[Arc<CMarkItem>], removed: Range<usize>) -> Option<SuggestedPatch> {
+    let (file, first_range) = file_range(&readme_items[removed.start])?;
+    let text = file.text();
+    let mut start = block_line_start(text, first_range.start)?;
+    let next_start = readme_items[removed.end..]
+        .iter()
+        .find_map(|item| file_range(item).filter(|(next_file, _)| *next_file == file));
+    let end = match next_start {
+        Some((_, next_range)) => block_line_start(text, next_range.start)?,
+        None => {
+            // Removes the trailing blocks together with the preceding blank lines.
+            let end = readme_items[removed]
+                .iter()
+                .filter_map(|item| file_range(item))
+                .map(|(_, range)| range.end)
+                .max()?;
+            let content_end = text[..start].trim_end().len();
+            if content_end > 0 {
+                // Keeps the line ending of the preceding content.
+                start = content_end + 1;
+            }
+            end + text[end..].len() - text[end..].trim_start().len()
+        }
+    };
+    (start < end).then(|| SuggestedPatch {
+        path: file.path().to_path_buf(),
+        range: start..end,
+        replacement: String::new(),
+        file,
+    })
+}
+
+/// Returns the item index in the specified items.
+fn item_index(items: &[Arc<CMarkItem>], node: &Option<Arc<CMarkItem>>) -> Option<usize> {
+    let node = node.as_ref()?;
+    items.iter().position(|item| Arc::ptr_eq(item, node))
+}
+
+/// Returns the nesting depth after the specified items.
+fn depth(items: &[Arc<CMarkItem>]) -> isize {
+    items
+        .iter()
+        .filter_map(|item| item.event())
+        .map(|event| match event {
+            Event::Start(_) => 1,
+            Event::End(_) => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Returns the index after the top-level block starting at the specified index.
+fn block_end(items: &[Arc<CMarkItem>], idx: usize) -> usize {
+    let mut depth = 0;
+    for (offset, item) in items[idx..].iter().enumerate() {
+        match item.event() {
+            Some(Event::Start(_)) => depth += 1,
+            Some(Event::End(_)) => depth -= 1,
+            Some(_) => {}
+            None => continue,
+        }
+        if depth <= 0 {
+            return idx + offset + 1;
+        }
+    }
+    items.len()
+}
+
+/// Returns `true` if the top-level blocks starting at the specified indices have the same events.
+///
+/// Returns `true` also if there are no more blocks in both items.
+fn blocks_eq(
+    lhs: &[Arc<CMarkItem>],
+    lhs_idx: usize,
+    rhs: &[Arc<CMarkItem>],
+    rhs_idx: usize,
+) -> bool {
+    block_events(lhs, lhs_idx).eq(block_events(rhs, rhs_idx))
+}
+
+/// Returns the events of the next top-level block starting from the specified index.
+fn block_events(items: &[Arc<CMarkItem>], idx: usize) -> impl Iterator<Item = &Event<'static>> {
+    let idx = items[idx.min(items.len())..]
+        .iter()
+        .position(|item| item.event().is_some())
+        .map_or(items.len(), |offset| idx + offset);
+    let end = if idx < items.len() {
+        block_end(items, idx)
+    } else {
+        idx
+    };
+    items[idx..end].iter().filter_map(|item| item.event())
+}
+
+/// Returns the readme file and the byte range covering all the item spans
+/// if all of them are in the same file.
+fn file_range(item: &CMarkItem) -> Option<(Arc<File>, Range<usize>)> {
+    let spans = item.spans();
+    let mut range: Option<(Arc<File>, Range<usize>)> = None;
+    for span in spans {
+        let file = match span.text_source {
+            TextSource::File(file) => file,
+            TextSource::FileDocs(_) => return None,
+        };
+        range = match range {
+            None => Some((Arc::clone(file), span.range.clone())),
+            Some((prev_file, prev_range)) => {
+                if !Arc::ptr_eq(&prev_file, file) {
+                    return None;
+                }
+                let start = prev_range.start.min(span.range.start);
+                let end = prev_range.end.max(span.range.end);
+                Some((prev_file, start..end))
+            }
+        };
+    }
+    range
+}
+
+/// Returns the start of the line if the block starts after indentation only.
+fn block_line_start(text: &str, offset: usize) -> Option<usize> {
+    let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    text[line_start..offset]
+        .chars()
+        .all(|ch| ch == ' ')
+        .then_some(line_start)
+}
+
+#[test]
+fn test_suggest_readme_patch() {
+    use crate::compare::find_mismatch;
+    use std::path::Path;
+
+    let data = |text: &str, path: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(path),
+            text.into(),
+        )))
+    };
+    let patch = |readme_text: &str, docs_text: &str| {
+        let readme = data(readme_text, "README.md");
+        let mismatch = find_mismatch(&readme, &data(docs_text, "lib.rs")).unwrap();
+        let patch = mismatch.suggested_patch?;
+        assert_eq!(patch.path, Path::new("README.md"));
+        let patched = patch.apply(readme_text);
+        let next_mismatch = find_mismatch(&data(&patched, "README.md"), &data(docs_text, "lib.rs"));
+        assert!(
+            next_mismatch.map_or(true, |next| next.matched_count > mismatch.matched_count),
+            "{:?}",
+            patched
+        );
+        Some(patched)
+    };
+
+    assert_eq!(
+        patch("# Title\n\nOld text.\n", "# Title\n\nNew *text*.\n").as_deref(),
+        None,
+    );
+    assert_eq!(
+        patch("# Title\n\nOld text.\n", "# Title\n\nNew text_.\n").as_deref(),
+        Some("# Title\n\nNew text\\_.\n"),
+    );
+    assert_eq!(
+        patch("Use `old()`.\n", "Use `new()`.\n").as_deref(),
+        Some("Use `new()`.\n"),
+    );
+    assert_eq!(
+        patch("```rust\nlet a = 1;\n```\n", "```rust\nlet a = 2;\n```\n").as_deref(),
+        Some("```rust\nlet a = 2;\n```\n"),
+    );
+    assert_eq!(
+        patch("# A\n\n# C\n", "# A\n\nText *b*.\n\n# C\n").as_deref(),
+        Some("# A\n\nText *b*.\n\n# C\n"),
+    );
+    assert_eq!(
+        patch("# A\n\nText.\n\n# C\n", "# A\n\n# C\n").as_deref(),
+        Some("# A\n\n# C\n"),
+    );
+    assert_eq!(
+        patch("# A\n\nText.\n\n- a\n- b\n", "# A\n").as_deref(),
+        Some("# A\n"),
+    );
+    assert_eq!(
+        patch("# A\n", "# A\n\n- a\n- b\n").as_deref(),
+        Some("# A\n\n- a\n- b\n"),
+    );
+    assert_eq!(
+        patch("# A\n\nB.\n", "# A\n\nC.\n\nD.\n").as_deref(),
+        Some("# A\n\nC.\n")
+    );
+    assert_eq!(
+        patch("# A\n\n- b\n\n# C\n", "# A\n\n# C\n\n- b\n").as_deref(),
+        None
+    );
+    assert_eq!(patch("Text &amp; b.\n", "Text and b.\n").as_deref(), None);
+}
+
+#[test]
+fn test_suggested_patch_to_unified_diff() {
+    use crate::test_utils::readme_file;
+
+    let file = readme_file("# Title\n\n1\n2\n3\n4\n5\n6\nOld text.\n");
+    let patch = SuggestedPatch {
+        path: PathBuf::from("README.md"),
+        range: 21..24,
+        replacement: String::from("New"),
+        file,
+    };
+    assert_eq!(
+        patch.to_unified_diff(),
+        "--- a/README.md\n+++ b/README.md\n@@ -6,4 +6,4 @@\n 4\n 5\n 6\n-Old text.\n+New text.\n"
+    );
+}
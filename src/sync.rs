@@ -3,10 +3,11 @@ use std::borrow::ToOwned;
 use std::path::Path;
 use std::string::String;
 
-use pulldown_cmark::CowStr;
 use thiserror::Error;
 
-use crate::{CMarkDocs, CMarkReadme};
+#[cfg(feature = "codemap-diagnostic")]
+use crate::codemap_spans::normalize_span_labels;
+use crate::{CMarkDocs, CMarkReadme, EmitOptions, SourceMetadata, SuggestedPatch, SyncMismatch};
 
 /// Asserts that the given readme and docs are the same.
 pub fn assert_sync<M1, M2>(readme: &CMarkReadme<&Path, M1>, docs: &CMarkDocs<&Path, M2>) {
@@ -19,73 +20,262 @@ pub fn assert_sync<M1, M2>(readme: &CMarkReadme<&Path, M1>, docs: &CMarkDocs<&Pa
     }
 }
 
+/// Asserts that the given readme and docs are the same,
+/// labeling the diagnostics with the specified readme and docs pair label.
+///
+/// It allows to tell which pair failed when several pairs are checked in one test,
+/// like localized readme variants checked against the localized docs.
+pub fn assert_sync_with_label<M1, M2>(
+    readme: &CMarkReadme<&Path, M1>,
+    docs: &CMarkDocs<&Path, M2>,
+    label: &str,
+) {
+    match check_sync(readme, docs) {
+        Ok(()) => {}
+        Err(CheckSyncError::MatchFailed(err)) => {
+            err.with_label(label).emit_to_stderr_colored();
+            panic!("readme and docs `{}` are not the same", label);
+        }
+    }
+}
+
+/// Options of readme and docs synchronization check.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SyncOptions {
+    /// The maximum number of source span labels shown in a single diagnostic message.
+    pub max_span_labels: usize,
+    /// Diagnostic messages rendering options.
+    pub emit: EmitOptions,
+    /// Whether diagnostics with the same level and message are merged into one.
+    ///
+    /// It is enabled by default and can be disabled to debug diagnostics creation.
+    pub coalesce_diagnostics: bool,
+    /// The maximum number of lines of a code block with several paragraphs
+    /// that is not reported as likely missing its closing fence.
+    ///
+    /// See `CMarkData::lint_suspicious_codeblocks_with_max_lines` for details.
+    pub max_codeblock_lines: usize,
+    /// Whether the last texts of the documents that differ only by trailing whitespace
+    /// are considered the same.
+    ///
+    /// It is enabled by default.
+    /// If it is disabled, such a mismatch is reported with a note describing the whitespace.
+    pub ignore_trailing_document_whitespace: bool,
+    /// Whether soft line breaks are replaced with spaces before the comparison.
+    ///
+    /// See `CMarkData::collapse_soft_breaks` for details.
+    pub collapse_soft_breaks: bool,
+    /// Whether whitespace runs in texts are collapsed before the comparison.
+    ///
+    /// See `CMarkData::collapse_whitespace` for details.
+    pub collapse_whitespace: bool,
+    /// Whether all links and images use the inline link type before the comparison.
+    ///
+    /// See `CMarkData::normalize_link_types` for details.
+    pub normalize_link_types: bool,
+    /// Whether custom heading ids, classes and attributes are removed before the comparison.
+    ///
+    /// See `CMarkData::remove_heading_attributes` for details.
+    pub remove_heading_attributes: bool,
+    /// Whether typographic punctuation is replaced with ASCII one before the comparison.
+    ///
+    /// See `CMarkData::normalize_punctuation` for details.
+    pub normalize_punctuation: bool,
+    /// Whether link and image titles are removed before the comparison.
+    ///
+    /// See `CMarkData::remove_link_titles` for details.
+    pub remove_link_titles: bool,
+    /// Whether hard line breaks are replaced with soft ones before the comparison.
+    ///
+    /// See `CMarkData::use_soft_breaks` for details.
+    pub use_soft_breaks: bool,
+    /// Whether the first level title headings are removed before the comparison.
+    ///
+    /// See `CMarkData::remove_title` for details.
+    pub remove_titles: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            max_span_labels: 8,
+            emit: EmitOptions::default(),
+            coalesce_diagnostics: true,
+            max_codeblock_lines: crate::DEFAULT_MAX_CODEBLOCK_LINES,
+            ignore_trailing_document_whitespace: true,
+            collapse_soft_breaks: false,
+            collapse_whitespace: false,
+            normalize_link_types: false,
+            remove_heading_attributes: false,
+            normalize_punctuation: false,
+            remove_link_titles: false,
+            use_soft_breaks: false,
+            remove_titles: false,
+        }
+    }
+}
+
 /// Returns `Ok(())` if the given readme and docs are the same, and `Err(CheckSyncError)` otherwise.
+#[allow(clippy::result_large_err)]
 pub fn check_sync<P1, P2, M1, M2>(
     readme: &CMarkReadme<P1, M1>,
     docs: &CMarkDocs<P2, M2>,
 ) -> Result<(), CheckSyncError> {
-    use std::vec::Vec;
+    check_sync_with_options(readme, docs, &SyncOptions::default())
+}
 
-    let mut readme_iter = readme.iter();
-    let mut docs_iter = docs.iter();
-    let mut matched_events = Vec::new();
-
-    loop {
-        let NextItem {
-            node: readme_node,
-            event: readme_event,
-            removed: readme_removed_nodes,
-        } = next_node(&mut readme_iter);
-
-        let NextItem {
-            node: docs_node,
-            event: docs_event,
-            removed: docs_removed_nodes,
-        } = next_node(&mut docs_iter);
-
-        if readme_node.is_none() && docs_node.is_none() {
-            break;
+/// Returns `Ok(())` if the given readme and docs are the same, and `Err(CheckSyncError)` otherwise,
+/// using the specified check options.
+#[allow(clippy::result_large_err)]
+pub fn check_sync_with_options<P1, P2, M1, M2>(
+    readme: &CMarkReadme<P1, M1>,
+    docs: &CMarkDocs<P2, M2>,
+    options: &SyncOptions,
+) -> Result<(), CheckSyncError> {
+    use crate::compare::find_mismatch_with_options;
+    use crate::strictness::sync_option_note;
+
+    let readme_data = options.normalize(readme.data());
+    let docs_data = options.normalize(docs.data());
+    match find_mismatch_with_options(&readme_data, &docs_data, &options.emit) {
+        Some(mismatch)
+            if options.ignore_trailing_document_whitespace && mismatch.trailing_whitespace_only =>
+        {
+            Ok(())
         }
-
-        if readme_event == docs_event {
-            matched_events.push(readme_event.unwrap());
-        } else {
-            use crate::CodemapFiles;
-            use std::sync::Arc;
-
-            let mut codemap_files = CodemapFiles::new();
-            let mut diags = std::vec![node_not_mached_diagnostic(
-                &mut codemap_files,
-                &readme_node,
-                &docs_node,
-            )];
-
-            diags.extend(removed_nodes_note(
-                &mut codemap_files,
-                &readme_removed_nodes,
-                "readme",
+        Some(mismatch) => {
+            let mut err = MatchFailed::from_mismatch(&mismatch, options);
+            err.prepend_notes(suspicious_codeblock_notes(
+                &mismatch,
+                &readme_data,
+                &docs_data,
+                options.max_codeblock_lines,
             ));
+            if *options != SyncOptions::default() {
+                if let Some(note) = sync_option_note(readme.data(), docs.data(), options) {
+                    err.push_note(note);
+                }
+            }
+            if let Some(note) = skipped_docs_note(docs.skipped_docs_summary()) {
+                err.push_note(note);
+            }
+            Err(CheckSyncError::MatchFailed(err))
+        }
+        None => Ok(()),
+    }
+}
 
-            diags.extend(removed_nodes_note(
-                &mut codemap_files,
-                &docs_removed_nodes,
-                "docs",
-            ));
+/// Returns `Ok(())` if the first `count` top-level blocks of the given readme and docs are the same,
+/// and `Err(CheckSyncError)` otherwise.
+///
+/// The readme badges paragraph and the title of both sides are removed first,
+/// and the blocks after the leading ones or after the first heading are ignored.
+/// See `CMarkData::take_leading_blocks` for details.
+#[allow(clippy::result_large_err)]
+pub fn check_leading_blocks_sync<P1, P2, M1, M2>(
+    readme: &CMarkReadme<P1, M1>,
+    docs: &CMarkDocs<P2, M2>,
+    count: usize,
+) -> Result<(), CheckSyncError> {
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        readme
+            .data()
+            .clone()
+            .remove_badges_paragraph()
+            .remove_title()
+            .take_leading_blocks(count),
+        (),
+        (),
+    );
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        docs.data()
+            .clone()
+            .remove_title()
+            .take_leading_blocks(count),
+        (),
+        (),
+    );
+    check_sync(&readme, &docs)
+}
 
-            if let (Some(readme_event), Some(docs_event)) = (readme_event, docs_event) {
-                diags.append(&mut event_diff_notes(&readme_event, &docs_event));
+/// Returns `Ok(())` if the given readme and docs are the same, and `Err(CheckSyncError)` otherwise.
+///
+/// If the readme and docs are not the same,
+/// the diagnostic messages are extended with a heuristic note about which side is likely stale,
+/// based on the specified source files metadata.
+#[allow(clippy::result_large_err)]
+pub fn check_sync_with_metadata<P1, P2, M1, M2>(
+    readme: &CMarkReadme<P1, M1>,
+    docs: &CMarkDocs<P2, M2>,
+    metadata: &SourceMetadata,
+) -> Result<(), CheckSyncError> {
+    check_sync(readme, docs).map_err(|err| match err {
+        CheckSyncError::MatchFailed(mut err) => {
+            if let Some(note) = metadata.freshness_note() {
+                err.push_note(note);
             }
+            CheckSyncError::MatchFailed(err)
+        }
+    })
+}
 
-            diags.push(previous_events_notes(&matched_events));
+/// Returns `Ok(())` if the given readme and docs Markdown texts are the same,
+/// and `Err(MatchFailed)` otherwise.
+///
+/// Both texts are parsed with the default parser options and compared without transformations.
+/// The specified names are used as file names in diagnostic messages.
+///
+/// ```rust
+/// use readme_sync::check_markdown_sync;
+///
+/// assert!(check_markdown_sync("# Title\n", "# Title\n", "README.md", "lib.md").is_ok());
+///
+/// let err = check_markdown_sync("# Title\n", "## Title\n", "README.md", "lib.md").unwrap_err();
+/// let lines = err.to_single_lines();
+/// assert!(lines[0].starts_with("README.md:1:1: error:"));
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn check_markdown_sync(
+    readme_text: &str,
+    docs_text: &str,
+    readme_name: &str,
+    docs_name: &str,
+) -> Result<(), MatchFailed> {
+    use crate::{CMarkData, File};
+    use std::path::PathBuf;
+    use std::sync::Arc;
 
-            let codemap_files = Arc::new(codemap_files);
-            return Err(CheckSyncError::MatchFailed(MatchFailed {
-                diags,
-                codemap_files,
-            }));
-        }
+    let data = |text: &str, name: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(name),
+            text.to_owned(),
+        )))
+    };
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        data(readme_text, readme_name),
+        (),
+        (),
+    );
+    let docs =
+        CMarkDocs::from_data_chunks_package_pach_and_manifest(data(docs_text, docs_name), (), ());
+    check_sync(&readme, &docs).map_err(|err| match err {
+        CheckSyncError::MatchFailed(err) => err,
+    })
+}
+
+/// Asserts that the given readme and docs Markdown texts are the same.
+///
+/// See `check_markdown_sync` for details.
+pub fn assert_markdown_sync(
+    readme_text: &str,
+    docs_text: &str,
+    readme_name: &str,
+    docs_name: &str,
+) {
+    if let Err(err) = check_markdown_sync(readme_text, docs_text, readme_name, docs_name) {
+        err.emit_to_stderr_colored();
+        panic!();
     }
-    Ok(())
 }
 
 /// An error which can occur when checking readme and docs for equality.
@@ -102,114 +292,330 @@ pub enum CheckSyncError {
 /// Readme and docs match failed diagnostics and codemap files.
 #[derive(Clone, Debug)]
 pub struct MatchFailed {
-    diags: std::vec::Vec<codemap_diagnostic::Diagnostic>,
-    codemap_files: std::sync::Arc<crate::CodemapFiles>,
+    diagnostics: Diagnostics,
+    suggested_patch: Option<SuggestedPatch>,
+    locations: std::vec::Vec<crate::SourceLocation>,
+}
+
+/// Diagnostic messages rendered with or without codemap.
+#[derive(Clone, Debug)]
+enum Diagnostics {
+    /// Diagnostic messages rendered with codemap.
+    #[cfg(feature = "codemap-diagnostic")]
+    Codemap {
+        diags: std::vec::Vec<codemap_diagnostic::Diagnostic>,
+        codemap_files: std::sync::Arc<crate::CodemapFiles>,
+    },
+    /// Diagnostic messages formatted without codemap.
+    Excerpts(ExcerptDiagnostics),
+}
+
+/// Diagnostic messages formatted without codemap.
+#[derive(Clone, Debug)]
+struct ExcerptDiagnostics {
+    diags: std::vec::Vec<crate::excerpt_emitter::ExcerptDiagnostic>,
+    focuses: std::vec::Vec<(crate::TextSource, usize)>,
+    max_line_len: usize,
 }
 
 impl MatchFailed {
+    /// Creates diagnostic messages for the specified mismatch.
+    ///
+    /// Source lines longer than `options.emit.max_excerpt_len` are trimmed
+    /// to a window around the texts difference if it is known.
+    ///
+    /// If `options.emit.lightweight_excerpts` is enabled
+    /// or the `codemap-diagnostic` feature is disabled, no files are copied into the codemap
+    /// and source excerpts are formatted directly from the file texts.
+    pub fn from_mismatch(mismatch: &SyncMismatch, options: &SyncOptions) -> Self {
+        #[cfg(feature = "codemap-diagnostic")]
+        let diagnostics = if options.emit.lightweight_excerpts {
+            Diagnostics::Excerpts(excerpt_diagnostics(mismatch, options))
+        } else {
+            codemap_diagnostics(mismatch, options)
+        };
+        #[cfg(not(feature = "codemap-diagnostic"))]
+        let diagnostics = Diagnostics::Excerpts(excerpt_diagnostics(mismatch, options));
+
+        Self {
+            diagnostics,
+            suggested_patch: mismatch.suggested_patch.clone(),
+            locations: mismatch.locations(),
+        }
+    }
+
+    #[cfg(feature = "codemap-diagnostic")]
+    pub(crate) fn from_diags(
+        diags: std::vec::Vec<codemap_diagnostic::Diagnostic>,
+        codemap_files: std::sync::Arc<crate::CodemapFiles>,
+    ) -> Self {
+        Self {
+            diagnostics: Diagnostics::Codemap {
+                diags,
+                codemap_files,
+            },
+            suggested_patch: None,
+            locations: std::vec::Vec::new(),
+        }
+    }
+
+    /// Returns the diagnostics with the leading note naming the checked readme and docs pair.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.prepend_notes(std::vec![std::format!(
+            "while checking readme and docs `{}`",
+            label
+        )]);
+        self
+    }
+
+    /// Inserts notes without source locations before the other diagnostics.
+    pub(crate) fn prepend_notes(&mut self, messages: std::vec::Vec<String>) {
+        use crate::excerpt_emitter::ExcerptDiagnostic;
+
+        match &mut self.diagnostics {
+            Diagnostics::Excerpts(excerpts) => {
+                let _ = excerpts
+                    .diags
+                    .splice(0..0, messages.into_iter().map(ExcerptDiagnostic::note));
+            }
+            #[cfg(feature = "codemap-diagnostic")]
+            Diagnostics::Codemap { diags, .. } => {
+                let _ = diags.splice(0..0, messages.into_iter().map(text_note));
+            }
+        }
+    }
+
+    /// Appends a note without source locations.
+    pub(crate) fn push_note(&mut self, message: String) {
+        use crate::excerpt_emitter::ExcerptDiagnostic;
+
+        match &mut self.diagnostics {
+            Diagnostics::Excerpts(excerpts) => {
+                excerpts.diags.push(ExcerptDiagnostic::note(message))
+            }
+            #[cfg(feature = "codemap-diagnostic")]
+            Diagnostics::Codemap { diags, .. } => diags.push(text_note(message)),
+        }
+    }
+
+    /// Returns the suggested readme edit resolving the mismatch.
+    ///
+    /// The edit is suggested for texts differences and missing or extra blocks
+    /// with well-defined readme file ranges, and `None` is returned in other cases.
+    pub fn suggest_readme_patch(&self) -> Option<SuggestedPatch> {
+        self.suggested_patch.clone()
+    }
+
+    /// Returns the file locations of the mismatched and removed nodes.
+    ///
+    /// Locations are available only for failures created from a mismatch,
+    /// and an empty vector is returned in other cases.
+    pub fn locations(&self) -> std::vec::Vec<crate::SourceLocation> {
+        self.locations.clone()
+    }
+
     /// Print dianostic messages to console with colors.
+    ///
+    /// Lightweight excerpts and diagnostics created without the `codemap-diagnostic` feature
+    /// are printed without colors.
     pub fn emit_to_stderr_colored(&self) {
-        use codemap_diagnostic::{ColorConfig, Emitter};
+        match &self.diagnostics {
+            Diagnostics::Excerpts(_) => std::eprint!("{}", self),
+            #[cfg(feature = "codemap-diagnostic")]
+            Diagnostics::Codemap {
+                diags,
+                codemap_files,
+            } => {
+                use codemap_diagnostic::{ColorConfig, Emitter};
 
-        let mut emitter = Emitter::stderr(ColorConfig::Always, Some(self.codemap_files.codemap()));
-        emitter.emit(&self.diags);
+                let mut emitter =
+                    Emitter::stderr(ColorConfig::Always, Some(codemap_files.codemap()));
+                emitter.emit(diags);
+            }
+        }
+    }
+
+    /// Returns a single-line message for every diagnostic.
+    ///
+    /// Each message is prefixed with the `file:line:column` location of its primary span if any.
+    pub fn to_single_lines(&self) -> std::vec::Vec<String> {
+        match &self.diagnostics {
+            Diagnostics::Excerpts(excerpts) => excerpts
+                .diags
+                .iter()
+                .map(crate::excerpt_emitter::excerpt_single_line)
+                .collect(),
+            #[cfg(feature = "codemap-diagnostic")]
+            Diagnostics::Codemap {
+                diags,
+                codemap_files,
+            } => {
+                use codemap_diagnostic::SpanStyle;
+                use std::format;
+
+                diags
+                    .iter()
+                    .map(|diag| {
+                        let message = diag
+                            .message
+                            .split_whitespace()
+                            .collect::<std::vec::Vec<_>>();
+                        let message = format!("{}: {}", diag.level, message.join(" "));
+                        let span = diag
+                            .spans
+                            .iter()
+                            .find(|label| label.style == SpanStyle::Primary)
+                            .or_else(|| diag.spans.first());
+                        match span {
+                            Some(label) => {
+                                let loc = codemap_files.codemap().look_up_span(label.span);
+                                format!(
+                                    "{}:{}:{}: {}",
+                                    loc.file.name(),
+                                    loc.begin.line + 1,
+                                    loc.begin.column + 1,
+                                    message
+                                )
+                            }
+                            None => message,
+                        }
+                    })
+                    .collect()
+            }
+        }
     }
 }
 
 impl Display for MatchFailed {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use codemap_diagnostic::Emitter;
-        use std::vec::Vec;
-
-        let mut raw = Vec::new();
-        {
-            let mut emitter = Emitter::vec(&mut raw, Some(self.codemap_files.codemap()));
-            emitter.emit(&self.diags);
+        match &self.diagnostics {
+            Diagnostics::Excerpts(excerpts) => crate::excerpt_emitter::write_excerpt_diagnostics(
+                f,
+                &excerpts.diags,
+                &excerpts.focuses,
+                excerpts.max_line_len,
+            ),
+            #[cfg(feature = "codemap-diagnostic")]
+            Diagnostics::Codemap {
+                diags,
+                codemap_files,
+            } => {
+                use codemap_diagnostic::Emitter;
+                use std::vec::Vec;
+
+                let mut raw = Vec::new();
+                {
+                    let mut emitter = Emitter::vec(&mut raw, Some(codemap_files.codemap()));
+                    emitter.emit(diags);
+                }
+                let msg = String::from_utf8_lossy(&raw);
+                write!(f, "{}", msg)
+            }
         }
-        let msg = String::from_utf8_lossy(&raw);
-        write!(f, "{}", msg)
     }
 }
 
-struct NextItem<'a> {
-    node: Option<std::sync::Arc<crate::CMarkItem>>,
-    event: Option<pulldown_cmark::Event<'a>>,
-    removed: std::vec::Vec<std::sync::Arc<crate::CMarkItem>>,
-}
-
-fn next_node<'a>(iter: &mut crate::CMarkDataIter<'a>) -> NextItem<'a> {
+/// Creates codemap diagnostic messages for the specified mismatch.
+#[cfg(feature = "codemap-diagnostic")]
+fn codemap_diagnostics(mismatch: &SyncMismatch, options: &SyncOptions) -> Diagnostics {
+    use crate::CodemapFiles;
     use std::sync::Arc;
-    use std::vec::Vec;
 
-    let mut removed = Vec::new();
-    loop {
-        if let Some(node) = iter.next() {
-            if let Some(event) = node.event() {
-                return NextItem {
-                    node: Some(Arc::clone(node)),
-                    event: Some(event.clone()),
-                    removed,
-                };
-            } else {
-                removed.push(Arc::clone(node));
-            }
-        } else {
-            return NextItem {
-                node: None,
-                event: None,
-                removed,
-            };
-        }
+    let mut codemap_files = CodemapFiles::with_max_line_len(options.emit.max_excerpt_len);
+    for (text_source, offset) in text_difference_focuses(mismatch) {
+        codemap_files.add_focus(&text_source, offset);
+    }
+    let mut diags = std::vec![node_not_mached_diagnostic(
+        &mut codemap_files,
+        mismatch,
+        options,
+    )];
+    diags.extend(removed_nodes_note(
+        &mut codemap_files,
+        &mismatch.readme_removed,
+        "readme",
+        options,
+    ));
+    diags.extend(removed_nodes_note(
+        &mut codemap_files,
+        &mismatch.docs_removed,
+        "docs",
+        options,
+    ));
+    diags.extend(mismatch.notes.iter().cloned().map(text_note));
+    diags.push(text_note(
+        mismatch.previous_events_note_with_options(&options.emit),
+    ));
+    if options.coalesce_diagnostics {
+        diags = coalesce_diagnostics(&codemap_files, diags, options.max_span_labels);
+    }
+
+    Diagnostics::Codemap {
+        diags,
+        codemap_files: Arc::new(codemap_files),
     }
 }
 
+#[cfg(feature = "codemap-diagnostic")]
 fn node_not_mached_diagnostic(
     codemap_files: &mut crate::CodemapFiles,
-    readme_node: &Option<std::sync::Arc<crate::CMarkItem>>,
-    docs_node: &Option<std::sync::Arc<crate::CMarkItem>>,
+    mismatch: &SyncMismatch,
+    options: &SyncOptions,
 ) -> codemap_diagnostic::Diagnostic {
     use crate::CodemapSpans;
     use codemap_diagnostic::{Diagnostic, Level};
-    use std::format;
 
-    let nodes = [readme_node, docs_node];
+    let nodes = [&mismatch.readme_node, &mismatch.docs_node];
     let spans = nodes
         .iter()
         .filter_map(|node| node.as_ref())
         .flat_map(|node| node.spans());
-    let span_labels = CodemapSpans::span_labels_from(codemap_files, spans);
-    let readme_event = readme_node.as_ref().and_then(|node| node.event());
-    let docs_event = docs_node.as_ref().and_then(|node| node.event());
-
-    let message = match (readme_event, docs_event) {
-        (Some(readme_event), Some(docs_event)) => format!(
-            "readme node\n`{}`\n does not match docs node\n`{}`",
-            FmtPrint(readme_event),
-            FmtPrint(docs_event)
-        ),
-        (Some(readme_event), None) => format!(
-            "readme node\n`{}`\n does not match any docs node",
-            FmtPrint(readme_event)
-        ),
-        (None, Some(docs_event)) => format!(
-            "docs node\n`{}`\n does not match any readme node",
-            FmtPrint(docs_event)
-        ),
-        (None, None) => unreachable!(),
-    };
+    let mut span_labels = CodemapSpans::span_labels_from(codemap_files, spans);
+    let omitted = normalize_span_labels(codemap_files, &mut span_labels, options.max_span_labels);
 
     Diagnostic {
         level: Level::Error,
-        message,
+        message: mismatch.message_with_options(&options.emit) + &omitted_span_labels_note(omitted),
         code: None,
         spans: span_labels,
     }
 }
 
+/// Returns the texts difference position of the mismatched nodes
+/// used as focuses of the trimmed source lines.
+fn text_difference_focuses(mismatch: &SyncMismatch) -> std::vec::Vec<(crate::TextSource, usize)> {
+    use crate::compare::{byte_offset, get_event_text};
+
+    let mut focuses = std::vec::Vec::new();
+    let pos = match mismatch.text_difference() {
+        Some(pos) => pos,
+        None => return focuses,
+    };
+    for node in [&mismatch.readme_node, &mismatch.docs_node]
+        .into_iter()
+        .flatten()
+    {
+        let mut offset = match node.event().and_then(get_event_text) {
+            Some(text) => byte_offset(text, pos),
+            None => continue,
+        };
+        for span in node.spans() {
+            if offset <= span.range.len() {
+                focuses.push((span.text_source.clone(), span.range.start + offset));
+                break;
+            }
+            offset -= span.range.len();
+        }
+    }
+    focuses
+}
+
+#[cfg(feature = "codemap-diagnostic")]
 fn removed_nodes_note(
     codemap_files: &mut crate::CodemapFiles,
     nodes: &[std::sync::Arc<crate::CMarkItem>],
     node_type: &str,
+    options: &SyncOptions,
 ) -> Option<codemap_diagnostic::Diagnostic> {
     use crate::CodemapSpans;
     use codemap_diagnostic::{Diagnostic, Level};
@@ -219,163 +625,237 @@ fn removed_nodes_note(
         None
     } else {
         let spans = nodes.iter().flat_map(|node| node.spans());
-        let span_labels = CodemapSpans::span_labels_from(codemap_files, spans);
+        let mut span_labels = CodemapSpans::span_labels_from(codemap_files, spans);
+        let omitted =
+            normalize_span_labels(codemap_files, &mut span_labels, options.max_span_labels);
         Some(Diagnostic {
             level: Level::Note,
-            message: format!("some {} nodes were removed before these", node_type),
+            message: format!("some {} nodes were removed before these", node_type)
+                + &omitted_span_labels_note(omitted),
             code: None,
             spans: span_labels,
         })
     }
 }
 
-fn event_diff_notes(
-    readme_event: &pulldown_cmark::Event<'_>,
-    docs_event: &pulldown_cmark::Event<'_>,
-) -> std::vec::Vec<codemap_diagnostic::Diagnostic> {
-    use std::iter::repeat;
-    use std::string::ToString;
-    use std::{format, vec};
-
-    use pulldown_cmark::{CodeBlockKind, Event, Tag};
+/// Creates diagnostic messages for the specified mismatch without codemap.
+///
+/// It mirrors the `MatchFailed::from_mismatch` codemap diagnostics.
+fn excerpt_diagnostics(mismatch: &SyncMismatch, options: &SyncOptions) -> ExcerptDiagnostics {
+    use crate::excerpt_emitter::Level;
+    use crate::excerpt_emitter::{
+        excerpt_labels_from, normalize_excerpt_labels, ExcerptDiagnostic,
+    };
+    use crate::TextSource;
+    use std::format;
+    use std::sync::Arc;
+    use std::vec::Vec;
 
-    let readme_event_name = get_event_name(readme_event);
-    let docs_event_name = get_event_name(docs_event);
-    if readme_event_name != docs_event_name {
-        return vec![
-            text_note(std::format!(
-                "readme node event name is \"{}\"",
-                readme_event_name
-            )),
-            text_note(std::format!(
-                "docs   node event name is \"{}\"",
-                docs_event_name
-            )),
-        ];
-    }
-
-    let readme_tag = get_event_start_tag(readme_event);
-    let docs_tag = get_event_start_tag(docs_event);
-    if let (Some(readme_tag), Some(docs_tag)) = (readme_tag, docs_tag) {
-        let readme_tag_name = get_start_tag_name(readme_tag);
-        let docs_tag_name = get_start_tag_name(docs_tag);
-        if readme_tag_name != docs_tag_name {
-            let mut notes = vec![
-                text_note(std::format!(
-                    "readme node event start tag name is \"{}\"",
-                    readme_tag_name
-                )),
-                text_note(std::format!(
-                    "docs   node event start tag name is \"{}\"",
-                    docs_tag_name
-                )),
-            ];
-            if let Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) = docs_event {
-                notes.push(text_note(
-                    concat!(
-                        "Possible issue: ",
-                        "Rustdoc ignore indents in the consecutive ",
-                        "doc-comments and doc-attributes. ",
-                        "However, the four-space indents should be ",
-                        "interpreted as Indented code blocks in CMark. ",
-                        "Issue: https://github.com/rust-lang/rust/issues/70732",
-                    )
-                    .to_string(),
-                ));
+    let diagnostic =
+        |level, message: String, nodes: &mut dyn Iterator<Item = &Arc<crate::CMarkItem>>| {
+            let mut labels = excerpt_labels_from(nodes.flat_map(|node| node.spans()));
+            let omitted = normalize_excerpt_labels(&mut labels, options.max_span_labels);
+            ExcerptDiagnostic {
+                level,
+                message: message + &omitted_span_labels_note(omitted),
+                labels,
+            }
+        };
+
+    let mut diags = std::vec![diagnostic(
+        Level::Error,
+        mismatch.message_with_options(&options.emit),
+        &mut [&mismatch.readme_node, &mismatch.docs_node]
+            .into_iter()
+            .flatten(),
+    )];
+    for (nodes, node_type) in [
+        (&mismatch.readme_removed, "readme"),
+        (&mismatch.docs_removed, "docs"),
+    ] {
+        if !nodes.is_empty() {
+            diags.push(diagnostic(
+                Level::Note,
+                format!("some {} nodes were removed before these", node_type),
+                &mut nodes.iter(),
+            ));
+        }
+    }
+    diags.extend(mismatch.notes.iter().cloned().map(ExcerptDiagnostic::note));
+    diags.push(ExcerptDiagnostic::note(
+        mismatch.previous_events_note_with_options(&options.emit),
+    ));
+
+    if options.coalesce_diagnostics {
+        let mut coalesced: Vec<(ExcerptDiagnostic, bool)> = Vec::new();
+        for diag in diags {
+            match coalesced
+                .iter_mut()
+                .find(|(other, _)| other.level == diag.level && other.message == diag.message)
+            {
+                Some((other, is_merged)) => {
+                    other.labels.extend(diag.labels);
+                    *is_merged = true;
+                }
+                None => coalesced.push((diag, false)),
             }
-            return notes;
         }
+        diags = coalesced
+            .into_iter()
+            .map(|(mut diag, is_merged)| {
+                if is_merged {
+                    let omitted =
+                        normalize_excerpt_labels(&mut diag.labels, options.max_span_labels);
+                    diag.message += &omitted_span_labels_note(omitted);
+                }
+                diag
+            })
+            .collect();
     }
 
-    let readme_tag = get_event_end_tag(readme_event);
-    let docs_tag = get_event_end_tag(docs_event);
-    if let (Some(readme_tag), Some(docs_tag)) = (readme_tag, docs_tag) {
-        let readme_tag_name = get_end_tag_name(readme_tag);
-        let docs_tag_name = get_end_tag_name(docs_tag);
-        if readme_tag_name != docs_tag_name {
-            let notes = vec![
-                text_note(std::format!(
-                    "readme node event end tag name is \"{}\"",
-                    readme_tag_name
-                )),
-                text_note(std::format!(
-                    "docs   node event end tag name is \"{}\"",
-                    docs_tag_name
-                )),
-            ];
-            return notes;
+    let mut focuses = Vec::new();
+    for (text_source, offset) in text_difference_focuses(mismatch) {
+        if let TextSource::FileDocs(file_docs) = &text_source {
+            if let Some(file_offset) = file_docs.remap_offset_to_file(offset) {
+                focuses.push((TextSource::File(Arc::clone(file_docs.file())), file_offset));
+            }
         }
+        focuses.push((text_source, offset));
     }
 
-    let readme_text = get_event_text(readme_event);
-    let docs_text = get_event_text(docs_event);
-    if let (Some(readme_text), Some(docs_text)) = (readme_text, docs_text) {
-        if readme_text != docs_text {
-            const OFFSET: usize = 32;
-            const LEN: usize = 32;
-
-            let readme_chars = readme_text.char_indices().map(Some).chain(repeat(None));
-            let docs_chars = docs_text.char_indices().map(Some).chain(repeat(None));
-            let mut chars = readme_chars.zip(docs_chars);
-            let pos = chars
-                .find_map(|pair| match pair {
-                    (Some(lhs), Some(rhs)) => {
-                        if lhs.1 != rhs.1 {
-                            assert_eq!(lhs.0, rhs.0);
-                            Some(lhs.0)
-                        } else {
-                            None
-                        }
-                    }
-                    (Some(lhs), _) => Some(lhs.0),
-                    (_, Some(rhs)) => Some(rhs.0),
-                    (None, None) => unreachable!(),
-                })
-                .unwrap();
-            let start = pos.saturating_sub(OFFSET);
-            let end = pos + LEN;
-
-            return vec![
-                text_note(std::format!(
-                    "readme node text part: \"{}\"",
-                    formatted_subslice(readme_text, start, end)
-                )),
-                text_note(std::format!(
-                    "docs   node text part: \"{}\"",
-                    formatted_subslice(docs_text, start, end)
-                )),
-            ];
+    ExcerptDiagnostics {
+        diags,
+        focuses,
+        max_line_len: options.emit.max_excerpt_len,
+    }
+}
+
+#[cfg(feature = "codemap-diagnostic")]
+/// Merges diagnostics with the same level and message into the first of them.
+///
+/// Span labels of the merged diagnostics are united, sorted and limited,
+/// and diagnostics without span labels are just deduplicated.
+fn coalesce_diagnostics(
+    codemap_files: &crate::CodemapFiles,
+    diags: std::vec::Vec<codemap_diagnostic::Diagnostic>,
+    max_span_labels: usize,
+) -> std::vec::Vec<codemap_diagnostic::Diagnostic> {
+    use std::vec::Vec;
+
+    let mut coalesced: Vec<(codemap_diagnostic::Diagnostic, bool)> = Vec::new();
+    for diag in diags {
+        match coalesced
+            .iter_mut()
+            .find(|(other, _)| other.level == diag.level && other.message == diag.message)
+        {
+            Some((other, is_merged)) => {
+                other.spans.extend(diag.spans);
+                *is_merged = true;
+            }
+            None => coalesced.push((diag, false)),
         }
     }
+    coalesced
+        .into_iter()
+        .map(|(mut diag, is_merged)| {
+            if is_merged {
+                let omitted =
+                    normalize_span_labels(codemap_files, &mut diag.spans, max_span_labels);
+                diag.message += &omitted_span_labels_note(omitted);
+            }
+            diag
+        })
+        .collect()
+}
 
-    vec![
-        text_note(format!("readme node: {}", FmtPrint(readme_event))),
-        text_note(format!("docs   node: {}", FmtPrint(docs_event))),
-    ]
+/// Returns notes about the fenced code blocks likely missing their closing fences
+/// that start before the mismatched nodes or at them.
+fn suspicious_codeblock_notes(
+    mismatch: &SyncMismatch,
+    readme: &crate::CMarkData,
+    docs: &crate::CMarkData,
+    max_codeblock_lines: usize,
+) -> std::vec::Vec<String> {
+    use crate::{LocationRole, SourceLocation};
+    use std::format;
+    use std::sync::Arc;
+
+    let mut notes = std::vec::Vec::new();
+    for (data, node, name, role) in [
+        (
+            readme,
+            &mismatch.readme_node,
+            "readme",
+            LocationRole::ReadmePrimary,
+        ),
+        (docs, &mismatch.docs_node, "docs", LocationRole::DocsPrimary),
+    ] {
+        let position =
+            |target: &Arc<crate::CMarkItem>| data.iter().position(|node| Arc::ptr_eq(node, target));
+        // A missing mismatched node means that the data ended before it.
+        let mismatch_position = node.as_ref().map_or(Some(usize::MAX), position);
+        for warning in data.lint_suspicious_codeblocks_with_max_lines(max_codeblock_lines) {
+            if position(&warning.node) > mismatch_position {
+                continue;
+            }
+            let location = warning
+                .node
+                .spans()
+                .first()
+                .and_then(|span| SourceLocation::from_span(span, role));
+            notes.push(match location {
+                Some(location) => format!(
+                    "Possible issue: {} {}:{}:{}: {}",
+                    name,
+                    location.path.display(),
+                    location.start.line,
+                    location.start.column,
+                    warning
+                ),
+                None => format!("Possible issue: {} {}", name, warning),
+            });
+        }
+    }
+    notes
 }
 
-fn previous_events_notes(events: &[pulldown_cmark::Event<'_>]) -> codemap_diagnostic::Diagnostic {
+/// The maximum number of predicates shown in the skipped docs note.
+const MAX_SKIPPED_DOCS_PREDICATES: usize = 3;
+
+/// Returns a note about the docs excluded by `cfg_attr` predicates evaluated to false.
+fn skipped_docs_note(skipped_docs: &[crate::SkippedDocs]) -> Option<String> {
     use std::format;
     use std::string::ToString;
 
-    const MAX_EVENTS_SHOWN: usize = 16;
+    if skipped_docs.is_empty() {
+        return None;
+    }
+    let mut note = "Possible issue: some doc lines were excluded by `cfg_attr` predicates, \
+        is your `Config` missing these features?"
+        .to_string();
+    for skipped in skipped_docs.iter().take(MAX_SKIPPED_DOCS_PREDICATES) {
+        note += &format!("\n{}", skipped);
+    }
+    match skipped_docs
+        .len()
+        .saturating_sub(MAX_SKIPPED_DOCS_PREDICATES)
+    {
+        0 => {}
+        1 => note += "\n(1 more predicate omitted)",
+        omitted => note += &format!("\n({} more predicates omitted)", omitted),
+    }
+    Some(note)
+}
 
-    if events.is_empty() {
-        text_note("match failed on first events".to_string())
-    } else {
-        let from = events.len().saturating_sub(MAX_EVENTS_SHOWN);
-        let mut note = "previous events: [\n".to_owned();
-        if from != 0 {
-            note += "    ...\n";
-        }
-        for event in &events[from..] {
-            note += &format!("    {}\n", FmtPrint(event));
-        }
-        note += "]";
-        text_note(note)
+fn omitted_span_labels_note(omitted: usize) -> String {
+    match omitted {
+        0 => String::new(),
+        1 => "\n(1 more source location omitted)".to_owned(),
+        _ => std::format!("\n({} more source locations omitted)", omitted),
     }
 }
 
+#[cfg(feature = "codemap-diagnostic")]
 fn text_note(message: String) -> codemap_diagnostic::Diagnostic {
     use codemap_diagnostic::{Diagnostic, Level};
     use std::vec::Vec;
@@ -388,397 +868,488 @@ fn text_note(message: String) -> codemap_diagnostic::Diagnostic {
     }
 }
 
-fn formatted_subslice(text: &str, start: usize, end: usize) -> String {
-    use std::format;
-
-    let skip_before = start > 3;
-    let start = if skip_before { start } else { 0 };
-    let skip_after = text.len().saturating_sub(end) > 3;
-    let end = if skip_after { end } else { text.len() };
+#[test]
+fn test_match_failed_to_single_lines() {
+    use crate::test_utils::readme_file;
+    use crate::{CMarkDocs, Config, File};
+    use std::path::PathBuf;
+    use std::sync::Arc;
 
-    format!(
-        "{}{}{}",
-        if skip_before { "..." } else { "" },
-        &text[start..end],
-        if skip_after { "..." } else { "" }
+    let readme = CMarkReadme::from_file(readme_file("# Title\n\nFirst  line\nsecond line\n"));
+    let docs = CMarkDocs::from_file_and_config(
+        Arc::new(File::from_path_and_text(
+            PathBuf::from("src/lib.rs"),
+            "//! # Title\n//!\n//! Other text\n".into(),
+        )),
+        &Config::default(),
     )
-}
-
-fn get_event_start_tag<'a>(
-    event: &'a pulldown_cmark::Event<'_>,
-) -> Option<&'a pulldown_cmark::Tag<'a>> {
-    use pulldown_cmark::Event;
-    match event {
-        Event::Start(tag) => Some(tag),
-        _ => None,
-    }
-}
+    .unwrap();
 
-fn get_event_end_tag<'a>(
-    event: &'a pulldown_cmark::Event<'_>,
-) -> Option<&'a pulldown_cmark::TagEnd> {
-    use pulldown_cmark::Event;
-    match event {
-        Event::End(tag) => Some(tag),
-        _ => None,
-    }
+    let lines = match check_sync(&readme, &docs) {
+        Err(CheckSyncError::MatchFailed(err)) => err.to_single_lines(),
+        Ok(()) => panic!("readme and docs should not match"),
+    };
+    assert!(lines[0].starts_with("README.md:3:1: error: "));
+    assert!(lines.iter().all(|line| !line.contains('\n')));
 }
 
-fn get_event_text<'a>(event: &'a pulldown_cmark::Event<'_>) -> Option<&'a str> {
+#[cfg(feature = "codemap-diagnostic")]
+#[test]
+fn test_span_labels_order_and_limit() {
+    use crate::test_utils::readme_file;
+    use crate::{CMarkData, CMarkDocs, CMarkItem, CMarkItemAsModified, TextSource};
     use pulldown_cmark::Event;
-    match event {
-        Event::Text(text) => Some(text),
-        Event::Code(text) => Some(text),
-        Event::Html(text) => Some(text),
-        Event::FootnoteReference(text) => Some(text),
-        _ => None,
-    }
-}
+    use std::sync::Arc;
 
-fn get_event_name<'a>(event: &pulldown_cmark::Event<'_>) -> &'a str {
-    use pulldown_cmark::Event;
-    match event {
-        Event::Start(..) => "Start",
-        Event::End(..) => "End",
-        Event::Text(..) => "Text",
-        Event::Code(..) => "Code",
-        Event::InlineMath(..) => "InlineMath",
-        Event::DisplayMath(..) => "DisplayMath",
-        Event::Html(..) => "Html",
-        Event::InlineHtml(..) => "InlineHtml",
-        Event::FootnoteReference(..) => "FootnoteReference",
-        Event::SoftBreak => "SoftBreak",
-        Event::HardBreak => "HardBreak",
-        Event::Rule => "Rule",
-        Event::TaskListMarker(..) => "TaskListMarker",
-    }
-}
-
-fn get_start_tag_name<'a>(tag: &'a pulldown_cmark::Tag<'_>) -> &'a str {
-    use pulldown_cmark::Tag;
-    match tag {
-        Tag::Paragraph => "Paragraph",
-        Tag::Heading { .. } => "Heading",
-        Tag::BlockQuote(..) => "BlockQuote",
-        Tag::CodeBlock(..) => "CodeBlock",
-        Tag::HtmlBlock { .. } => "HtmlBlock",
-        Tag::List(..) => "List",
-        Tag::Item => "Item",
-        Tag::FootnoteDefinition(..) => "FootnoteDefinition",
-        Tag::DefinitionList => "DefinitionList",
-        Tag::DefinitionListTitle => "DefinitionListTitle",
-        Tag::DefinitionListDefinition => "DefinitionListDefinition",
-        Tag::Table(..) => "Table",
-        Tag::TableHead => "TableHead",
-        Tag::TableRow => "TableRow",
-        Tag::TableCell => "TableCell",
-        Tag::Emphasis => "Emphasis",
-        Tag::Strong => "Strong",
-        Tag::Strikethrough => "Strikethrough",
-        Tag::Link { .. } => "Link",
-        Tag::Image { .. } => "Image",
-        Tag::MetadataBlock(..) => "MetadataBlock",
-    }
-}
-
-fn get_end_tag_name(tag: &pulldown_cmark::TagEnd) -> &str {
-    use pulldown_cmark::TagEnd;
-    match tag {
-        TagEnd::Paragraph => "Paragraph",
-        TagEnd::Heading { .. } => "Heading",
-        TagEnd::BlockQuote(..) => "BlockQuote",
-        TagEnd::CodeBlock => "CodeBlock",
-        TagEnd::HtmlBlock { .. } => "HtmlBlock",
-        TagEnd::List(..) => "List",
-        TagEnd::Item => "Item",
-        TagEnd::FootnoteDefinition => "FootnoteDefinition",
-        TagEnd::DefinitionList => "DefinitionList",
-        TagEnd::DefinitionListTitle => "DefinitionListTitle",
-        TagEnd::DefinitionListDefinition => "DefinitionListDefinition",
-        TagEnd::Table => "Table",
-        TagEnd::TableHead => "TableHead",
-        TagEnd::TableRow => "TableRow",
-        TagEnd::TableCell => "TableCell",
-        TagEnd::Emphasis => "Emphasis",
-        TagEnd::Strong => "Strong",
-        TagEnd::Strikethrough => "Strikethrough",
-        TagEnd::Link { .. } => "Link",
-        TagEnd::Image { .. } => "Image",
-        TagEnd::MetadataBlock(..) => "MetadataBlock",
-    }
-}
-
-pub trait Print {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result;
-}
+    let text = "abcdefghijklmnopqrstuvwxyz";
+    let file = readme_file(text);
+    let text_source = TextSource::File(Arc::clone(&file));
+    let parsed = |idx: usize| {
+        let event = Event::Text(text[idx..idx + 1].to_owned().into());
+        CMarkItem::from(event, idx..idx + 1, text_source.clone())
+    };
 
-#[derive(Clone, Debug)]
-pub struct FmtPrint<T>(T);
+    // 12 distinct ranges in reverse order and duplicates of some of them.
+    let nodes: std::vec::Vec<_> = (0..12).rev().chain([3, 5, 3]).map(parsed).collect();
+    let node = nodes.into_modified(Event::Text("readme".into()), "test".into());
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        CMarkData::from_items(std::vec![node]),
+        (),
+        (),
+    );
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        CMarkData::from_items(std::vec![CMarkItem::new(
+            Event::Text("docs".into()),
+            "test".into()
+        )]),
+        (),
+        (),
+    );
+
+    let check = |max_span_labels| match check_sync_with_options(
+        &readme,
+        &docs,
+        &SyncOptions {
+            max_span_labels,
+            ..SyncOptions::default()
+        },
+    ) {
+        Err(CheckSyncError::MatchFailed(err)) => {
+            let Diagnostics::Codemap {
+                diags,
+                codemap_files,
+            } = err.diagnostics
+            else {
+                panic!("codemap diagnostics expected");
+            };
+            let diag = diags[0].clone();
+            let file = codemap_files.files()[&file].clone();
+            let ranges: std::vec::Vec<_> = diag
+                .spans
+                .iter()
+                .map(|label| label.span.low() - file.span.low())
+                .collect();
+            (ranges, diag.message)
+        }
+        Ok(()) => panic!("readme and docs should not match"),
+    };
 
-impl<T> Display for FmtPrint<T>
-where
-    T: Print,
-{
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        T::fmt(&self.0, f)
-    }
-}
+    let (ranges, message) = check(8);
+    assert_eq!(ranges, [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert!(message.ends_with("\n(4 more source locations omitted)"));
 
-impl<T> Print for &T
-where
-    T: ?Sized + Print,
-{
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        T::fmt(self, f)
-    }
+    let (ranges, message) = check(100);
+    assert_eq!(ranges, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    assert!(message.ends_with("does not match docs node\n`Text(\"docs\")`"));
 }
 
-impl<T> Print for Option<T>
-where
-    T: Print,
-{
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Some(value) => write!(fmt, "Some({})", FmtPrint(value)),
-            None => write!(fmt, "None"),
-        }
-    }
-}
+#[cfg(feature = "codemap-diagnostic")]
+#[test]
+fn test_coalesce_diagnostics() {
+    use crate::test_utils::readme_file;
+    use crate::{CMarkSpan, CodemapFiles, CodemapSpans, TextSource};
+    use codemap_diagnostic::{Diagnostic, Level};
+    use std::string::ToString;
+    use std::sync::Arc;
 
-impl<T> Print for [T]
-where
-    T: Print,
-{
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let mut iter = self.iter();
-        writeln!(fmt, "[")?;
-        if let Some(first) = iter.next() {
-            writeln!(fmt, "{}", FmtPrint(first))?;
-            for item in iter {
-                writeln!(fmt, ", {}", FmtPrint(item))?;
-            }
+    let file = readme_file("First line.\nSecond line.\n");
+    let text_source = TextSource::File(Arc::clone(&file));
+    let mut codemap_files = CodemapFiles::new();
+    let mut diag = |level, message: &str, ranges: &[(usize, usize)]| {
+        let ranges: std::vec::Vec<_> = ranges.iter().map(|&(start, end)| start..end).collect();
+        let spans = ranges.iter().map(|range| CMarkSpan {
+            range,
+            text_source: &text_source,
+            note: String::new(),
+        });
+        Diagnostic {
+            level,
+            message: message.to_owned(),
+            code: None,
+            spans: CodemapSpans::span_labels_from(&mut codemap_files, spans),
         }
-        writeln!(fmt, "]")?;
-        Ok(())
-    }
+    };
+    let diags = std::vec![
+        diag(Level::Error, "mismatch", &[(0, 5)]),
+        diag(Level::Note, "removed nodes", &[(12, 18)]),
+        diag(Level::Note, "removed nodes", &[(0, 5), (12, 18)]),
+        diag(Level::Note, "removed nodes", &[(6, 10)]),
+        diag(Level::Note, "text note", &[]),
+        diag(Level::Note, "text note", &[]),
+        diag(Level::Error, "text note", &[]),
+    ];
+
+    let coalesced = coalesce_diagnostics(&codemap_files, diags.clone(), 2);
+    let summary: std::vec::Vec<_> = coalesced
+        .iter()
+        .map(|diag| (diag.level, diag.message.as_str(), diag.spans.len()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (Level::Error, "mismatch", 1),
+            (
+                Level::Note,
+                "removed nodes\n(1 more source location omitted)",
+                2
+            ),
+            (Level::Note, "text note", 0),
+            (Level::Error, "text note", 0),
+        ]
+    );
+
+    let codemap_files = Arc::new(codemap_files);
+    let output = MatchFailed::from_diags(coalesced, Arc::clone(&codemap_files)).to_string();
+    assert_eq!(output.matches("removed nodes").count(), 1);
+    assert_eq!(output.matches("note: text note").count(), 1);
+    let output = MatchFailed::from_diags(diags, codemap_files).to_string();
+    assert_eq!(output.matches("removed nodes").count(), 3);
 }
 
-impl Print for str {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(fmt, "{}", self)
-    }
-}
+#[test]
+fn test_check_leading_blocks_sync() {
+    use crate::{CMarkData, File};
+    use std::path::PathBuf;
+    use std::sync::Arc;
 
-impl Print for CowStr<'_> {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(fmt, "{}", self)
-    }
-}
+    let data = |text: &str, name: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(name),
+            text.into(),
+        )))
+    };
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        data(
+            concat!(
+                "# crate\n\n",
+                "[![Crates.io](https://img.shields.io/crates/v/crate.svg)](https://crates.io/crates/crate)\n\n",
+                "The elevator pitch.\n\n",
+                "- first\n- second\n\n",
+                "Readme only paragraph.\n",
+            ),
+            "README.md",
+        ),
+        (),
+        (),
+    );
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        data(
+            concat!(
+                "The elevator pitch.\n\n",
+                "- first\n- second\n\n",
+                "## Docs only section\n",
+            ),
+            "lib.rs",
+        ),
+        (),
+        (),
+    );
 
-impl<T1, T2> Print for (T1, T2)
-where
-    T1: Print,
-    T2: Print,
-{
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(fmt, "({}, {})", FmtPrint(&self.0), FmtPrint(&self.1))
-    }
+    assert!(check_leading_blocks_sync(&readme, &docs, 2).is_ok());
+    assert!(check_leading_blocks_sync(&readme, &docs, 3).is_err());
+    assert!(check_sync(&readme, &docs).is_err());
 }
 
-impl Print for pulldown_cmark::Event<'_> {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::Event;
-
-        let event_name = get_event_name(self);
-        write!(fmt, "{}", event_name)?;
+#[test]
+fn test_skipped_docs_note() {
+    use crate::test_utils::readme_file;
+    use crate::{CMarkDocs, Config, File};
+    use std::path::PathBuf;
+    use std::string::ToString;
+    use std::sync::Arc;
 
-        match self {
-            Event::Start(tag) => write!(fmt, "({})", FmtPrint(tag)),
-            Event::End(tag) => write!(fmt, "({})", FmtPrint(tag)),
-            Event::Text(text)
-            | Event::Code(text)
-            | Event::InlineMath(text)
-            | Event::DisplayMath(text)
-            | Event::Html(text)
-            | Event::InlineHtml(text)
-            | Event::FootnoteReference(text) => write!(fmt, "(\"{}\")", &text),
-            Event::SoftBreak => Ok(()),
-            Event::HardBreak => Ok(()),
-            Event::Rule => Ok(()),
-            Event::TaskListMarker(ch) => write!(fmt, "({})", ch),
+    let readme = CMarkReadme::from_file(readme_file("# Crate\n\nText.\n"));
+    let check = |text: &str| {
+        let docs = CMarkDocs::from_file_and_config(
+            Arc::new(File::from_path_and_text(
+                PathBuf::from("lib.rs"),
+                text.into(),
+            )),
+            &Config::default(),
+        )
+        .unwrap();
+        match check_sync(&readme, &docs) {
+            Err(CheckSyncError::MatchFailed(err)) => err.to_string(),
+            Ok(()) => panic!("readme and docs should not match"),
         }
-    }
+    };
+
+    let hidden = check(concat!(
+        "#![cfg_attr(feature = \"docs\", doc = \"# Crate\")]\n",
+        "#![cfg_attr(feature = \"docs\", doc = \"\")]\n",
+        "#![cfg_attr(feature = \"docs\", doc = \"Text.\")]\n",
+    ));
+    assert!(hidden.contains("is your `Config` missing these features?"));
+    assert!(hidden.contains("3 doc line(s) excluded by `cfg_attr(feature = \"docs\", ...)`"));
+
+    let partial = check(concat!(
+        "//! # Crate\n",
+        "//!\n",
+        "#![cfg_attr(feature = \"std\", doc = \"Text.\")]\n",
+        "#![cfg_attr(not(feature = \"std\"), doc = \"Other text.\")]\n",
+    ));
+    assert!(partial.contains("1 doc line(s) excluded by `cfg_attr(feature = \"std\", ...)`"));
+
+    let shown = check("//! # Crate\n//!\n//! Other text.\n");
+    assert!(!shown.contains("excluded by"));
 }
 
-impl Print for pulldown_cmark::Tag<'_> {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::{MetadataBlockKind, Tag};
+#[test]
+fn test_check_markdown_sync() {
+    use std::string::ToString;
 
-        let tag_name = get_start_tag_name(self);
-        write!(fmt, "{}", tag_name)?;
+    assert!(check_markdown_sync("Text *a*.\n", "Text *a*.\n", "README.md", "lib.rs").is_ok());
+    assert_markdown_sync("- item\n", "* item\n", "README.md", "lib.rs");
 
-        match self {
-            Tag::Paragraph => Ok(()),
-            Tag::Heading {
-                level,
-                id,
-                classes,
-                attrs,
-            } => write!(
-                fmt,
-                "({}, \"{}\", {}, {})",
-                level,
-                FmtPrint(id.as_deref()),
-                FmtPrint(classes.as_slice()),
-                FmtPrint(attrs.as_slice())
-            ),
-            Tag::BlockQuote(kind) => write!(fmt, "({})", FmtPrint(kind)),
-            Tag::CodeBlock(kind) => write!(fmt, "({})", FmtPrint(kind)),
-            Tag::HtmlBlock => Ok(()),
-            Tag::List(Some(first)) => write!(fmt, "(Some({}))", first),
-            Tag::List(None) => write!(fmt, "(None)"),
-            Tag::Item => Ok(()),
-            Tag::FootnoteDefinition(label) => write!(fmt, "(\"{}\")", &label),
-            Tag::DefinitionList => Ok(()),
-            Tag::DefinitionListTitle => Ok(()),
-            Tag::DefinitionListDefinition => Ok(()),
-            Tag::Table(alignment) => write!(fmt, "({})", FmtPrint(&alignment[..])),
-            Tag::TableHead => Ok(()),
-            Tag::TableRow => Ok(()),
-            Tag::TableCell => Ok(()),
-            Tag::Emphasis => Ok(()),
-            Tag::Strong => Ok(()),
-            Tag::Strikethrough => Ok(()),
-            Tag::Link {
-                link_type,
-                dest_url,
-                title,
-                id,
-            } => {
-                write!(
-                    fmt,
-                    "({}, \"{}\", \"{}\", \"{}\")",
-                    FmtPrint(link_type),
-                    dest_url,
-                    title,
-                    id
-                )
-            }
-            Tag::Image {
-                link_type,
-                dest_url,
-                title,
-                id,
-            } => {
-                write!(
-                    fmt,
-                    "({}, \"{}\", \"{}\", \"{}\")",
-                    FmtPrint(link_type),
-                    dest_url,
-                    title,
-                    id
-                )
-            }
-            Tag::MetadataBlock(MetadataBlockKind::YamlStyle) => {
-                write!(fmt, "(\"yaml style\")")
-            }
-            Tag::MetadataBlock(MetadataBlockKind::PlusesStyle) => {
-                write!(fmt, "(\"pluses style\")")
-            }
-        }
-    }
+    let err = check_markdown_sync("Text.\n", "Other text.\n", "README.md", "lib.rs").unwrap_err();
+    let lines = err.to_single_lines();
+    assert!(lines[0].starts_with("README.md:1:1: error:"), "{:?}", lines);
+    assert!(err.to_string().contains("lib.rs"));
+    let patch = err.suggest_readme_patch().unwrap();
+    assert_eq!(patch.apply("Text.\n"), "Other text.\n");
+    assert!(patch.to_unified_diff().contains("-Text.\n+Other text.\n"));
 }
 
-impl Print for pulldown_cmark::TagEnd {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::{MetadataBlockKind, TagEnd};
-
-        let tag_name = get_end_tag_name(self);
-        write!(fmt, "/{}", tag_name)?;
-
-        match self {
-            TagEnd::Paragraph => Ok(()),
-            TagEnd::Heading(level) => write!(fmt, "({})", level),
-            TagEnd::BlockQuote(kind) => write!(fmt, "({})", FmtPrint(kind)),
-            TagEnd::CodeBlock => Ok(()),
-            TagEnd::HtmlBlock => Ok(()),
-            TagEnd::List(is_ordered) => write!(fmt, "({})", &is_ordered),
-            TagEnd::Item => Ok(()),
-            TagEnd::FootnoteDefinition => Ok(()),
-            TagEnd::DefinitionList => Ok(()),
-            TagEnd::DefinitionListTitle => Ok(()),
-            TagEnd::DefinitionListDefinition => Ok(()),
-            TagEnd::Table => Ok(()),
-            TagEnd::TableHead => Ok(()),
-            TagEnd::TableRow => Ok(()),
-            TagEnd::TableCell => Ok(()),
-            TagEnd::Emphasis => Ok(()),
-            TagEnd::Strong => Ok(()),
-            TagEnd::Strikethrough => Ok(()),
-            TagEnd::Link => Ok(()),
-            TagEnd::Image => Ok(()),
-            TagEnd::MetadataBlock(MetadataBlockKind::YamlStyle) => {
-                write!(fmt, "(\"yaml style\")")
-            }
-            TagEnd::MetadataBlock(MetadataBlockKind::PlusesStyle) => {
-                write!(fmt, "(\"pluses style\")")
-            }
-        }
+#[test]
+fn test_long_single_line_excerpts() {
+    use crate::{CMarkData, CMarkDocs, File};
+    use std::path::PathBuf;
+    use std::string::ToString;
+    use std::sync::Arc;
+
+    let line = "x".repeat(8000);
+    let readme = std::format!("```\n{}a{}\n```\n", line, line);
+    let docs = std::format!("```\n{}b{}\n```\n", line, line);
+    let err = check_markdown_sync(&readme, &docs, "README.md", "lib.rs").unwrap_err();
+    let message = err.to_string();
+    assert!(message.len() < 4000, "{}", message.len());
+    assert!(message.contains("xxax") && message.contains("xxbx"));
+
+    let data = |text: String, name: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(
+            PathBuf::from(name),
+            text,
+        )))
+    };
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(
+        data(std::format!("Text {}a{}\n", line, line), "README.md"),
+        (),
+        (),
+    );
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(
+        data(std::format!("Text {}b{}\n", line, line), "lib.rs"),
+        (),
+        (),
+    );
+    for lightweight_excerpts in [false, true] {
+        let options = SyncOptions {
+            emit: EmitOptions {
+                max_excerpt_len: 16,
+                lightweight_excerpts,
+            },
+            ..SyncOptions::default()
+        };
+        let err = match check_sync_with_options(&readme, &docs, &options) {
+            Err(CheckSyncError::MatchFailed(err)) => err,
+            Ok(()) => panic!("readme and docs should not match"),
+        };
+        let message = err.to_string();
+        assert!(message.len() < 2000, "{}", message.len());
+        assert!(message.contains("xxax") && message.contains("xxbx"));
     }
 }
 
-impl Print for pulldown_cmark::CodeBlockKind<'_> {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::CodeBlockKind;
+#[test]
+fn test_appended_docs_mismatch_spans() {
+    use crate::{CMarkData, CMarkDocs, Config, File};
+    use std::string::ToString;
+    use std::sync::Arc;
 
-        match self {
-            CodeBlockKind::Indented => write!(fmt, "Indented"),
-            CodeBlockKind::Fenced(tag) => write!(fmt, "Fenced({})", tag),
-        }
-    }
+    let file =
+        |path: &str, text: &str| Arc::new(File::from_path_and_text(path.into(), text.into()));
+    let readme = CMarkReadme::from_file(file(
+        "README.md",
+        "# Title\n\nText.\n\n## Overview\n\nOther text.\n",
+    ));
+    let architecture = CMarkData::from_file(file(
+        "ARCHITECTURE.md",
+        "## Overview\n\nOverview text.\n\n## Internals\n\nHidden.\n",
+    ))
+    .first_section();
+    let docs = CMarkDocs::from_file_and_config(
+        file("src/lib.rs", "//! # Title\n//!\n//! Text.\n"),
+        &Config::default(),
+    )
+    .unwrap()
+    .append(architecture);
+
+    let err = match check_sync(&readme, &docs) {
+        Err(CheckSyncError::MatchFailed(err)) => err,
+        Ok(()) => panic!("readme and docs should not match"),
+    };
+    let message = err.to_string();
+    assert!(message.contains("--> ARCHITECTURE.md:3:1"), "{}", message);
+    assert!(message.contains("::: README.md"), "{}", message);
 }
 
-impl Print for pulldown_cmark::Alignment {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::Alignment;
+#[test]
+fn test_lightweight_excerpts() {
+    use crate::{CMarkDocs, Config, File};
+    use std::string::ToString;
+    use std::sync::Arc;
+    use std::vec::Vec;
 
-        match self {
-            Alignment::None => write!(fmt, "None"),
-            Alignment::Left => write!(fmt, "Left"),
-            Alignment::Center => write!(fmt, "Center"),
-            Alignment::Right => write!(fmt, "Right"),
-        }
-    }
+    let file =
+        |path: &str, text: &str| Arc::new(File::from_path_and_text(path.into(), text.into()));
+    let readme = CMarkReadme::from_file(file(
+        "README.md",
+        "# Title\n\nSome text.\n\n## Usage\n\nFirst  line\nsecond line\n",
+    ));
+    let docs = CMarkDocs::from_file_and_config(
+        file(
+            "src/lib.rs",
+            "//! # Title\n//!\n//! Some text.\n//!\n//! ## Usage\n//!\n//! First line\n//! other line\n",
+        ),
+        &Config::default(),
+    )
+    .unwrap();
+
+    let check = |emit: EmitOptions| match check_sync_with_options(
+        &readme,
+        &docs,
+        &SyncOptions {
+            emit,
+            ..SyncOptions::default()
+        },
+    ) {
+        Err(CheckSyncError::MatchFailed(err)) => err,
+        Ok(()) => panic!("readme and docs should not match"),
+    };
+    let codemap = check(EmitOptions::default());
+    let lightweight = check(EmitOptions::default().lightweight_excerpts(true));
+    assert!(matches!(lightweight.diagnostics, Diagnostics::Excerpts(_)));
+    assert_eq!(lightweight.to_single_lines(), codemap.to_single_lines());
+    assert_eq!(lightweight.locations(), codemap.locations());
+
+    let headers = |err: &MatchFailed| -> Vec<_> {
+        err.to_string()
+            .lines()
+            .filter(|line| line.contains("--> ") || line.contains("::: "))
+            .map(|line| line.trim().to_string())
+            .collect()
+    };
+    assert_eq!(headers(&lightweight), headers(&codemap));
 }
 
-impl Print for pulldown_cmark::LinkType {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::LinkType;
+#[test]
+fn test_suspicious_codeblock_notes() {
+    use crate::{CMarkDocs, Config, File};
+    use std::sync::Arc;
 
-        match self {
-            LinkType::Inline => write!(fmt, "Inline"),
-            LinkType::Reference => write!(fmt, "Reference"),
-            LinkType::ReferenceUnknown => write!(fmt, "ReferenceUnknown"),
-            LinkType::Collapsed => write!(fmt, "Collapsed"),
-            LinkType::CollapsedUnknown => write!(fmt, "CollapsedUnknown"),
-            LinkType::Shortcut => write!(fmt, "Shortcut"),
-            LinkType::ShortcutUnknown => write!(fmt, "ShortcutUnknown"),
-            LinkType::Autolink => write!(fmt, "Autolink"),
-            LinkType::Email => write!(fmt, "Email"),
-        }
-    }
+    let file =
+        |path: &str, text: &str| Arc::new(File::from_path_and_text(path.into(), text.into()));
+    let docs = CMarkDocs::from_file_and_config(
+        file(
+            "src/lib.rs",
+            "//! ```json\n//! { \"foo\": 1 }\n//! ```\n//!\n//! ## License\n//!\n//! MIT\n",
+        ),
+        &Config::default(),
+    )
+    .unwrap();
+    let check =
+        |text: &str| match check_sync(&CMarkReadme::from_file(file("README.md", text)), &docs) {
+            Err(CheckSyncError::MatchFailed(err)) => err.to_single_lines(),
+            Ok(()) => panic!("readme and docs should not match"),
+        };
+
+    let lines = check("```json\n{ \"foo\": 1 }\n\n## License\n\nMIT\n");
+    assert_eq!(
+        lines[0],
+        "note: Possible issue: readme README.md:1:1: code block may be missing its closing fence, \
+            it contains the heading-like line \"## License\""
+    );
+    assert!(lines[1].starts_with("README.md:2:1: error: "));
+
+    // The suspicious code block is after the mismatch.
+    let lines = check("Text.\n\n```json\n{ \"foo\": 1 }\n\n## License\n");
+    assert!(lines[0].starts_with("README.md:1:1: error: "));
+    assert!(lines.iter().all(|line| !line.contains("Possible issue")));
 }
 
-impl Print for pulldown_cmark::BlockQuoteKind {
-    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        use pulldown_cmark::BlockQuoteKind;
+#[test]
+fn test_trailing_document_whitespace() {
+    use crate::{CMarkData, CMarkDocs, File};
+    use std::sync::Arc;
 
-        match self {
-            BlockQuoteKind::Note => write!(fmt, "Note"),
-            BlockQuoteKind::Tip => write!(fmt, "Tip"),
-            BlockQuoteKind::Important => write!(fmt, "Important"),
-            BlockQuoteKind::Warning => write!(fmt, "Warning"),
-            BlockQuoteKind::Caution => write!(fmt, "Caution"),
-        }
-    }
+    let data = |path: &str, text: &str| {
+        CMarkData::from_file(Arc::new(File::from_path_and_text(path.into(), text.into())))
+    };
+    let check = |readme: &str, docs: &str, ignore_trailing_document_whitespace| {
+        let readme =
+            CMarkReadme::from_data_and_package_path_and_manifest(data("README.md", readme), (), ());
+        let docs =
+            CMarkDocs::from_data_chunks_package_pach_and_manifest(data("lib.rs", docs), (), ());
+        let options = SyncOptions {
+            ignore_trailing_document_whitespace,
+            ..SyncOptions::default()
+        };
+        check_sync_with_options(&readme, &docs, &options).map_err(|err| match err {
+            CheckSyncError::MatchFailed(err) => err.to_single_lines(),
+        })
+    };
+
+    // Newline versus none.
+    assert_eq!(
+        check("Text.\n\n```\ncode\n", "Text.\n\n```\ncode", true),
+        Ok(())
+    );
+    let lines = check("Text.\n\n```\ncode\n", "Text.\n\n```\ncode", false).unwrap_err();
+    assert_eq!(
+        lines[1],
+        "note: the documents differ only by trailing whitespace at end of file: \
+            readme ends with \"\\n\", docs end with \"\""
+    );
+
+    // Double newline.
+    assert_eq!(check("```\ncode\n\n", "```\ncode\n```\n", true), Ok(()));
+    let lines = check("```\ncode\n\n", "```\ncode\n```\n", false).unwrap_err();
+    assert!(lines[1].ends_with("readme ends with \"\\n\\n\", docs end with \"\\n\""));
+
+    // A real content difference in the final node.
+    let lines = check("```\ncode\n", "```\nother code", true).unwrap_err();
+    assert!(lines[0].contains("error: readme node"));
+    assert!(lines
+        .iter()
+        .all(|line| !line.contains("trailing whitespace")));
+
+    // Trailing whitespace of a node followed by other content.
+    let lines = check(
+        "```\ncode\n```\n\nText.\n",
+        "```\ncode\n\n```\n\nText.\n",
+        true,
+    )
+    .unwrap_err();
+    assert!(lines
+        .iter()
+        .all(|line| !line.contains("trailing whitespace")));
 }
@@ -0,0 +1,592 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{PipelineSpec, PipelineStep};
+
+/// The name of the readme-sync configuration file.
+pub const SYNC_CONFIG_FILE_NAME: &str = ".readme-sync.toml";
+
+/// Readme-sync settings loaded from `.readme-sync.toml` files.
+///
+/// The file may be placed in the package directory,
+/// in the workspace root directory or in both of them.
+/// Package-level values take precedence over workspace-level ones:
+/// each key specified in the package-level file replaces the workspace-level value,
+/// except `section-aliases` whose entries are merged by their keys.
+///
+/// ```toml
+/// badge-patterns = ["https://example.com/*/badge.svg"]
+/// ignored-sections = [{ heading = "License", level = 2 }]
+///
+/// [section-aliases]
+/// "Example" = "Examples"
+///
+/// [readme]
+/// pipeline = [
+///     "remove_badges_paragraph",
+///     { remove_section = { heading = "Documentation", level = 2 } },
+///     "use_absolute_repository_blob_urls",
+/// ]
+///
+/// [docs]
+/// pipeline = [
+///     "increment_heading_levels",
+///     "add_package_title",
+///     { map_link = { from = "CMarkDocs", to = "struct.CMarkDocs.html" } },
+///     "use_absolute_package_docs_urls",
+/// ]
+/// ```
+///
+/// Pipeline steps are named after `CMarkReadme` and `CMarkDocs` methods.
+/// Steps without arguments are specified by name,
+/// and steps with arguments are specified as a table with a single key.
+/// If a pipeline is not specified, the default one is used.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncConfigFile {
+    paths: Vec<PathBuf>,
+    pipeline: PipelineSpec,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct PartialSyncConfig {
+    readme: Option<Vec<PipelineStep>>,
+    docs: Option<Vec<PipelineStep>>,
+    ignored_sections: Option<Vec<(String, u32)>>,
+    section_aliases: Vec<(String, String)>,
+    badge_patterns: Option<Vec<String>>,
+}
+
+impl SyncConfigFile {
+    /// Looks for configuration files starting from the specified directory
+    /// and up to the workspace root directory.
+    ///
+    /// If the package is not a part of a workspace, only the directories up to
+    /// the package root directory are searched, so configuration files
+    /// in unrelated parent directories are ignored.
+    ///
+    /// Returns `None` if no configuration files found.
+    pub fn discover(start_dir: &Path) -> Result<Option<Self>, SyncConfigFileError> {
+        let start_dir = start_dir
+            .canonicalize()
+            .unwrap_or_else(|_| start_dir.to_path_buf());
+
+        let dirs: Vec<&Path> = start_dir.ancestors().collect();
+        let last = dirs
+            .iter()
+            .position(|dir| is_workspace_root(dir))
+            .or_else(|| dirs.iter().position(|dir| dir.join("Cargo.toml").is_file()))
+            .unwrap_or(0);
+
+        let mut paths = Vec::new();
+        for dir in &dirs[..=last] {
+            let path = dir.join(SYNC_CONFIG_FILE_NAME);
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        if paths.is_empty() {
+            return Ok(None);
+        }
+
+        paths.reverse();
+        let mut config = PartialSyncConfig::default();
+        for path in &paths {
+            config = config.merged_with(PartialSyncConfig::from_path(path)?);
+        }
+        Ok(Some(Self {
+            paths,
+            pipeline: config.into_pipeline(),
+        }))
+    }
+
+    /// Reads a single configuration file from the specified path.
+    pub fn from_path(path: PathBuf) -> Result<Self, SyncConfigFileError> {
+        let pipeline = PartialSyncConfig::from_path(&path)?.into_pipeline();
+        Ok(Self {
+            paths: std::vec![path],
+            pipeline,
+        })
+    }
+
+    /// Returns the paths of the merged configuration files, from the workspace-level one.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the configured pipeline.
+    pub fn pipeline(&self) -> &PipelineSpec {
+        &self.pipeline
+    }
+
+    /// Consumes the `SyncConfigFile`, returning the configured pipeline.
+    pub fn into_pipeline(self) -> PipelineSpec {
+        self.pipeline
+    }
+}
+
+/// Checks that the readme and docs of the package in the specified directory are synchronized
+/// using the pipeline from the discovered `.readme-sync.toml` files.
+///
+/// The default pipeline is used if no configuration files found.
+///
+/// # Panics
+///
+/// Panics with diagnostic messages if the readme and docs are not the same
+/// or if the package, configuration files, readme or docs can not be read.
+pub fn run_sync_check_with_config_file<P: AsRef<Path>>(manifest_dir: P) {
+    use crate::assert_sync_self;
+
+    let manifest_dir = manifest_dir.as_ref();
+    let pipeline = SyncConfigFile::discover(manifest_dir)
+        .unwrap_or_else(|err| panic!("Failed to read configuration file: {}", err))
+        .map(SyncConfigFile::into_pipeline);
+    assert_sync_self(manifest_dir, pipeline);
+}
+
+fn is_workspace_root(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .is_some_and(|manifest| manifest.contains_key("workspace"))
+}
+
+impl PartialSyncConfig {
+    fn from_path(path: &Path) -> Result<Self, SyncConfigFileError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|err| SyncConfigFileError::IoError {
+                path: path.to_path_buf(),
+                err,
+            })?;
+        Self::from_content(path, &content)
+    }
+
+    fn from_content(path: &Path, content: &str) -> Result<Self, SyncConfigFileError> {
+        let table: toml::Table =
+            content
+                .parse()
+                .map_err(|err| SyncConfigFileError::ParseError {
+                    path: path.to_path_buf(),
+                    err,
+                })?;
+        Self::from_table(&table).map_err(|err| err.with_path(path))
+    }
+
+    fn from_table(table: &toml::Table) -> Result<Self, KeyError> {
+        use std::string::ToString;
+
+        let mut config = Self::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "readme" | "docs" => {
+                    let steps = parse_side(value, key)?;
+                    if key == "readme" {
+                        config.readme = steps;
+                    } else {
+                        config.docs = steps;
+                    }
+                }
+                "ignored-sections" => {
+                    let sections = as_array(value, key)?
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, value)| parse_section(value, &std::format!("{}[{}]", key, idx)))
+                        .collect::<Result<_, _>>()?;
+                    config.ignored_sections = Some(sections);
+                }
+                "section-aliases" => {
+                    let aliases = value.as_table().ok_or_else(|| KeyError::table(key))?;
+                    for (from, to) in aliases {
+                        let to = as_str(to, &[key.as_str(), ".", from].concat())?;
+                        config
+                            .section_aliases
+                            .push((from.to_string(), to.to_string()));
+                    }
+                }
+                "badge-patterns" => {
+                    let patterns = as_array(value, key)?
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, value)| {
+                            as_str(value, &std::format!("{}[{}]", key, idx)).map(String::from)
+                        })
+                        .collect::<Result<_, _>>()?;
+                    config.badge_patterns = Some(patterns);
+                }
+                _ => return Err(KeyError::unknown(key)),
+            }
+        }
+        Ok(config)
+    }
+
+    fn merged_with(self, other: Self) -> Self {
+        let mut section_aliases = self.section_aliases;
+        for (from, to) in other.section_aliases {
+            match section_aliases.iter_mut().find(|(key, _)| *key == from) {
+                Some(alias) => alias.1 = to,
+                None => section_aliases.push((from, to)),
+            }
+        }
+        Self {
+            readme: other.readme.or(self.readme),
+            docs: other.docs.or(self.docs),
+            ignored_sections: other.ignored_sections.or(self.ignored_sections),
+            section_aliases,
+            badge_patterns: other.badge_patterns.or(self.badge_patterns),
+        }
+    }
+
+    fn into_pipeline(self) -> PipelineSpec {
+        let default = PipelineSpec::default();
+        PipelineSpec {
+            readme: self.readme.unwrap_or(default.readme),
+            docs: self.docs.unwrap_or(default.docs),
+            ignored_sections: self.ignored_sections.unwrap_or_default(),
+            section_aliases: self.section_aliases,
+            badge_patterns: self.badge_patterns.unwrap_or_default(),
+        }
+    }
+}
+
+fn parse_side(value: &toml::Value, key: &str) -> Result<Option<Vec<PipelineStep>>, KeyError> {
+    let table = value.as_table().ok_or_else(|| KeyError::table(key))?;
+    let mut steps = None;
+    for (name, value) in table {
+        let path = [key, ".", name].concat();
+        match name.as_str() {
+            "pipeline" => {
+                steps = Some(
+                    as_array(value, &path)?
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, value)| parse_step(value, &std::format!("{}[{}]", path, idx)))
+                        .collect::<Result<_, _>>()?,
+                );
+            }
+            _ => return Err(KeyError::unknown(&path)),
+        }
+    }
+    Ok(steps)
+}
+
+fn parse_step(value: &toml::Value, path: &str) -> Result<PipelineStep, KeyError> {
+    use std::string::ToString;
+
+    if let Some(name) = value.as_str() {
+        return Ok(match name {
+            "concat_texts" => PipelineStep::ConcatTexts,
+            "collapse_whitespace" => PipelineStep::CollapseWhitespace,
+            "canonicalize_emphasis_nesting" => PipelineStep::CanonicalizeEmphasisNesting,
+            "increment_heading_levels" => PipelineStep::IncrementHeadingLevels,
+            "add_package_title" => PipelineStep::AddPackageTitle,
+            "remove_badges_paragraph" => PipelineStep::RemoveBadgesParagraph,
+            "remove_documentation_section" => PipelineStep::RemoveDocumentationSection,
+            "normalize_codeblock_tags" => PipelineStep::NormalizeCodeblockTags,
+            "remove_codeblock_rust_test_tags" => PipelineStep::RemoveCodeblockRustTestTags,
+            "use_default_codeblock_rust_tag" => PipelineStep::UseDefaultCodeblockRustTag,
+            "remove_hidden_rust_code" => PipelineStep::RemoveHiddenRustCode,
+            "normalize_toml_codeblocks" => PipelineStep::NormalizeTomlCodeblocks,
+            "disallow_absolute_repository_blob_links" => {
+                PipelineStep::DisallowAbsoluteRepositoryBlobLinks
+            }
+            "use_absolute_repository_blob_urls" => PipelineStep::UseAbsoluteRepositoryBlobUrls,
+            "disallow_absolute_package_docs_links" => {
+                PipelineStep::DisallowAbsolutePackageDocsLinks
+            }
+            "use_absolute_package_docs_urls" => PipelineStep::UseAbsolutePackageDocsUrls,
+            _ => return Err(KeyError::unknown(&[path, ".", name].concat())),
+        });
+    }
+
+    let table = value.as_table().ok_or_else(|| KeyError::step(path))?;
+    let mut entries = table.iter();
+    let (name, value) = match (entries.next(), entries.next()) {
+        (Some(entry), None) => entry,
+        _ => return Err(KeyError::step(path)),
+    };
+    let path = &[path, ".", name].concat();
+    Ok(match name.as_str() {
+        "add_title" => PipelineStep::AddTitle(as_str(value, path)?.to_string()),
+        "remove_section" => {
+            let (heading, level) = parse_section(value, path)?;
+            PipelineStep::RemoveSection { heading, level }
+        }
+        "remove_codeblock_tag" => {
+            PipelineStep::RemoveCodeblockTags(std::vec![as_str(value, path)?.to_string()])
+        }
+        "remove_codeblock_tags" => PipelineStep::RemoveCodeblockTags(
+            as_array(value, path)?
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| {
+                    as_str(value, &std::format!("{}[{}]", path, idx)).map(String::from)
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        "remove_codeblocks_with_tag" => {
+            PipelineStep::RemoveCodeblocksWithTag(as_str(value, path)?.to_string())
+        }
+        "use_default_codeblock_tag" => {
+            PipelineStep::UseDefaultCodeblockTag(as_str(value, path)?.to_string())
+        }
+        "map_link" => {
+            let mut from = None;
+            let mut to = None;
+            for (key, value) in value.as_table().ok_or_else(|| KeyError::table(path))? {
+                let path = &[path, ".", key].concat();
+                match key.as_str() {
+                    "from" => from = Some(as_str(value, path)?.to_string()),
+                    "to" => to = Some(as_str(value, path)?.to_string()),
+                    _ => return Err(KeyError::unknown(path)),
+                }
+            }
+            PipelineStep::MapLink {
+                from: from.ok_or_else(|| KeyError::missing(&[path, ".from"].concat()))?,
+                to: to.ok_or_else(|| KeyError::missing(&[path, ".to"].concat()))?,
+            }
+        }
+        _ => return Err(KeyError::unknown(path)),
+    })
+}
+
+fn parse_section(value: &toml::Value, path: &str) -> Result<(String, u32), KeyError> {
+    use std::string::ToString;
+
+    let mut heading = None;
+    let mut level = None;
+    for (key, value) in value.as_table().ok_or_else(|| KeyError::table(path))? {
+        let path = &[path, ".", key].concat();
+        match key.as_str() {
+            "heading" => heading = Some(as_str(value, path)?.to_string()),
+            "level" => {
+                level = Some(
+                    value
+                        .as_integer()
+                        .filter(|level| (1..=6).contains(level))
+                        .ok_or_else(|| KeyError::invalid(path, "a heading level from 1 to 6"))?
+                        as u32,
+                )
+            }
+            _ => return Err(KeyError::unknown(path)),
+        }
+    }
+    Ok((
+        heading.ok_or_else(|| KeyError::missing(&[path, ".heading"].concat()))?,
+        level.ok_or_else(|| KeyError::missing(&[path, ".level"].concat()))?,
+    ))
+}
+
+fn as_str<'a>(value: &'a toml::Value, path: &str) -> Result<&'a str, KeyError> {
+    value
+        .as_str()
+        .ok_or_else(|| KeyError::invalid(path, "a string"))
+}
+
+fn as_array<'a>(value: &'a toml::Value, path: &str) -> Result<&'a [toml::Value], KeyError> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| KeyError::invalid(path, "an array"))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct KeyError {
+    key: String,
+    expected: Option<&'static str>,
+}
+
+impl KeyError {
+    fn unknown(key: &str) -> Self {
+        Self {
+            key: key.into(),
+            expected: None,
+        }
+    }
+
+    fn invalid(key: &str, expected: &'static str) -> Self {
+        Self {
+            key: key.into(),
+            expected: Some(expected),
+        }
+    }
+
+    fn missing(key: &str) -> Self {
+        Self::invalid(key, "a required value")
+    }
+
+    fn table(key: &str) -> Self {
+        Self::invalid(key, "a table")
+    }
+
+    fn step(key: &str) -> Self {
+        Self::invalid(key, "a step name or a table with a single step name key")
+    }
+
+    fn with_path(self, path: &Path) -> SyncConfigFileError {
+        let path = path.to_path_buf();
+        match self.expected {
+            None => SyncConfigFileError::UnknownKey {
+                path,
+                key: self.key,
+            },
+            Some(expected) => SyncConfigFileError::InvalidValue {
+                path,
+                key: self.key,
+                expected,
+            },
+        }
+    }
+}
+
+/// An error which can occur when reading readme-sync configuration files.
+#[derive(Debug, Error)]
+pub enum SyncConfigFileError {
+    /// File reading failed.
+    #[error("Failed to read configuration file at `{path}`: {err}")]
+    IoError {
+        /// File path.
+        path: PathBuf,
+        /// Rust `io::Error`.
+        #[source]
+        err: io::Error,
+    },
+    /// File parsing failed.
+    #[error("Failed to parse configuration file at `{path}`: {err}")]
+    ParseError {
+        /// File path.
+        path: PathBuf,
+        /// The corresponding parse error.
+        #[source]
+        err: toml::de::Error,
+    },
+    /// Configuration file contains an unknown key.
+    #[error("Unknown key `{key}` in configuration file at `{path}`.")]
+    UnknownKey {
+        /// File path.
+        path: PathBuf,
+        /// Full key path.
+        key: String,
+    },
+    /// Configuration file contains a value of unexpected type.
+    #[error("Invalid value of `{key}` in configuration file at `{path}`, expected {expected}.")]
+    InvalidValue {
+        /// File path.
+        path: PathBuf,
+        /// Full key path.
+        key: String,
+        /// Expected value description.
+        expected: &'static str,
+    },
+}
+
+#[test]
+fn test_sync_config_file_keys() {
+    let parse = |content| PartialSyncConfig::from_content(Path::new("a.toml"), content);
+    let config = parse(
+        r#"
+        ignored-sections = [{ heading = "License", level = 2 }]
+        [readme]
+        pipeline = ["remove_badges_paragraph", { remove_codeblock_tag = "no_sync" }]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.readme.unwrap(),
+        [
+            PipelineStep::RemoveBadgesParagraph,
+            PipelineStep::RemoveCodeblockTags(std::vec!["no_sync".into()])
+        ]
+    );
+    assert_eq!(config.docs, None);
+
+    let unknown_key = |content| match parse(content) {
+        Err(SyncConfigFileError::UnknownKey { key, .. }) => key,
+        result => panic!("unexpected result: {:?}", result),
+    };
+    assert_eq!(unknown_key("badge-pattern = []"), "badge-pattern");
+    assert_eq!(unknown_key("[docs]\npipelines = []"), "docs.pipelines");
+    assert_eq!(
+        unknown_key("[docs]\npipeline = [\"concat_texts\", \"concat_text\"]"),
+        "docs.pipeline[1].concat_text"
+    );
+    assert_eq!(
+        unknown_key("ignored-sections = [{ heading = \"A\", lvl = 2 }]"),
+        "ignored-sections[0].lvl"
+    );
+}
+
+#[test]
+fn test_sync_config_file_merge() {
+    let parse = |content| PartialSyncConfig::from_content(Path::new("a.toml"), content).unwrap();
+    let workspace = parse(
+        r#"
+        badge-patterns = ["https://example.com/*"]
+        section-aliases = { Example = "Examples", Test = "Tests" }
+        [docs]
+        pipeline = ["increment_heading_levels"]
+        "#,
+    );
+    let package = parse(
+        r#"
+        section-aliases = { Test = "Testing" }
+        [docs]
+        pipeline = []
+        "#,
+    );
+    let pipeline = workspace.merged_with(package).into_pipeline();
+    assert_eq!(pipeline.readme, PipelineSpec::default().readme);
+    assert_eq!(pipeline.docs, []);
+    assert_eq!(pipeline.badge_patterns, ["https://example.com/*"]);
+    assert_eq!(
+        pipeline.section_aliases,
+        [
+            ("Example".into(), "Examples".into()),
+            ("Test".into(), "Testing".into())
+        ]
+    );
+}
+
+#[test]
+fn test_sync_config_file_discover() {
+    use std::fs;
+
+    let root = std::env::temp_dir().join(std::format!(
+        "readme-sync-test-config-discover-{}",
+        std::process::id()
+    ));
+    let package = root.join("workspace/crates/foo");
+    fs::create_dir_all(package.join("src")).unwrap();
+    fs::write(root.join(SYNC_CONFIG_FILE_NAME), "badge-patterns = []\n").unwrap();
+    fs::write(
+        package.join("Cargo.toml"),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    let paths = |dir: &Path| {
+        SyncConfigFile::discover(dir)
+            .unwrap()
+            .map(|config| config.paths().len())
+    };
+
+    assert_eq!(paths(&package), None);
+    assert_eq!(paths(&package.join("src")), None);
+
+    fs::write(package.join(SYNC_CONFIG_FILE_NAME), "").unwrap();
+    assert_eq!(paths(&package.join("src")), Some(1));
+
+    let workspace = root.join("workspace");
+    fs::write(workspace.join(SYNC_CONFIG_FILE_NAME), "").unwrap();
+    assert_eq!(paths(&package), Some(1));
+
+    fs::write(
+        workspace.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/foo\"]\n",
+    )
+    .unwrap();
+    assert_eq!(paths(&package), Some(2));
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,263 @@
+use std::borrow::Cow;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+use crate::{CMarkData, CMarkItem};
+
+/// Table contents summary used to select tables to remove.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TableSummary {
+    /// Header row cells.
+    pub head: Vec<TableCellSummary>,
+    /// Body rows cells.
+    pub rows: Vec<Vec<TableCellSummary>>,
+}
+
+/// Table cell contents summary.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TableCellSummary {
+    /// Urls of the cell images.
+    pub image_urls: Vec<String>,
+    /// The cell text and inline code outside of images.
+    pub text: String,
+    /// `true` if the cell contains events other than text, inline code, images and links.
+    pub has_other_content: bool,
+}
+
+impl TableSummary {
+    /// Iterate over header and body cells.
+    pub fn cells(&self) -> impl Iterator<Item = &TableCellSummary> {
+        self.head.iter().chain(self.rows.iter().flatten())
+    }
+
+    pub(crate) fn from_items(items: &[Arc<CMarkItem>]) -> Self {
+        use std::string::ToString;
+
+        let mut summary = Self::default();
+        let mut cell = None;
+        let mut row = None;
+        let mut image_depth = 0_usize;
+
+        for event in items.iter().filter_map(|node| node.event()) {
+            match event {
+                Event::Start(Tag::TableRow) => row = Some(Vec::new()),
+                Event::End(TagEnd::TableRow) => summary.rows.extend(row.take()),
+                Event::Start(Tag::TableCell) => cell = Some(TableCellSummary::default()),
+                Event::End(TagEnd::TableCell) => match (row.as_mut(), cell.take()) {
+                    (Some(row), Some(cell)) => row.push(cell),
+                    (None, Some(cell)) => summary.head.push(cell),
+                    (_, None) => {}
+                },
+                _ => {
+                    if let Some(cell) = cell.as_mut() {
+                        match event {
+                            Event::Start(Tag::Image { dest_url, .. }) => {
+                                image_depth += 1;
+                                cell.image_urls.push(dest_url.to_string());
+                            }
+                            Event::End(TagEnd::Image) => image_depth -= 1,
+                            Event::Text(text) | Event::Code(text) if image_depth == 0 => {
+                                cell.text += text;
+                            }
+                            Event::Text(_) | Event::Code(_) => {}
+                            Event::Start(Tag::Link { .. }) | Event::End(TagEnd::Link) => {}
+                            Event::SoftBreak => cell.text += " ",
+                            _ => cell.has_other_content = true,
+                        }
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+impl CMarkData {
+    /// Remove tables for which the specified predicate returns `true`.
+    pub fn remove_table_matching<P>(self, predicate: P) -> Self
+    where
+        P: Fn(&TableSummary) -> bool,
+    {
+        self.remove_tables_with(predicate, Cow::from("remove_table_matching()"))
+    }
+
+    /// Remove all tables.
+    pub fn remove_tables(self) -> Self {
+        self.remove_tables_with(|_| true, Cow::from("remove_tables()"))
+    }
+
+    /// Remove the table with the specified zero-based index.
+    pub fn remove_table_at(self, index: usize) -> Self {
+        let mut table_index = 0;
+        self.remove_tables_with(
+            |_| {
+                table_index += 1;
+                table_index - 1 == index
+            },
+            Cow::from(std::format!("remove_table_at(index = {})", index)),
+        )
+    }
+
+    /// Remove tables that contain only badges.
+    ///
+    /// Every body cell must contain only images or image-links with badge urls, or nothing.
+    /// Header cells may contain badges or plain text labels.
+    pub fn remove_badges_table(self) -> Self {
+        let patterns = crate::badge_url_patterns();
+        let is_badges = |cell: &TableCellSummary| {
+            cell.image_urls
+                .iter()
+                .all(|url| patterns.iter().any(|pattern| pattern.matches(url)))
+                && !cell.has_other_content
+        };
+        self.remove_tables_with(
+            |table| {
+                table.cells().any(|cell| !cell.image_urls.is_empty())
+                    && table.cells().all(is_badges)
+                    && table
+                        .rows
+                        .iter()
+                        .flatten()
+                        .all(|cell| cell.text.trim().is_empty())
+                    && table
+                        .head
+                        .iter()
+                        .all(|cell| cell.image_urls.is_empty() || cell.text.trim().is_empty())
+            },
+            Cow::from("remove_badges_table()"),
+        )
+    }
+
+    fn remove_tables_with<P>(self, mut predicate: P, note: Cow<'static, str>) -> Self
+    where
+        P: FnMut(&TableSummary) -> bool,
+    {
+        use crate::CMarkItemAsRemoved;
+        use core::mem::take;
+
+        let mut result = Vec::new();
+        let mut table = Vec::new();
+
+        for node in self.into_items() {
+            match node.event() {
+                Some(Event::Start(Tag::Table(_))) if table.is_empty() => table.push(node),
+                Some(Event::End(TagEnd::Table)) if !table.is_empty() => {
+                    table.push(node);
+                    if predicate(&TableSummary::from_items(&table)) {
+                        result.push(take(&mut table).into_removed(note.clone()));
+                    } else {
+                        result.append(&mut table);
+                    }
+                }
+                _ if !table.is_empty() => table.push(node),
+                _ => result.push(node),
+            }
+        }
+
+        result.append(&mut table);
+        Self::from_items(result)
+    }
+}
+
+#[test]
+fn test_remove_badges_table() {
+    use crate::test_utils::readme_data;
+
+    let events = |text: &str| {
+        let data = readme_data(text).remove_badges_table();
+        assert_eq!(data.validate_structure(), Ok(()));
+        data.iter_events()
+            .map(|event| std::format!("{:?}", event))
+            .collect::<Vec<_>>()
+    };
+
+    let readme = concat!(
+        "# Crate\n\n",
+        "| Crate | Docs | CI |\n",
+        "|:-----:|:----:|:--:|\n",
+        "| [![Crates.io](https://img.shields.io/crates/v/foo.svg)](https://crates.io/crates/foo) ",
+        "| [![Docs.rs](https://img.shields.io/docsrs/foo)](https://docs.rs/foo) ",
+        "| ![CI](https://github.com/user/foo/workflows/CI/badge.svg) |\n\n",
+        "Text.\n",
+    );
+    assert_eq!(events(readme), events("# Crate\n\nText.\n"));
+
+    let headless = concat!(
+        "| [![Crates.io](https://img.shields.io/crates/v/foo.svg)](https://crates.io/crates/foo) ",
+        "| ![CI](https://github.com/user/foo/workflows/CI/badge.svg) |\n",
+        "|---|---|\n",
+    );
+    assert_eq!(events(headless), [""; 0]);
+
+    let not_badges = concat!(
+        "| Name | Logo |\n",
+        "|------|------|\n",
+        "| foo | ![Logo](https://example.com/logo.svg) |\n",
+    );
+    assert_eq!(events(not_badges).len(), 20);
+}
+
+#[test]
+fn test_remove_table_matching() {
+    use crate::test_utils::readme_data;
+
+    let data = readme_data("| A | B |\n|---|---|\n| `a` ![i](i.png) | b |\n\n| Other |\n|---|\n");
+
+    let data = data.remove_table_matching(|table| table.head.len() == 2);
+    assert_eq!(data.validate_structure(), Ok(()));
+    assert_eq!(
+        data.iter_events()
+            .filter(|event| matches!(event, Event::Start(Tag::Table(_))))
+            .count(),
+        1
+    );
+
+    let items: Vec<_> = readme_data("| A | B |\n|---|---|\n| `a` ![i](i.png) | b |\n").into_items();
+    let summary = TableSummary::from_items(&items);
+    assert_eq!(summary.head[0].text, "A");
+    assert_eq!(summary.rows.len(), 1);
+    assert_eq!(summary.rows[0][0].text.trim(), "a");
+    assert_eq!(summary.rows[0][0].image_urls, ["i.png"]);
+    assert_eq!(summary.rows[0][1].text, "b");
+}
+
+#[test]
+fn test_remove_tables() {
+    use crate::test_utils::readme_data;
+
+    let data = || readme_data("| A | B |\n|:--|--:|\n| a | b |\n\nText.\n\n| C |\n|---|\n| c |\n");
+    let heads = |data: CMarkData| {
+        assert_eq!(data.validate_structure(), Ok(()));
+        let items = data.into_items();
+        let tables: Vec<_> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.event(), Some(Event::Start(Tag::Table(_)))))
+            .map(|(idx, _)| TableSummary::from_items(&items[idx..]).head[0].text.clone())
+            .collect();
+        let removed = items
+            .iter()
+            .filter(|node| matches!(node.as_ref(), CMarkItem::Removed { .. }))
+            .count();
+        (tables, removed)
+    };
+
+    assert_eq!(
+        heads(data()),
+        (std::vec![String::from("A"), String::from("C")], 0)
+    );
+    assert_eq!(heads(data().remove_tables()), (std::vec![], 2));
+    assert_eq!(
+        heads(data().remove_table_at(0)),
+        (std::vec![String::from("C")], 1)
+    );
+    assert_eq!(
+        heads(data().remove_table_at(1)),
+        (std::vec![String::from("A")], 1)
+    );
+    assert_eq!(heads(data().remove_table_at(2)).1, 0);
+}
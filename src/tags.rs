@@ -1,3 +1,6 @@
+use std::string::String;
+use std::vec::Vec;
+
 /// Returns a slice of currently known tags
 /// used by `cargo test` in Markdown fenced code blocks.
 ///
@@ -11,5 +14,217 @@ pub fn codeblock_rust_test_tags() -> &'static [&'static str] {
         "edition2015",
         "edition2018",
         "edition2021",
+        "edition2024",
+        "standalone_crate",
+        "custom",
     ]
 }
+
+/// Returns `true` if the specified code block tag is a rustdoc error code like `E0277`.
+///
+/// Error codes are used together with `compile_fail` to check the expected compiler error,
+/// like in `compile_fail,E0277`.
+pub fn is_codeblock_rust_error_code_tag(tag: &str) -> bool {
+    tag.len() == 5 && tag.starts_with('E') && tag[1..].bytes().all(|ch| ch.is_ascii_digit())
+}
+
+/// Returns `true` if the specified code block text looks like Rust code.
+///
+/// It is a heuristic: the text looks like Rust code if any line starts with an item
+/// or a statement keyword like `fn `, `let ` or `use `, if at least half of the lines
+/// end with a semicolon, or if it contains `::` paths and any line ends with a semicolon.
+/// So console output, shell commands and tree diagrams do not look like Rust code.
+pub fn looks_like_rust(text: &str) -> bool {
+    const LINE_PREFIXES: [&str; 14] = [
+        "fn ", "let ", "use ", "pub ", "mod ", "struct ", "enum ", "trait ", "impl ", "impl<",
+        "const ", "static ", "#[", "#![",
+    ];
+
+    let lines: Vec<_> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect();
+    let has_keywords = lines
+        .iter()
+        .any(|line| LINE_PREFIXES.iter().any(|prefix| line.starts_with(prefix)));
+    let statements = lines.iter().filter(|line| line.ends_with(';')).count();
+    has_keywords
+        || (statements > 0 && statements * 2 >= lines.len())
+        || (statements > 0 && text.contains("::"))
+}
+
+/// Fenced code block info string split into tags.
+///
+/// Rustdoc accepts tags separated by commas, whitespace or both,
+/// so the tags are split by all of them and the original separators are kept
+/// to rebuild the info string in the same style.
+/// The first tag is usually the code block language.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CodeblockTags<'a> {
+    /// Tags with the separators preceding them.
+    tags: Vec<(&'a str, &'a str)>,
+    /// Separators after the last tag.
+    trailing: &'a str,
+}
+
+impl<'a> CodeblockTags<'a> {
+    /// Splits the specified fenced code block info string into tags.
+    pub fn parse(info: &'a str) -> Self {
+        let is_separator = |ch: char| ch == ',' || ch.is_whitespace();
+
+        let mut tags = Vec::new();
+        let mut rest = info;
+        loop {
+            let tag_start = rest.find(|ch| !is_separator(ch)).unwrap_or(rest.len());
+            let (separator, tail) = rest.split_at(tag_start);
+            if tail.is_empty() {
+                return Self {
+                    tags,
+                    trailing: separator,
+                };
+            }
+            let tag_end = tail.find(is_separator).unwrap_or(tail.len());
+            let (tag, tail) = tail.split_at(tag_end);
+            tags.push((separator, tag));
+            rest = tail;
+        }
+    }
+
+    /// Iterate over tags.
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.tags.iter().map(|(_, tag)| *tag)
+    }
+
+    /// Returns `true` if the info string contains the specified tag.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.iter().any(|node_tag| node_tag == tag)
+    }
+
+    /// Rebuilds the info string with only the tags the predicate returns `true` for.
+    ///
+    /// The separators of the kept tags are preserved,
+    /// while the first kept tag gets the separator of the first original tag.
+    pub fn retain<F>(&self, mut predicate: F) -> String
+    where
+        F: FnMut(&str) -> bool,
+    {
+        let mut result = String::new();
+        let mut is_empty = true;
+        for &(separator, tag) in &self.tags {
+            if predicate(tag) {
+                result += if is_empty { self.tags[0].0 } else { separator };
+                result += tag;
+                is_empty = false;
+            }
+        }
+        if !is_empty {
+            result += self.trailing;
+        }
+        result
+    }
+
+    /// Rebuilds the info string with the specified tag replaced by another one.
+    ///
+    /// The separators are preserved.
+    pub fn replace(&self, from: &str, to: &str) -> String {
+        self.map(|tag| if tag == from { to } else { tag })
+    }
+
+    /// Rebuilds the info string with every tag replaced by the function result.
+    ///
+    /// The separators are preserved.
+    pub fn map<'b, F>(&self, mut func: F) -> String
+    where
+        'a: 'b,
+        F: FnMut(&'a str) -> &'b str,
+    {
+        let mut result = String::new();
+        for &(separator, tag) in &self.tags {
+            result += separator;
+            result += func(tag);
+        }
+        result + self.trailing
+    }
+
+    /// Rebuilds the info string with tags separated by commas only.
+    pub fn to_canonical(&self) -> String {
+        self.iter().collect::<Vec<_>>().join(",")
+    }
+}
+
+#[test]
+fn test_codeblock_tags_parse() {
+    let tags = |info| CodeblockTags::parse(info).iter().collect::<Vec<_>>();
+    assert_eq!(tags(""), [""; 0]);
+    assert_eq!(tags("rust"), ["rust"]);
+    assert_eq!(tags("rust,ignore"), ["rust", "ignore"]);
+    assert_eq!(tags("rust ignore"), ["rust", "ignore"]);
+    assert_eq!(
+        tags("rust, no_run\tedition2018"),
+        ["rust", "no_run", "edition2018"]
+    );
+    assert_eq!(tags(",rust,,ignore, "), ["rust", "ignore"]);
+}
+
+#[test]
+fn test_codeblock_tags_retain() {
+    let retain = |info, removed| CodeblockTags::parse(info).retain(|tag| tag != removed);
+    assert_eq!(retain("rust,ignore", "ignore"), "rust");
+    assert_eq!(retain("rust ignore", "ignore"), "rust");
+    assert_eq!(retain("rust ignore no_run", "ignore"), "rust no_run");
+    assert_eq!(retain("rust, ignore,no_run", "ignore"), "rust,no_run");
+    assert_eq!(retain("ignore rust", "ignore"), "rust");
+    assert_eq!(retain(" rust ignore,", "ignore"), " rust,");
+    assert_eq!(retain("ignore", "ignore"), "");
+    assert_eq!(retain("rust", "ignore"), "rust");
+    assert_eq!(
+        CodeblockTags::parse(" rust  ignore,").to_canonical(),
+        "rust,ignore"
+    );
+}
+
+#[test]
+fn test_codeblock_tags_replace() {
+    let replace = |info| CodeblockTags::parse(info).replace("sh", "bash");
+    assert_eq!(replace("sh"), "bash");
+    assert_eq!(replace("sh, ignore "), "bash, ignore ");
+    assert_eq!(replace("rust ignore"), "rust ignore");
+    assert_eq!(replace(""), "");
+}
+
+#[test]
+fn test_is_codeblock_rust_error_code_tag() {
+    assert!(is_codeblock_rust_error_code_tag("E0277"));
+    assert!(is_codeblock_rust_error_code_tag("E0502"));
+    assert!(!is_codeblock_rust_error_code_tag("E027"));
+    assert!(!is_codeblock_rust_error_code_tag("E02770"));
+    assert!(!is_codeblock_rust_error_code_tag("e0277"));
+    assert!(!is_codeblock_rust_error_code_tag("EABCD"));
+    assert!(!is_codeblock_rust_error_code_tag("rust"));
+}
+
+#[test]
+fn test_looks_like_rust() {
+    assert!(looks_like_rust(
+        "fn main() {\n    println!(\"Hello\");\n}\n"
+    ));
+    assert!(looks_like_rust("let a = 1;\nassert_eq!(a, 1);\n"));
+    assert!(looks_like_rust("use std::fmt;\n"));
+    assert!(looks_like_rust("println!(\"Hello\");\n"));
+    assert!(looks_like_rust(
+        "// Comment.\nreadme_sync::assert_sync(&readme, &docs);\n"
+    ));
+
+    assert!(!looks_like_rust(""));
+    assert!(!looks_like_rust("$ cargo add readme-sync\n"));
+    assert!(!looks_like_rust(
+        "   Compiling foo v0.1.0 (/tmp/foo)\n    Finished dev [unoptimized] target(s)\n"
+    ));
+    assert!(!looks_like_rust(
+        "running 1 test\ntest tests::it_works ... ok\n\ntest result: ok.\n"
+    ));
+    assert!(!looks_like_rust(
+        "foo\n├── Cargo.toml\n└── src\n    └── lib.rs\n"
+    ));
+}
@@ -0,0 +1,73 @@
+//! Shared fixtures of the unit tests.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+
+use crate::{CMarkData, CMarkItem, File, ParseProfile, TextSource};
+
+/// Returns the `README.md` file with the specified text.
+pub(crate) fn readme_file(text: &str) -> Arc<File> {
+    Arc::new(File::from_path_and_text(
+        PathBuf::from("README.md"),
+        text.into(),
+    ))
+}
+
+/// Returns the data parsed from the `README.md` file with the specified text.
+pub(crate) fn readme_data(text: &str) -> CMarkData {
+    CMarkData::from_file(readme_file(text))
+}
+
+/// Returns the data parsed from the `README.md` file with the specified text
+/// using the GitHub parse profile.
+pub(crate) fn github_readme_data(text: &str) -> CMarkData {
+    CMarkData::from_text_source_with_parse_profile(
+        TextSource::File(readme_file(text)),
+        ParseProfile::github(),
+    )
+}
+
+/// Returns the data parsed from the `lib.md` docs file with the specified text.
+pub(crate) fn docs_data(text: &str) -> CMarkData {
+    CMarkData::from_file(Arc::new(File::from_path_and_text(
+        PathBuf::from("lib.md"),
+        text.into(),
+    )))
+}
+
+/// Returns the events of the specified data.
+pub(crate) fn static_events(data: &CMarkData) -> Vec<Event<'static>> {
+    data.iter_events()
+        .map(|event| event.clone().into_static())
+        .collect()
+}
+
+/// Returns the removed items of the specified data.
+pub(crate) fn removed_items(data: &CMarkData) -> Vec<&Arc<CMarkItem>> {
+    data.iter()
+        .filter(|node| matches!(node.as_ref(), CMarkItem::Removed { .. }))
+        .collect()
+}
+
+/// Returns the notes of the removed items of the specified data.
+pub(crate) fn removed_notes(data: &CMarkData) -> Vec<&str> {
+    data.iter()
+        .filter_map(|node| match node.as_ref() {
+            CMarkItem::Removed { note, .. } => Some(note.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the notes of the modified items of the specified data.
+pub(crate) fn modified_notes(data: &CMarkData) -> Vec<&str> {
+    data.iter()
+        .filter_map(|node| match node.as_ref() {
+            CMarkItem::Modified { note, .. } => Some(note.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
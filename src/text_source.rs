@@ -4,7 +4,7 @@ use crate::{File, FileDocs};
 
 /// Markdown parser text source which may be either readme file contents
 /// or package target parsed documentation.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum TextSource {
     /// File text contents
     File(Arc<File>),
@@ -0,0 +1,207 @@
+use core::fmt::{self, Debug};
+use std::boxed::Box;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem};
+
+/// The text of a title added with `add_title_from`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TitleSource {
+    /// The package name as is, like `readme-sync`.
+    PackageName,
+    /// The package name with dashes and underscores replaced with spaces
+    /// and capitalized words, like `Readme Sync`.
+    PackageNameHumanized,
+    /// The specified text.
+    Custom(String),
+}
+
+impl TitleSource {
+    /// Returns the title text for the specified package name.
+    pub fn title(&self, package_name: &str) -> String {
+        match self {
+            Self::PackageName => String::from(package_name),
+            Self::PackageNameHumanized => humanize_package_name(package_name),
+            Self::Custom(text) => text.clone(),
+        }
+    }
+}
+
+/// A predicate called with the title and the package name.
+pub type TitlePredicate = dyn Fn(&str, &str) -> bool;
+
+/// The expected relationship between the first level heading text and the package name.
+pub enum TitlePolicy {
+    /// The title is the package name as is.
+    PackageName,
+    /// The title is the humanized package name, see `TitleSource::PackageNameHumanized`.
+    PackageNameHumanized,
+    /// The title satisfies the predicate called with the title and the package name.
+    Predicate(Box<TitlePredicate>),
+}
+
+impl TitlePolicy {
+    /// Creates a policy accepting titles satisfying the predicate
+    /// called with the title and the package name.
+    pub fn predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&str, &str) -> bool + 'static,
+    {
+        Self::Predicate(Box::new(predicate))
+    }
+
+    /// Returns the expected title for the specified package name if the policy defines it.
+    pub fn expected_title(&self, package_name: &str) -> Option<String> {
+        match self {
+            Self::PackageName => Some(TitleSource::PackageName.title(package_name)),
+            Self::PackageNameHumanized => {
+                Some(TitleSource::PackageNameHumanized.title(package_name))
+            }
+            Self::Predicate(_) => None,
+        }
+    }
+
+    /// Returns `true` if the title satisfies the policy for the specified package name.
+    pub fn accepts(&self, title: &str, package_name: &str) -> bool {
+        match self {
+            Self::Predicate(predicate) => predicate(title, package_name),
+            _ => self.expected_title(package_name).as_deref() == Some(title),
+        }
+    }
+}
+
+impl Debug for TitlePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PackageName => write!(f, "PackageName"),
+            Self::PackageNameHumanized => write!(f, "PackageNameHumanized"),
+            Self::Predicate(_) => write!(f, "Predicate(..)"),
+        }
+    }
+}
+
+/// An error which can occur when verifying the title against a `TitlePolicy`.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum TitleError {
+    /// The first level heading is not found.
+    #[error("The first level heading is not found")]
+    NotFound,
+    /// The first level heading text violates the policy.
+    #[error(
+        "The title \"{title}\" does not match the package name `{package_name}`{}",
+        expected.as_ref().map_or_else(String::new, |expected| std::format!(", expected \"{}\"", expected))
+    )]
+    Mismatch {
+        /// The first level heading text.
+        title: String,
+        /// The package name.
+        package_name: String,
+        /// The expected title if the policy defines it.
+        expected: Option<String>,
+        /// The heading start node.
+        node: Arc<CMarkItem>,
+    },
+}
+
+impl CMarkData {
+    /// Returns the first level heading text and start node of the first first-level heading.
+    pub fn first_title(&self) -> Option<(String, &Arc<CMarkItem>)> {
+        use crate::cmark_data::heading_text;
+        use pulldown_cmark::{Event, HeadingLevel, Tag};
+
+        let items: Vec<_> = self.iter().cloned().collect();
+        let idx = items.iter().position(|node| {
+            matches!(
+                node.event(),
+                Some(Event::Start(Tag::Heading {
+                    level: HeadingLevel::H1,
+                    ..
+                }))
+            )
+        })?;
+        let title = heading_text(&items[idx..])?;
+        self.iter().nth(idx).map(|node| (title, node))
+    }
+
+    /// Verifies the first first-level heading text against the policy
+    /// for the specified package name.
+    pub fn verify_title(&self, policy: &TitlePolicy, package_name: &str) -> Result<(), TitleError> {
+        let (title, node) = self.first_title().ok_or(TitleError::NotFound)?;
+        if policy.accepts(title.trim(), package_name) {
+            Ok(())
+        } else {
+            Err(TitleError::Mismatch {
+                title,
+                package_name: String::from(package_name),
+                expected: policy.expected_title(package_name),
+                node: Arc::clone(node),
+            })
+        }
+    }
+}
+
+/// Returns the package name with dashes and underscores replaced with spaces
+/// and the first letters of words in uppercase.
+fn humanize_package_name(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    words.join(" ")
+}
+
+#[test]
+fn test_verify_title() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    assert_eq!(humanize_package_name("readme-sync"), "Readme Sync");
+    assert_eq!(humanize_package_name("foo__bar-baz"), "Foo Bar Baz");
+    assert_eq!(
+        TitleSource::Custom("Sync".into()).title("readme-sync"),
+        "Sync"
+    );
+    let readme = readme_data("[![badge](badge.svg)](link)\n\n# Readme Sync\n\nText.\n");
+    assert!(readme
+        .verify_title(&TitlePolicy::PackageNameHumanized, "readme-sync")
+        .is_ok());
+    let err = readme
+        .verify_title(&TitlePolicy::PackageName, "readme-sync")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The title \"Readme Sync\" does not match the package name `readme-sync`, \
+            expected \"readme-sync\""
+    );
+    assert!(matches!(
+        err,
+        TitleError::Mismatch { node, .. } if node.spans()[0].range.start == 29
+    ));
+
+    let policy =
+        TitlePolicy::predicate(|title, name| title.to_lowercase().replace(' ', "-") == name);
+    assert!(readme.verify_title(&policy, "readme-sync").is_ok());
+    assert_eq!(
+        readme
+            .verify_title(&policy, "other")
+            .map_err(|err| err.to_string()),
+        Err(String::from(
+            "The title \"Readme Sync\" does not match the package name `other`"
+        ))
+    );
+    assert_eq!(
+        readme_data("Text.\n").verify_title(&policy, "readme-sync"),
+        Err(TitleError::NotFound)
+    );
+}
@@ -0,0 +1,136 @@
+use std::borrow::Cow;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+use thiserror::Error;
+
+use crate::{CMarkData, CMarkItem};
+
+/// A fenced code block tagged `toml` that can not be parsed.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error("Failed to parse toml code block: {err}")]
+pub struct InvalidTomlCodeblock {
+    /// The code block text node.
+    pub node: Arc<CMarkItem>,
+    /// The parse error.
+    pub err: toml::de::Error,
+}
+
+impl CMarkData {
+    /// Replaces texts of fenced code blocks tagged `toml` with their canonical formatting.
+    ///
+    /// Code blocks are parsed as TOML documents and serialized back
+    /// with sorted keys and the standard spacing,
+    /// so code blocks differing only by formatting, key order or comments become the same.
+    /// Code blocks that can not be parsed are left as is,
+    /// use `invalid_toml_codeblocks` to report them.
+    pub fn normalize_toml_codeblocks(self) -> Self {
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::CowStr;
+
+        let mut is_toml_codeblock = false;
+        self.map(|node| {
+            match node.event() {
+                Some(Event::Start(tag)) => is_toml_codeblock = is_toml_codeblock_tag(tag),
+                Some(Event::End(_)) => is_toml_codeblock = false,
+                Some(Event::Text(text)) if is_toml_codeblock => {
+                    if let Ok(canonical) = canonical_toml(text) {
+                        if canonical != text.as_ref() {
+                            let event = Event::Text(CowStr::Boxed(canonical.into_boxed_str()));
+                            return node
+                                .into_modified(event, Cow::from("normalize_toml_codeblocks()"));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            node
+        })
+    }
+
+    /// Returns fenced code blocks tagged `toml` that can not be parsed.
+    pub fn invalid_toml_codeblocks(&self) -> Vec<InvalidTomlCodeblock> {
+        let mut invalid = Vec::new();
+        let mut is_toml_codeblock = false;
+        for node in self.iter() {
+            match node.event() {
+                Some(Event::Start(tag)) => is_toml_codeblock = is_toml_codeblock_tag(tag),
+                Some(Event::End(_)) => is_toml_codeblock = false,
+                Some(Event::Text(text)) if is_toml_codeblock => {
+                    if let Err(err) = canonical_toml(text) {
+                        invalid.push(InvalidTomlCodeblock {
+                            node: Arc::clone(node),
+                            err,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        invalid
+    }
+}
+
+/// Returns `true` if the tag is a fenced code block tagged `toml`.
+fn is_toml_codeblock_tag(tag: &pulldown_cmark::Tag<'_>) -> bool {
+    use crate::tags::CodeblockTags;
+    use pulldown_cmark::{CodeBlockKind, Tag};
+
+    match tag {
+        Tag::CodeBlock(CodeBlockKind::Fenced(tags)) => CodeblockTags::parse(tags).contains("toml"),
+        _ => false,
+    }
+}
+
+/// Returns the TOML document with sorted keys and the standard spacing.
+fn canonical_toml(text: &str) -> Result<String, toml::de::Error> {
+    let table: toml::Table = toml::from_str(text)?;
+    // Serialization of a parsed table never fails.
+    Ok(toml::to_string(&table).unwrap_or_else(|_| String::from(text)))
+}
+
+#[test]
+fn test_normalize_toml_codeblocks() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let readme = readme_data(concat!(
+        "```toml\n",
+        "[dependencies]\n",
+        "serde    = { version = \"1.0\", features = [\"derive\"] }\n",
+        "readme-sync = \"0.3\" # for tests\n",
+        "```\n\n",
+        "```text\na  =  1\n```\n",
+    ));
+    let docs = readme_data(concat!(
+        "```toml\n",
+        "[dependencies]\n",
+        "readme-sync = \"0.3\"\n",
+        "serde = { features = [\"derive\"], version = \"1.0\" }\n",
+        "```\n\n",
+        "```text\na  =  1\n```\n",
+    ));
+    assert_ne!(readme, docs);
+    let readme = readme.normalize_toml_codeblocks();
+    let docs = docs.normalize_toml_codeblocks();
+    assert_eq!(readme.to_markdown(), docs.to_markdown());
+    assert!(readme.to_markdown().contains("```text\na  =  1\n```"));
+
+    let changed =
+        readme_data("```toml\n[dependencies]\nserde = \"1.1\"\n```\n").normalize_toml_codeblocks();
+    assert_ne!(changed.to_markdown(), docs.to_markdown());
+
+    let invalid = readme_data("```toml\n[dependencies\n```\n");
+    assert_eq!(
+        invalid.clone().normalize_toml_codeblocks().to_markdown(),
+        invalid.to_markdown()
+    );
+    let errors = invalid.invalid_toml_codeblocks();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .to_string()
+        .starts_with("Failed to parse toml code block: "));
+    assert!(readme.invalid_toml_codeblocks().is_empty());
+}
@@ -0,0 +1,236 @@
+use std::borrow::Cow;
+use std::vec::Vec;
+
+use pulldown_cmark::Event;
+
+use crate::CMarkData;
+
+/// A user-defined transformation of CMark items.
+///
+/// It allows to chain custom transformations with the built-in ones
+/// using the `apply` method of `CMarkReadme`, `CMarkDocs` and `CMarkData`.
+/// Transformations should mark the changed items as modified or removed
+/// with a note, like the built-in ones do, so they are shown in diagnostic messages.
+///
+/// ```rust
+/// use readme_sync::{CMarkData, CMarkReadme, CMarkTransform, File};
+/// use std::path::PathBuf;
+/// use std::sync::Arc;
+///
+/// struct CompanyBoilerplateRemover;
+///
+/// impl CMarkTransform for CompanyBoilerplateRemover {
+///     fn apply(&self, data: CMarkData) -> CMarkData {
+///         data.remove_section("Contributing", 2)
+///             .remove_section("Support", 2)
+///     }
+/// }
+///
+/// let readme = CMarkReadme::from_file(Arc::new(File::from_path_and_text(
+///     PathBuf::from("README.md"),
+///     "# Crate\n\nText.\n\n## Support\n\nContact us.\n".into(),
+/// )));
+/// let readme = readme
+///     .remove_badges_paragraph()
+///     .apply(&CompanyBoilerplateRemover);
+/// assert_eq!(readme.data().to_markdown(), "# Crate\n\nText.\n");
+/// ```
+pub trait CMarkTransform {
+    /// Returns the transformed data.
+    fn apply(&self, data: CMarkData) -> CMarkData;
+}
+
+/// A user-defined transformation of CMark items that may fail.
+///
+/// It is implemented for all `CMarkTransform` types with the `Infallible` error.
+pub trait TryCMarkTransform {
+    /// The transformation error.
+    type Error;
+
+    /// Returns the transformed data or an error.
+    fn try_apply(&self, data: CMarkData) -> Result<CMarkData, Self::Error>;
+}
+
+impl<F> CMarkTransform for F
+where
+    F: Fn(CMarkData) -> CMarkData,
+{
+    fn apply(&self, data: CMarkData) -> CMarkData {
+        self(data)
+    }
+}
+
+impl<T: CMarkTransform> TryCMarkTransform for T {
+    type Error = core::convert::Infallible;
+
+    fn try_apply(&self, data: CMarkData) -> Result<CMarkData, Self::Error> {
+        Ok(self.apply(data))
+    }
+}
+
+/// The note of the sixth level headings that can not be incremented.
+pub(crate) const INCREMENT_HEADING_LEVELS_OVERFLOW_NOTE: &str =
+    "increment_heading_levels() warning: the sixth level heading is not incremented";
+
+/// Increments levels of all headings.
+///
+/// See `CMarkData::increment_heading_levels` for details.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IncrementHeadingLevels;
+
+impl CMarkTransform for IncrementHeadingLevels {
+    fn apply(&self, data: CMarkData) -> CMarkData {
+        use crate::cmark_data::increase_heading_level;
+        use crate::CMarkItemAsModified;
+        use pulldown_cmark::{HeadingLevel, Tag, TagEnd};
+
+        data.map(|node| {
+            let event = match node.event() {
+                Some(Event::Start(Tag::Heading {
+                    level,
+                    id,
+                    classes,
+                    attrs,
+                })) => Some(Event::Start(Tag::Heading {
+                    level: increase_heading_level(*level),
+                    id: id.clone(),
+                    classes: classes.clone(),
+                    attrs: attrs.clone(),
+                })),
+                Some(Event::End(TagEnd::Heading(level))) => {
+                    Some(Event::End(TagEnd::Heading(increase_heading_level(*level))))
+                }
+                _ => None,
+            };
+            // The sixth level headings are left at the same level and noted with a warning
+            // since they become indistinguishable from the incremented fifth level ones.
+            let is_overflowed = matches!(
+                node.event(),
+                Some(Event::Start(Tag::Heading {
+                    level: HeadingLevel::H6,
+                    ..
+                })) | Some(Event::End(TagEnd::Heading(HeadingLevel::H6)))
+            );
+            match event {
+                Some(event) if is_overflowed => {
+                    node.into_modified(event, Cow::from(INCREMENT_HEADING_LEVELS_OVERFLOW_NOTE))
+                }
+                Some(event) => node.into_modified(event, Cow::from("increment_heading_levels()")),
+                None => node,
+            }
+        })
+    }
+}
+
+/// Removes the first level heading if it is the first block.
+///
+/// See `CMarkData::remove_title` for details.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RemoveTitle;
+
+impl CMarkTransform for RemoveTitle {
+    fn apply(&self, data: CMarkData) -> CMarkData {
+        use crate::CMarkItemAsRemoved;
+        use pulldown_cmark::{HeadingLevel, Tag, TagEnd};
+
+        let mut nodes = data.into_items();
+        let start = match nodes.iter().position(|node| node.event().is_some()) {
+            Some(start) => start,
+            None => return CMarkData::from_items(nodes),
+        };
+        if let Some(Event::Start(Tag::Heading {
+            level: HeadingLevel::H1,
+            ..
+        })) = nodes[start].event()
+        {
+            let end = nodes[start..]
+                .iter()
+                .position(|node| matches!(node.event(), Some(Event::End(TagEnd::Heading(_)))));
+            if let Some(end) = end {
+                let title: Vec<_> = nodes.drain(start..=start + end).collect();
+                nodes.insert(start, title.into_removed(Cow::from("remove_title()")));
+            }
+        }
+        CMarkData::from_items(nodes)
+    }
+}
+
+impl CMarkData {
+    /// Applies the specified transformation.
+    pub fn apply<T: CMarkTransform + ?Sized>(self, transform: &T) -> Self {
+        transform.apply(self)
+    }
+
+    /// Applies the specified transformation that may fail.
+    pub fn try_apply<T: TryCMarkTransform + ?Sized>(self, transform: &T) -> Result<Self, T::Error> {
+        transform.try_apply(self)
+    }
+}
+
+#[test]
+fn test_cmark_transforms() {
+    use crate::test_utils::readme_data;
+    use crate::{CMarkDocs, CMarkItem, CMarkReadme, File};
+    use std::path::PathBuf;
+    use std::string::String;
+    use std::sync::Arc;
+
+    struct Failing;
+
+    impl TryCMarkTransform for Failing {
+        type Error = String;
+
+        fn try_apply(&self, _: CMarkData) -> Result<CMarkData, Self::Error> {
+            Err(String::from("failed"))
+        }
+    }
+
+    let data = || readme_data("# Title\n\n## Usage\n\nText.\n");
+    assert_eq!(
+        data().apply(&RemoveTitle).apply(&IncrementHeadingLevels),
+        data().remove_title().increment_heading_levels()
+    );
+    let transformed = data().apply(&RemoveTitle).apply(&IncrementHeadingLevels);
+    assert_eq!(transformed.to_markdown(), "### Usage\n\nText.\n");
+    let notes: Vec<_> = transformed
+        .iter()
+        .filter_map(|node| match &**node {
+            CMarkItem::Modified { note, .. } | CMarkItem::Removed { note, .. } => {
+                Some(note.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            "remove_title()",
+            "increment_heading_levels()",
+            "increment_heading_levels()"
+        ]
+    );
+
+    let readme = CMarkReadme::from_data_and_package_path_and_manifest(data(), (), ())
+        .apply(&|data: CMarkData| data.remove_title());
+    assert_eq!(readme.data().to_markdown(), "## Usage\n\nText.\n");
+    let readme = readme.try_apply(&IncrementHeadingLevels).unwrap();
+    assert_eq!(readme.data().to_markdown(), "### Usage\n\nText.\n");
+
+    let docs = CMarkDocs::from_data_chunks_package_pach_and_manifest(data(), (), ());
+    assert_eq!(docs.try_apply(&Failing).unwrap_err(), "failed");
+
+    let deep = CMarkData::from_file(Arc::new(File::from_path_and_text(
+        PathBuf::from("lib.md"),
+        "##### Foo\n\n###### Bar\n".into(),
+    )))
+    .increment_heading_levels();
+    assert_eq!(deep.to_markdown(), "###### Foo\n\n###### Bar\n");
+    let spans: Vec<_> = deep.iter().flat_map(|node| node.spans()).collect();
+    assert_eq!(
+        spans
+            .iter()
+            .filter(|span| span.note.contains(INCREMENT_HEADING_LEVELS_OVERFLOW_NOTE))
+            .count(),
+        2
+    );
+}
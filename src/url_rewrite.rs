@@ -0,0 +1,221 @@
+use core::fmt::Display;
+use std::borrow::Cow;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::CMarkData;
+
+/// Rules rewriting urls of one document to the equivalent urls of another one,
+/// like the repository docs folder files to the pages of the rendered docs site.
+///
+/// The `map_prefix` rules select the urls to rewrite,
+/// and the other rules are applied to the mapped urls in the order they are added.
+/// If there are no `map_prefix` rules, all relative urls are rewritten,
+/// except fragment-only ones like `#usage`.
+/// The url query and fragment are preserved.
+///
+/// ```rust
+/// use readme_sync::UrlRewriteRules;
+///
+/// let rules = UrlRewriteRules::new()
+///     .map_prefix("docs/", "https://example.github.io/proj/")
+///     .strip_extension(".md")
+///     .ensure_trailing_slash();
+/// assert_eq!(
+///     rules.apply("docs/usage.md#configuration"),
+///     "https://example.github.io/proj/usage/#configuration"
+/// );
+/// assert_eq!(rules.apply("LICENSE.md"), "LICENSE.md");
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UrlRewriteRules {
+    prefixes: Vec<(String, String)>,
+    rules: Vec<UrlRewriteRule>,
+}
+
+/// A rule applied to the mapped urls.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum UrlRewriteRule {
+    StripExtension(String),
+    EnsureTrailingSlash,
+}
+
+impl UrlRewriteRules {
+    /// Creates empty rules that leave all urls unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule replacing the specified url prefix with another one.
+    ///
+    /// The first matching prefix is used.
+    /// The prefixes are matched like in `CMarkData::disallow_urls_with_prefix`,
+    /// with the case-insensitive host and the optional trailing slash.
+    pub fn map_prefix(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.prefixes.push((from.into(), to.into()));
+        self
+    }
+
+    /// Adds a rule removing the specified extension, like `.md`, from the url path end.
+    pub fn strip_extension(mut self, extension: impl Into<String>) -> Self {
+        self.rules
+            .push(UrlRewriteRule::StripExtension(extension.into()));
+        self
+    }
+
+    /// Adds a rule appending `/` to the url path if it does not end with it.
+    pub fn ensure_trailing_slash(mut self) -> Self {
+        self.rules.push(UrlRewriteRule::EnsureTrailingSlash);
+        self
+    }
+
+    /// Returns `true` if the rules leave all urls unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty() && self.rules.is_empty()
+    }
+
+    /// Returns the url rewritten with the rules.
+    pub fn apply<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        use crate::cmark_data::{is_absolute_url, is_fragment, strip_url_prefix};
+
+        let (path, suffix) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+        let mut path = if self.prefixes.is_empty() {
+            if is_absolute_url(url) || has_scheme(url) || is_fragment(url) {
+                return Cow::from(url);
+            }
+            Cow::from(path)
+        } else {
+            let mapped = self.prefixes.iter().find_map(|(from, to)| {
+                strip_url_prefix(path, from).map(|rest| Cow::from([to.as_str(), rest].concat()))
+            });
+            match mapped {
+                Some(path) => path,
+                None => return Cow::from(url),
+            }
+        };
+        for rule in &self.rules {
+            match rule {
+                UrlRewriteRule::StripExtension(extension) => {
+                    if let Some(stripped) = path.strip_suffix(extension.as_str()) {
+                        path = Cow::from(String::from(stripped));
+                    }
+                }
+                UrlRewriteRule::EnsureTrailingSlash => {
+                    if !path.ends_with('/') {
+                        path = Cow::from([&path, "/"].concat());
+                    }
+                }
+            }
+        }
+        match path {
+            Cow::Borrowed(_) => Cow::from(url),
+            Cow::Owned(path) => Cow::from(path + suffix),
+        }
+    }
+}
+
+/// Returns `true` if the url starts with a scheme, like `https:` or `mailto:`.
+fn has_scheme(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+    })
+}
+
+impl Display for UrlRewriteRules {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let prefixes = self
+            .prefixes
+            .iter()
+            .map(|(from, to)| std::format!("map_prefix({:?}, {:?})", from, to));
+        let rules = self.rules.iter().map(|rule| match rule {
+            UrlRewriteRule::StripExtension(extension) => {
+                std::format!("strip_extension({:?})", extension)
+            }
+            UrlRewriteRule::EnsureTrailingSlash => String::from("ensure_trailing_slash()"),
+        });
+        write!(f, "{}", prefixes.chain(rules).collect::<Vec<_>>().join("."))
+    }
+}
+
+impl CMarkData {
+    /// Rewrites link urls with the specified rules.
+    pub fn rewrite_urls(self, rules: &UrlRewriteRules) -> Self {
+        self.map_links(
+            |url| rules.apply(url),
+            Cow::from(std::format!("rewrite_urls(rules = {})", rules)),
+        )
+    }
+}
+
+#[test]
+fn test_url_rewrite_rules() {
+    use crate::test_utils::readme_data;
+    use std::string::ToString;
+
+    let rules = UrlRewriteRules::new()
+        .map_prefix("docs/", "https://example.github.io/proj/")
+        .map_prefix("./docs/", "https://example.github.io/proj/")
+        .strip_extension(".md")
+        .ensure_trailing_slash();
+    assert_eq!(
+        rules.apply("docs/usage.md#configuration"),
+        "https://example.github.io/proj/usage/#configuration"
+    );
+    assert_eq!(
+        rules.apply("./docs/guide/intro.md?lang=en#top"),
+        "https://example.github.io/proj/guide/intro/?lang=en#top"
+    );
+    assert_eq!(rules.apply("docs/"), "https://example.github.io/proj/");
+    assert!(matches!(
+        rules.apply("src/lib.rs#L1"),
+        Cow::Borrowed("src/lib.rs#L1")
+    ));
+    assert_eq!(
+        rules.to_string(),
+        "map_prefix(\"docs/\", \"https://example.github.io/proj/\")\
+            .map_prefix(\"./docs/\", \"https://example.github.io/proj/\")\
+            .strip_extension(\".md\").ensure_trailing_slash()"
+    );
+
+    let rules = UrlRewriteRules::new().ensure_trailing_slash();
+    assert_eq!(rules.apply("docs/usage#a"), "docs/usage/#a");
+    assert!(matches!(rules.apply("a/"), Cow::Borrowed("a/")));
+    assert!(matches!(rules.apply("#a"), Cow::Borrowed("#a")));
+    assert!(matches!(
+        rules.apply("mailto:x@y"),
+        Cow::Borrowed("mailto:x@y")
+    ));
+    assert!(matches!(
+        rules.apply("https://example.com#a"),
+        Cow::Borrowed("https://example.com#a")
+    ));
+    assert!(UrlRewriteRules::new().is_empty());
+
+    let rules = UrlRewriteRules::new().map_prefix("https://docs.rs/foo/*/foo/", "../foo/");
+    assert_eq!(
+        rules.apply("https://Docs.rs/foo/*/foo/index.html"),
+        "../foo/index.html"
+    );
+    assert_eq!(
+        rules.apply("https://docs.rs/foo/*/foo#features"),
+        "../foo/#features"
+    );
+    assert!(matches!(
+        rules.apply("https://docs.rs/foo/*/foobar/"),
+        Cow::Borrowed(_)
+    ));
+
+    let data = readme_data("See [usage](docs/usage.md#configuration) and [license](LICENSE).\n");
+    let rules = UrlRewriteRules::new()
+        .map_prefix("docs/", "https://example.github.io/proj/")
+        .strip_extension(".md")
+        .ensure_trailing_slash();
+    assert_eq!(
+        data.rewrite_urls(&rules).to_markdown(),
+        "See [usage](https://example.github.io/proj/usage/#configuration) \
+            and [license](LICENSE).\n"
+    );
+}
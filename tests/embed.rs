@@ -0,0 +1,43 @@
+#![cfg(feature = "macros")]
+
+use readme_sync::{
+    check_sync, embed, CheckSyncError, Config, Manifest, PipelineSpec, PipelineStep,
+};
+
+#[test]
+fn embed_test() {
+    let manifest = Manifest::from_cargo_toml_content(include_str!("../Cargo.toml")).unwrap();
+    let config = Config::new().with_features(manifest.docs_rs_features());
+    let (readme, docs) = embed!(config).unwrap();
+
+    let mut pipeline = PipelineSpec::default();
+    let position = pipeline
+        .docs
+        .iter()
+        .position(|step| *step == PipelineStep::DisallowAbsolutePackageDocsLinks)
+        .unwrap();
+    pipeline.docs.insert(
+        position,
+        PipelineStep::MapLink {
+            from: "CMarkDocs::map_links".into(),
+            to: "struct.CMarkDocs.html#method.map_links".into(),
+        },
+    );
+    let readme = readme
+        .with_manifest(&manifest)
+        .apply_pipeline_with_repository_subpath(&pipeline, "")
+        .unwrap();
+    let docs = docs
+        .with_manifest(&manifest)
+        .apply_pipeline_with_repository_subpath(&pipeline, "")
+        .unwrap();
+    check_sync(&readme, &docs).unwrap();
+
+    let (readme, docs) = embed!("README.md", "src/lib.rs").unwrap();
+    let err = match check_sync(&readme, &docs) {
+        Err(CheckSyncError::MatchFailed(err)) => err.to_string(),
+        result => panic!("unexpected result: {:?}", result),
+    };
+    assert!(err.contains(" --> README.md:1:1\n"), "{}", err);
+    assert!(err.contains(" ::: src/lib.rs\n"), "{}", err);
+}
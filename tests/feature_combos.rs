@@ -0,0 +1,28 @@
+//! Checks that the main API is available with every feature combination.
+//!
+//! Run it with different features, like `cargo test --no-default-features`,
+//! to check the corresponding feature combination.
+
+#[test]
+fn feature_combos_test() {
+    use readme_sync::{
+        assert_sync, assert_sync_self, assert_sync_with_label, check_sync, CMarkDocs, CMarkReadme,
+        CheckSyncError, MatchFailed, SyncOptions, WildcardPattern,
+    };
+
+    let _ = assert_sync::<(), ()>;
+    let _ = assert_sync_with_label::<(), ()>;
+    let _ = assert_sync_self;
+    let _ = check_sync::<(), (), (), ()>;
+    let _: fn(&MatchFailed) -> String = ToString::to_string;
+    let _: Option<CheckSyncError> = None;
+    let _: Option<(CMarkReadme<(), ()>, CMarkDocs<(), ()>)> = None;
+    let _ = SyncOptions::default();
+    let _ = WildcardPattern::new("*").unwrap();
+
+    #[cfg(feature = "codemap")]
+    let _ = readme_sync::CodemapFiles::new();
+
+    #[cfg(feature = "platforms")]
+    let _ = readme_sync::Config::with_target_arch_os_env;
+}
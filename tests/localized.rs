@@ -0,0 +1,63 @@
+use std::fs;
+
+use readme_sync::{
+    assert_sync_with_label, check_sync, CMarkDocs, CMarkReadme, CMarkReadmeFromPackageError,
+    CheckSyncError, Config, Package,
+};
+
+#[test]
+fn localized_readme_test() {
+    let root =
+        std::env::temp_dir().join(format!("readme-sync-test-localized-{}", std::process::id()));
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n\
+            [features]\ndocs-zh = []\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("src/lib.rs"),
+        "#![cfg_attr(not(feature = \"docs-zh\"), doc = \"Hello.\")]\n\
+            #![cfg_attr(feature = \"docs-zh\", doc = \"你好。\")]\n",
+    )
+    .unwrap();
+    fs::write(root.join("README.md"), "# foo\n\nHello.\n").unwrap();
+    fs::write(root.join("README.zh-CN.md"), "# foo\n\n你好。\n").unwrap();
+    let package = Package::from_path(root.clone()).unwrap();
+
+    let configs = [Config::new(), Config::new().with_features(["docs-zh"])];
+    let docs: Vec<_> = CMarkDocs::from_package_and_configs(&package, &configs)
+        .unwrap()
+        .into_iter()
+        .map(CMarkDocs::add_package_title)
+        .collect();
+    assert_eq!(docs.len(), 2);
+
+    let readme = CMarkReadme::from_package(&package).unwrap();
+    assert_sync_with_label(&readme, &docs[0], "en");
+    let readme_zh = CMarkReadme::from_package_and_readme_name(&package, "README.zh-CN.md").unwrap();
+    assert_sync_with_label(&readme_zh, &docs[1], "zh-CN");
+
+    match check_sync(&readme_zh, &docs[0]) {
+        Err(CheckSyncError::MatchFailed(err)) => {
+            let message = err.with_label("zh-CN").to_string();
+            assert!(
+                message.starts_with("note: while checking readme and docs `zh-CN`\n"),
+                "{}",
+                message
+            );
+            assert!(message.contains("README.zh-CN.md"), "{}", message);
+        }
+        Ok(()) => panic!("localized readme matches the default docs"),
+    }
+
+    match CMarkReadme::from_package_and_readme_name(&package, "README.de.md") {
+        Err(CMarkReadmeFromPackageError::NamedNotFound(path)) => {
+            assert_eq!(path, std::path::Path::new("README.de.md"));
+        }
+        result => panic!("{:?}", result.map(|_| ())),
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
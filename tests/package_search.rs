@@ -0,0 +1,59 @@
+//! This test changes the process environment and the working directory,
+//! so it is kept in its own test binary.
+
+use std::fs;
+use std::path::PathBuf;
+
+use readme_sync::{Package, PackageSearchAttempt, PackageSearchError, TomlReadError};
+
+#[test]
+fn test_package_from_env_or_search() {
+    let root = std::env::temp_dir().join(format!(
+        "readme-sync-test-package-search-{}",
+        std::process::id()
+    ));
+    let package = |name: &str| {
+        let path = root.join(name);
+        fs::create_dir_all(path.join("src/nested")).unwrap();
+        fs::write(
+            path.join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+        )
+        .unwrap();
+        path.canonicalize().unwrap()
+    };
+    let foo = package("foo");
+    let bar = package("bar");
+
+    std::env::remove_var("CARGO_MANIFEST_DIR");
+    std::env::set_current_dir(foo.join("src/nested")).unwrap();
+    let found = Package::from_env_or_search().unwrap();
+    assert_eq!(found.path(), foo);
+    assert_eq!(found.manifest().package.name, "foo");
+
+    std::env::set_var("CARGO_MANIFEST_DIR", &bar);
+    assert_eq!(Package::from_env_or_search().unwrap().path(), bar);
+
+    std::env::set_var("CARGO_MANIFEST_DIR", root.join("missing"));
+    assert_eq!(Package::from_env_or_search().unwrap().path(), foo);
+
+    let err = PackageSearchError {
+        is_manifest_dir_set: false,
+        attempts: vec![PackageSearchAttempt {
+            origin: "current directory",
+            path: PathBuf::from("/work/src"),
+            err: TomlReadError::ManifestNotFound {
+                path: PathBuf::from("/work/src"),
+                candidates: Vec::new(),
+            },
+        }],
+    };
+    assert_eq!(
+        err.to_string(),
+        "Package not found (`CARGO_MANIFEST_DIR` is not set), tried:\n\
+            - current directory `/work/src`: `Cargo.toml` not found in `/work/src`"
+    );
+
+    std::env::set_current_dir(std::env::temp_dir()).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,4 @@
+#[test]
+fn readme_sync_test() {
+    readme_sync::run_sync_check_with_config_file(env!("CARGO_MANIFEST_DIR"));
+}
@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use readme_sync::{
+    CMarkData, CMarkDocs, CMarkItem, CMarkReadme, Config, File, FileDocs, Manifest, Package,
+};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn send_sync_test() {
+    assert_send_sync::<CMarkData>();
+    assert_send_sync::<CMarkItem>();
+    assert_send_sync::<CMarkReadme<&Path, &Manifest>>();
+    assert_send_sync::<CMarkDocs<&Path, &Manifest>>();
+    assert_send_sync::<File>();
+    assert_send_sync::<FileDocs>();
+    assert_send_sync::<Package>();
+    assert_send_sync::<Manifest>();
+    assert_send_sync::<Config<'_>>();
+}
+
+#[test]
+fn send_sync_diagnostics_test() {
+    assert_send_sync::<readme_sync::MatchFailed>();
+    #[cfg(feature = "codemap")]
+    assert_send_sync::<readme_sync::CodemapFiles>();
+}
+
+#[test]
+fn concurrent_check_sync_test() {
+    use readme_sync::{check_sync, SyncConfigFile};
+    use std::sync::Arc;
+
+    let package = Arc::new(Package::from_path(env!("CARGO_MANIFEST_DIR").into()).unwrap());
+    let pipeline = SyncConfigFile::discover(package.path())
+        .unwrap()
+        .unwrap()
+        .into_pipeline();
+    let config = Config::from_package_docs_rs_features(&package);
+    let readme = CMarkReadme::from_package(&package).unwrap();
+    let docs = CMarkDocs::from_package_and_config(&package, &config).unwrap();
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let package = Arc::clone(&package);
+            let (pipeline, readme, docs) = (&pipeline, &readme, &docs);
+            let _ = scope.spawn(move || {
+                let config = Config::from_package_docs_rs_features(&package);
+                let own_docs = CMarkDocs::from_package_and_config(&package, &config).unwrap();
+                let readme = readme.clone().apply_pipeline(pipeline).unwrap();
+                let docs = docs.clone().apply_pipeline(pipeline).unwrap();
+                let own_docs = own_docs.apply_pipeline(pipeline).unwrap();
+                assert!(check_sync(&readme, &docs).is_ok());
+                assert!(check_sync(&readme, &own_docs).is_ok());
+            });
+        }
+    });
+}